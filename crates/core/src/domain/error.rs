@@ -21,6 +21,12 @@ pub enum ErrorCode {
     Rewrite,
     #[serde(rename = "E_PIPELINE")]
     Pipeline,
+    #[serde(rename = "E_INVALID_SETTINGS")]
+    InvalidSettings,
+    #[serde(rename = "E_AUDIO_FORMAT")]
+    AudioFormat,
+    #[serde(rename = "E_CANCELED")]
+    Canceled,
 }
 
 impl std::fmt::Display for ErrorCode {
@@ -35,6 +41,9 @@ impl std::fmt::Display for ErrorCode {
             ErrorCode::Storage => write!(f, "E_STORAGE"),
             ErrorCode::Rewrite => write!(f, "E_REWRITE"),
             ErrorCode::Pipeline => write!(f, "E_PIPELINE"),
+            ErrorCode::InvalidSettings => write!(f, "E_INVALID_SETTINGS"),
+            ErrorCode::AudioFormat => write!(f, "E_AUDIO_FORMAT"),
+            ErrorCode::Canceled => write!(f, "E_CANCELED"),
         }
     }
 }
@@ -48,6 +57,14 @@ pub struct AppError {
 }
 
 impl AppError {
+    pub fn permission(msg: impl Into<String>) -> Self {
+        Self {
+            code: ErrorCode::Permission,
+            message: msg.into(),
+            recoverable: true,
+        }
+    }
+
     pub fn invalid_state(msg: impl Into<String>) -> Self {
         Self {
             code: ErrorCode::InvalidState,
@@ -79,6 +96,46 @@ impl AppError {
             recoverable: false,
         }
     }
+
+    pub fn invalid_settings(msg: impl Into<String>) -> Self {
+        Self {
+            code: ErrorCode::InvalidSettings,
+            message: msg.into(),
+            recoverable: true,
+        }
+    }
+
+    pub fn audio_format(msg: impl Into<String>) -> Self {
+        Self {
+            code: ErrorCode::AudioFormat,
+            message: msg.into(),
+            recoverable: true,
+        }
+    }
+
+    pub fn stt_unavailable(msg: impl Into<String>) -> Self {
+        Self {
+            code: ErrorCode::SttUnavailable,
+            message: msg.into(),
+            recoverable: true,
+        }
+    }
+
+    pub fn timeout(msg: impl Into<String>) -> Self {
+        Self {
+            code: ErrorCode::Timeout,
+            message: msg.into(),
+            recoverable: true,
+        }
+    }
+
+    pub fn canceled(msg: impl Into<String>) -> Self {
+        Self {
+            code: ErrorCode::Canceled,
+            message: msg.into(),
+            recoverable: true,
+        }
+    }
 }
 
 impl std::fmt::Display for AppError {
@@ -2,7 +2,7 @@
 mod tests {
     use crate::domain::error::{AppError, ErrorCode};
     use crate::domain::session::{SessionState, StateTransition};
-    use crate::domain::types::{DeliverPolicy, DictionaryEntry, DictionaryScope, Mode};
+    use crate::domain::types::{DeliverPolicy, DictionaryEntry, DictionaryScope, Mode, WrapStyle};
 
     #[test]
     fn test_mode_serialization() {
@@ -30,11 +30,45 @@ mod tests {
 
     #[test]
     fn test_deliver_policy_serialization() {
-        let policy = DeliverPolicy::Clipboard;
+        let policy = DeliverPolicy::Clipboard { wrap: None };
         let json = serde_json::to_string(&policy).unwrap();
         assert!(json.contains("clipboard"));
     }
 
+    #[test]
+    fn test_deliver_policy_legacy_clipboard_payload() {
+        let policy: DeliverPolicy = serde_json::from_str(r#"{"target":"clipboard"}"#).unwrap();
+        assert!(matches!(policy, DeliverPolicy::Clipboard { wrap: None }));
+
+        let policy: DeliverPolicy =
+            serde_json::from_str(r#"{"target":"clipboard","wrap":"code_fence"}"#).unwrap();
+        assert!(matches!(
+            policy,
+            DeliverPolicy::Clipboard {
+                wrap: Some(WrapStyle::CodeFence)
+            }
+        ));
+
+        let json = serde_json::to_string(&DeliverPolicy::Clipboard { wrap: None }).unwrap();
+        assert_eq!(json, r#"{"target":"clipboard"}"#);
+    }
+
+    #[test]
+    fn test_wrap_style_apply() {
+        let text = "一行目\n二行目";
+        let ts = "2024-01-02 03:04";
+        assert_eq!(WrapStyle::None.apply(text, ts), text);
+        assert_eq!(
+            WrapStyle::CodeFence.apply(text, ts),
+            "```\n一行目\n二行目\n```"
+        );
+        assert_eq!(WrapStyle::Quote.apply(text, ts), "> 一行目\n> 二行目");
+        assert_eq!(
+            WrapStyle::TimestampPrefix.apply(text, ts),
+            "[2024-01-02 03:04] 一行目\n二行目"
+        );
+    }
+
     #[test]
     fn test_session_state_serialization() {
         assert_eq!(
@@ -103,6 +137,7 @@ mod tests {
             replacement: "テスト".to_string(),
             priority: 10,
             enabled: true,
+            word_boundary: false,
         };
         let json = serde_json::to_string(&entry).unwrap();
         let roundtrip: DictionaryEntry = serde_json::from_str(&json).unwrap();
@@ -1,7 +1,8 @@
 use serde::Serialize;
 
 use super::error::AppError;
-use super::types::{DeliverPolicy, Mode};
+use super::settings::SegmentLimitAction;
+use super::types::{DeliverPolicy, Mode, ModeSwitchedByVoice, WhisperTask};
 
 /// セッション状態
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
@@ -9,7 +10,11 @@ use super::types::{DeliverPolicy, Mode};
 pub enum SessionState {
     Idle,
     Recording,
+    /// 録音を一時停止中（録音中のセグメントは確定させずに保持する）
+    Paused,
     Transcribing,
+    /// 書き起こし結果をユーザーが確認・修正中（review_before_deliver 有効時）
+    Reviewing,
     Rewriting,
     Delivering,
     Error {
@@ -24,7 +29,9 @@ impl SessionState {
         match self {
             Self::Idle => "idle",
             Self::Recording => "recording",
+            Self::Paused => "paused",
             Self::Transcribing => "transcribing",
+            Self::Reviewing => "reviewing",
             Self::Rewriting => "rewriting",
             Self::Delivering => "delivering",
             Self::Error { .. } => "error",
@@ -39,6 +46,8 @@ pub struct Session {
     pub state: SessionState,
     pub mode: Mode,
     pub deliver_policy: DeliverPolicy,
+    /// 書き起こしか英訳か（録音していない間のみ変更可能）
+    pub task: WhisperTask,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -50,6 +59,7 @@ impl Session {
             state: SessionState::Idle,
             mode,
             deliver_policy,
+            task: WhisperTask::default(),
             created_at: now.clone(),
             updated_at: now,
         }
@@ -57,6 +67,9 @@ impl Session {
 }
 
 /// セッションマネージャー（単一アクティブセッション）
+///
+/// DB への書き込みと揃えるため、遷移は clone に対して計算し、書き込みが成功してから書き戻す。
+#[derive(Debug, Clone)]
 pub struct SessionManager {
     active: Option<Session>,
 }
@@ -95,7 +108,26 @@ impl SessionManager {
         Ok(session)
     }
 
-    /// pause_recording: Recording→Idle（パイプラインモード用）
+    /// アクティブセッションを終え、状態・モード・出力ポリシー・タスクを引き継いだ新しいセッションに切り替える
+    ///
+    /// 録音中のパイプラインはそのまま新しいセッションへ書き込み続ける。終えたセッションを返す。
+    pub fn rollover_session(
+        &mut self,
+        session_id: String,
+        now: String,
+    ) -> Result<Session, AppError> {
+        let ended = self
+            .active
+            .take()
+            .ok_or_else(|| AppError::internal("アクティブセッションがありません"))?;
+        let mut next = Session::new(session_id, ended.mode, ended.deliver_policy.clone(), now);
+        next.state = ended.state.clone();
+        next.task = ended.task;
+        self.active = Some(next);
+        Ok(ended)
+    }
+
+    /// pause_recording: Recording/Paused→Idle（パイプラインモード用）
     pub fn pause_recording(&mut self, now: String) -> Result<StateTransition, AppError> {
         let session = self
             .active
@@ -105,7 +137,7 @@ impl SessionManager {
         let prev = session.state.as_str().to_string();
 
         match &session.state {
-            SessionState::Recording => {
+            SessionState::Recording | SessionState::Paused => {
                 session.state = SessionState::Idle;
                 session.updated_at = now;
                 Ok(StateTransition {
@@ -121,6 +153,58 @@ impl SessionManager {
         }
     }
 
+    /// pause_capture: Recording→Paused（セッションを確定させずに録音だけ止める）
+    pub fn pause_capture(&mut self, now: String) -> Result<StateTransition, AppError> {
+        let session = self
+            .active
+            .as_mut()
+            .ok_or_else(|| AppError::internal("アクティブセッションがありません"))?;
+
+        let prev = session.state.as_str().to_string();
+
+        match &session.state {
+            SessionState::Recording => {
+                session.state = SessionState::Paused;
+                session.updated_at = now;
+                Ok(StateTransition {
+                    session_id: session.session_id.clone(),
+                    prev_state: prev,
+                    new_state: session.state.clone(),
+                })
+            }
+            other => Err(AppError::invalid_state(format!(
+                "pause_capture は {} 状態では実行できません",
+                other.as_str()
+            ))),
+        }
+    }
+
+    /// resume_capture: Paused→Recording
+    pub fn resume_capture(&mut self, now: String) -> Result<StateTransition, AppError> {
+        let session = self
+            .active
+            .as_mut()
+            .ok_or_else(|| AppError::internal("アクティブセッションがありません"))?;
+
+        let prev = session.state.as_str().to_string();
+
+        match &session.state {
+            SessionState::Paused => {
+                session.state = SessionState::Recording;
+                session.updated_at = now;
+                Ok(StateTransition {
+                    session_id: session.session_id.clone(),
+                    prev_state: prev,
+                    new_state: session.state.clone(),
+                })
+            }
+            other => Err(AppError::invalid_state(format!(
+                "resume_capture は {} 状態では実行できません",
+                other.as_str()
+            ))),
+        }
+    }
+
     /// toggle_recording: Idle→Recording, Recording→Transcribing
     pub fn toggle_recording(&mut self, now: String) -> Result<StateTransition, AppError> {
         let session = self
@@ -186,6 +270,62 @@ impl SessionManager {
         }
     }
 
+    /// STT完了（レビューあり）: Transcribing→Reviewing
+    pub fn start_review(&mut self, now: String) -> Result<StateTransition, AppError> {
+        let session = self
+            .active
+            .as_mut()
+            .ok_or_else(|| AppError::internal("アクティブセッションがありません"))?;
+
+        let prev = session.state.as_str().to_string();
+
+        match &session.state {
+            SessionState::Transcribing => {
+                session.state = SessionState::Reviewing;
+                session.updated_at = now;
+                Ok(StateTransition {
+                    session_id: session.session_id.clone(),
+                    prev_state: prev,
+                    new_state: session.state.clone(),
+                })
+            }
+            other => Err(AppError::invalid_state(format!(
+                "start_review は {} 状態では実行できません",
+                other.as_str()
+            ))),
+        }
+    }
+
+    /// レビュー確定: Reviewing→Rewriting (mode≠raw) or Reviewing→Delivering (mode=raw)
+    pub fn commit_review(&mut self, now: String) -> Result<StateTransition, AppError> {
+        let session = self
+            .active
+            .as_mut()
+            .ok_or_else(|| AppError::internal("アクティブセッションがありません"))?;
+
+        let prev = session.state.as_str().to_string();
+
+        match &session.state {
+            SessionState::Reviewing => {
+                if session.mode == Mode::Raw {
+                    session.state = SessionState::Delivering;
+                } else {
+                    session.state = SessionState::Rewriting;
+                }
+                session.updated_at = now;
+                Ok(StateTransition {
+                    session_id: session.session_id.clone(),
+                    prev_state: prev,
+                    new_state: session.state.clone(),
+                })
+            }
+            other => Err(AppError::invalid_state(format!(
+                "commit_review は {} 状態では実行できません",
+                other.as_str()
+            ))),
+        }
+    }
+
     /// リライト完了: Rewriting→Delivering
     pub fn on_rewrite_done(&mut self, now: String) -> Result<StateTransition, AppError> {
         let session = self
@@ -297,6 +437,38 @@ impl SessionManager {
         session.mode = mode;
         Ok(())
     }
+
+    /// 出力ポリシー変更（次の配信から反映）
+    pub fn set_deliver_policy(&mut self, deliver_policy: DeliverPolicy) -> Result<(), AppError> {
+        let session = self
+            .active
+            .as_mut()
+            .ok_or_else(|| AppError::internal("アクティブセッションがありません"))?;
+        session.deliver_policy = deliver_policy;
+        Ok(())
+    }
+
+    /// Whisper タスク変更（次の録音開始から反映）
+    ///
+    /// 録音中のパイプラインはタスクを開始時に固定するため、録音中・一時停止中は変更できない。
+    pub fn set_task(&mut self, task: WhisperTask) -> Result<(), AppError> {
+        let session = self
+            .active
+            .as_mut()
+            .ok_or_else(|| AppError::internal("アクティブセッションがありません"))?;
+        match &session.state {
+            SessionState::Recording | SessionState::Paused => {
+                Err(AppError::invalid_state(format!(
+                    "set_task は {} 状態では実行できません",
+                    session.state.as_str()
+                )))
+            }
+            _ => {
+                session.task = task;
+                Ok(())
+            }
+        }
+    }
 }
 
 impl Default for SessionManager {
@@ -313,6 +485,71 @@ pub struct StateTransition {
     pub new_state: SessionState,
 }
 
+/// セッションのセグメント数が `max_segments_per_session` に達した通知
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SessionSegmentLimit {
+    pub session_id: String,
+    pub segment_count: u32,
+    pub limit: u32,
+    pub action: SegmentLimitAction,
+    /// `new_session` で引き継いだ新しいセッション（`stop` では None）
+    pub new_session_id: Option<String>,
+}
+
+/// パイプラインで確定した書き起こし 1 件の処理結果（UI・ヘッドレスの転送側がイベントに変換する）
+#[derive(Debug, Clone)]
+pub enum TranscriptOutcome {
+    /// 音声コマンドでモードを切り替えた（セグメントとしては保存しない）
+    ModeSwitched(ModeSwitchedByVoice),
+    /// セグメントとして保存した
+    Saved(SavedTranscript),
+}
+
+/// 保存した確定セグメント（[`TranscriptOutcome::Saved`]）
+#[derive(Debug, Clone)]
+pub struct SavedTranscript {
+    pub segment_id: String,
+    /// 後処理済みのテキスト
+    pub text: String,
+    pub confidence: f32,
+    /// 確定字幕（caption_update）を送る（`captions_enabled`）
+    pub caption: bool,
+    /// レビュー待ちにした（`review_before_deliver`）。リライト・配信は `commit_segment` の後に行う
+    pub for_review: bool,
+    /// セグメント数が上限に達して行った処理（上限未満なら None）
+    pub segment_limit: Option<(SessionSegmentLimit, Vec<StateTransition>)>,
+}
+
+/// セッションの録音時間が `max_session_duration_secs` に達した通知
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SessionDurationLimit {
+    pub session_id: String,
+    /// このセッションで録音した時間の累計（一時停止中を除く）
+    pub recorded_secs: u64,
+    pub limit_secs: u32,
+}
+
+/// 音声パイプラインの動作状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PipelineStatus {
+    Stopped,
+    Running,
+    Paused,
+}
+
+/// 現在の状態のスナップショット（UI の再読み込み時などにイベントを取りこぼした分を同期する）
+#[derive(Debug, Clone, Serialize)]
+pub struct AppSnapshot {
+    /// アクティブセッションが無ければ以下のセッション情報は None
+    pub session_id: Option<String>,
+    pub state: Option<SessionState>,
+    pub mode: Option<Mode>,
+    pub pipeline: PipelineStatus,
+    /// 直近の部分書き起こし（確定・録音開始でクリアされる）
+    pub last_partial_text: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -327,7 +564,7 @@ mod tests {
         mgr.start_session(
             "test-session".to_string(),
             Mode::Memo,
-            DeliverPolicy::Clipboard,
+            DeliverPolicy::Clipboard { wrap: None },
             now(),
         )
         .unwrap();
@@ -351,6 +588,41 @@ mod tests {
         assert_eq!(t.new_state, SessionState::Transcribing);
     }
 
+    #[test]
+    fn test_recording_paused_round_trip() {
+        let mut mgr = setup_manager();
+        mgr.toggle_recording(now()).unwrap();
+
+        let t = mgr.pause_capture(now()).unwrap();
+        assert_eq!(t.prev_state, "recording");
+        assert_eq!(t.new_state, SessionState::Paused);
+        // 一時停止中は録音の切り替えもできない（resume で戻す）
+        assert!(mgr.toggle_recording(now()).is_err());
+        assert!(mgr.pause_capture(now()).is_err());
+
+        let t = mgr.resume_capture(now()).unwrap();
+        assert_eq!(t.prev_state, "paused");
+        assert_eq!(t.new_state, SessionState::Recording);
+        assert!(mgr.resume_capture(now()).is_err());
+    }
+
+    #[test]
+    fn test_pause_capture_requires_recording() {
+        let mut mgr = setup_manager();
+        assert!(mgr.pause_capture(now()).is_err());
+        assert!(mgr.resume_capture(now()).is_err());
+    }
+
+    #[test]
+    fn test_pause_recording_from_paused_goes_idle() {
+        let mut mgr = setup_manager();
+        mgr.toggle_recording(now()).unwrap();
+        mgr.pause_capture(now()).unwrap();
+        let t = mgr.pause_recording(now()).unwrap();
+        assert_eq!(t.prev_state, "paused");
+        assert_eq!(t.new_state, SessionState::Idle);
+    }
+
     #[test]
     fn test_transcribing_to_rewriting_when_not_raw() {
         let mut mgr = setup_manager();
@@ -366,7 +638,7 @@ mod tests {
         mgr.start_session(
             "raw-session".to_string(),
             Mode::Raw,
-            DeliverPolicy::Clipboard,
+            DeliverPolicy::Clipboard { wrap: None },
             now(),
         )
         .unwrap();
@@ -376,6 +648,46 @@ mod tests {
         assert_eq!(t.new_state, SessionState::Delivering);
     }
 
+    #[test]
+    fn test_transcribing_to_reviewing_to_delivering_when_raw() {
+        let mut mgr = SessionManager::new();
+        mgr.start_session(
+            "review-session".to_string(),
+            Mode::Raw,
+            DeliverPolicy::Clipboard { wrap: None },
+            now(),
+        )
+        .unwrap();
+        mgr.toggle_recording(now()).unwrap();
+        mgr.toggle_recording(now()).unwrap();
+
+        let t = mgr.start_review(now()).unwrap();
+        assert_eq!(t.prev_state, "transcribing");
+        assert_eq!(t.new_state, SessionState::Reviewing);
+
+        let t = mgr.commit_review(now()).unwrap();
+        assert_eq!(t.prev_state, "reviewing");
+        assert_eq!(t.new_state, SessionState::Delivering);
+    }
+
+    #[test]
+    fn test_commit_review_goes_to_rewriting_when_not_raw() {
+        let mut mgr = setup_manager();
+        mgr.toggle_recording(now()).unwrap();
+        mgr.toggle_recording(now()).unwrap();
+        mgr.start_review(now()).unwrap();
+        let t = mgr.commit_review(now()).unwrap();
+        assert_eq!(t.new_state, SessionState::Rewriting);
+    }
+
+    #[test]
+    fn test_commit_review_requires_reviewing() {
+        let mut mgr = setup_manager();
+        mgr.toggle_recording(now()).unwrap();
+        assert!(mgr.commit_review(now()).is_err());
+        assert!(mgr.start_review(now()).is_err());
+    }
+
     #[test]
     fn test_rewriting_to_delivering() {
         let mut mgr = setup_manager();
@@ -457,6 +769,22 @@ mod tests {
         assert!(mgr.active().is_none());
     }
 
+    #[test]
+    fn test_rollover_session_keeps_state_and_settings() {
+        let mut mgr = setup_manager();
+        mgr.toggle_recording(now()).unwrap();
+        mgr.set_deliver_policy(DeliverPolicy::Paste).unwrap();
+
+        let ended = mgr.rollover_session("next".to_string(), now()).unwrap();
+        assert_eq!(ended.session_id, "test-session");
+
+        let active = mgr.active().unwrap();
+        assert_eq!(active.session_id, "next");
+        assert_eq!(active.state, SessionState::Recording);
+        assert_eq!(active.mode, Mode::Memo);
+        assert!(matches!(active.deliver_policy, DeliverPolicy::Paste));
+    }
+
     #[test]
     fn test_set_mode() {
         let mut mgr = setup_manager();
@@ -464,6 +792,18 @@ mod tests {
         assert_eq!(mgr.active().unwrap().mode, Mode::Tech);
     }
 
+    #[test]
+    fn test_set_task_rejected_while_recording() {
+        let mut mgr = setup_manager();
+        assert_eq!(mgr.active().unwrap().task, WhisperTask::Transcribe);
+        mgr.set_task(WhisperTask::Translate).unwrap();
+        assert_eq!(mgr.active().unwrap().task, WhisperTask::Translate);
+
+        mgr.toggle_recording(now()).unwrap();
+        assert!(mgr.set_task(WhisperTask::Transcribe).is_err());
+        assert_eq!(mgr.active().unwrap().task, WhisperTask::Translate);
+    }
+
     #[test]
     fn test_start_session_with_paste_policy() {
         let mut mgr = SessionManager::new();
@@ -1,5 +1,22 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 
+use super::types::{DeliverPolicy, DeliverTarget, Mode};
+
+/// セグメント自動削除日数の上限
+const MAX_SEGMENT_TTL_DAYS: u32 = 3650;
+/// リライトのタイムアウトの許容範囲（秒）
+const REWRITE_TIMEOUT_SECS_RANGE: std::ops::RangeInclusive<u64> = 1..=600;
+/// セグメント長（VAD 最大長・連続モードのチャンク長）の許容範囲（ms）
+const SEGMENT_MS_RANGE: std::ops::RangeInclusive<u64> = 1_000..=120_000;
+/// 事前キャプチャで保持する秒数の許容範囲
+const PREBUFFER_SECS_RANGE: std::ops::RangeInclusive<u32> = 1..=10;
+/// 無音による自動停止までの秒数の上限
+const MAX_SESSION_IDLE_TIMEOUT_SECS: u32 = 3600;
+const MAX_TOGGLE_DEBOUNCE_MS: u32 = 2000;
+
 /// アプリケーション設定
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSettings {
@@ -11,6 +28,30 @@ pub struct AppSettings {
     pub default_deliver_target: String,
     /// リライト有効/無効（デフォルト）
     pub rewrite_enabled: bool,
+    /// モードごとの自動リライト有効/無効（未指定のモードは rewrite_enabled に従う）
+    #[serde(default)]
+    pub auto_rewrite_by_mode: HashMap<Mode, bool>,
+    /// モードごとの Whisper initial_prompt プレフィックス（辞書ヒントの前に置く文）
+    #[serde(default)]
+    pub whisper_prompt_prefix: HashMap<Mode, String>,
+    /// Whisper initial_prompt の最大文字数（0=無制限）
+    ///
+    /// 辞書が大きいとプロンプトが Whisper のコンテキストに収まらず精度が落ちるため、
+    /// 超えた分は優先度の低い辞書ヒントから落とす。
+    #[serde(default = "default_max_prompt_chars")]
+    pub max_prompt_chars: u32,
+    /// 辞書ヒントを直前のセグメントに関係する語に絞る（全語を渡すとプロンプトが薄まるため）
+    #[serde(default)]
+    pub smart_hints: bool,
+    /// smart_hints 有効時に STT へ渡す辞書ヒントの最大件数
+    #[serde(default = "default_smart_hints_max_terms")]
+    pub smart_hints_max_terms: u32,
+    /// モードごとの出力ポリシー（出力先を指定せずに開始したときと、モード切り替え時に使う）
+    #[serde(default)]
+    pub deliver_policy_by_mode: HashMap<Mode, DeliverPolicy>,
+    /// リライト 1 回あたりのタイムアウト（秒）
+    #[serde(default = "default_rewrite_timeout_secs")]
+    pub rewrite_timeout_secs: u64,
     /// 貼り付けallowlist（bundle id）
     pub paste_allowlist: Vec<String>,
     /// 貼り付け前に確認するか
@@ -19,9 +60,15 @@ pub struct AppSettings {
     pub audio_retention: AudioRetention,
     /// セグメント自動削除（日数、0=無期限）
     pub segment_ttl_days: u32,
+    /// 保持するセッション数の上限（超えた分は古い順に削除、ピン留めは対象外。0=無制限）
+    #[serde(default)]
+    pub max_history_sessions: u32,
     /// グローバルホットキー（toggle_recording）
     pub hotkey_toggle: String,
-    /// Claude API キー（ローカル SQLite に保存）
+    /// 直前の録音トグルからこのミリ秒数以内のトグルを無視する（ホットキーのチャタリング・ダブルクリック対策、0=無効）
+    #[serde(default = "default_toggle_debounce_ms")]
+    pub toggle_debounce_ms: u32,
+    /// Claude API キー（実体はシークレットストアに保存し、読み込み時は `secret:claude_api_key` 参照のまま返す）
     pub claude_api_key: Option<String>,
     /// Soniox API キー
     pub soniox_api_key: Option<String>,
@@ -29,8 +76,267 @@ pub struct AppSettings {
     pub language: String,
     /// VAD セグメント最大長のオーバーライド（ms, None=エンジンデフォルト）
     pub vad_max_segment_ms: Option<u64>,
+    /// VAD 設定（None の場合は STT エンジン別のデフォルト）
+    #[serde(default)]
+    pub vad: Option<VadConfig>,
     /// Whisper モデルサイズ
     pub whisper_model_size: WhisperModelSize,
+    /// 空きメモリが足りないとき、起動時に収まる小さいインストール済みモデルへ切り替える
+    #[serde(default)]
+    pub auto_downgrade_model: bool,
+    /// Whisper のエントロピーしきい値（これを超える繰り返し気味の結果は温度を上げて再デコード）
+    #[serde(default = "default_whisper_entropy_thold")]
+    pub whisper_entropy_thold: f32,
+    /// Whisper の平均対数確率しきい値（これを下回る自信の無い結果は温度を上げて再デコード）
+    #[serde(default = "default_whisper_logprob_thold")]
+    pub whisper_logprob_thold: f32,
+    /// 音声の区切り方（VAD / 連続）
+    #[serde(default)]
+    pub capture_mode: CaptureMode,
+    /// 複数チャンネル入力のチャンネル選択
+    #[serde(default)]
+    pub input_channel: ChannelMode,
+    /// 入力デバイスのネイティブサンプルレートがこれ未満なら警告する（Hz、0=警告しない）
+    #[serde(default = "default_min_native_sample_rate")]
+    pub min_native_sample_rate: u32,
+    /// 連続モードの目標チャンク長（ms）
+    #[serde(default = "default_chunk_target_ms")]
+    pub chunk_target_ms: u64,
+    /// 連続モードで切れ目を探す窓の幅（ms）
+    #[serde(default = "default_boundary_search_ms")]
+    pub boundary_search_ms: u64,
+    /// 利用可能な STT エンジンが無い場合に Noop（モック結果）を使う（開発用）
+    #[serde(default)]
+    pub allow_noop_stt: bool,
+    /// ローカルエンジンが使えない場合にクラウド（Soniox）へフォールバックする
+    ///
+    /// 音声を外部へ送ることになるため既定では無効（クラウドは選択中のときだけ使う）。
+    #[serde(default)]
+    pub allow_cloud_fallback: bool,
+    /// 同じ音声の書き起こし結果をメモリ上で再利用する（開発時の繰り返し用、録音では通常オフ）
+    #[serde(default)]
+    pub cache_transcripts: bool,
+    /// 確定結果と一緒に直前の部分書き起こしをセグメントに保存する（認識の揺れを調べる開発用）
+    ///
+    /// 部分書き起こしを返さないエンジン（Whisper など）でも、有効なら発話中の短い間で書き起こしを試して部分書き起こしを作る。
+    #[serde(default)]
+    pub store_partials: bool,
+    /// 配信テキストを蓄積し、クリップボードには蓄積済みの全文を書き込む（連続メモ用）
+    #[serde(default)]
+    pub accumulate_deliveries: bool,
+    /// 蓄積時の区切り
+    #[serde(default)]
+    pub accumulate_separator: AccumulateSeparator,
+    /// クリップボード出力後に読み戻して書き込みを確認する（サンドボックス環境向け）
+    #[serde(default)]
+    pub verify_clipboard: bool,
+    /// 1 回の配信の最大文字数（超えた分は切り捨てて deliver_truncated を通知、0=無制限）
+    #[serde(default)]
+    pub max_deliver_chars: u32,
+    /// 配信時に揃える改行コード
+    #[serde(default)]
+    pub deliver_line_endings: LineEnding,
+    /// 配信時に行末・末尾の空白を取り除く
+    #[serde(default)]
+    pub deliver_trim: bool,
+    /// 書き起こし後、配信前にテキストを確認・修正する（Reviewing 状態で待機）
+    #[serde(default)]
+    pub review_before_deliver: bool,
+    /// 確定テキストの機密情報マスキングを行うか
+    #[serde(default)]
+    pub redaction_enabled: bool,
+    /// マスキング規則（辞書置換の後に上から順に適用）
+    #[serde(default = "default_redaction_rules")]
+    pub redaction_rules: Vec<RedactionRule>,
+    /// STT 結果の繰り返し・定型句フィルタを行うか
+    #[serde(default)]
+    pub transcript_filter_enabled: bool,
+    /// 同じ句がこの回数を超えて連続したら 1 つにまとめる
+    #[serde(default = "default_repetition_threshold")]
+    pub repetition_threshold: usize,
+    /// 除去する定型句（Whisper が無音から出力しがちなもの）
+    #[serde(default = "default_hallucination_blocklist")]
+    pub hallucination_blocklist: Vec<String>,
+    /// 日本語の数詞を算用数字に変換するか（"ひゃくにじゅうえん" → "120円"）
+    #[serde(default)]
+    pub number_normalization_enabled: bool,
+    /// 確定テキストが音声コマンド（「モード：議事録」など）ならモードを切り替え、そのセグメントは保存・配信しない
+    #[serde(default)]
+    pub voice_commands_enabled: bool,
+    /// 音声コマンドのフレーズと切り替え先のモード（空白・句読点は無視して全体一致で比較する）
+    #[serde(default = "default_voice_mode_commands")]
+    pub voice_mode_commands: HashMap<String, Mode>,
+    /// 待機中もマイクを開き、録音開始前の直近音声を最初のセグメントに含める
+    ///
+    /// 録音していない間も音声を取り込むため、プライバシーに配慮して既定は無効。
+    #[serde(default)]
+    pub prebuffer_enabled: bool,
+    /// 事前キャプチャで保持する秒数
+    #[serde(default = "default_prebuffer_secs")]
+    pub prebuffer_secs: u32,
+    /// 発話の無い状態がこの秒数続いたら録音を自動停止する（ハンズフリー用、0=無効）
+    #[serde(default)]
+    pub session_idle_timeout_secs: u32,
+    /// 録音中にキャプチャのフレームがこの秒数届かなければ入力ストリームを開き直す（0=監視しない）
+    #[serde(default = "default_capture_stall_timeout_secs")]
+    pub capture_stall_timeout_secs: u32,
+    /// 録音開始時に入力ストリームの初期化がこの秒数で終わらなければ開始を取りやめる（0=待ち続ける）
+    ///
+    /// 起動直後はデバイスの初期化が数秒止まることがあり、その間 UI から開始を取り消せないため。
+    #[serde(default = "default_device_init_timeout_secs")]
+    pub device_init_timeout_secs: u32,
+    /// 録音が止まる回復可能なエラー（デバイス切断など）の後、自動で Idle に戻す
+    #[serde(default)]
+    pub auto_recover: bool,
+    /// 1 セッションに保存するセグメント数の上限（0=無制限）
+    ///
+    /// 上限に達したら `segment_limit_action` に従って録音を止めるか、新しいセッションへ引き継ぐ。
+    #[serde(default)]
+    pub max_segments_per_session: u32,
+    #[serde(default)]
+    pub segment_limit_action: SegmentLimitAction,
+    /// 1 セッションで録音する時間の上限（秒、一時停止中は含まない。0=無制限）
+    ///
+    /// 録音を付けっぱなしにしたときの安全策。上限に達したら録音中のセグメントを確定して録音を止める。
+    #[serde(default)]
+    pub max_session_duration_secs: u32,
+    /// 発話中の短い間で書き起こしを試し、文末記号（。！？.!?）で終わっていれば無音の待ち時間を待たずに確定する
+    ///
+    /// 短い音声コマンドの遅延を減らすため。確定しなかった結果は部分書き起こしとして送る。
+    #[serde(default)]
+    pub commit_on_sentence_end: bool,
+    /// 字幕表示用の `caption_update` イベントを送る
+    ///
+    /// 部分書き起こしは後処理（辞書・句読点）を通さずパイプラインから直接送り、確定時だけ後処理済みのテキストを送る。
+    /// 部分書き起こしは発話中の短い間で試す書き起こしから作るため、`commit_on_sentence_end` に関係なく試す。
+    #[serde(default)]
+    pub captions_enabled: bool,
+    /// セグメント音声（WAV）の保存先（未指定なら VAD 設定の output_dir）
+    ///
+    /// システムの一時ディレクトリは他プロセスから読めるため、アプリ専用ディレクトリを指定できる。
+    /// どちらの場合もディレクトリは所有者のみアクセス可（unix では 0700）で作成する。
+    #[serde(default)]
+    pub audio_dir: Option<PathBuf>,
+    /// 音声ファイルを削除する前にゼロで上書きする
+    #[serde(default)]
+    pub secure_delete: bool,
+}
+
+/// Whisper の initial_prompt に使えるのはコンテキスト（448 トークン）の半分まで。日本語は 1 文字 1 トークン前後になる
+fn default_max_prompt_chars() -> u32 {
+    200
+}
+
+fn default_smart_hints_max_terms() -> u32 {
+    10
+}
+
+fn default_toggle_debounce_ms() -> u32 {
+    300
+}
+
+fn default_prebuffer_secs() -> u32 {
+    2
+}
+
+fn default_capture_stall_timeout_secs() -> u32 {
+    5
+}
+
+fn default_device_init_timeout_secs() -> u32 {
+    10
+}
+
+fn default_min_native_sample_rate() -> u32 {
+    16_000
+}
+
+fn default_whisper_entropy_thold() -> f32 {
+    2.4
+}
+
+fn default_whisper_logprob_thold() -> f32 {
+    -1.0
+}
+
+fn default_rewrite_timeout_secs() -> u64 {
+    30
+}
+
+fn default_repetition_threshold() -> usize {
+    3
+}
+
+/// Whisper が無音・ノイズから出力しがちな定型句
+pub fn default_hallucination_blocklist() -> Vec<String> {
+    [
+        "ご視聴ありがとうございました",
+        "チャンネル登録よろしくお願いします",
+        "最後までご視聴いただきありがとうございます",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+/// 既定の音声コマンド（「モード：<モード名>」）
+pub fn default_voice_mode_commands() -> HashMap<String, Mode> {
+    [
+        ("モード：そのまま", Mode::Raw),
+        ("モード：メモ", Mode::Memo),
+        ("モード：技術", Mode::Tech),
+        ("モード：メール", Mode::EmailJp),
+        ("モード：議事録", Mode::Minutes),
+    ]
+    .into_iter()
+    .map(|(phrase, mode)| (phrase.to_string(), mode))
+    .collect()
+}
+
+fn default_chunk_target_ms() -> u64 {
+    15_000
+}
+
+fn default_boundary_search_ms() -> u64 {
+    1_500
+}
+
+/// マスキング規則（正規表現にマッチした部分を置換文字列に置き換える）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactionRule {
+    /// 規則名（UI 表示用）
+    pub name: String,
+    /// 正規表現パターン
+    pub pattern: String,
+    /// 置換文字列
+    pub replacement: String,
+}
+
+impl RedactionRule {
+    fn new(name: &str, pattern: &str, replacement: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+        }
+    }
+}
+
+/// デフォルトのマスキング規則: メールアドレス、長い数字列（カード番号等）、電話番号
+pub fn default_redaction_rules() -> Vec<RedactionRule> {
+    vec![
+        RedactionRule::new(
+            "email",
+            r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}",
+            "[EMAIL]",
+        ),
+        RedactionRule::new("long_number", r"\d(?:[ -]?\d){11,}", "[NUMBER]"),
+        RedactionRule::new(
+            "phone",
+            r"(?:\+\d{1,3}[ -]?|0)\d{1,4}[ -]\d{1,4}[ -]\d{3,4}",
+            "[PHONE]",
+        ),
+    ]
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -53,6 +359,96 @@ pub enum AudioRetention {
     Permanent,
 }
 
+/// 音声セグメントの区切り方
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureMode {
+    /// VAD で発話区間ごとに区切る（デフォルト）
+    #[default]
+    Vad,
+    /// 一定間隔で、境界付近の低エネルギー点で区切る（BGM 下での長時間口述向け）
+    Continuous,
+}
+
+/// 複数チャンネル入力から mono を作る方法
+///
+/// 片側にだけマイクが繋がったオーディオインターフェースでは、平均すると信号が半減し
+/// 空きチャンネルのノイズも混ざるため、使うチャンネルを選べるようにする。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelMode {
+    /// 全チャンネルの平均（デフォルト）
+    #[default]
+    Mix,
+    /// 左（チャンネル 0）
+    Left,
+    /// 右（チャンネル 1）
+    Right,
+    /// 0 始まりのチャンネル番号
+    Channel(usize),
+}
+
+impl ChannelMode {
+    /// 取り出すチャンネル番号（Mix は None）
+    pub fn index(self) -> Option<usize> {
+        match self {
+            Self::Mix => None,
+            Self::Left => Some(0),
+            Self::Right => Some(1),
+            Self::Channel(index) => Some(index),
+        }
+    }
+}
+
+/// 配信時の改行コード
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    /// `\n`（デフォルト）
+    #[default]
+    Lf,
+    /// `\r\n`（Windows 向けツール・リモートセッション）
+    CrLf,
+    /// 実行中の OS の改行（Windows は `\r\n`、それ以外は `\n`）
+    Native,
+}
+
+impl LineEnding {
+    /// 改行文字列
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+            Self::Native if cfg!(windows) => "\r\n",
+            Self::Native => "\n",
+        }
+    }
+}
+
+/// 蓄積配信の区切り
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccumulateSeparator {
+    /// 改行（デフォルト）
+    #[default]
+    Newline,
+    /// 半角スペース
+    Space,
+    /// 箇条書き（"- " + 改行）
+    Bullet,
+}
+
+/// セッションのセグメント数が上限に達したときの動作
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentLimitAction {
+    /// セッションを停止する（デフォルト）
+    #[default]
+    Stop,
+    /// 同じモード・出力ポリシーの新しいセッションを開始し、録音はそのまま続ける
+    NewSession,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum WhisperModelSize {
@@ -93,16 +489,725 @@ impl Default for AppSettings {
             default_mode: "raw".to_string(),
             default_deliver_target: "clipboard".to_string(),
             rewrite_enabled: false,
+            auto_rewrite_by_mode: HashMap::new(),
+            whisper_prompt_prefix: HashMap::new(),
+            max_prompt_chars: default_max_prompt_chars(),
+            smart_hints: false,
+            smart_hints_max_terms: default_smart_hints_max_terms(),
+            deliver_policy_by_mode: HashMap::new(),
+            rewrite_timeout_secs: default_rewrite_timeout_secs(),
             paste_allowlist: vec![],
             paste_confirm: true,
             audio_retention: AudioRetention::None,
             segment_ttl_days: 0,
+            max_history_sessions: 0,
             hotkey_toggle: "CmdOrCtrl+Shift+R".to_string(),
+            toggle_debounce_ms: default_toggle_debounce_ms(),
             claude_api_key: None,
             soniox_api_key: None,
             language: "ja-JP".to_string(),
             vad_max_segment_ms: None,
+            vad: None,
             whisper_model_size: WhisperModelSize::Base,
+            auto_downgrade_model: false,
+            whisper_entropy_thold: default_whisper_entropy_thold(),
+            whisper_logprob_thold: default_whisper_logprob_thold(),
+            capture_mode: CaptureMode::Vad,
+            input_channel: ChannelMode::Mix,
+            min_native_sample_rate: default_min_native_sample_rate(),
+            chunk_target_ms: default_chunk_target_ms(),
+            boundary_search_ms: default_boundary_search_ms(),
+            allow_noop_stt: false,
+            allow_cloud_fallback: false,
+            cache_transcripts: false,
+            store_partials: false,
+            accumulate_deliveries: false,
+            accumulate_separator: AccumulateSeparator::Newline,
+            verify_clipboard: false,
+            max_deliver_chars: 0,
+            deliver_line_endings: LineEnding::Lf,
+            deliver_trim: false,
+            review_before_deliver: false,
+            redaction_enabled: false,
+            redaction_rules: default_redaction_rules(),
+            transcript_filter_enabled: false,
+            repetition_threshold: default_repetition_threshold(),
+            hallucination_blocklist: default_hallucination_blocklist(),
+            number_normalization_enabled: false,
+            voice_commands_enabled: false,
+            voice_mode_commands: default_voice_mode_commands(),
+            prebuffer_enabled: false,
+            prebuffer_secs: default_prebuffer_secs(),
+            session_idle_timeout_secs: 0,
+            capture_stall_timeout_secs: default_capture_stall_timeout_secs(),
+            device_init_timeout_secs: default_device_init_timeout_secs(),
+            auto_recover: false,
+            max_segments_per_session: 0,
+            segment_limit_action: SegmentLimitAction::Stop,
+            max_session_duration_secs: 0,
+            commit_on_sentence_end: false,
+            captions_enabled: false,
+            audio_dir: None,
+            secure_delete: false,
+        }
+    }
+}
+
+/// VAD が扱う最短の時間単位（ms）。これより短いフレーム・判定時間は丸め誤差で計算が崩れる
+pub const MIN_VAD_FRAME_MS: u64 = 5;
+
+/// VAD（Voice Activity Detection）設定
+///
+/// 未指定の項目はデフォルト値で補う（設定画面から一部だけ保存できるように）。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VadConfig {
+    /// RMS エネルギーしきい値（これ以上で発話とみなす）
+    pub energy_threshold: f32,
+    /// 無音タイムアウト（ms）：この長さ無音が続いたらセグメント終了
+    pub silence_timeout_ms: u64,
+    /// セグメント最大長（ms）：強制カット
+    pub max_segment_ms: u64,
+    /// 発話開始に必要な連続音声時間（ms）：ヒステリシス
+    pub speech_start_ms: u64,
+    /// 動的しきい値を有効にする（キャリブレーション）
+    pub adaptive_threshold: bool,
+    /// キャリブレーション期間（ms）
+    pub calibration_duration_ms: u64,
+    /// ノイズフロアに対する倍率（動的しきい値 = noise_floor * multiplier）
+    pub threshold_multiplier: f32,
+    /// ZCR（ゼロクロッシング率）を発話判定に使用する
+    pub use_zcr: bool,
+    /// ZCR しきい値（これ以上で発話候補）
+    pub zcr_threshold: f32,
+    /// セグメント確定時に RMS ゲインを適用する（有効時はピーク正規化の代わりに使う）
+    pub normalize_gain: bool,
+    /// RMS ゲインの目標値
+    pub target_rms: f32,
+    /// セグメント確定時に前後のしきい値未満の区間（無音）を切り詰める
+    pub trim_silence: bool,
+    /// 切り詰め後もこの長さ（ms）は残す
+    pub min_segment_ms: u64,
+    /// セグメント音声（WAV）の書き出し先
+    pub output_dir: PathBuf,
+    /// セグメント WAV のビット深度（16 で 16bit PCM、32 で 32bit float）
+    pub wav_bits: u16,
+    /// 入力レベルメーターの平滑化係数（0〜1、1 で平滑化なし）。発話判定には影響しない
+    pub level_smoothing_alpha: f32,
+    /// 入力レベルイベントを送る頻度（回/秒、0 でフレームごと）。間引いた間のピーク値を送る
+    pub level_event_hz: u32,
+    /// フレーム中のクリップ（絶対値 0.99 以上）したサンプルの割合がこれ以上なら clipping イベントを送る（0 で無効）
+    pub clip_ratio_threshold: f32,
+    /// キャプチャ段で自動ゲイン制御（AGC）を行う（録音開始時に反映）
+    pub agc: bool,
+    /// AGC の目標 RMS
+    pub agc_target_rms: f32,
+    /// AGC の最大ゲイン（倍率）
+    pub agc_max_gain: f32,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            energy_threshold: 0.02,
+            silence_timeout_ms: 700,
+            max_segment_ms: 30_000,
+            speech_start_ms: 50,
+            adaptive_threshold: true,
+            calibration_duration_ms: 2000,
+            threshold_multiplier: 3.0,
+            use_zcr: false,
+            zcr_threshold: 0.3,
+            normalize_gain: false,
+            target_rms: 0.1,
+            trim_silence: false,
+            min_segment_ms: 300,
+            output_dir: std::env::temp_dir().join("voiceTypeless"),
+            wav_bits: 16,
+            level_smoothing_alpha: 0.3,
+            level_event_hz: 15,
+            clip_ratio_threshold: 0.01,
+            agc: false,
+            agc_target_rms: 0.1,
+            agc_max_gain: 10.0,
+        }
+    }
+}
+
+impl VadConfig {
+    /// STT エンジンに応じた最適なデフォルト設定を返す
+    pub fn for_engine(engine: SttEngineChoice) -> Self {
+        match engine {
+            SttEngineChoice::Whisper => Self {
+                max_segment_ms: 20_000,
+                ..Default::default()
+            },
+            SttEngineChoice::Apple => Self {
+                max_segment_ms: 60_000,
+                silence_timeout_ms: 1000,
+                ..Default::default()
+            },
+            SttEngineChoice::Cloud => Self::default(),
+            SttEngineChoice::Soniox => Self::default(),
+        }
+    }
+
+    /// サンプルレートとの組み合わせを含めて設定値を検証する（不正な項目をすべて返す）
+    pub fn validate(&self, sample_rate: u32) -> Result<(), Vec<SettingsError>> {
+        let mut errors = Vec::new();
+
+        if u64::from(sample_rate) * MIN_VAD_FRAME_MS / 1000 == 0 {
+            errors.push(SettingsError::new(
+                "vad.sample_rate",
+                format!("{sample_rate}Hz では {MIN_VAD_FRAME_MS}ms のフレームを構成できません"),
+            ));
+        }
+        if !(f32::EPSILON..=1.0).contains(&self.energy_threshold) {
+            errors.push(SettingsError::new(
+                "vad.energy_threshold",
+                "0 より大きく 1 以下の値を指定してください",
+            ));
+        }
+        if self.speech_start_ms < MIN_VAD_FRAME_MS {
+            errors.push(SettingsError::new(
+                "vad.speech_start_ms",
+                format!("{MIN_VAD_FRAME_MS}ms 以上を指定してください"),
+            ));
+        }
+        if self.silence_timeout_ms < MIN_VAD_FRAME_MS {
+            errors.push(SettingsError::new(
+                "vad.silence_timeout_ms",
+                format!("{MIN_VAD_FRAME_MS}ms 以上を指定してください"),
+            ));
+        }
+        if self.speech_start_ms >= self.max_segment_ms {
+            errors.push(SettingsError::new(
+                "vad.speech_start_ms",
+                "max_segment_ms より小さい値を指定してください",
+            ));
+        }
+        if self.silence_timeout_ms >= self.max_segment_ms {
+            errors.push(SettingsError::new(
+                "vad.silence_timeout_ms",
+                "max_segment_ms より小さい値を指定してください",
+            ));
+        }
+        if !matches!(self.wav_bits, 16 | 32) {
+            errors.push(SettingsError::new(
+                "vad.wav_bits",
+                "16 か 32 を指定してください",
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.clip_ratio_threshold) {
+            errors.push(SettingsError::new(
+                "vad.clip_ratio_threshold",
+                "0 以上 1 以下の値を指定してください",
+            ));
+        }
+        if self.agc_max_gain < 1.0 {
+            errors.push(SettingsError::new(
+                "vad.agc_max_gain",
+                "1.0 以上を指定してください",
+            ));
+        }
+        if self.min_segment_ms > self.max_segment_ms {
+            errors.push(SettingsError::new(
+                "vad.min_segment_ms",
+                "max_segment_ms 以下の値を指定してください",
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// 不正な項目を動作可能な範囲に丸めた設定を返す（サンプルレートは呼び出し側で補正する）
+    pub fn clamped(mut self) -> Self {
+        self.energy_threshold = self.energy_threshold.clamp(f32::EPSILON, 1.0);
+        self.max_segment_ms = self.max_segment_ms.max(MIN_VAD_FRAME_MS * 2);
+        let upper = self.max_segment_ms - MIN_VAD_FRAME_MS;
+        self.speech_start_ms = self.speech_start_ms.clamp(MIN_VAD_FRAME_MS, upper);
+        self.silence_timeout_ms = self.silence_timeout_ms.clamp(MIN_VAD_FRAME_MS, upper);
+        self.min_segment_ms = self.min_segment_ms.min(self.max_segment_ms);
+        if !matches!(self.wav_bits, 16 | 32) {
+            self.wav_bits = 16;
+        }
+        self
+    }
+}
+
+/// 設定値の検証エラー（項目ごと）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SettingsError {
+    /// 不正な項目名（AppSettings のフィールド名）
+    pub field: String,
+    pub message: String,
+}
+
+impl SettingsError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl AppSettings {
+    /// 書き起こし確定後に自動リライトすべきか（Raw は常に false）
+    pub fn should_auto_rewrite(&self, mode: Mode) -> bool {
+        if mode == Mode::Raw {
+            return false;
+        }
+        self.auto_rewrite_by_mode
+            .get(&mode)
+            .copied()
+            .unwrap_or(self.rewrite_enabled)
+    }
+
+    /// モードに対応する Whisper initial_prompt プレフィックス（未設定なら None）
+    pub fn prompt_prefix_for(&self, mode: Option<Mode>) -> Option<String> {
+        mode.and_then(|m| self.whisper_prompt_prefix.get(&m))
+            .filter(|p| !p.trim().is_empty())
+            .cloned()
+    }
+
+    /// STT へ渡す辞書ヒントの最大件数（smart_hints が無効なら None）
+    pub fn smart_hints_limit(&self) -> Option<usize> {
+        self.smart_hints
+            .then_some(self.smart_hints_max_terms as usize)
+    }
+
+    /// モードの既定の出力ポリシー（未設定のモードは default_deliver_target、不明ならクリップボード）
+    pub fn deliver_policy_for(&self, mode: Mode) -> DeliverPolicy {
+        self.deliver_policy_by_mode
+            .get(&mode)
+            .cloned()
+            .or_else(|| {
+                serde_json::from_value(serde_json::json!({ "target": self.default_deliver_target }))
+                    .ok()
+            })
+            .unwrap_or(DeliverPolicy::Clipboard { wrap: None })
+    }
+
+    /// 保存前に設定値を検証する（不正な項目をすべて返す）
+    pub fn validate(&self) -> Result<(), Vec<SettingsError>> {
+        let mut errors = Vec::new();
+
+        if serde_json::from_value::<Mode>(self.default_mode.clone().into()).is_err() {
+            errors.push(SettingsError::new(
+                "default_mode",
+                format!("不明なモードです: {}", self.default_mode),
+            ));
+        }
+        if serde_json::from_value::<DeliverTarget>(self.default_deliver_target.clone().into())
+            .is_err()
+        {
+            errors.push(SettingsError::new(
+                "default_deliver_target",
+                format!("不明な出力先です: {}", self.default_deliver_target),
+            ));
+        }
+        if !REWRITE_TIMEOUT_SECS_RANGE.contains(&self.rewrite_timeout_secs) {
+            errors.push(SettingsError::new(
+                "rewrite_timeout_secs",
+                format!(
+                    "{}〜{} の範囲で指定してください",
+                    REWRITE_TIMEOUT_SECS_RANGE.start(),
+                    REWRITE_TIMEOUT_SECS_RANGE.end()
+                ),
+            ));
+        }
+        for bundle_id in &self.paste_allowlist {
+            if !is_valid_bundle_id(bundle_id) {
+                errors.push(SettingsError::new(
+                    "paste_allowlist",
+                    format!("bundle id の形式が不正です: {bundle_id}"),
+                ));
+            }
+        }
+        if self.segment_ttl_days > MAX_SEGMENT_TTL_DAYS {
+            errors.push(SettingsError::new(
+                "segment_ttl_days",
+                format!("0〜{MAX_SEGMENT_TTL_DAYS} の範囲で指定してください"),
+            ));
+        }
+        if self.smart_hints && self.smart_hints_max_terms == 0 {
+            errors.push(SettingsError::new(
+                "smart_hints_max_terms",
+                "1 以上を指定してください",
+            ));
+        }
+        if self.hotkey_toggle.trim().is_empty() {
+            errors.push(SettingsError::new("hotkey_toggle", "ホットキーが空です"));
+        }
+        if self.toggle_debounce_ms > MAX_TOGGLE_DEBOUNCE_MS {
+            errors.push(SettingsError::new(
+                "toggle_debounce_ms",
+                format!("0〜{MAX_TOGGLE_DEBOUNCE_MS} の範囲で指定してください"),
+            ));
+        }
+        if !is_valid_language(&self.language) {
+            errors.push(SettingsError::new(
+                "language",
+                format!("言語コードの形式が不正です: {}", self.language),
+            ));
+        }
+        if let Some(max_ms) = self.vad_max_segment_ms {
+            if !SEGMENT_MS_RANGE.contains(&max_ms) {
+                errors.push(SettingsError::new(
+                    "vad_max_segment_ms",
+                    format!(
+                        "{}〜{} の範囲で指定してください",
+                        SEGMENT_MS_RANGE.start(),
+                        SEGMENT_MS_RANGE.end()
+                    ),
+                ));
+            }
+        }
+        if !SEGMENT_MS_RANGE.contains(&self.chunk_target_ms) {
+            errors.push(SettingsError::new(
+                "chunk_target_ms",
+                format!(
+                    "{}〜{} の範囲で指定してください",
+                    SEGMENT_MS_RANGE.start(),
+                    SEGMENT_MS_RANGE.end()
+                ),
+            ));
         }
+        if self.boundary_search_ms >= self.chunk_target_ms {
+            errors.push(SettingsError::new(
+                "boundary_search_ms",
+                "chunk_target_ms より小さい値を指定してください",
+            ));
+        }
+        if self
+            .voice_mode_commands
+            .keys()
+            .any(|phrase| !phrase.chars().any(char::is_alphanumeric))
+        {
+            errors.push(SettingsError::new(
+                "voice_mode_commands",
+                "フレーズが空です（空白・記号だけのフレーズは使えません）",
+            ));
+        }
+        if !(1..=100).contains(&self.repetition_threshold) {
+            errors.push(SettingsError::new(
+                "repetition_threshold",
+                "1〜100 の範囲で指定してください",
+            ));
+        }
+        if !PREBUFFER_SECS_RANGE.contains(&self.prebuffer_secs) {
+            errors.push(SettingsError::new(
+                "prebuffer_secs",
+                format!(
+                    "{}〜{} の範囲で指定してください",
+                    PREBUFFER_SECS_RANGE.start(),
+                    PREBUFFER_SECS_RANGE.end()
+                ),
+            ));
+        }
+        if !(self.whisper_entropy_thold.is_finite() && self.whisper_entropy_thold > 0.0) {
+            errors.push(SettingsError::new(
+                "whisper_entropy_thold",
+                "0 より大きい値を指定してください",
+            ));
+        }
+        if !(self.whisper_logprob_thold.is_finite() && self.whisper_logprob_thold <= 0.0) {
+            errors.push(SettingsError::new(
+                "whisper_logprob_thold",
+                "0 以下の値を指定してください",
+            ));
+        }
+        if self.session_idle_timeout_secs > MAX_SESSION_IDLE_TIMEOUT_SECS {
+            errors.push(SettingsError::new(
+                "session_idle_timeout_secs",
+                format!("0〜{MAX_SESSION_IDLE_TIMEOUT_SECS} の範囲で指定してください"),
+            ));
+        }
+        if let Some(dir) = &self.audio_dir {
+            if !dir.is_absolute() {
+                errors.push(SettingsError::new(
+                    "audio_dir",
+                    format!("絶対パスを指定してください: {}", dir.display()),
+                ));
+            }
+        }
+        for rule in &self.redaction_rules {
+            if let Err(e) = regex::Regex::new(&rule.pattern) {
+                errors.push(SettingsError::new(
+                    "redaction_rules",
+                    format!("正規表現が不正です（{}）: {e}", rule.name),
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// 言語コード（"ja", "ja-JP", "auto" など）の形式チェック
+fn is_valid_language(code: &str) -> bool {
+    if code == "auto" {
+        return true;
+    }
+    let (lang, region) = match code.split_once('-') {
+        Some((lang, region)) => (lang, Some(region)),
+        None => (code, None),
+    };
+    let lang_ok = (2..=3).contains(&lang.len()) && lang.chars().all(|c| c.is_ascii_lowercase());
+    let region_ok = match region {
+        None => true,
+        Some(r) => r.len() == 2 && r.chars().all(|c| c.is_ascii_uppercase()),
+    };
+    lang_ok && region_ok
+}
+
+/// macOS bundle id（逆ドメイン形式、例: "com.apple.TextEdit"）の形式チェック
+fn is_valid_bundle_id(id: &str) -> bool {
+    let segments: Vec<&str> = id.split('.').collect();
+    segments.len() >= 2
+        && segments.iter().all(|seg| {
+            !seg.is_empty()
+                && seg
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid_fields(settings: &AppSettings) -> Vec<String> {
+        settings
+            .validate()
+            .unwrap_err()
+            .into_iter()
+            .map(|e| e.field)
+            .collect()
+    }
+
+    #[test]
+    fn test_default_settings_are_valid() {
+        assert!(AppSettings::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_default_mode() {
+        let settings = AppSettings {
+            default_mode: "poem".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(invalid_fields(&settings), vec!["default_mode"]);
+    }
+
+    #[test]
+    fn test_invalid_deliver_target() {
+        let settings = AppSettings {
+            default_deliver_target: "fax".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(invalid_fields(&settings), vec!["default_deliver_target"]);
+    }
+
+    #[test]
+    fn test_invalid_bundle_id() {
+        let settings = AppSettings {
+            paste_allowlist: vec!["com.apple.TextEdit".to_string(), "TextEdit".to_string()],
+            ..Default::default()
+        };
+        let errors = settings.validate().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "paste_allowlist");
+        assert!(errors[0].message.contains("TextEdit"));
+
+        let settings = AppSettings {
+            paste_allowlist: vec!["com..app".to_string(), "com.my app".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(invalid_fields(&settings).len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_rewrite_timeout() {
+        let settings = AppSettings {
+            rewrite_timeout_secs: 0,
+            ..Default::default()
+        };
+        assert_eq!(invalid_fields(&settings), vec!["rewrite_timeout_secs"]);
+    }
+
+    #[test]
+    fn test_invalid_prebuffer_secs() {
+        let settings = AppSettings {
+            prebuffer_secs: 0,
+            ..Default::default()
+        };
+        assert_eq!(invalid_fields(&settings), vec!["prebuffer_secs"]);
+    }
+
+    #[test]
+    fn test_invalid_whisper_thresholds() {
+        let settings = AppSettings {
+            whisper_entropy_thold: 0.0,
+            whisper_logprob_thold: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(
+            invalid_fields(&settings),
+            vec!["whisper_entropy_thold", "whisper_logprob_thold"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_session_idle_timeout() {
+        let settings = AppSettings {
+            session_idle_timeout_secs: 86_400,
+            ..Default::default()
+        };
+        assert_eq!(invalid_fields(&settings), vec!["session_idle_timeout_secs"]);
+    }
+
+    #[test]
+    fn test_invalid_blank_voice_command() {
+        let mut settings = AppSettings::default();
+        settings
+            .voice_mode_commands
+            .insert("： 。".to_string(), Mode::Memo);
+        assert_eq!(invalid_fields(&settings), vec!["voice_mode_commands"]);
+    }
+
+    #[test]
+    fn test_invalid_relative_audio_dir() {
+        let settings = AppSettings {
+            audio_dir: Some(PathBuf::from("recordings")),
+            ..Default::default()
+        };
+        assert_eq!(invalid_fields(&settings), vec!["audio_dir"]);
+    }
+
+    #[test]
+    fn test_invalid_ttl() {
+        let settings = AppSettings {
+            segment_ttl_days: 100_000,
+            ..Default::default()
+        };
+        assert_eq!(invalid_fields(&settings), vec!["segment_ttl_days"]);
+    }
+
+    #[test]
+    fn test_empty_hotkey() {
+        let settings = AppSettings {
+            hotkey_toggle: "  ".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(invalid_fields(&settings), vec!["hotkey_toggle"]);
+    }
+
+    #[test]
+    fn test_language_codes() {
+        for ok in ["ja", "ja-JP", "en-US", "yue", "auto"] {
+            let settings = AppSettings {
+                language: ok.to_string(),
+                ..Default::default()
+            };
+            assert!(settings.validate().is_ok(), "{ok} should be valid");
+        }
+        for bad in ["", "japanese", "ja_JP", "JA-jp", "ja-JP-x"] {
+            let settings = AppSettings {
+                language: bad.to_string(),
+                ..Default::default()
+            };
+            assert_eq!(invalid_fields(&settings), vec!["language"], "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_should_auto_rewrite_by_mode() {
+        let settings = AppSettings {
+            rewrite_enabled: true,
+            auto_rewrite_by_mode: HashMap::from([
+                (Mode::Raw, true),
+                (Mode::Memo, true),
+                (Mode::EmailJp, false),
+            ]),
+            ..Default::default()
+        };
+        // Raw は設定に関わらず常にスキップ
+        assert!(!settings.should_auto_rewrite(Mode::Raw));
+        assert!(settings.should_auto_rewrite(Mode::Memo));
+        assert!(!settings.should_auto_rewrite(Mode::EmailJp));
+        // 未指定のモードは rewrite_enabled に従う
+        assert!(settings.should_auto_rewrite(Mode::Tech));
+        assert!(!AppSettings::default().should_auto_rewrite(Mode::Tech));
+    }
+
+    #[test]
+    fn test_deliver_policy_for_mode() {
+        let settings = AppSettings {
+            deliver_policy_by_mode: HashMap::from([(Mode::Minutes, DeliverPolicy::FileAppend)]),
+            default_deliver_target: "keystroke".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            settings.deliver_policy_for(Mode::Minutes),
+            DeliverPolicy::FileAppend
+        ));
+        // 未設定のモードは default_deliver_target
+        assert!(matches!(
+            settings.deliver_policy_for(Mode::Raw),
+            DeliverPolicy::Keystroke
+        ));
+    }
+
+    #[test]
+    fn test_auto_rewrite_by_mode_serde() {
+        let json = serde_json::json!({"memo": true, "email_jp": false});
+        let map: HashMap<Mode, bool> = serde_json::from_value(json).unwrap();
+        assert_eq!(map.get(&Mode::Memo), Some(&true));
+        assert_eq!(map.get(&Mode::EmailJp), Some(&false));
+    }
+
+    #[test]
+    fn test_invalid_redaction_pattern() {
+        let mut settings = AppSettings::default();
+        settings
+            .redaction_rules
+            .push(RedactionRule::new("broken", "(unclosed", "***"));
+        let errors = settings.validate().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "redaction_rules");
+        assert!(errors[0].message.contains("broken"));
+    }
+
+    #[test]
+    fn test_invalid_segment_lengths() {
+        let settings = AppSettings {
+            vad_max_segment_ms: Some(10),
+            chunk_target_ms: 500,
+            ..Default::default()
+        };
+        assert_eq!(
+            invalid_fields(&settings),
+            vec![
+                "vad_max_segment_ms",
+                "chunk_target_ms",
+                "boundary_search_ms"
+            ]
+        );
     }
 }
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 /// 書き起こし/リライトモード
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Mode {
     Raw,
@@ -23,33 +23,116 @@ impl std::fmt::Display for Mode {
     }
 }
 
+/// Whisper の推論タスク
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WhisperTask {
+    /// 話した言語のまま書き起こす
+    #[default]
+    Transcribe,
+    /// 英語に翻訳して出力する（Whisper 以外のエンジンは書き起こしのみ）
+    Translate,
+}
+
+impl WhisperTask {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Transcribe => "transcribe",
+            Self::Translate => "translate",
+        }
+    }
+}
+
 /// 出力ポリシー
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "target", rename_all = "snake_case")]
 pub enum DeliverPolicy {
-    Clipboard,
+    Clipboard {
+        /// クリップボードへ書き込む前のテキスト整形（省略時は整形なし）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        wrap: Option<WrapStyle>,
+    },
     Paste,
     FileAppend,
     Webhook,
+    Keystroke,
+    /// クリップボードを使わず、アプリ内エディタへ deliver_to_editor イベントで渡す
+    AppEvent,
+    /// `OutputRouter::register` で登録した出力先に名前で配信する
+    Custom {
+        name: String,
+    },
 }
 
-/// 配信先ターゲット
+impl DeliverPolicy {
+    /// 配信先ターゲット
+    pub fn target(&self) -> DeliverTarget {
+        match self {
+            Self::Clipboard { .. } => DeliverTarget::Clipboard,
+            Self::Paste => DeliverTarget::Paste,
+            Self::FileAppend => DeliverTarget::FileAppend,
+            Self::Webhook => DeliverTarget::Webhook,
+            Self::Keystroke => DeliverTarget::Keystroke,
+            Self::AppEvent => DeliverTarget::AppEvent,
+            Self::Custom { name } => DeliverTarget::Custom(name.clone()),
+        }
+    }
+}
+
+/// クリップボード出力時のテキスト整形（ノートアプリ向け）
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
+pub enum WrapStyle {
+    None,
+    /// ``` で囲む
+    CodeFence,
+    /// 各行の先頭に "> " を付ける
+    Quote,
+    /// 先頭に "[timestamp] " を付ける
+    TimestampPrefix,
+}
+
+impl WrapStyle {
+    /// テキストを整形する（timestamp は TimestampPrefix でのみ使用）
+    pub fn apply(&self, text: &str, timestamp: &str) -> String {
+        match self {
+            Self::None => text.to_string(),
+            Self::CodeFence => format!("```\n{text}\n```"),
+            Self::Quote => text
+                .lines()
+                .map(|line| format!("> {line}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Self::TimestampPrefix => format!("[{timestamp}] {text}"),
+        }
+    }
+}
+
+/// 配信先ターゲット
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DeliverTarget {
     Clipboard,
     Paste,
     FileAppend,
     Webhook,
+    Keystroke,
+    AppEvent,
+    /// 登録済みの独自出力先（`OutputRouter` の登録名）
+    Custom(String),
 }
 
 impl DeliverTarget {
-    pub fn as_str(&self) -> &'static str {
+    /// `OutputRouter` に登録される出力先の名前
+    pub fn as_str(&self) -> &str {
         match self {
             Self::Clipboard => "clipboard",
             Self::Paste => "paste",
             Self::FileAppend => "file_append",
             Self::Webhook => "webhook",
+            Self::Keystroke => "keystroke",
+            Self::AppEvent => "app_event",
+            Self::Custom(name) => name,
         }
     }
 }
@@ -63,6 +146,35 @@ pub struct Segment {
     pub rewritten_text: Option<String>,
     pub confidence: f32,
     pub created_at: String,
+    /// 録音時にアクティブだったモード（辞書・リライトはこのモードで行う）
+    pub mode: Mode,
+    /// 書き起こしか英訳か（列追加前のセグメントは書き起こし）
+    #[serde(default)]
+    pub task: WhisperTask,
+    /// 確定直前の部分書き起こし（`store_partials` 有効時のみ保存）
+    #[serde(default)]
+    pub last_partial: Option<String>,
+}
+
+/// STT が返した単語・区間単位のタイミング（時刻はセグメント先頭からのミリ秒）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptTiming {
+    pub text: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// トークン確率（エンジンが返さない場合は None）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probability: Option<f32>,
+}
+
+/// 信頼度ヒートマップ用の区間（UI で低信頼度の箇所を色付けする）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SegmentTiming {
+    pub text: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// トークン確率（無い区間はセグメントの confidence で補う）
+    pub probability: f32,
 }
 
 /// セッションサマリー（履歴一覧用）
@@ -75,6 +187,12 @@ pub struct SessionSummary {
     pub updated_at: String,
     pub segment_count: u32,
     pub preview_text: Option<String>,
+    /// ピン留めされているか（履歴の先頭に固定表示する）
+    #[serde(default)]
+    pub pinned: bool,
+    /// セッションに付けたタグ（名前順）
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 /// セッション詳細（セグメント付き）
@@ -87,6 +205,19 @@ pub struct SessionDetail {
     pub segments: Vec<Segment>,
 }
 
+/// get_history の引数（UI・ヘッドレスモード共通）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetHistoryArgs {
+    #[serde(default)]
+    pub query: Option<String>,
+    pub limit: u32,
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub pinned_first: bool,
+}
+
 /// 履歴ページ（カーソルベースページネーション）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryPage {
@@ -94,6 +225,129 @@ pub struct HistoryPage {
     pub next_cursor: Option<String>,
 }
 
+/// セッション削除結果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeleteSessionResult {
+    pub sessions_deleted: u32,
+    pub segments_deleted: u32,
+    pub audio_files_deleted: u32,
+}
+
+/// 音声の保存先ディレクトリにある WAV の集計（audio_cache_stats）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudioCacheStats {
+    pub file_count: u32,
+    pub total_bytes: u64,
+    /// 最も古いファイルの更新日時（RFC 3339、ファイルが無ければ None）
+    pub oldest: Option<String>,
+}
+
+/// 保存済み音声の一括書き起こし: 1 セグメント分の進捗
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscribeProgress {
+    pub session_id: String,
+    pub segment_id: String,
+    /// 処理済みセグメント数（失敗を含む）
+    pub completed: u32,
+    pub total: u32,
+    /// 書き起こし結果（失敗時は None）
+    pub text: Option<String>,
+    /// 失敗理由（音声ファイルが無い等）
+    pub error: Option<String>,
+}
+
+/// 保存済み音声の一括書き起こし結果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscribeSessionResult {
+    pub transcribed: u32,
+    pub failed: u32,
+    /// 書き起こし済みのためスキップしたセグメント数
+    pub skipped: u32,
+}
+
+/// 音声ファイルの書き起こし結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscribeFileResult {
+    /// 書き起こしを保存した新しいセッション
+    pub session_id: String,
+    pub transcribed: u32,
+    pub failed: u32,
+}
+
+/// deliver_last で出力するテキストの選択
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliverText {
+    /// リライト済みがあればそれを、無ければ生テキスト
+    #[default]
+    Auto,
+    Raw,
+    Rewritten,
+}
+
+impl DeliverText {
+    /// 出力テキストと、Rewritten 指定なのに生テキストへフォールバックしたかを返す
+    pub fn select<'a>(&self, raw: &'a str, rewritten: Option<&'a str>) -> (&'a str, bool) {
+        match (self, rewritten) {
+            (Self::Raw, _) => (raw, false),
+            (_, Some(rewritten)) => (rewritten, false),
+            (Self::Auto, None) => (raw, false),
+            (Self::Rewritten, None) => (raw, true),
+        }
+    }
+}
+
+/// セッション全体のテキストを出力するときのセグメントの区切り
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JoinStyle {
+    /// 改行（デフォルト）
+    #[default]
+    Newline,
+    /// 空行（段落区切り）
+    DoubleNewline,
+    /// 半角スペース
+    Space,
+}
+
+impl JoinStyle {
+    pub fn separator(&self) -> &'static str {
+        match self {
+            Self::Newline => "\n",
+            Self::DoubleNewline => "\n\n",
+            Self::Space => " ",
+        }
+    }
+}
+
+/// deliver_last の結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliverLastResult {
+    pub text: String,
+    pub target: DeliverTarget,
+    /// リライト済みテキストを要求したが無かったため生テキストを出力した
+    pub fell_back_to_raw: bool,
+}
+
+/// 配信テキストを `max_deliver_chars` で切り詰めた通知（文字数は Unicode スカラー値単位）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliverTruncated {
+    pub original_len: usize,
+    pub delivered_len: usize,
+}
+
+/// マイクテストの結果（フレームごとの RMS の統計）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MicTestResult {
+    /// フレーム RMS の最大値
+    pub peak: f32,
+    /// フレーム RMS の平均値
+    pub mean: f32,
+    /// クリップ（|sample| がほぼ 1.0）したサンプルを含むフレーム数
+    pub clipped_frames: u32,
+    pub device_name: String,
+}
+
 /// 辞書エントリ
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DictionaryEntry {
@@ -104,6 +358,9 @@ pub struct DictionaryEntry {
     pub replacement: String,
     pub priority: i32,
     pub enabled: bool,
+    /// 前後が英数字で続く一致（"AISLE" の中の "AI" など）は置換しない。CJK 文字との境目は常に単語境界
+    #[serde(default)]
+    pub word_boundary: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -113,6 +370,22 @@ pub enum DictionaryScope {
     Mode,
 }
 
+/// 辞書登録の候補語（保存済みセグメントで繰り返し現れ、辞書に無い語）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TermSuggestion {
+    pub term: String,
+    pub occurrences: u32,
+}
+
+/// 音声コマンドでモードを切り替えた通知（mode_switched_by_voice イベント）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModeSwitchedByVoice {
+    pub session_id: String,
+    pub mode: Mode,
+    /// 一致した設定上のフレーズ
+    pub phrase: String,
+}
+
 /// セットアップ不備の個別項目
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SetupIssue {
@@ -128,6 +401,24 @@ pub struct SetupIssue {
     pub navigate_to: Option<String>,
 }
 
+/// 診断の個別チェック結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosticCheck {
+    /// チェック名（"input_device" など）
+    pub name: String,
+    pub ok: bool,
+    /// 結果の詳細（失敗理由や検出値）
+    pub detail: String,
+}
+
+/// STT セットアップ全体の診断結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticsReport {
+    /// 全チェックが ok なら true
+    pub ok: bool,
+    pub checks: Vec<DiagnosticCheck>,
+}
+
 /// セットアップ状態の全体像
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SetupStatus {
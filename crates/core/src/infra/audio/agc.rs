@@ -0,0 +1,97 @@
+use super::vad::VadProcessor;
+
+/// これ未満の RMS は無音とみなし、ゲインを更新しない
+const SILENCE_RMS: f32 = 1e-3;
+/// ゲインを下げるときの追従係数（クリップを避けるため速く）
+const ATTACK: f32 = 0.5;
+/// ゲインを上げるときの追従係数（ポンピングを避けるため遅く）
+const RELEASE: f32 = 0.05;
+
+/// キャプチャ段の自動ゲイン制御（AGC）
+///
+/// フレームごとに RMS を測り、目標 RMS に近づくようゲインを平滑化して適用する。
+/// 増幅のみ行い（ゲイン 1.0〜max_gain）、無音フレームではゲインを保持する。
+/// コールバック内で使うため、処理中にアロケーションしない。
+#[derive(Debug, Clone)]
+pub struct AutomaticGainControl {
+    target_rms: f32,
+    max_gain: f32,
+    gain: f32,
+}
+
+impl AutomaticGainControl {
+    pub fn new(target_rms: f32, max_gain: f32) -> Self {
+        Self {
+            target_rms: target_rms.max(f32::EPSILON),
+            max_gain: max_gain.max(1.0),
+            gain: 1.0,
+        }
+    }
+
+    /// 現在のゲイン
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// フレームにゲインを適用する（in-place、結果は [-1, 1] にクリップ）
+    pub fn process(&mut self, frame: &mut [f32]) {
+        let rms = VadProcessor::rms(frame);
+        if rms >= SILENCE_RMS {
+            let desired = (self.target_rms / rms).clamp(1.0, self.max_gain);
+            let coeff = if desired < self.gain { ATTACK } else { RELEASE };
+            self.gain += coeff * (desired - self.gain);
+        }
+
+        if self.gain > 1.0 {
+            for s in frame.iter_mut() {
+                *s = (*s * self.gain).clamp(-1.0, 1.0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 10ms（16kHz）の正弦波フレーム
+    fn sine_frame(amplitude: f32) -> Vec<f32> {
+        (0..160)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 16_000.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_quiet_sine_converges_to_target() {
+        let mut agc = AutomaticGainControl::new(0.1, 20.0);
+        let mut out_rms = 0.0;
+        for _ in 0..200 {
+            let mut frame = sine_frame(0.02);
+            agc.process(&mut frame);
+            out_rms = VadProcessor::rms(&frame);
+        }
+        assert!((out_rms - 0.1).abs() < 0.01, "rms={out_rms}");
+    }
+
+    #[test]
+    fn test_loud_sine_is_not_boosted() {
+        let mut agc = AutomaticGainControl::new(0.1, 20.0);
+        let original = sine_frame(0.5);
+        let mut frame = original.clone();
+        for _ in 0..50 {
+            frame.copy_from_slice(&original);
+            agc.process(&mut frame);
+        }
+        assert_eq!(agc.gain(), 1.0);
+        assert_eq!(frame, original);
+    }
+
+    #[test]
+    fn test_silence_is_not_amplified() {
+        let mut agc = AutomaticGainControl::new(0.1, 20.0);
+        let mut frame = vec![0.0005; 160];
+        agc.process(&mut frame);
+        assert_eq!(agc.gain(), 1.0);
+        assert!(frame.iter().all(|&s| s == 0.0005));
+    }
+}
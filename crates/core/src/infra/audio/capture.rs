@@ -1,9 +1,13 @@
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc;
 use std::sync::Arc;
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 
+use super::agc::AutomaticGainControl;
+use super::frame_queue::FrameSender;
+use crate::domain::error::AppError;
+pub use crate::domain::settings::ChannelMode;
+
 /// 音声キャプチャエラー
 #[derive(Debug, thiserror::Error)]
 pub enum AudioCaptureError {
@@ -13,6 +17,37 @@ pub enum AudioCaptureError {
     Config(String),
     #[error("Audio stream error: {0}")]
     Stream(String),
+    #[error("Microphone permission denied: {0}")]
+    PermissionDenied(String),
+    #[error("Audio device did not start within {0}s")]
+    InitTimeout(u32),
+}
+
+impl AudioCaptureError {
+    /// stream の生成・開始エラーを分類する（OS がマイクへのアクセスを拒否した場合は権限エラー）
+    fn from_stream(message: String) -> Self {
+        let lower = message.to_lowercase();
+        if ["permission", "not permitted", "denied", "unauthorized"]
+            .iter()
+            .any(|needle| lower.contains(needle))
+        {
+            Self::PermissionDenied(message)
+        } else {
+            Self::Stream(message)
+        }
+    }
+}
+
+impl From<AudioCaptureError> for AppError {
+    fn from(e: AudioCaptureError) -> Self {
+        match e {
+            AudioCaptureError::PermissionDenied(_) => AppError::permission(e.to_string()),
+            AudioCaptureError::NoDevice
+            | AudioCaptureError::Config(_)
+            | AudioCaptureError::Stream(_)
+            | AudioCaptureError::InitTimeout(_) => AppError::device(e.to_string()),
+        }
+    }
 }
 
 /// キャプチャ設定（実際のデバイスから取得した値）
@@ -20,6 +55,24 @@ pub enum AudioCaptureError {
 pub struct CaptureConfig {
     pub sample_rate: u32,
     pub channels: u16,
+    /// 入力デバイス名（取得できなければ空）
+    pub device_name: String,
+    /// 実際に適用するチャンネル選択（デバイスに無いチャンネルを指定した場合は Mix）
+    pub channel_mode: ChannelMode,
+}
+
+impl CaptureConfig {
+    /// ネイティブサンプルレートが `min_native_sample_rate` 未満なら警告文を返す（0 なら判定しない）
+    ///
+    /// 低いレートでも処理側でリサンプルして動くが、高域が欠けて認識精度が落ちる。
+    pub fn low_sample_rate_warning(&self, min_native_sample_rate: u32) -> Option<String> {
+        (min_native_sample_rate > 0 && self.sample_rate < min_native_sample_rate).then(|| {
+            format!(
+                "入力デバイスのサンプルレートが低いため認識精度が落ちる可能性があります: {}Hz（推奨 {}Hz 以上）",
+                self.sample_rate, min_native_sample_rate
+            )
+        })
+    }
 }
 
 /// デバイスの存在と設定を事前チェックする（stream は作らない）
@@ -36,17 +89,23 @@ pub fn check_device() -> Result<CaptureConfig, AudioCaptureError> {
     Ok(CaptureConfig {
         sample_rate: supported_config.sample_rate().0,
         channels: supported_config.channels(),
+        device_name: device.name().unwrap_or_default(),
+        channel_mode: ChannelMode::Mix,
     })
 }
 
-/// マイクキャプチャを開始し、mono PCM サンプルを channel 経由で送出する
+/// マイクキャプチャを開始し、mono PCM サンプルを有界キュー経由で送出する（満杯なら古いフレームを捨てる）
 ///
 /// **注意**: cpal::Stream は Send ではないため、この関数は
 /// stream を使うスレッド上で呼び出す必要がある。
 /// 返された stream は呼び出し側が保持する（drop で停止）。
+/// `agc` を渡すと mono 化したフレームに自動ゲイン制御を適用してから送出する。
+/// `channel_mode` で mono 化の方法（平均 / 特定チャンネルのみ）を選ぶ。
 pub fn start_capture(
-    sample_tx: mpsc::Sender<Vec<f32>>,
+    sample_tx: FrameSender,
     stop_flag: Arc<AtomicBool>,
+    agc: Option<AutomaticGainControl>,
+    channel_mode: ChannelMode,
 ) -> Result<CaptureConfig, AudioCaptureError> {
     let host = cpal::default_host();
     let device = host
@@ -57,9 +116,11 @@ pub fn start_capture(
         .default_input_config()
         .map_err(|e| AudioCaptureError::Config(e.to_string()))?;
 
+    let device_name = device.name().unwrap_or_default();
     let sample_rate = supported_config.sample_rate().0;
     let channels = supported_config.channels();
     let sample_format = supported_config.sample_format();
+    let channel_mode = resolve_channel_mode(channel_mode, channels);
 
     let config = cpal::StreamConfig {
         channels,
@@ -68,6 +129,8 @@ pub fn start_capture(
     };
 
     let stop_flag_clone = stop_flag.clone();
+    let mut agc_f32 = agc.clone();
+    let mut agc_i16 = agc;
 
     let stream = match sample_format {
         cpal::SampleFormat::F32 => device.build_input_stream(
@@ -76,8 +139,11 @@ pub fn start_capture(
                 if stop_flag_clone.load(Ordering::Relaxed) {
                     return;
                 }
-                let mono = to_mono(data, channels);
-                let _ = sample_tx.send(mono);
+                let mut mono = to_mono(data, channels, channel_mode);
+                if let Some(agc) = agc_f32.as_mut() {
+                    agc.process(&mut mono);
+                }
+                sample_tx.send(mono);
             },
             |err| {
                 log::error!("Audio stream error: {}", err);
@@ -94,8 +160,11 @@ pub fn start_capture(
                         return;
                     }
                     let f32_data: Vec<f32> = data.iter().map(|&s| s as f32 / 32768.0).collect();
-                    let mono = to_mono(&f32_data, channels);
-                    let _ = tx.send(mono);
+                    let mut mono = to_mono(&f32_data, channels, channel_mode);
+                    if let Some(agc) = agc_i16.as_mut() {
+                        agc.process(&mut mono);
+                    }
+                    tx.send(mono);
                 },
                 |err| {
                     log::error!("Audio stream error: {}", err);
@@ -110,17 +179,18 @@ pub fn start_capture(
             )));
         }
     }
-    .map_err(|e| AudioCaptureError::Stream(e.to_string()))?;
+    .map_err(|e| AudioCaptureError::from_stream(e.to_string()))?;
 
     stream
         .play()
-        .map_err(|e| AudioCaptureError::Stream(e.to_string()))?;
+        .map_err(|e| AudioCaptureError::from_stream(e.to_string()))?;
 
     log::info!(
-        "Audio capture started: {}Hz, {} channels, {:?}",
+        "Audio capture started: {}Hz, {} channels, {:?}, {:?}",
         sample_rate,
         channels,
-        sample_format
+        sample_format,
+        channel_mode
     );
 
     // stream を意図的にリークさせてスレッド上で生き続けるようにする
@@ -131,15 +201,103 @@ pub fn start_capture(
     Ok(CaptureConfig {
         sample_rate,
         channels,
+        device_name,
+        channel_mode,
     })
 }
 
-/// ステレオ → モノ変換（チャンネル平均）
-fn to_mono(data: &[f32], channels: u16) -> Vec<f32> {
+/// デバイスに無いチャンネルが指定されていたら Mix に戻す
+fn resolve_channel_mode(mode: ChannelMode, channels: u16) -> ChannelMode {
+    match mode.index() {
+        Some(index) if index >= usize::from(channels) => {
+            log::warn!("Channel {index} not available ({channels} channels), falling back to mix");
+            ChannelMode::Mix
+        }
+        _ => mode,
+    }
+}
+
+/// インターリーブされた入力 → モノ変換（チャンネル平均、または指定チャンネルのみ）
+fn to_mono(data: &[f32], channels: u16, mode: ChannelMode) -> Vec<f32> {
     if channels <= 1 {
         return data.to_vec();
     }
-    data.chunks(channels as usize)
-        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
-        .collect()
+    let frames = data.chunks(channels as usize);
+    match mode.index() {
+        Some(index) => frames
+            .map(|frame| frame.get(index).copied().unwrap_or(0.0))
+            .collect(),
+        None => frames
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// L/R がそれぞれ 1.0 / 0.0、0.5 / 0.1 の 2 フレーム
+    const STEREO: [f32; 4] = [1.0, 0.0, 0.5, 0.1];
+
+    fn config(sample_rate: u32, channels: u16) -> CaptureConfig {
+        CaptureConfig {
+            sample_rate,
+            channels,
+            device_name: "test".into(),
+            channel_mode: ChannelMode::Mix,
+        }
+    }
+
+    #[test]
+    fn test_low_sample_rate_warning() {
+        let warning = config(8_000, 1).low_sample_rate_warning(16_000).unwrap();
+        assert!(warning.contains("8000Hz"), "{warning}");
+        assert_eq!(config(16_000, 1).low_sample_rate_warning(16_000), None);
+        assert_eq!(config(48_000, 2).low_sample_rate_warning(16_000), None);
+        // 0 は判定しない
+        assert_eq!(config(8_000, 1).low_sample_rate_warning(0), None);
+    }
+
+    #[test]
+    fn test_stream_error_classifies_permission() {
+        assert!(matches!(
+            AudioCaptureError::from_stream("Operation not permitted".into()),
+            AudioCaptureError::PermissionDenied(_)
+        ));
+        assert!(matches!(
+            AudioCaptureError::from_stream("Device busy".into()),
+            AudioCaptureError::Stream(_)
+        ));
+    }
+
+    #[test]
+    fn test_to_mono_mix_averages_channels() {
+        assert_eq!(to_mono(&STEREO, 2, ChannelMode::Mix), vec![0.5, 0.3]);
+    }
+
+    #[test]
+    fn test_to_mono_picks_single_channel() {
+        assert_eq!(to_mono(&STEREO, 2, ChannelMode::Left), vec![1.0, 0.5]);
+        assert_eq!(to_mono(&STEREO, 2, ChannelMode::Right), vec![0.0, 0.1]);
+
+        let four = [0.0, 0.1, 0.2, 0.3, 1.0, 1.1, 1.2, 1.3];
+        assert_eq!(to_mono(&four, 4, ChannelMode::Channel(2)), vec![0.2, 1.2]);
+    }
+
+    #[test]
+    fn test_unavailable_channel_falls_back_to_mix() {
+        assert_eq!(
+            resolve_channel_mode(ChannelMode::Channel(4), 2),
+            ChannelMode::Mix
+        );
+        assert_eq!(
+            resolve_channel_mode(ChannelMode::Right, 1),
+            ChannelMode::Mix
+        );
+        assert_eq!(
+            resolve_channel_mode(ChannelMode::Right, 2),
+            ChannelMode::Right
+        );
+    }
 }
@@ -0,0 +1,186 @@
+use super::vad::VadProcessor;
+
+/// 低エネルギー点とみなす比率（探索窓の平均 RMS に対する割合）
+const LOW_ENERGY_RATIO: f32 = 0.5;
+/// 境界探索のフレーム長（ms）
+const FRAME_MS: u64 = 10;
+
+/// 連続キャプチャモードのチャンク分割設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkerConfig {
+    /// 目標チャンク長（ms）
+    pub chunk_target_ms: u64,
+    /// 目標位置の前後で低エネルギー点を探す幅（ms、窓全体の長さ）
+    pub boundary_search_ms: u64,
+}
+
+impl Default for ChunkerConfig {
+    fn default() -> Self {
+        Self {
+            chunk_target_ms: 15_000,
+            boundary_search_ms: 1_500,
+        }
+    }
+}
+
+/// VAD を使わず一定間隔でチャンクを切り出すプロセッサ
+///
+/// 目標長付近の探索窓内で最も静かなフレームを切れ目にすることで、
+/// 単語の途中で切れるのを避ける。低エネルギー点が無ければ目標長で強制カットする。
+pub struct ContinuousChunker {
+    config: ChunkerConfig,
+    sample_rate: u32,
+    buffer: Vec<f32>,
+}
+
+impl ContinuousChunker {
+    pub fn new(config: ChunkerConfig, sample_rate: u32) -> Self {
+        Self {
+            config,
+            sample_rate,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// サンプルを追加し、確定したチャンクを返す
+    pub fn push(&mut self, samples: &[f32]) -> Vec<Vec<f32>> {
+        self.buffer.extend_from_slice(samples);
+
+        let target = ms_to_samples(self.config.chunk_target_ms, self.sample_rate)
+            .max(frame_len(self.sample_rate));
+        let half_window = ms_to_samples(self.config.boundary_search_ms / 2, self.sample_rate);
+
+        let mut chunks = Vec::new();
+        while self.buffer.len() >= target + half_window {
+            let cut = find_boundary(&self.buffer, self.sample_rate, &self.config).max(1);
+            chunks.push(self.buffer.drain(..cut).collect());
+        }
+        chunks
+    }
+
+    /// 残りのサンプルを取り出す（停止時）
+    pub fn flush(&mut self) -> Option<Vec<f32>> {
+        if self.buffer.is_empty() {
+            None
+        } else {
+            Some(std::mem::take(&mut self.buffer))
+        }
+    }
+}
+
+/// チャンクの切れ目（サンプル位置）を決める
+///
+/// 目標位置 ± `boundary_search_ms / 2` の窓を 10ms フレームに分け、
+/// RMS が最小のフレームの中央を返す。最小フレームが窓平均の半分未満でなければ
+/// 低エネルギー点なしとして目標位置（ハードカット）を返す。
+pub fn find_boundary(samples: &[f32], sample_rate: u32, config: &ChunkerConfig) -> usize {
+    let frame = frame_len(sample_rate);
+    let target = ms_to_samples(config.chunk_target_ms, sample_rate)
+        .max(frame)
+        .min(samples.len());
+    let half_window = ms_to_samples(config.boundary_search_ms / 2, sample_rate);
+    let start = target.saturating_sub(half_window);
+    let end = (target + half_window).min(samples.len());
+
+    let mut quietest: Option<(usize, f32)> = None;
+    let mut rms_sum = 0.0;
+    let mut frames = 0;
+    let mut pos = start;
+    while pos + frame <= end {
+        let rms = VadProcessor::rms(&samples[pos..pos + frame]);
+        rms_sum += rms;
+        frames += 1;
+        if !matches!(quietest, Some((_, min)) if rms >= min) {
+            quietest = Some((pos, rms));
+        }
+        pos += frame;
+    }
+
+    match quietest {
+        Some((pos, rms)) if rms < (rms_sum / frames as f32) * LOW_ENERGY_RATIO => pos + frame / 2,
+        _ => target,
+    }
+}
+
+fn ms_to_samples(ms: u64, sample_rate: u32) -> usize {
+    (ms * sample_rate as u64 / 1000) as usize
+}
+
+fn frame_len(sample_rate: u32) -> usize {
+    ms_to_samples(FRAME_MS, sample_rate).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    fn config() -> ChunkerConfig {
+        ChunkerConfig {
+            chunk_target_ms: 1000,
+            boundary_search_ms: 600,
+        }
+    }
+
+    /// 全体を loud で埋め、指定位置（ms）の 10ms フレームを指定振幅にする
+    fn signal(total_ms: u64, dips: &[(u64, f32)]) -> Vec<f32> {
+        let mut samples = vec![0.5; ms_to_samples(total_ms, RATE)];
+        for &(at_ms, amp) in dips {
+            let start = ms_to_samples(at_ms, RATE);
+            let end = start + ms_to_samples(FRAME_MS, RATE);
+            samples[start..end].fill(amp);
+        }
+        samples
+    }
+
+    #[test]
+    fn test_boundary_lands_on_quietest_frame() {
+        // 窓は 700ms〜1300ms。900ms はやや静か、1200ms が最も静か
+        let samples = signal(2000, &[(900, 0.1), (1200, 0.01)]);
+        let cut = find_boundary(&samples, RATE, &config());
+        let frame_start = ms_to_samples(1200, RATE);
+        assert!(
+            (frame_start..frame_start + frame_len(RATE)).contains(&cut),
+            "cut {cut} should be inside the 1200ms frame"
+        );
+    }
+
+    #[test]
+    fn test_boundary_ignores_quiet_frame_outside_window() {
+        // 500ms の無音は窓外なので無視し、窓内の 800ms を選ぶ
+        let samples = signal(2000, &[(500, 0.0), (800, 0.05)]);
+        let cut = find_boundary(&samples, RATE, &config());
+        assert_eq!(
+            cut / frame_len(RATE),
+            ms_to_samples(800, RATE) / frame_len(RATE)
+        );
+    }
+
+    #[test]
+    fn test_hard_cut_when_no_low_energy_point() {
+        let samples = signal(2000, &[]);
+        let cut = find_boundary(&samples, RATE, &config());
+        assert_eq!(cut, ms_to_samples(1000, RATE));
+    }
+
+    #[test]
+    fn test_push_emits_chunks_and_flush_returns_rest() {
+        let mut chunker = ContinuousChunker::new(config(), RATE);
+        let samples = signal(2300, &[(1100, 0.0)]);
+
+        // 目標 + 半窓（1300ms）に達するまでは出力しない
+        assert!(chunker
+            .push(&samples[..ms_to_samples(1200, RATE)])
+            .is_empty());
+
+        let chunks = chunker.push(&samples[ms_to_samples(1200, RATE)..]);
+        assert_eq!(chunks.len(), 1);
+        let expected = ms_to_samples(1100, RATE) + frame_len(RATE) / 2;
+        assert_eq!(chunks[0].len(), expected);
+
+        let rest = chunker.flush().unwrap();
+        assert_eq!(chunks[0].len() + rest.len(), samples.len());
+        assert!(chunker.flush().is_none());
+    }
+}
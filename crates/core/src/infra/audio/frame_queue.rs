@@ -0,0 +1,195 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// キャプチャ → 処理スレッド間で保持するフレーム数の既定値
+///
+/// cpal の 1 フレームは 10〜20ms 程度のため、おおよそ 10〜20 秒分になる。
+pub const DEFAULT_FRAME_QUEUE_CAPACITY: usize = 1024;
+
+/// キャプチャしたフレームを処理スレッドへ渡す有界キュー
+///
+/// STT が詰まって処理側が読み出せない間も、キューは `capacity` フレームで頭打ちになる。
+/// 満杯時は最も古いフレームを捨て、捨てた数を `frames_dropped` に数える。
+/// 受信側の API は `mpsc::Receiver` に合わせてある（送信側が全て drop されたら `Disconnected`）。
+pub fn frame_channel(capacity: usize) -> (FrameSender, FrameReceiver) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            frames: VecDeque::with_capacity(capacity),
+            senders: 1,
+        }),
+        ready: Condvar::new(),
+        capacity: capacity.max(1),
+        dropped: Arc::new(AtomicU64::new(0)),
+        sent: Arc::new(AtomicU64::new(0)),
+    });
+    (
+        FrameSender {
+            shared: shared.clone(),
+        },
+        FrameReceiver { shared },
+    )
+}
+
+struct Shared {
+    state: Mutex<State>,
+    ready: Condvar,
+    capacity: usize,
+    dropped: Arc<AtomicU64>,
+    sent: Arc<AtomicU64>,
+}
+
+struct State {
+    frames: VecDeque<Vec<f32>>,
+    /// 生きている送信側の数（0 になったら切断）
+    senders: usize,
+}
+
+/// フレームの送信側（キャプチャコールバックが保持する。ブロックしない）
+pub struct FrameSender {
+    shared: Arc<Shared>,
+}
+
+impl FrameSender {
+    /// フレームを末尾に追加する（満杯なら最も古いフレームを捨てる）
+    pub fn send(&self, frame: Vec<f32>) {
+        let mut state = self.shared.state.lock().unwrap();
+        if state.frames.len() >= self.shared.capacity {
+            state.frames.pop_front();
+            self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        state.frames.push_back(frame);
+        drop(state);
+        self.shared.sent.fetch_add(1, Ordering::Relaxed);
+        self.shared.ready.notify_one();
+    }
+}
+
+impl Drop for FrameSender {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().senders -= 1;
+        self.shared.ready.notify_all();
+    }
+}
+
+/// フレームの受信側（処理スレッドが保持する）
+pub struct FrameReceiver {
+    shared: Arc<Shared>,
+}
+
+impl FrameReceiver {
+    /// 最も古いフレームを取り出す（`timeout` まで待つ）
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Vec<f32>, RecvTimeoutError> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.state.lock().unwrap();
+        loop {
+            if let Some(frame) = state.frames.pop_front() {
+                return Ok(frame);
+            }
+            if state.senders == 0 {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+            let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+                return Err(RecvTimeoutError::Timeout);
+            };
+            state = self.shared.ready.wait_timeout(state, remaining).unwrap().0;
+        }
+    }
+
+    /// 待たずに取り出せるフレームを全て取り出す
+    pub fn drain(&self) -> Vec<Vec<f32>> {
+        self.shared.state.lock().unwrap().frames.drain(..).collect()
+    }
+
+    /// 現在キューに残っているフレーム数
+    pub fn len(&self) -> usize {
+        self.shared.state.lock().unwrap().frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 満杯で捨てたフレーム数のカウンタ（clone して呼び出し元と共有する）
+    pub fn dropped_counter(&self) -> Arc<AtomicU64> {
+        self.shared.dropped.clone()
+    }
+
+    /// 送信されたフレーム数のカウンタ（キャプチャが進んでいるかの監視用、捨てたフレームも含む）
+    pub fn sent_counter(&self) -> Arc<AtomicU64> {
+        self.shared.sent.clone()
+    }
+
+    /// 同じキューへの送信側を追加する（キャプチャを開き直すときに使う）
+    pub fn new_sender(&self) -> FrameSender {
+        self.shared.state.lock().unwrap().senders += 1;
+        FrameSender {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_slow_consumer_keeps_queue_bounded() {
+        let (tx, rx) = frame_channel(4);
+        let dropped = rx.dropped_counter();
+        for i in 0..10 {
+            tx.send(vec![i as f32; 160]);
+        }
+
+        // 読み出さない間もキューは容量で頭打ちになり、古いフレームから捨てられる
+        assert_eq!(rx.len(), 4);
+        assert_eq!(dropped.load(Ordering::Relaxed), 6);
+        let firsts: Vec<f32> = rx.drain().iter().map(|f| f[0]).collect();
+        assert_eq!(firsts, vec![6.0, 7.0, 8.0, 9.0]);
+    }
+
+    #[test]
+    fn test_recv_timeout_and_disconnect() {
+        let (tx, rx) = frame_channel(4);
+        assert_eq!(
+            rx.recv_timeout(Duration::from_millis(10)),
+            Err(RecvTimeoutError::Timeout)
+        );
+
+        let sender = thread::spawn(move || tx.send(vec![0.5]));
+        assert_eq!(rx.recv_timeout(Duration::from_secs(1)), Ok(vec![0.5]));
+        sender.join().unwrap();
+
+        // 送信側が drop されたら切断を返す
+        assert_eq!(
+            rx.recv_timeout(Duration::from_millis(10)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn test_added_sender_keeps_queue_connected() {
+        let (tx, rx) = frame_channel(4);
+        let sent = rx.sent_counter();
+        let second = rx.new_sender();
+        tx.send(vec![0.1]);
+        drop(tx);
+
+        // 残りの送信側が生きている間は切断しない
+        second.send(vec![0.2]);
+        assert_eq!(sent.load(Ordering::Relaxed), 2);
+        assert_eq!(rx.drain(), vec![vec![0.1], vec![0.2]]);
+        assert_eq!(
+            rx.recv_timeout(Duration::from_millis(10)),
+            Err(RecvTimeoutError::Timeout)
+        );
+        drop(second);
+        assert_eq!(
+            rx.recv_timeout(Duration::from_millis(10)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    }
+}
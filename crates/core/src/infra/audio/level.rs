@@ -0,0 +1,259 @@
+use super::vad::VadProcessor;
+use crate::domain::types::MicTestResult;
+
+/// この絶対値以上のサンプルをクリップとみなす
+const CLIP_LEVEL: f32 = 0.99;
+
+/// 入力レベルメーター用の RMS 平滑化（指数移動平均）
+///
+/// UI 表示専用。VAD の発話判定には平滑化前の RMS を使う（検出遅延を増やさないため）。
+#[derive(Debug, Clone)]
+pub struct LevelSmoother {
+    alpha: f32,
+    current: Option<f32>,
+}
+
+impl LevelSmoother {
+    /// alpha: 新しい値の重み（1.0 で平滑化なし）
+    pub fn new(alpha: f32) -> Self {
+        Self {
+            alpha: alpha.clamp(f32::EPSILON, 1.0),
+            current: None,
+        }
+    }
+
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.alpha = alpha.clamp(f32::EPSILON, 1.0);
+    }
+
+    /// RMS を取り込み、平滑化後の値を返す（最初の値はそのまま）
+    pub fn smooth(&mut self, rms: f32) -> f32 {
+        let next = match self.current {
+            Some(prev) => prev + self.alpha * (rms - prev),
+            None => rms,
+        };
+        self.current = Some(next);
+        next
+    }
+}
+
+/// 入力レベルイベントを `hz` 回/秒に間引く（前回の送信以降のピーク値を送る）
+///
+/// フレームごと（50 回/秒前後）に送ると IPC が詰まり UI がカクつくため。
+/// ピークを保持するので、間引いた間の短い音もメーターに現れる。
+#[derive(Debug, Clone)]
+pub struct LevelThrottle {
+    /// 0 なら間引かない
+    interval_samples: u64,
+    elapsed_samples: u64,
+    peak: Option<f32>,
+}
+
+impl LevelThrottle {
+    pub fn new(hz: u32, sample_rate: u32) -> Self {
+        Self {
+            interval_samples: Self::interval(hz, sample_rate),
+            elapsed_samples: 0,
+            peak: None,
+        }
+    }
+
+    pub fn set_hz(&mut self, hz: u32, sample_rate: u32) {
+        self.interval_samples = Self::interval(hz, sample_rate);
+    }
+
+    fn interval(hz: u32, sample_rate: u32) -> u64 {
+        if hz == 0 {
+            0
+        } else {
+            u64::from(sample_rate) / u64::from(hz)
+        }
+    }
+
+    /// 1 フレーム分のレベルを取り込み、送るタイミングならそれまでのピーク値を返す
+    pub fn push(&mut self, samples: usize, level: f32) -> Option<f32> {
+        self.peak = Some(self.peak.map_or(level, |peak| peak.max(level)));
+        self.elapsed_samples += samples as u64;
+        if self.interval_samples > 0 {
+            if self.elapsed_samples < self.interval_samples {
+                return None;
+            }
+            // 端数は次の区間に持ち越す（フレーム長が区間を割り切れなくても平均の頻度を保つ）
+            self.elapsed_samples %= self.interval_samples;
+        }
+        self.peak.take()
+    }
+}
+
+/// フレーム中でクリップしているサンプルの割合（0.0〜1.0、空なら 0.0）
+pub fn clip_ratio(frame: &[f32]) -> f32 {
+    if frame.is_empty() {
+        return 0.0;
+    }
+    let clipped = frame.iter().filter(|s| s.abs() >= CLIP_LEVEL).count();
+    clipped as f32 / frame.len() as f32
+}
+
+/// クリップ率がしきい値を超えたフレームを検出し、入力レベルと同じ頻度に間引いて通知する
+///
+/// 間引いた間で最も高いクリップ率を返すため、一瞬の音割れも取りこぼさない。
+#[derive(Debug, Clone)]
+pub struct ClipMonitor {
+    /// 0 なら検出しない
+    threshold: f32,
+    throttle: LevelThrottle,
+}
+
+impl ClipMonitor {
+    pub fn new(threshold: f32, hz: u32, sample_rate: u32) -> Self {
+        Self {
+            threshold,
+            throttle: LevelThrottle::new(hz, sample_rate),
+        }
+    }
+
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold;
+    }
+
+    pub fn set_hz(&mut self, hz: u32, sample_rate: u32) {
+        self.throttle.set_hz(hz, sample_rate);
+    }
+
+    /// 1 フレームを取り込み、送るタイミングでクリップ率がしきい値以上ならその値を返す
+    pub fn push(&mut self, frame: &[f32]) -> Option<f32> {
+        if self.threshold <= 0.0 {
+            return None;
+        }
+        self.throttle
+            .push(frame.len(), clip_ratio(frame))
+            .filter(|&ratio| ratio > 0.0 && ratio >= self.threshold)
+    }
+}
+
+/// マイクテスト用にフレームごとの RMS を集計する
+#[derive(Debug, Default)]
+pub struct LevelProfile {
+    peak: f32,
+    sum: f32,
+    frames: u32,
+    clipped_frames: u32,
+}
+
+impl LevelProfile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// キャプチャした 1 フレームを取り込む
+    pub fn push(&mut self, frame: &[f32]) {
+        if frame.is_empty() {
+            return;
+        }
+        let rms = VadProcessor::rms(frame);
+        self.peak = self.peak.max(rms);
+        self.sum += rms;
+        self.frames += 1;
+        if frame.iter().any(|s| s.abs() >= CLIP_LEVEL) {
+            self.clipped_frames += 1;
+        }
+    }
+
+    pub fn finish(self, device_name: String) -> MicTestResult {
+        MicTestResult {
+            peak: self.peak,
+            mean: if self.frames == 0 {
+                0.0
+            } else {
+                self.sum / self.frames as f32
+            },
+            clipped_frames: self.clipped_frames,
+            device_name,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_profile_stats() {
+        let mut profile = LevelProfile::new();
+        profile.push(&[0.1; 160]);
+        profile.push(&[0.3; 160]);
+        let mut clipped = vec![0.2; 160];
+        clipped[10] = 1.0;
+        profile.push(&clipped);
+        profile.push(&[]);
+
+        let result = profile.finish("Built-in Microphone".to_string());
+        assert!((result.peak - 0.3).abs() < 1e-6);
+        let expected_mean = (0.1 + 0.3 + VadProcessor::rms(&clipped)) / 3.0;
+        assert!((result.mean - expected_mean).abs() < 1e-6);
+        assert_eq!(result.clipped_frames, 1);
+        assert_eq!(result.device_name, "Built-in Microphone");
+
+        let empty = LevelProfile::new().finish(String::new());
+        assert_eq!(
+            (empty.peak, empty.mean, empty.clipped_frames),
+            (0.0, 0.0, 0)
+        );
+    }
+
+    #[test]
+    fn test_full_scale_frame_reports_clipping() {
+        assert_eq!(clip_ratio(&[1.0; 160]), 1.0);
+        assert_eq!(clip_ratio(&[-0.995; 160]), 1.0);
+        assert_eq!(clip_ratio(&[0.05; 160]), 0.0);
+        assert_eq!(clip_ratio(&[]), 0.0);
+
+        let mut monitor = ClipMonitor::new(0.01, 0, 16000);
+        assert_eq!(monitor.push(&[1.0; 160]), Some(1.0));
+        assert_eq!(monitor.push(&[0.05; 160]), None);
+
+        // しきい値 0 なら検出しない
+        let mut disabled = ClipMonitor::new(0.0, 0, 16000);
+        assert_eq!(disabled.push(&[1.0; 160]), None);
+    }
+
+    #[test]
+    fn test_step_change_is_smoothed_toward_new_value() {
+        let mut smoother = LevelSmoother::new(0.3);
+        assert_eq!(smoother.smooth(0.0), 0.0);
+
+        let levels: Vec<f32> = (0..10).map(|_| smoother.smooth(1.0)).collect();
+        assert!((levels[0] - 0.3).abs() < 1e-6);
+        assert!(levels.windows(2).all(|w| w[0] < w[1]));
+        assert!(levels[9] > 0.95 && levels[9] < 1.0);
+    }
+
+    #[test]
+    fn test_throttle_emits_peak_at_configured_rate() {
+        // 1kHz・10 サンプルのフレーム 50 個（0.5 秒）を 10Hz に間引くと 5 回
+        let mut throttle = LevelThrottle::new(10, 1000);
+        let emitted: Vec<f32> = (0..50)
+            .filter_map(|i| {
+                let level = match i {
+                    3 => 0.9,
+                    27 => 0.5,
+                    _ => 0.1,
+                };
+                throttle.push(10, level)
+            })
+            .collect();
+        assert_eq!(emitted, vec![0.9, 0.1, 0.5, 0.1, 0.1]);
+
+        // 0 なら毎フレーム送る
+        let mut every = LevelThrottle::new(0, 1000);
+        assert_eq!(every.push(10, 0.2), Some(0.2));
+        assert_eq!(every.push(10, 0.1), Some(0.1));
+    }
+
+    #[test]
+    fn test_alpha_one_disables_smoothing() {
+        let mut smoother = LevelSmoother::new(1.0);
+        smoother.smooth(0.0);
+        assert_eq!(smoother.smooth(0.8), 0.8);
+    }
+}
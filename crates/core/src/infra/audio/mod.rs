@@ -1,4 +1,11 @@
+pub mod agc;
 pub mod capture;
+pub mod chunker;
+pub mod frame_queue;
+pub mod level;
 pub mod pipeline;
 pub mod preprocess;
+pub mod ring_buffer;
+pub mod source;
 pub mod vad;
+pub mod wav;
@@ -1,74 +1,274 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::mpsc;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
-use super::capture::{self, AudioCaptureError};
+use super::agc::AutomaticGainControl;
+use super::capture::{AudioCaptureError, ChannelMode};
+use super::chunker::{ChunkerConfig, ContinuousChunker};
+use super::frame_queue::{self, FrameReceiver, FrameSender, DEFAULT_FRAME_QUEUE_CAPACITY};
+use super::level::{ClipMonitor, LevelSmoother, LevelThrottle};
 use super::preprocess::{AudioPreprocessor, PreprocessConfig};
+use super::ring_buffer::PrebufferedAudio;
+use super::source::AudioSource;
 use super::vad::{VadConfig, VadEvent, VadProcessor};
-use crate::infra::stt::{AudioSegment, SttContext, SttEngine};
+use super::wav;
+use crate::domain::error::AppError;
+use crate::domain::types::{Mode, TranscriptTiming};
+use crate::infra::log_context::{LogContext, TARGET_PIPELINE};
+use crate::infra::stt::{
+    select_hints, AudioSegment, CancelToken, SttContext, SttEngine, SttError, TranscriptResult,
+};
 
 /// パイプラインイベント（Tauri イベントに変換される）
 #[derive(Debug, Clone)]
 pub enum PipelineEvent {
     /// マイク入力レベル（RMS）
     AudioLevel(f32),
+    /// クリップしたサンプルの割合が `clip_ratio_threshold` を超えた（入力レベルと同じ頻度に間引く）
+    Clipping { ratio: f32 },
     /// 部分的な書き起こし結果（ストリーミング STT 用）
     TranscriptPartial { text: String },
-    /// 確定した書き起こし結果
-    TranscriptFinal { text: String, confidence: f32 },
-    /// パイプラインエラー
-    Error(String),
+    /// 字幕の更新（部分書き起こしは後処理を通さずに送る。`captions` 有効時のみ）
+    CaptionUpdate { text: String, is_partial: bool },
+    /// 確定した書き起こし結果（segment_id はログ相関と DB 保存に使う）
+    TranscriptFinal {
+        segment_id: String,
+        text: String,
+        confidence: f32,
+        /// 単語・区間単位のタイミング（エンジンが返さない場合は空）
+        timings: Vec<TranscriptTiming>,
+        /// このセグメントを録音していたときのモード（`SttContext.mode`）
+        mode: Option<Mode>,
+    },
+    /// キャプチャ開始時にデバイスと合意したネイティブ形式（リサンプル前）
+    CaptureFormat { sample_rate: u32, channels: u16 },
+    /// 音声のみ録音で保存したセグメント（STT は行わない）
+    AudioSegmentSaved {
+        segment_id: String,
+        audio_path: String,
+    },
+    /// 発話の無い状態が `session_idle_timeout_secs` を超えた（録音を止める合図、1 回だけ送る）
+    SessionIdleTimeout,
+    /// 録音した時間が `max_duration_ms` に達した（録音を止める合図、1 回だけ送る）
+    SessionDurationLimit,
+    /// パイプラインエラー（元のエラー種別に対応するコードを保持する）
+    Error(AppError),
 }
 
+/// 書き起こしを中断したため音声だけ保存したセグメント（[`AudioPipeline::cancel`] が返す）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedSegment {
+    pub segment_id: String,
+    pub audio_path: String,
+}
+
+/// 録音の区切り方と開始・終了に関するオプション
+#[derive(Debug, Default)]
+pub struct CaptureOptions {
+    /// 指定時は VAD の代わりに連続モードで区切る
+    pub chunker: Option<ChunkerConfig>,
+    /// 録音開始前に取り溜めた音声（最初のセグメントの先頭に付ける）
+    pub prebuffer: Option<PrebufferedAudio>,
+    /// 発話の無い状態がこの秒数続いたら [`PipelineEvent::SessionIdleTimeout`] を送る（0=無効）
+    pub session_idle_timeout_secs: u32,
+    /// 複数チャンネル入力のチャンネル選択
+    pub channel_mode: ChannelMode,
+    /// ネイティブサンプルレートがこれ未満なら警告イベントを送る（0=警告しない）
+    pub min_native_sample_rate: u32,
+    /// キャプチャのフレームがこの秒数届かなければストリームを開き直す（0=監視しない）
+    pub stall_timeout_secs: u32,
+    /// 録音した時間（一時停止中を除く）がこのミリ秒数に達したら
+    /// [`PipelineEvent::SessionDurationLimit`] を送る（0=無効）
+    pub max_duration_ms: u64,
+    /// 発話中の短い間で書き起こしを試し、文末記号で終わっていれば無音タイムアウトを待たずに確定する
+    pub commit_on_sentence_end: bool,
+    /// 指定時は辞書ヒントを直前のセグメントに関係する語から優先し、この件数までに絞る
+    pub smart_hints: Option<usize>,
+    /// 入力ストリームの開始がこの秒数で終わらなければ開始を取りやめる（0=待ち続ける）
+    pub device_init_timeout_secs: u32,
+    /// 部分書き起こしと一緒に [`PipelineEvent::CaptionUpdate`] を送る
+    ///
+    /// 有効なら `commit_on_sentence_end` が無効でも発話中の短い間で書き起こしを試し、部分書き起こしを送る
+    /// （文末記号で終わっていても確定はしない）。
+    pub captions: bool,
+    /// 発話中の短い間で書き起こしを試し、[`PipelineEvent::TranscriptPartial`] を送る（確定はしない）
+    ///
+    /// `commit_on_sentence_end` / `captions` が無効でも部分書き起こしを必要とする呼び出し側（部分書き起こしの保存）向け。
+    pub partials: bool,
+}
+
+/// キャプチャ停止の監視間隔
+const STALL_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 文末での早期確定を試すまでの無音の長さ（ms、VAD の無音タイムアウトより短くする）
+const SENTENCE_END_PROBE_MS: u64 = 200;
+
+/// 録音済みの音声を VAD に流すときのフレーム長（ms、キャプチャの 1 フレームと同程度）
+const OFFLINE_FRAME_MS: usize = 10;
+
 /// AudioPipeline: capture → VAD → STT → イベント発火のオーケストレータ
 ///
-/// cpal::Stream は Send ではないため、AudioCapture は処理スレッド内で作成・保持する。
+/// 音声入力は [`AudioSource`] で差し替えられる（実機は cpal、テストは固定フレーム）。
+/// cpal::Stream は Send ではないため、キャプチャは処理スレッド内で開始・保持する。
 /// AudioPipeline 自体は Send + Sync で、Tauri State に格納できる。
 pub struct AudioPipeline {
     stop_flag: Arc<AtomicBool>,
+    /// 一時停止中はキャプチャしたフレームを捨てる（蓄積中のセグメントは保持）
+    paused: Arc<AtomicBool>,
+    /// VAD ミュート中のフレームは発話判定・セグメントに使わない（スピーカー再生中など）
+    vad_muted: Arc<AtomicBool>,
+    /// 書き起こし中の STT を中断する（`stt_ctx.cancel` と共有）
+    stt_cancel: CancelToken,
+    /// 中断で書き起こさなかったセグメント
+    canceled_rx: Mutex<mpsc::Receiver<SavedSegment>>,
     process_thread: Option<thread::JoinHandle<()>>,
+    /// 録音中の VAD 設定更新を処理スレッドへ送る
+    vad_update_tx: mpsc::Sender<VadConfig>,
+    /// 録音中のモード切り替えを処理スレッドへ送る
+    mode_update_tx: mpsc::Sender<Option<Mode>>,
+    /// 処理が追いつかずキャプチャキューから捨てたフレーム数
+    frames_dropped: Arc<AtomicU64>,
+    /// 録音した時間（ms、一時停止中のフレームは含まない）
+    recorded_ms: Arc<AtomicU64>,
 }
 
-// AudioPipeline は stop_flag (Arc<AtomicBool>) と JoinHandle だけなので Send + Sync
+// AudioPipeline は stop_flag / paused / vad_muted / stt_cancel (Arc<AtomicBool>)、JoinHandle、Sender、
+// Mutex<Receiver>、frames_dropped / recorded_ms (Arc<AtomicU64>) だけなので Send + Sync
 unsafe impl Send for AudioPipeline {}
 unsafe impl Sync for AudioPipeline {}
 
 impl AudioPipeline {
     /// パイプラインを開始する
     ///
-    /// まずデバイスの存在を確認し（エラーなら即座に返す）、
+    /// まず `source` の事前チェック（デバイスの存在確認）を行い（エラーなら即座に返す）、
     /// その後バックグラウンドスレッドで capture → VAD → STT を処理する。
+    /// 区切り方（VAD / 連続モード）や事前キャプチャ音声は `options` で指定する。
+    /// `stt_ctx` は各セグメントの STT 呼び出しで共有される（segment_id はセグメントごとに付与）。
+    /// `stt_engine` が None の場合は音声のみ録音となり、各セグメントを WAV に保存するだけで STT は呼ばない。
     pub fn start(
-        stt_engine: Arc<dyn SttEngine>,
+        source: Arc<dyn AudioSource>,
+        stt_engine: Option<Arc<dyn SttEngine>>,
         event_tx: mpsc::Sender<PipelineEvent>,
         vad_config: VadConfig,
-        language: String,
-        dictionary_hints: Vec<String>,
+        options: CaptureOptions,
+        stt_ctx: SttContext,
     ) -> Result<Self, AudioCaptureError> {
         // デバイスの事前チェック（高速にエラー検出）
-        let _config = capture::check_device()?;
+        source.check()?;
 
         let stop_flag = Arc::new(AtomicBool::new(false));
-        let stop_flag_clone = stop_flag.clone();
+        let paused = Arc::new(AtomicBool::new(false));
+        let vad_muted = Arc::new(AtomicBool::new(false));
+        let stt_cancel = stt_ctx.cancel.clone();
+        let (vad_update_tx, vad_update_rx) = mpsc::channel();
+        let (mode_update_tx, mode_update_rx) = mpsc::channel();
+        // STT が詰まってもキャプチャ側のフレームが溜まり続けないよう有界キューで受け渡す
+        let (sample_tx, sample_rx) = frame_queue::frame_channel(DEFAULT_FRAME_QUEUE_CAPACITY);
+        let frames_dropped = sample_rx.dropped_counter();
+        let recorded_ms = Arc::new(AtomicU64::new(0));
+        let (init_tx, init_rx) = mpsc::channel();
+        let (canceled_tx, canceled_rx) = mpsc::channel();
+        let init_timeout_secs = options.device_init_timeout_secs;
+        let control = LoopControl {
+            stop_flag: stop_flag.clone(),
+            paused: paused.clone(),
+            vad_muted: vad_muted.clone(),
+            recorded_ms: recorded_ms.clone(),
+            vad_update_rx,
+            mode_update_rx,
+            sample_tx,
+            sample_rx,
+            init_tx,
+            canceled_tx,
+        };
 
         let process_thread = thread::spawn(move || {
             Self::processing_loop(
-                stop_flag_clone,
-                stt_engine,
-                event_tx,
-                vad_config,
-                language,
-                dictionary_hints,
+                source, control, stt_engine, event_tx, vad_config, options, stt_ctx,
             );
         });
 
+        // 起動直後はデバイスの初期化が数秒止まることがあるため、待ち時間に上限を設ける。
+        // 超えたら停止フラグを立てて処理スレッドを切り離す（遅れて開いたストリームはすぐ閉じられる）
+        let init_timeout =
+            (init_timeout_secs > 0).then(|| Duration::from_secs(u64::from(init_timeout_secs)));
+        if !wait_for_device_init(&init_rx, init_timeout) {
+            stop_flag.store(true, Ordering::Relaxed);
+            return Err(AudioCaptureError::InitTimeout(init_timeout_secs));
+        }
+
         Ok(Self {
             stop_flag,
+            paused,
+            vad_muted,
+            stt_cancel,
+            canceled_rx: Mutex::new(canceled_rx),
             process_thread: Some(process_thread),
+            vad_update_tx,
+            mode_update_tx,
+            frames_dropped,
+            recorded_ms,
         })
     }
 
+    /// 録音中の VAD 設定を更新する（次に受信したチャンクから反映）
+    pub fn update_vad_config(&self, config: VadConfig) {
+        // 処理スレッドが既に終了している場合は何もしない
+        let _ = self.vad_update_tx.send(config);
+    }
+
+    /// 録音中のモード切り替えを反映する（発話の途中ではなく次のセグメントから）
+    ///
+    /// 切り替え後に録音したセグメントの [`PipelineEvent::TranscriptFinal`] に新しいモードが載る。
+    pub fn update_mode(&self, mode: Option<Mode>) {
+        let _ = self.mode_update_tx.send(mode);
+    }
+
+    /// 録音を一時停止する（キャプチャは開いたまま、蓄積中のセグメントは確定させない）
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    /// 一時停止を解除し、同じセグメントへの蓄積を再開する
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// VAD をミュートする（次に受信したチャンクから反映、[`VadProcessor::set_muted`] 参照）
+    pub fn set_vad_muted(&self, muted: bool) {
+        self.vad_muted.store(muted, Ordering::Relaxed);
+    }
+
+    pub fn is_vad_muted(&self) -> bool {
+        self.vad_muted.load(Ordering::Relaxed)
+    }
+
+    /// 処理スレッドが動いているか（停止要求後、またはキャプチャ開始失敗などで終了したら false）
+    pub fn is_running(&self) -> bool {
+        !self.stop_flag.load(Ordering::Relaxed)
+            && self
+                .process_thread
+                .as_ref()
+                .is_some_and(|thread| !thread.is_finished())
+    }
+
+    /// 処理が追いつかずに捨てたキャプチャフレーム数（開始からの累計）
+    pub fn frames_dropped(&self) -> u64 {
+        self.frames_dropped.load(Ordering::Relaxed)
+    }
+
+    /// 開始から録音した時間（ms、一時停止中は含まない）
+    pub fn recorded_ms(&self) -> u64 {
+        self.recorded_ms.load(Ordering::Relaxed)
+    }
+
     /// パイプラインを停止する（最終セグメントの処理完了まで待機）
     pub fn stop(&mut self) {
         self.stop_flag.store(true, Ordering::Relaxed);
@@ -78,33 +278,131 @@ impl AudioPipeline {
         }
     }
 
+    /// 書き起こし中のセグメントを中断してパイプラインを停止する
+    ///
+    /// 中断したセグメントと未処理の最終セグメントは書き起こさずに WAV で保存して返す
+    /// （呼び出し側が音声のみのセグメントとして登録し、後から書き起こせるようにする）。
+    pub fn cancel(&mut self) -> Vec<SavedSegment> {
+        self.stt_cancel.cancel();
+        self.stop();
+        self.canceled_rx.get_mut().unwrap().try_iter().collect()
+    }
+
     /// 処理ループ（バックグラウンドスレッドで実行）
     ///
-    /// cpal::Stream はこのスレッド上で作成し、スレッド終了時に drop される。
+    /// キャプチャ（cpal::Stream）はこのスレッド上で開始し、スレッド終了時に drop される。
     fn processing_loop(
-        stop_flag: Arc<AtomicBool>,
-        stt_engine: Arc<dyn SttEngine>,
+        source: Arc<dyn AudioSource>,
+        control: LoopControl,
+        stt_engine: Option<Arc<dyn SttEngine>>,
         event_tx: mpsc::Sender<PipelineEvent>,
         vad_config: VadConfig,
-        language: String,
-        dictionary_hints: Vec<String>,
+        options: CaptureOptions,
+        mut stt_ctx: SttContext,
     ) {
+        let LoopControl {
+            stop_flag,
+            paused,
+            vad_muted,
+            recorded_ms,
+            vad_update_rx,
+            mode_update_rx,
+            sample_tx,
+            sample_rx,
+            init_tx,
+            canceled_tx,
+        } = control;
         // このスレッド上でキャプチャを開始
-        let (sample_tx, sample_rx) = mpsc::channel::<Vec<f32>>();
-        let capture_config = match capture::start_capture(sample_tx, stop_flag.clone()) {
+        // （停止フラグはストリームごと。開き直したときに古いストリームだけを止める）
+        let agc_params = vad_config
+            .agc
+            .then_some((vad_config.agc_target_rms, vad_config.agc_max_gain));
+        let make_agc =
+            move || agc_params.map(|(target, max)| AutomaticGainControl::new(target, max));
+        let mut capture_stop = Arc::new(AtomicBool::new(false));
+        let started = source.start(
+            sample_tx,
+            capture_stop.clone(),
+            make_agc(),
+            options.channel_mode,
+        );
+        let _ = init_tx.send(());
+        let capture_config = match started {
             Ok(config) => config,
             Err(e) => {
-                let _ = event_tx.send(PipelineEvent::Error(format!(
-                    "Failed to start audio capture: {}",
+                log::error!(
+                    target: TARGET_PIPELINE,
+                    "{} Failed to start audio capture: {}",
+                    stt_ctx.log_ctx,
                     e
-                )));
+                );
+                // エラーを受け取った側が is_running で終了を判定できるよう、送信前に止める
+                stop_flag.store(true, Ordering::Relaxed);
+                let _ = event_tx.send(PipelineEvent::Error(e.into()));
                 return;
             }
         };
+        let _ = event_tx.send(PipelineEvent::CaptureFormat {
+            sample_rate: capture_config.sample_rate,
+            channels: capture_config.channels,
+        });
+        if let Some(warning) =
+            capture_config.low_sample_rate_warning(options.min_native_sample_rate)
+        {
+            log::warn!(target: TARGET_PIPELINE, "{} {warning}", stt_ctx.log_ctx);
+            let _ = event_tx.send(PipelineEvent::Error(AppError::device(warning)));
+        }
 
         let sample_rate = capture_config.sample_rate;
+        let mut level = LevelSmoother::new(vad_config.level_smoothing_alpha);
+        let mut level_throttle = LevelThrottle::new(vad_config.level_event_hz, sample_rate);
+        let mut clip_monitor = ClipMonitor::new(
+            vad_config.clip_ratio_threshold,
+            vad_config.level_event_hz,
+            sample_rate,
+        );
         let mut vad = VadProcessor::new(vad_config, sample_rate);
+        let mut chunker = options
+            .chunker
+            .map(|config| ContinuousChunker::new(config, sample_rate));
         let mut segment_buffer: Vec<f32> = Vec::new();
+        let mut idle_timer = SessionIdleTimer::new(options.session_idle_timeout_secs, sample_rate);
+        let mut duration_cap = RecordingDurationCap::new(options.max_duration_ms, sample_rate);
+        let smart_hints = SmartHints::new(&mut stt_ctx, options.smart_hints);
+        // 発話中の間で試す書き起こしは文末での確定と、部分書き起こし（字幕・保存用）を兼ねる
+        let probe_enabled = options.commit_on_sentence_end || options.captions || options.partials;
+        let mut sentence_probe =
+            SentenceEndProbe::new(probe_enabled && stt_engine.is_some(), SENTENCE_END_PROBE_MS);
+        let mut probe_worker = stt_engine
+            .clone()
+            .filter(|_| probe_enabled)
+            .map(|engine| SentenceProbeWorker::spawn(engine, options.commit_on_sentence_end));
+        // セグメントを確定するたびに進める（確定前に渡した試しの結果を見分けるため）
+        let mut segment_generation: u64 = 0;
+        // 録音中に切り替えられ、次のセグメントから使うモード
+        let mut pending_mode: Option<Option<Mode>> = None;
+        let mut pause_gate = PauseGate::new(paused);
+        let mut drop_monitor = DropMonitor::new(sample_rx.dropped_counter());
+        let frames_sent = sample_rx.sent_counter();
+        let mut watchdog = StallWatchdog::new(options.stall_timeout_secs, Instant::now());
+
+        // 事前キャプチャ音声（デバイスが変わってサンプルレートが合わなければ捨てる）
+        let mut prebuffer = options
+            .prebuffer
+            .and_then(|pre| {
+                if pre.sample_rate == sample_rate {
+                    Some(pre.samples)
+                } else {
+                    log::warn!(
+                        target: TARGET_PIPELINE,
+                        "{} Prebuffer discarded: {}Hz != {sample_rate}Hz",
+                        stt_ctx.log_ctx,
+                        pre.sample_rate
+                    );
+                    None
+                }
+            })
+            .filter(|samples| !samples.is_empty());
 
         // STT 呼び出し用の tokio ランタイム
         let rt = match tokio::runtime::Builder::new_current_thread()
@@ -113,116 +411,1634 @@ impl AudioPipeline {
         {
             Ok(rt) => rt,
             Err(e) => {
-                let _ = event_tx.send(PipelineEvent::Error(format!(
+                stop_flag.store(true, Ordering::Relaxed);
+                let _ = event_tx.send(PipelineEvent::Error(AppError::internal(format!(
                     "Failed to create tokio runtime: {}",
                     e
-                )));
+                ))));
                 return;
             }
         };
 
+        // 連続モードでは区切り位置の探索に含めるため、事前キャプチャ音声を先に流し込む
+        if let Some(chunker) = chunker.as_mut() {
+            for chunk in chunker.push(&prebuffer.take().unwrap_or_default()) {
+                let text = Self::run_stt(
+                    &rt,
+                    &stt_engine,
+                    &event_tx,
+                    &canceled_tx,
+                    &vad,
+                    AudioSegment {
+                        samples: chunk,
+                        sample_rate,
+                    },
+                    &stt_ctx,
+                );
+                smart_hints.update(&mut stt_ctx, text.as_deref());
+            }
+        }
+
         while !stop_flag.load(Ordering::Relaxed) {
             match sample_rx.recv_timeout(std::time::Duration::from_millis(100)) {
                 Ok(samples) => {
-                    // オーディオレベル送信
+                    for config in vad_update_rx.try_iter() {
+                        log::info!(
+                            target: TARGET_PIPELINE,
+                            "{} VAD config updated",
+                            stt_ctx.log_ctx
+                        );
+                        level.set_alpha(config.level_smoothing_alpha);
+                        level_throttle.set_hz(config.level_event_hz, sample_rate);
+                        clip_monitor.set_threshold(config.clip_ratio_threshold);
+                        clip_monitor.set_hz(config.level_event_hz, sample_rate);
+                        vad.update_config(config);
+                    }
+                    // モードの切り替えは発話の途中では反映せず、次のセグメントの頭から使う
+                    if let Some(mode) = mode_update_rx.try_iter().last() {
+                        pending_mode = Some(mode);
+                    }
+                    if segment_buffer.is_empty() {
+                        if let Some(mode) = pending_mode.take() {
+                            stt_ctx.mode = mode;
+                        }
+                    }
+
+                    // 取りこぼしがあっても VAD はそのまま続ける（途切れた区間は無音と同じ扱いになる）
+                    drop_monitor.check(&stt_ctx.log_ctx);
+
+                    // 一時停止中のフレームはどこにも渡さない（蓄積中のセグメントはそのまま残る）
+                    if !pause_gate.admit(&stt_ctx.log_ctx) {
+                        continue;
+                    }
+
+                    // 録音時間の上限（止めるのは受け取った側。残りのセグメントは停止時に確定する）
+                    let reached = duration_cap.observe(samples.len());
+                    recorded_ms.store(duration_cap.recorded_ms(), Ordering::Relaxed);
+                    if reached {
+                        log::info!(
+                            target: TARGET_PIPELINE,
+                            "{} Recording duration limit reached: {}ms",
+                            stt_ctx.log_ctx,
+                            duration_cap.recorded_ms()
+                        );
+                        let _ = event_tx.send(PipelineEvent::SessionDurationLimit);
+                    }
+
+                    // オーディオレベル送信（メーター表示用に平滑化して間引く。VAD は全フレームの生の RMS で判定する）
                     let rms = VadProcessor::rms(&samples);
-                    let _ = event_tx.send(PipelineEvent::AudioLevel(rms));
+                    if let Some(peak) = level_throttle.push(samples.len(), level.smooth(rms)) {
+                        let _ = event_tx.send(PipelineEvent::AudioLevel(peak));
+                    }
+                    if let Some(ratio) = clip_monitor.push(&samples) {
+                        let _ = event_tx.send(PipelineEvent::Clipping { ratio });
+                    }
 
-                    // VAD 処理
-                    let vad_events = vad.process(&samples);
+                    // 既知の再生区間（VAD ミュート中）のフレームは捨てる
+                    vad.set_muted(vad_muted.load(Ordering::Relaxed));
 
-                    // 発話中 or 発話開始 → バッファに蓄積
-                    let speech_starting = vad_events
-                        .iter()
-                        .any(|e| matches!(e, VadEvent::SpeechStart));
-                    if vad.is_in_speech() || speech_starting {
-                        segment_buffer.extend_from_slice(&samples);
+                    // 連続モード: VAD を使わず低エネルギー点で区切る
+                    if let Some(chunker) = chunker.as_mut() {
+                        if vad.is_muted() {
+                            continue;
+                        }
+                        for chunk in chunker.push(&samples) {
+                            let text = Self::run_stt(
+                                &rt,
+                                &stt_engine,
+                                &event_tx,
+                                &canceled_tx,
+                                &vad,
+                                AudioSegment {
+                                    samples: chunk,
+                                    sample_rate,
+                                },
+                                &stt_ctx,
+                            );
+                            smart_hints.update(&mut stt_ctx, text.as_deref());
+                        }
+                        // 連続モードは無音でもチャンクを出すため、無音判定にだけ VAD を使う
+                        if idle_timer.is_enabled() {
+                            vad.process(&samples);
+                            if idle_timer.observe(samples.len(), vad.is_in_speech()) {
+                                Self::notify_idle_timeout(&event_tx, &stt_ctx);
+                            }
+                        }
+                        continue;
                     }
 
+                    // VAD 処理（発話中 or 発話開始 → バッファに蓄積）
+                    let (vad_events, speaking) =
+                        feed_vad(&mut vad, &mut segment_buffer, &mut prebuffer, &samples);
+
                     // VAD イベント処理
                     for vad_event in vad_events {
                         match vad_event {
                             VadEvent::SpeechEnd | VadEvent::MaxLengthReached => {
                                 if !segment_buffer.is_empty() {
-                                    Self::run_stt(
+                                    let text = Self::run_stt(
                                         &rt,
                                         &stt_engine,
                                         &event_tx,
-                                        std::mem::take(&mut segment_buffer),
-                                        sample_rate,
-                                        &language,
-                                        &dictionary_hints,
+                                        &canceled_tx,
+                                        &vad,
+                                        AudioSegment {
+                                            samples: std::mem::take(&mut segment_buffer),
+                                            sample_rate,
+                                        },
+                                        &stt_ctx,
                                     );
+                                    smart_hints.update(&mut stt_ctx, text.as_deref());
+                                    idle_timer.reset();
+                                    segment_generation += 1;
                                 }
                             }
                             VadEvent::SpeechStart => {}
                         }
                     }
+
+                    // 文末での早期確定（発話中の短い間の書き起こしはワーカーに任せ、ここでは待たない）
+                    let probe_due = sentence_probe.observe(vad.trailing_silence_ms());
+                    if let Some(worker) = probe_worker.as_mut() {
+                        let done = worker
+                            .try_result()
+                            .filter(|done| done.generation == segment_generation);
+                        match done.and_then(|done| done.outcome.map(|o| (done.covered, o))) {
+                            Some((_, ProbeOutcome::Partial(text))) => {
+                                if options.captions {
+                                    let _ = event_tx.send(PipelineEvent::CaptionUpdate {
+                                        text: text.clone(),
+                                        is_partial: true,
+                                    });
+                                }
+                                let _ = event_tx.send(PipelineEvent::TranscriptPartial { text });
+                            }
+                            Some((covered, ProbeOutcome::SentenceEnd { segment_id, result })) => {
+                                let _ = event_tx.send(PipelineEvent::TranscriptFinal {
+                                    segment_id,
+                                    text: result.text.clone(),
+                                    confidence: result.confidence,
+                                    timings: result.timings,
+                                    mode: stt_ctx.mode,
+                                });
+                                drain_committed(
+                                    &mut segment_buffer,
+                                    &mut vad,
+                                    covered,
+                                    sample_rate,
+                                );
+                                segment_generation += 1;
+                                idle_timer.reset();
+                                smart_hints.update(&mut stt_ctx, Some(&result.text));
+                            }
+                            None => {}
+                        }
+
+                        if probe_due && !segment_buffer.is_empty() && !worker.is_busy() {
+                            worker.submit(ProbeJob {
+                                generation: segment_generation,
+                                covered: segment_buffer.len(),
+                                audio: AudioSegment {
+                                    samples: prepare_segment(&vad, segment_buffer.clone()),
+                                    sample_rate,
+                                },
+                                ctx: stt_ctx.clone(),
+                            });
+                        }
+                    }
+
+                    if idle_timer.observe(samples.len(), speaking) {
+                        Self::notify_idle_timeout(&event_tx, &stt_ctx);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    // エラー通知なしにフレームが途絶えた（スリープ復帰後など）ならストリームを開き直す
+                    if !watchdog.check(frames_sent.load(Ordering::Relaxed), Instant::now()) {
+                        continue;
+                    }
+                    log::warn!(
+                        target: TARGET_PIPELINE,
+                        "{} Capture stalled for {}s: restarting stream",
+                        stt_ctx.log_ctx,
+                        options.stall_timeout_secs
+                    );
+                    match Self::restart_capture(
+                        source.as_ref(),
+                        &sample_rx,
+                        &mut capture_stop,
+                        make_agc(),
+                        options.channel_mode,
+                        sample_rate,
+                    ) {
+                        Ok(()) => {
+                            let _ = event_tx.send(PipelineEvent::Error(AppError::device(
+                                "音声入力が途絶えたため入力デバイスを開き直しました",
+                            )));
+                        }
+                        Err(e) => {
+                            log::error!(
+                                target: TARGET_PIPELINE,
+                                "{} Failed to restart audio capture: {}",
+                                stt_ctx.log_ctx,
+                                e
+                            );
+                            stop_flag.store(true, Ordering::Relaxed);
+                            let _ = event_tx.send(PipelineEvent::Error(e));
+                            break;
+                        }
+                    }
                 }
-                Err(mpsc::RecvTimeoutError::Timeout) => continue,
                 Err(mpsc::RecvTimeoutError::Disconnected) => break,
             }
         }
+        capture_stop.store(true, Ordering::Relaxed);
 
         // 残りのセグメントをフラッシュ
+        if let Some(rest) = chunker.as_mut().and_then(ContinuousChunker::flush) {
+            segment_buffer = rest;
+        }
         if !segment_buffer.is_empty() {
             log::info!(
-                "Flushing remaining segment: {} samples",
+                target: TARGET_PIPELINE,
+                "{} Flushing remaining segment: {} samples",
+                stt_ctx.log_ctx,
                 segment_buffer.len()
             );
             Self::run_stt(
                 &rt,
                 &stt_engine,
                 &event_tx,
-                segment_buffer,
-                sample_rate,
-                &language,
-                &dictionary_hints,
+                &canceled_tx,
+                &vad,
+                AudioSegment {
+                    samples: segment_buffer,
+                    sample_rate,
+                },
+                &stt_ctx,
             );
         }
     }
 
-    /// STT エンジンを呼び出し、結果をイベントとして送信
+    /// 止まったストリームを開き直し、同じキューへ送出させる（古いストリームは送出を止める）
+    ///
+    /// サンプルレートが変わった場合は VAD 等をそのまま使えないためエラーを返す。
+    fn restart_capture(
+        source: &dyn AudioSource,
+        sample_rx: &FrameReceiver,
+        capture_stop: &mut Arc<AtomicBool>,
+        agc: Option<AutomaticGainControl>,
+        channel_mode: ChannelMode,
+        sample_rate: u32,
+    ) -> Result<(), AppError> {
+        capture_stop.store(true, Ordering::Relaxed);
+        *capture_stop = Arc::new(AtomicBool::new(false));
+        let config = source.start(
+            sample_rx.new_sender(),
+            capture_stop.clone(),
+            agc,
+            channel_mode,
+        )?;
+        if config.sample_rate != sample_rate {
+            return Err(AppError::device(format!(
+                "開き直した入力デバイスのサンプルレートが変わったため録音を停止しました: {sample_rate}Hz → {}Hz",
+                config.sample_rate
+            )));
+        }
+        Ok(())
+    }
+
+    fn notify_idle_timeout(event_tx: &mpsc::Sender<PipelineEvent>, stt_ctx: &SttContext) {
+        log::info!(
+            target: TARGET_PIPELINE,
+            "{} Session idle timeout reached",
+            stt_ctx.log_ctx
+        );
+        let _ = event_tx.send(PipelineEvent::SessionIdleTimeout);
+    }
+
+    /// STT エンジンを呼び出し、結果をイベントとして送信（音声のみ録音では WAV 保存のみ）
+    ///
+    /// 確定として送ったテキストを返す（次のセグメントの辞書ヒントを選ぶのに使う）。
+    /// 書き起こしを中断したセグメントは捨てずに WAV で保存し、`canceled_tx` へ送る。
     fn run_stt(
         rt: &tokio::runtime::Runtime,
-        stt_engine: &Arc<dyn SttEngine>,
+        stt_engine: &Option<Arc<dyn SttEngine>>,
         event_tx: &mpsc::Sender<PipelineEvent>,
-        mut samples: Vec<f32>,
-        sample_rate: u32,
-        language: &str,
-        dictionary_hints: &[String],
-    ) {
-        // 音声前処理を適用
-        AudioPreprocessor::process(&mut samples, &PreprocessConfig::default());
+        canceled_tx: &mpsc::Sender<SavedSegment>,
+        vad: &VadProcessor,
+        segment: AudioSegment,
+        stt_ctx: &SttContext,
+    ) -> Option<String> {
+        let segment_id = uuid::Uuid::new_v4().to_string();
+        let log_ctx = stt_ctx.log_ctx.with_segment(&segment_id);
+        let sample_rate = segment.sample_rate;
+        log::debug!(
+            target: TARGET_PIPELINE,
+            "{log_ctx} Segment ready: {} samples @ {sample_rate}Hz",
+            segment.samples.len()
+        );
+
+        let samples = prepare_segment(vad, segment.samples);
+
+        let Some(stt_engine) = stt_engine else {
+            if let Some(saved) =
+                Self::save_audio_only(event_tx, vad, segment_id, &samples, sample_rate, &log_ctx)
+            {
+                let _ = event_tx.send(PipelineEvent::AudioSegmentSaved {
+                    segment_id: saved.segment_id,
+                    audio_path: saved.audio_path,
+                });
+            }
+            return None;
+        };
+
+        if stt_ctx.cancel.is_canceled() {
+            log::info!(target: TARGET_PIPELINE, "{log_ctx} STT canceled, segment saved audio-only");
+            if let Some(saved) =
+                Self::save_audio_only(event_tx, vad, segment_id, &samples, sample_rate, &log_ctx)
+            {
+                let _ = canceled_tx.send(saved);
+            }
+            return None;
+        }
 
         let audio = AudioSegment {
-            samples,
+            samples: samples.clone(),
             sample_rate,
         };
         let ctx = SttContext {
-            language: language.to_string(),
-            dictionary: dictionary_hints.to_vec(),
+            log_ctx: log_ctx.clone(),
+            ..stt_ctx.clone()
         };
 
+        let start = std::time::Instant::now();
         match rt.block_on(stt_engine.transcribe(audio, ctx)) {
             Ok(result) => {
-                if !result.text.is_empty() {
-                    let _ = event_tx.send(PipelineEvent::TranscriptFinal {
-                        text: result.text,
-                        confidence: result.confidence,
-                    });
+                log::debug!(
+                    target: TARGET_PIPELINE,
+                    "{log_ctx} STT done in {}ms: {} chars",
+                    start.elapsed().as_millis(),
+                    result.text.chars().count()
+                );
+                if result.text.is_empty() {
+                    return None;
+                }
+                let _ = event_tx.send(PipelineEvent::TranscriptFinal {
+                    segment_id,
+                    text: result.text.clone(),
+                    confidence: result.confidence,
+                    timings: result.timings,
+                    mode: stt_ctx.mode,
+                });
+                Some(result.text)
+            }
+            Err(SttError::NoSpeech) => {
+                log::debug!(target: TARGET_PIPELINE, "{log_ctx} STT: no speech, skipped");
+                None
+            }
+            Err(SttError::Canceled) => {
+                log::info!(target: TARGET_PIPELINE, "{log_ctx} STT canceled, segment saved audio-only");
+                if let Some(saved) = Self::save_audio_only(
+                    event_tx,
+                    vad,
+                    segment_id,
+                    &samples,
+                    sample_rate,
+                    &log_ctx,
+                ) {
+                    let _ = canceled_tx.send(saved);
                 }
+                None
+            }
+            Err(e) => {
+                log::error!(target: TARGET_PIPELINE, "{log_ctx} STT error: {}", e);
+                let _ = event_tx.send(PipelineEvent::Error(e.into()));
+                None
+            }
+        }
+    }
+
+    /// セグメントを WAV で保存する（失敗したらエラーイベントを送って None）
+    fn save_audio_only(
+        event_tx: &mpsc::Sender<PipelineEvent>,
+        vad: &VadProcessor,
+        segment_id: String,
+        samples: &[f32],
+        sample_rate: u32,
+        log_ctx: &LogContext,
+    ) -> Option<SavedSegment> {
+        let config = vad.config();
+        match save_segment_wav(
+            &config.output_dir,
+            &segment_id,
+            samples,
+            sample_rate,
+            config.wav_bits,
+        ) {
+            Ok(path) => {
+                log::debug!(target: TARGET_PIPELINE, "{log_ctx} Audio saved: {}", path.display());
+                Some(SavedSegment {
+                    segment_id,
+                    audio_path: path.to_string_lossy().to_string(),
+                })
+            }
+            Err(e) => {
+                log::error!(target: TARGET_PIPELINE, "{log_ctx} Failed to save audio: {e}");
+                let _ = event_tx.send(PipelineEvent::Error(AppError::storage(format!(
+                    "音声の保存に失敗しました: {e}"
+                ))));
+                None
             }
+        }
+    }
+
+    /// 発話中のセグメントを試しに書き起こし、文末記号で終わっているかを判定する
+    ///
+    /// `audio` は前処理済みのセグメントの写しで、[`SentenceProbeWorker`] のスレッド上で呼ばれる。
+    /// 文末記号で終わっていれば確定、そうでなければ部分書き起こしとして返す。
+    /// 試しの書き起こしの失敗は、最終的な書き起こしで改めて扱うためログに残すだけにする（None）。
+    fn probe_sentence_end(
+        rt: &tokio::runtime::Runtime,
+        stt_engine: &dyn SttEngine,
+        audio: AudioSegment,
+        stt_ctx: &SttContext,
+        commit: bool,
+    ) -> Option<ProbeOutcome> {
+        let segment_id = uuid::Uuid::new_v4().to_string();
+        let log_ctx = stt_ctx.log_ctx.with_segment(&segment_id);
+        let ctx = SttContext {
+            log_ctx: log_ctx.clone(),
+            ..stt_ctx.clone()
+        };
+
+        let result = match rt.block_on(stt_engine.transcribe(audio, ctx)) {
+            Ok(result) => result,
             Err(e) => {
-                log::error!("STT error: {}", e);
-                let _ = event_tx.send(PipelineEvent::Error(e.to_string()));
+                log::debug!(target: TARGET_PIPELINE, "{log_ctx} Sentence-end probe failed: {e}");
+                return None;
             }
+        };
+        if commit && ends_with_sentence_punctuation(&result.text) {
+            log::debug!(target: TARGET_PIPELINE, "{log_ctx} Sentence end detected, committing early");
+            return Some(ProbeOutcome::SentenceEnd { segment_id, result });
+        }
+        (!result.text.is_empty()).then_some(ProbeOutcome::Partial(result.text))
+    }
+}
+
+/// 文末記号（。！？.!?）で終わっているか（末尾の空白は無視する）
+fn ends_with_sentence_punctuation(text: &str) -> bool {
+    text.trim_end().ends_with(['。', '！', '？', '.', '!', '?'])
+}
+
+/// 処理スレッドへの制御入力
+struct LoopControl {
+    stop_flag: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    vad_muted: Arc<AtomicBool>,
+    /// 録音した時間（[`AudioPipeline::recorded_ms`] と共有）
+    recorded_ms: Arc<AtomicU64>,
+    /// 録音中の VAD 設定更新
+    vad_update_rx: mpsc::Receiver<VadConfig>,
+    /// 録音中のモード切り替え
+    mode_update_rx: mpsc::Receiver<Option<Mode>>,
+    /// キャプチャ → 処理スレッドの有界キュー
+    sample_tx: FrameSender,
+    sample_rx: FrameReceiver,
+    /// 最初のキャプチャ開始が終わった（成否を問わない）ことを [`AudioPipeline::start`] へ知らせる
+    init_tx: mpsc::Sender<()>,
+    /// 中断で書き起こさずに保存したセグメントを [`AudioPipeline::cancel`] へ返す
+    canceled_tx: mpsc::Sender<SavedSegment>,
+}
+
+/// 処理スレッドでのキャプチャ開始を `timeout` まで待つ（None なら終わるまで待つ）
+///
+/// 開始が終わったか、処理スレッドが先に終了していれば true。開始の失敗はエラーイベントで別に伝わる。
+fn wait_for_device_init(init_rx: &mpsc::Receiver<()>, timeout: Option<Duration>) -> bool {
+    match timeout {
+        Some(timeout) => !matches!(
+            init_rx.recv_timeout(timeout),
+            Err(mpsc::RecvTimeoutError::Timeout)
+        ),
+        None => {
+            let _ = init_rx.recv();
+            true
+        }
+    }
+}
+
+/// キャプチャキューで捨てたフレーム数を監視し、増えたときだけログに残す
+#[derive(Debug)]
+struct DropMonitor {
+    dropped: Arc<AtomicU64>,
+    reported: u64,
+}
+
+impl DropMonitor {
+    fn new(dropped: Arc<AtomicU64>) -> Self {
+        Self {
+            dropped,
+            reported: 0,
+        }
+    }
+
+    /// 前回から増えた取りこぼし数（増えていなければ 0）
+    fn check(&mut self, log_ctx: &LogContext) -> u64 {
+        let total = self.dropped.load(Ordering::Relaxed);
+        let new = total - self.reported;
+        if new > 0 {
+            log::warn!(
+                target: TARGET_PIPELINE,
+                "{log_ctx} Processing fell behind capture: dropped {new} frames (total {total})"
+            );
+            self.reported = total;
+        }
+        new
+    }
+}
+
+/// 一時停止フラグを見て、受信したフレームを処理に回すか決める
+#[derive(Debug)]
+struct PauseGate {
+    paused: Arc<AtomicBool>,
+    was_paused: bool,
+}
+
+impl PauseGate {
+    fn new(paused: Arc<AtomicBool>) -> Self {
+        Self {
+            paused,
+            was_paused: false,
+        }
+    }
+
+    /// フレームを処理してよいか（一時停止・再開の切り替わりをログに残す）
+    fn admit(&mut self, log_ctx: &LogContext) -> bool {
+        let paused = self.paused.load(Ordering::Relaxed);
+        if paused != self.was_paused {
+            log::info!(
+                target: TARGET_PIPELINE,
+                "{log_ctx} Capture {}",
+                if paused { "paused" } else { "resumed" }
+            );
+            self.was_paused = paused;
+        }
+        !paused
+    }
+}
+
+/// 確定したセグメントに無音トリムと前処理を適用し、STT に渡す音声にする
+fn prepare_segment(vad: &VadProcessor, samples: Vec<f32>) -> Vec<f32> {
+    // 無音トリムは VAD しきい値（生の振幅）で判定するため前処理より先に行う
+    let mut samples = vad.finalize_segment(samples);
+    // 音声前処理を適用（RMS ゲイン有効時はピーク正規化をそちらに置き換える）
+    let preprocess = PreprocessConfig {
+        normalize_gain: !vad.config().normalize_gain,
+        ..Default::default()
+    };
+    AudioPreprocessor::process(&mut samples, &preprocess);
+    samples
+}
+
+/// 録音済みの音声を VAD で発話ごとに区切り、STT に渡せるセグメントを返す（ファイルの書き起こし用）
+///
+/// 録音時と同じく [`OFFLINE_FRAME_MS`] ごとのフレームで VAD に流し、各セグメントに録音時と同じ前処理を行う。
+/// 末尾で発話中だった分も 1 セグメントとして返す。
+pub fn split_into_segments(
+    samples: &[f32],
+    sample_rate: u32,
+    vad_config: VadConfig,
+) -> Vec<Vec<f32>> {
+    let mut vad = VadProcessor::new(vad_config, sample_rate);
+    let frame_len = (sample_rate as usize * OFFLINE_FRAME_MS / 1000).max(1);
+    let mut prebuffer = None;
+    let mut segment_buffer = Vec::new();
+    let mut segments = Vec::new();
+
+    for frame in samples.chunks(frame_len) {
+        let (vad_events, _) = feed_vad(&mut vad, &mut segment_buffer, &mut prebuffer, frame);
+        let ended = vad_events
+            .iter()
+            .any(|e| matches!(e, VadEvent::SpeechEnd | VadEvent::MaxLengthReached));
+        if ended && !segment_buffer.is_empty() {
+            segments.push(std::mem::take(&mut segment_buffer));
+        }
+    }
+    if !segment_buffer.is_empty() {
+        segments.push(segment_buffer);
+    }
+
+    segments
+        .into_iter()
+        .map(|segment| prepare_segment(&vad, segment))
+        .collect()
+}
+
+/// VAD にフレームを渡し、発話中ならセグメントに蓄積する
+///
+/// 発生した VAD イベントと、このフレームが発話中（開始を含む）だったかを返す。
+/// VAD ミュート中のフレームは蓄積せず、発話中とも扱わない。
+fn feed_vad(
+    vad: &mut VadProcessor,
+    segment_buffer: &mut Vec<f32>,
+    prebuffer: &mut Option<Vec<f32>>,
+    samples: &[f32],
+) -> (Vec<VadEvent>, bool) {
+    if vad.is_muted() {
+        return (Vec::new(), false);
+    }
+    let vad_events = vad.process(samples);
+    let speaking = vad.is_in_speech()
+        || vad_events
+            .iter()
+            .any(|e| matches!(e, VadEvent::SpeechStart));
+    if speaking {
+        append_segment_samples(segment_buffer, prebuffer, samples);
+    }
+    (vad_events, speaking)
+}
+
+/// セグメントをまたいで無音の継続時間を測り、上限に達したら 1 度だけ知らせる
+#[derive(Debug)]
+struct SessionIdleTimer {
+    /// 0 なら無効
+    timeout_samples: u64,
+    silent_samples: u64,
+    fired: bool,
+}
+
+impl SessionIdleTimer {
+    fn new(timeout_secs: u32, sample_rate: u32) -> Self {
+        Self {
+            timeout_samples: u64::from(timeout_secs) * u64::from(sample_rate),
+            silent_samples: 0,
+            fired: false,
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.timeout_samples > 0
+    }
+
+    /// フレームを取り込み、今回初めて上限に達したら true を返す（発話中なら計測をやり直す）
+    fn observe(&mut self, samples: usize, speech: bool) -> bool {
+        if speech {
+            self.reset();
+            return false;
+        }
+        self.silent_samples += samples as u64;
+        if !self.is_enabled() || self.fired || self.silent_samples < self.timeout_samples {
+            return false;
         }
+        self.fired = true;
+        true
+    }
+
+    /// セグメントを出力したら計測をやり直す
+    fn reset(&mut self) {
+        self.silent_samples = 0;
     }
 }
 
+/// 録音したサンプル数を数え、上限の時間に達したら 1 度だけ知らせる
+#[derive(Debug)]
+struct RecordingDurationCap {
+    /// 0 なら無効
+    limit_samples: u64,
+    recorded_samples: u64,
+    sample_rate: u32,
+    fired: bool,
+}
+
+impl RecordingDurationCap {
+    fn new(limit_ms: u64, sample_rate: u32) -> Self {
+        Self {
+            limit_samples: limit_ms * u64::from(sample_rate) / 1000,
+            recorded_samples: 0,
+            sample_rate,
+            fired: false,
+        }
+    }
+
+    /// フレームを取り込み、今回初めて上限に達したら true を返す
+    fn observe(&mut self, samples: usize) -> bool {
+        self.recorded_samples += samples as u64;
+        if self.limit_samples == 0 || self.fired || self.recorded_samples < self.limit_samples {
+            return false;
+        }
+        self.fired = true;
+        true
+    }
+
+    fn recorded_ms(&self) -> u64 {
+        self.recorded_samples * 1000 / u64::from(self.sample_rate.max(1))
+    }
+}
+
+/// 直前のセグメントの確定テキストに関係する辞書ヒントを選び直す（設定 smart_hints）
+#[derive(Debug)]
+struct SmartHints {
+    /// 有効な全ての辞書ヒント（元の順）
+    terms: Vec<String>,
+    /// None なら無効（全てのヒントをそのまま渡す）
+    max_terms: Option<usize>,
+}
+
+impl SmartHints {
+    /// 全ての辞書ヒントを預かり、有効なら文脈が無い状態の選択を `ctx` に反映する
+    fn new(ctx: &mut SttContext, max_terms: Option<usize>) -> Self {
+        let hints = Self {
+            terms: ctx.dictionary.clone(),
+            max_terms,
+        };
+        hints.update(ctx, Some(""));
+        hints
+    }
+
+    /// 確定テキストがあれば、それに関係する語を優先して `ctx` の辞書ヒントを選び直す
+    fn update(&self, ctx: &mut SttContext, previous_text: Option<&str>) {
+        if let (Some(max_terms), Some(text)) = (self.max_terms, previous_text) {
+            ctx.dictionary = select_hints(&self.terms, text, max_terms);
+        }
+    }
+}
+
+/// 発話中の間（無音）を見て、文末での早期確定を試す時点を 1 つの間につき 1 度だけ知らせる
+#[derive(Debug)]
+struct SentenceEndProbe {
+    /// None なら無効
+    probe_ms: Option<u64>,
+    /// この間で既に試したか（発話が再開したら戻す）
+    probed: bool,
+}
+
+impl SentenceEndProbe {
+    fn new(enabled: bool, probe_ms: u64) -> Self {
+        Self {
+            probe_ms: enabled.then_some(probe_ms),
+            probed: false,
+        }
+    }
+
+    /// 発話中の無音の長さを渡し、今回の間で初めて `probe_ms` に達したら true を返す
+    fn observe(&mut self, silence_ms: u64) -> bool {
+        let Some(probe_ms) = self.probe_ms else {
+            return false;
+        };
+        if silence_ms == 0 {
+            self.probed = false;
+            return false;
+        }
+        if self.probed || silence_ms < probe_ms {
+            return false;
+        }
+        self.probed = true;
+        true
+    }
+}
+
+/// 文末判定の試し書き起こしを処理スレッドの外で行うワーカー
+///
+/// 処理スレッドはセグメントの写しを渡すだけで待たないため、書き起こし中もフレームを読み続けられる
+/// （待つと有界キューが溢れて音声を取りこぼす）。同時に走らせる試しは 1 つだけ。
+/// 結果には渡したときのセグメントの世代が付き、その間に確定したセグメントの結果は呼び出し側で捨てる。
+struct SentenceProbeWorker {
+    job_tx: mpsc::Sender<ProbeJob>,
+    result_rx: mpsc::Receiver<ProbeDone>,
+    busy: bool,
+}
+
+/// 試しの書き起こしの依頼
+struct ProbeJob {
+    generation: u64,
+    /// 写しを取ったときのセグメントのサンプル数
+    covered: usize,
+    audio: AudioSegment,
+    ctx: SttContext,
+}
+
+/// 終わった試しの書き起こし（失敗したら outcome は None）
+struct ProbeDone {
+    generation: u64,
+    covered: usize,
+    outcome: Option<ProbeOutcome>,
+}
+
+/// 試しの書き起こしの結果
+enum ProbeOutcome {
+    /// 文末記号で終わった（確定として送る）
+    SentenceEnd {
+        segment_id: String,
+        result: TranscriptResult,
+    },
+    /// 文末で終わらなかった（部分書き起こしとして送る）
+    Partial(String),
+}
+
+impl SentenceProbeWorker {
+    /// ワーカースレッドを起動する（依頼の送信側が drop されたら終了する）
+    ///
+    /// `commit` が false なら文末記号で終わっていても確定せず、部分書き起こしとして返す。
+    fn spawn(stt_engine: Arc<dyn SttEngine>, commit: bool) -> Self {
+        let (job_tx, job_rx) = mpsc::channel::<ProbeJob>();
+        let (result_tx, result_rx) = mpsc::channel();
+        thread::spawn(move || {
+            let rt = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(rt) => rt,
+                Err(e) => {
+                    log::error!(target: TARGET_PIPELINE, "Failed to create probe runtime: {e}");
+                    return;
+                }
+            };
+            for job in job_rx {
+                let outcome = AudioPipeline::probe_sentence_end(
+                    &rt,
+                    stt_engine.as_ref(),
+                    job.audio,
+                    &job.ctx,
+                    commit,
+                );
+                let done = ProbeDone {
+                    generation: job.generation,
+                    covered: job.covered,
+                    outcome,
+                };
+                if result_tx.send(done).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            job_tx,
+            result_rx,
+            busy: false,
+        }
+    }
+
+    fn is_busy(&self) -> bool {
+        self.busy
+    }
+
+    fn submit(&mut self, job: ProbeJob) {
+        self.busy = self.job_tx.send(job).is_ok();
+    }
+
+    /// 終わった試しの結果を待たずに受け取る
+    fn try_result(&mut self) -> Option<ProbeDone> {
+        let done = self.result_rx.try_recv().ok()?;
+        self.busy = false;
+        Some(done)
+    }
+}
+
+/// 試しの書き起こしで確定した先頭 `covered` サンプルをセグメントから除く
+///
+/// 試しの後に届いた分が無音だけならまとめて捨てて VAD を戻し、次の発話を待つ。
+/// 書き起こしの間に発話が再開していれば、その分は次のセグメントの先頭として残す。
+fn drain_committed(
+    segment_buffer: &mut Vec<f32>,
+    vad: &mut VadProcessor,
+    covered: usize,
+    sample_rate: u32,
+) {
+    let tail = segment_buffer.len().saturating_sub(covered);
+    let tail_ms = tail as u64 * 1000 / u64::from(sample_rate.max(1));
+    if vad.trailing_silence_ms() >= tail_ms {
+        segment_buffer.clear();
+        vad.reset();
+    } else {
+        segment_buffer.drain(..covered.min(segment_buffer.len()));
+    }
+}
+
+/// キャプチャの送出フレーム数が進まなくなったことを検出する
+///
+/// cpal のストリームはエラーを通知せずに止まることがある（macOS のスリープ復帰など）。
+/// [`STALL_CHECK_INTERVAL`] ごとにフレーム数を見て、`timeout` の間進んでいなければ停止とみなす。
+#[derive(Debug)]
+struct StallWatchdog {
+    /// None なら監視しない
+    timeout: Option<Duration>,
+    last_count: u64,
+    last_progress: Instant,
+    last_check: Instant,
+}
+
+impl StallWatchdog {
+    fn new(timeout_secs: u32, now: Instant) -> Self {
+        Self {
+            timeout: (timeout_secs > 0).then(|| Duration::from_secs(u64::from(timeout_secs))),
+            last_count: 0,
+            last_progress: now,
+            last_check: now,
+        }
+    }
+
+    /// 現在の送出フレーム数を渡し、停止を検出したら true を返す（検出後は計測をやり直す）
+    fn check(&mut self, frame_count: u64, now: Instant) -> bool {
+        let Some(timeout) = self.timeout else {
+            return false;
+        };
+        if now.duration_since(self.last_check) < STALL_CHECK_INTERVAL {
+            return false;
+        }
+        self.last_check = now;
+        if frame_count != self.last_count {
+            self.last_count = frame_count;
+            self.last_progress = now;
+            return false;
+        }
+        if now.duration_since(self.last_progress) < timeout {
+            return false;
+        }
+        self.last_progress = now;
+        true
+    }
+}
+
+/// 発話中のサンプルをセグメントに追加する
+///
+/// 最初のセグメントの開始時だけ、事前キャプチャ音声を先頭に付ける。
+fn append_segment_samples(
+    segment_buffer: &mut Vec<f32>,
+    prebuffer: &mut Option<Vec<f32>>,
+    samples: &[f32],
+) {
+    if segment_buffer.is_empty() {
+        if let Some(pre) = prebuffer.take() {
+            segment_buffer.extend(pre);
+        }
+    }
+    segment_buffer.extend_from_slice(samples);
+}
+
+/// セグメント音声を `<dir>/<segment_id>.wav` に保存し、保存先パスを返す（`wav_bits` は 16 か 32）
+pub fn save_segment_wav(
+    dir: &Path,
+    segment_id: &str,
+    samples: &[f32],
+    sample_rate: u32,
+    wav_bits: u16,
+) -> Result<PathBuf, String> {
+    wav::create_private_dir(dir)
+        .map_err(|e| format!("ディレクトリを作成できません ({}): {e}", dir.display()))?;
+    let path = dir.join(format!("{segment_id}.wav"));
+    wav::write_wav(&path, samples, sample_rate, wav_bits).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
 impl Drop for AudioPipeline {
     fn drop(&mut self) {
         self.stop();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prebuffer_is_prepended_to_first_segment_only() {
+        let mut prebuffer = Some(vec![0.1, 0.2]);
+        let mut segment = Vec::new();
+        append_segment_samples(&mut segment, &mut prebuffer, &[0.3]);
+        append_segment_samples(&mut segment, &mut prebuffer, &[0.4]);
+        assert_eq!(segment, vec![0.1, 0.2, 0.3, 0.4]);
+
+        let mut next = Vec::new();
+        append_segment_samples(&mut next, &mut prebuffer, &[0.5]);
+        assert_eq!(next, vec![0.5]);
+    }
+
+    #[test]
+    fn test_error_event_keeps_underlying_error_code() {
+        use crate::domain::error::ErrorCode;
+
+        let cases: Vec<(AppError, ErrorCode)> = vec![
+            (AudioCaptureError::NoDevice.into(), ErrorCode::Device),
+            (
+                AudioCaptureError::Config("x".into()).into(),
+                ErrorCode::Device,
+            ),
+            (
+                AudioCaptureError::Stream("x".into()).into(),
+                ErrorCode::Device,
+            ),
+            (
+                AudioCaptureError::PermissionDenied("x".into()).into(),
+                ErrorCode::Permission,
+            ),
+            (AudioCaptureError::InitTimeout(10).into(), ErrorCode::Device),
+            (
+                SttError::EngineNotAvailable("x".into()).into(),
+                ErrorCode::SttUnavailable,
+            ),
+            (
+                SttError::AudioFormat("x".into()).into(),
+                ErrorCode::AudioFormat,
+            ),
+            (SttError::Timeout.into(), ErrorCode::Timeout),
+            (
+                SttError::TranscriptionFailed("x".into()).into(),
+                ErrorCode::Internal,
+            ),
+        ];
+        for (error, expected) in cases {
+            assert_eq!(error.code, expected, "{}", error.message);
+        }
+    }
+
+    #[test]
+    fn test_muted_frames_do_not_start_segment() {
+        let config = VadConfig {
+            energy_threshold: 0.01,
+            adaptive_threshold: false,
+            use_zcr: false,
+            speech_start_ms: 10,
+            silence_timeout_ms: 30,
+            ..Default::default()
+        };
+        let mut vad = VadProcessor::new(config, 16000);
+        let mut segment = Vec::new();
+        let mut prebuffer = Some(vec![0.2; 16]);
+        let loud = vec![0.05; 160];
+
+        vad.set_muted(true);
+        for _ in 0..5 {
+            let (events, speaking) = feed_vad(&mut vad, &mut segment, &mut prebuffer, &loud);
+            assert!(events.is_empty());
+            assert!(!speaking);
+        }
+        assert!(segment.is_empty());
+        // 事前キャプチャ音声も次の発話まで残る
+        assert!(prebuffer.is_some());
+
+        vad.set_muted(false);
+        feed_vad(&mut vad, &mut segment, &mut prebuffer, &loud);
+        let (events, _) = feed_vad(&mut vad, &mut segment, &mut prebuffer, &loud);
+        assert_eq!(events, vec![VadEvent::SpeechStart]);
+        assert!(!segment.is_empty());
+    }
+
+    #[test]
+    fn test_segment_buffer_survives_pause() {
+        let config = VadConfig {
+            energy_threshold: 0.01,
+            adaptive_threshold: false,
+            use_zcr: false,
+            speech_start_ms: 10,
+            silence_timeout_ms: 30,
+            ..Default::default()
+        };
+        let mut vad = VadProcessor::new(config, 16000);
+        let paused = Arc::new(AtomicBool::new(false));
+        let mut gate = PauseGate::new(paused.clone());
+        let log_ctx = LogContext::default();
+        let loud = vec![0.05; 160];
+        let quiet = vec![0.001; 160];
+        let mut segment = Vec::new();
+        let mut prebuffer = None;
+        let mut events = Vec::new();
+
+        let mut feed = |gate: &mut PauseGate, segment: &mut Vec<f32>, samples: &[f32]| {
+            if gate.admit(&log_ctx) {
+                events.extend(feed_vad(&mut vad, segment, &mut prebuffer, samples).0);
+            }
+        };
+
+        feed(&mut gate, &mut segment, &loud);
+        feed(&mut gate, &mut segment, &loud);
+        let before_pause = segment.len();
+        assert!(before_pause > 0);
+
+        // 一時停止中は無音タイムアウトを超えてもセグメントは確定しない
+        paused.store(true, Ordering::Relaxed);
+        for _ in 0..10 {
+            feed(&mut gate, &mut segment, &quiet);
+        }
+        assert_eq!(segment.len(), before_pause);
+
+        // 再開後のフレームは同じセグメントに続けて蓄積される
+        paused.store(false, Ordering::Relaxed);
+        feed(&mut gate, &mut segment, &loud);
+        drop(feed);
+        assert_eq!(segment.len(), before_pause + loud.len());
+        assert!(!events.contains(&VadEvent::SpeechEnd));
+    }
+
+    #[test]
+    fn test_split_into_segments_separates_utterances() {
+        let config = VadConfig {
+            energy_threshold: 0.01,
+            adaptive_threshold: false,
+            use_zcr: false,
+            speech_start_ms: 10,
+            silence_timeout_ms: 30,
+            ..Default::default()
+        };
+        // 発話 200ms → 無音 100ms → 発話 100ms（末尾は発話中のまま終わる）@16kHz
+        let samples: Vec<f32> = [vec![0.05; 3200], vec![0.001; 1600], vec![0.05; 1600]].concat();
+
+        let segments = split_into_segments(&samples, 16000, config.clone());
+        assert_eq!(segments.len(), 2);
+        assert!(segments.iter().all(|s| !s.is_empty()));
+        // 無音だけならセグメントは無い
+        assert!(split_into_segments(&[0.001; 16000], 16000, config).is_empty());
+    }
+
+    #[test]
+    fn test_vad_segments_after_dropped_frames() {
+        let config = VadConfig {
+            energy_threshold: 0.01,
+            adaptive_threshold: false,
+            use_zcr: false,
+            speech_start_ms: 10,
+            silence_timeout_ms: 30,
+            ..Default::default()
+        };
+        let mut vad = VadProcessor::new(config, 16000);
+        let (tx, rx) = frame_queue::frame_channel(4);
+        let mut monitor = DropMonitor::new(rx.dropped_counter());
+        let log_ctx = LogContext::default();
+        let mut segment = Vec::new();
+        let mut prebuffer = None;
+        let mut events = Vec::new();
+
+        // 処理が止まっている間に発話フレームが溢れ、古い 2 フレームが捨てられる
+        for _ in 0..6 {
+            tx.send(vec![0.05; 160]);
+        }
+        assert_eq!(rx.len(), 4);
+        assert_eq!(monitor.check(&log_ctx), 2);
+        assert_eq!(monitor.check(&log_ctx), 0);
+        for frame in rx.drain() {
+            events.extend(feed_vad(&mut vad, &mut segment, &mut prebuffer, &frame).0);
+        }
+
+        // 残ったフレームだけでも発話の開始・終了を検出できる
+        for _ in 0..4 {
+            tx.send(vec![0.001; 160]);
+        }
+        for frame in rx.drain() {
+            events.extend(feed_vad(&mut vad, &mut segment, &mut prebuffer, &frame).0);
+        }
+        assert!(events.contains(&VadEvent::SpeechStart));
+        assert!(events.contains(&VadEvent::SpeechEnd));
+        assert!(!segment.is_empty());
+    }
+
+    #[test]
+    fn test_idle_timer_fires_once_after_prolonged_silence() {
+        // 100Hz × 3 秒 = 300 サンプルで発火
+        let mut timer = SessionIdleTimer::new(3, 100);
+        let fired: Vec<bool> = (0..10).map(|_| timer.observe(100, false)).collect();
+        assert_eq!(fired.iter().filter(|&&f| f).count(), 1);
+        assert!(fired[2]);
+    }
+
+    #[test]
+    fn test_idle_timer_restarts_on_speech_and_segments() {
+        let mut timer = SessionIdleTimer::new(3, 100);
+        assert!(!timer.observe(200, false));
+        assert!(!timer.observe(100, true));
+        assert!(!timer.observe(200, false));
+        timer.reset();
+        assert!(!timer.observe(200, false));
+        assert!(timer.observe(100, false));
+
+        // 0 秒なら無効
+        let mut disabled = SessionIdleTimer::new(0, 100);
+        assert!(!disabled.observe(1_000_000, false));
+    }
+
+    #[test]
+    fn test_duration_cap_fires_once_after_limit() {
+        // 100Hz × 1.5 秒 = 150 サンプルで発火
+        let mut cap = RecordingDurationCap::new(1500, 100);
+        let fired: Vec<bool> = (0..10).map(|_| cap.observe(50)).collect();
+        assert_eq!(fired.iter().filter(|&&f| f).count(), 1);
+        assert!(fired[2]);
+        assert_eq!(cap.recorded_ms(), 5000);
+
+        // 0 なら無効（録音時間だけ数える）
+        let mut disabled = RecordingDurationCap::new(0, 100);
+        assert!(!disabled.observe(1_000_000));
+        assert_eq!(disabled.recorded_ms(), 10_000_000);
+    }
+
+    #[test]
+    fn test_sentence_end_probe_fires_once_per_pause() {
+        let mut probe = SentenceEndProbe::new(true, 200);
+        assert!(!probe.observe(0));
+        assert!(!probe.observe(100));
+        assert!(probe.observe(200));
+        assert!(!probe.observe(300));
+        // 発話が再開したら次の間で再び試す
+        assert!(!probe.observe(0));
+        assert!(probe.observe(250));
+
+        let mut disabled = SentenceEndProbe::new(false, 200);
+        assert!(!disabled.observe(500));
+    }
+
+    /// 固定のテキストを返す（`delay` の間は書き起こし中のまま止まる）エンジン
+    struct FixedEngine {
+        text: &'static str,
+        delay: Duration,
+    }
+
+    impl FixedEngine {
+        fn new(text: &'static str) -> Self {
+            Self {
+                text,
+                delay: Duration::ZERO,
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl SttEngine for FixedEngine {
+        async fn transcribe(
+            &self,
+            _audio: AudioSegment,
+            _ctx: SttContext,
+        ) -> Result<TranscriptResult, SttError> {
+            thread::sleep(self.delay);
+            Ok(TranscriptResult {
+                text: self.text.to_string(),
+                confidence: 0.9,
+                is_partial: false,
+                timings: Vec::new(),
+            })
+        }
+
+        fn supports_partial(&self) -> bool {
+            false
+        }
+
+        fn name(&self) -> &str {
+            "fixed"
+        }
+    }
+
+    fn test_ctx() -> SttContext {
+        SttContext {
+            language: "ja-JP".to_string(),
+            dictionary: vec![],
+            mode: None,
+            prompt_prefix: None,
+            max_prompt_chars: 0,
+            task: Default::default(),
+            log_ctx: Default::default(),
+            cancel: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_sentence_end_probe_commits_only_on_final_punctuation() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let ctx = test_ctx();
+        let audio = || AudioSegment {
+            samples: vec![0.05; 1600],
+            sample_rate: 16000,
+        };
+
+        // 文末記号で終わる結果は確定
+        let engine = FixedEngine::new("電気をつけて。");
+        assert!(matches!(
+            AudioPipeline::probe_sentence_end(&rt, &engine, audio(), &ctx, true),
+            Some(ProbeOutcome::SentenceEnd { result, .. }) if result.text == "電気をつけて。"
+        ));
+        // 確定しない設定（字幕のみ）なら部分書き起こしとして返す
+        assert!(matches!(
+            AudioPipeline::probe_sentence_end(&rt, &engine, audio(), &ctx, false),
+            Some(ProbeOutcome::Partial(text)) if text == "電気をつけて。"
+        ));
+
+        // 終わっていなければ部分書き起こしとし、確定は VAD に任せる
+        let engine = FixedEngine::new("電気を");
+        assert!(matches!(
+            AudioPipeline::probe_sentence_end(&rt, &engine, audio(), &ctx, true),
+            Some(ProbeOutcome::Partial(text)) if text == "電気を"
+        ));
+
+        let engine = FixedEngine::new("");
+        assert!(AudioPipeline::probe_sentence_end(&rt, &engine, audio(), &ctx, true).is_none());
+    }
+
+    #[test]
+    fn test_drain_committed_keeps_speech_after_probe() {
+        let config = VadConfig {
+            energy_threshold: 0.01,
+            adaptive_threshold: false,
+            use_zcr: false,
+            speech_start_ms: 10,
+            silence_timeout_ms: 5000,
+            ..Default::default()
+        };
+        let mut vad = VadProcessor::new(config, 16000);
+        let mut segment = Vec::new();
+        let loud = vec![0.05; 160];
+        let quiet = vec![0.0; 160];
+        for frame in [&loud, &loud, &quiet] {
+            feed_vad(&mut vad, &mut segment, &mut None, frame);
+        }
+        let covered = segment.len();
+
+        // 試しの間に発話が再開していれば、その分は残す
+        feed_vad(&mut vad, &mut segment, &mut None, &loud);
+        drain_committed(&mut segment, &mut vad, covered, 16000);
+        assert_eq!(segment, loud);
+        assert!(vad.is_in_speech());
+
+        // 無音だけなら捨てて次の発話を待つ
+        let covered = segment.len();
+        feed_vad(&mut vad, &mut segment, &mut None, &quiet);
+        drain_committed(&mut segment, &mut vad, covered, 16000);
+        assert!(segment.is_empty());
+        assert!(!vad.is_in_speech());
+    }
+
+    /// 開始後に別スレッドから `interval` ごとにフレームを送る入力（実機のキャプチャと同じく処理を待たない）
+    struct PacedAudioSource {
+        frames: Vec<Vec<f32>>,
+        interval: Duration,
+    }
+
+    impl AudioSource for PacedAudioSource {
+        fn check(&self) -> Result<(), AudioCaptureError> {
+            Ok(())
+        }
+
+        fn start(
+            &self,
+            sample_tx: FrameSender,
+            stop_flag: Arc<AtomicBool>,
+            _agc: Option<AutomaticGainControl>,
+            _channel_mode: ChannelMode,
+        ) -> Result<super::super::capture::CaptureConfig, AudioCaptureError> {
+            let frames = self.frames.clone();
+            let interval = self.interval;
+            thread::spawn(move || {
+                for frame in frames {
+                    if stop_flag.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    thread::sleep(interval);
+                    sample_tx.send(frame);
+                }
+            });
+            Ok(super::super::capture::CaptureConfig {
+                sample_rate: 16000,
+                channels: 1,
+                device_name: "paced".to_string(),
+                channel_mode: ChannelMode::Mix,
+            })
+        }
+    }
+
+    /// 発話のあと長い間を置く音声を小さなキューで流し、(取りこぼしたフレーム数, イベント) を返す
+    fn run_with_slow_probe(
+        text: &'static str,
+        options: CaptureOptions,
+    ) -> (u64, Vec<PipelineEvent>) {
+        let mut frames = vec![vec![0.05; 160]; 30];
+        frames.extend(vec![vec![0.0; 160]; 200]);
+        let source = Arc::new(PacedAudioSource {
+            frames,
+            interval: Duration::from_millis(3),
+        });
+        let engine: Arc<dyn SttEngine> = Arc::new(FixedEngine {
+            text,
+            delay: Duration::from_millis(300),
+        });
+        let vad_config = VadConfig {
+            energy_threshold: 0.01,
+            adaptive_threshold: false,
+            use_zcr: false,
+            speech_start_ms: 10,
+            silence_timeout_ms: 5000,
+            ..Default::default()
+        };
+
+        // 書き起こしの間に届く約 100 フレームより十分小さいキュー
+        let (control, _mode_update_tx) = test_control(8);
+        let dropped = control.sample_rx.dropped_counter();
+        let (event_tx, event_rx) = mpsc::channel();
+        AudioPipeline::processing_loop(
+            source,
+            control,
+            Some(engine),
+            event_tx,
+            vad_config,
+            options,
+            test_ctx(),
+        );
+        (
+            dropped.load(Ordering::Relaxed),
+            event_rx.try_iter().collect(),
+        )
+    }
+
+    /// 処理ループを直接動かすための制御一式（キューの容量と、モード切り替えの送信側）
+    fn test_control(capacity: usize) -> (LoopControl, mpsc::Sender<Option<Mode>>) {
+        let (sample_tx, sample_rx) = frame_queue::frame_channel(capacity);
+        let (init_tx, _init_rx) = mpsc::channel();
+        let (_vad_update_tx, vad_update_rx) = mpsc::channel();
+        let (mode_update_tx, mode_update_rx) = mpsc::channel();
+        let (canceled_tx, _canceled_rx) = mpsc::channel();
+        let control = LoopControl {
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            vad_muted: Arc::new(AtomicBool::new(false)),
+            recorded_ms: Arc::new(AtomicU64::new(0)),
+            vad_update_rx,
+            mode_update_rx,
+            sample_tx,
+            sample_rx,
+            init_tx,
+            canceled_tx,
+        };
+        (control, mode_update_tx)
+    }
+
+    #[test]
+    fn test_slow_sentence_end_probe_does_not_drop_frames() {
+        let (dropped, events) = run_with_slow_probe(
+            "電気を",
+            CaptureOptions {
+                commit_on_sentence_end: true,
+                captions: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(dropped, 0);
+        assert!(events.iter().any(|e| matches!(
+            e,
+            PipelineEvent::CaptionUpdate { text, is_partial: true } if text == "電気を"
+        )));
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, PipelineEvent::TranscriptPartial { text } if text == "電気を")));
+    }
+
+    #[test]
+    fn test_captions_emit_partials_without_sentence_end_commit() {
+        let (_, events) = run_with_slow_probe(
+            "電気をつけて。",
+            CaptureOptions {
+                commit_on_sentence_end: false,
+                captions: true,
+                ..Default::default()
+            },
+        );
+        let partial = events.iter().position(|e| {
+            matches!(
+                e,
+                PipelineEvent::CaptionUpdate { text, is_partial: true } if text == "電気をつけて。"
+            )
+        });
+        let committed = events
+            .iter()
+            .position(|e| matches!(e, PipelineEvent::TranscriptFinal { .. }));
+        // 文末記号で終わっていても間では確定せず、録音終了時のフラッシュで初めて確定する
+        assert!(partial.is_some());
+        assert!(partial < committed);
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| matches!(e, PipelineEvent::TranscriptFinal { .. }))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_partials_option_emits_transcript_partial_only() {
+        let (_, events) = run_with_slow_probe(
+            "会議の",
+            CaptureOptions {
+                partials: true,
+                ..Default::default()
+            },
+        );
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, PipelineEvent::TranscriptPartial { text } if text == "会議の")));
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, PipelineEvent::CaptionUpdate { .. })));
+    }
+
+    #[test]
+    fn test_mode_switch_applies_from_next_segment() {
+        // 発話（約 300ms）→ 無音で確定 → 次の発話。最初の発話の途中でモードを切り替える
+        let mut frames = vec![vec![0.05; 160]; 100];
+        frames.extend(vec![vec![0.0; 160]; 20]);
+        frames.extend(vec![vec![0.05; 160]; 30]);
+        frames.extend(vec![vec![0.0; 160]; 20]);
+        let source = Arc::new(PacedAudioSource {
+            frames,
+            interval: Duration::from_millis(3),
+        });
+        let vad_config = VadConfig {
+            energy_threshold: 0.01,
+            adaptive_threshold: false,
+            use_zcr: false,
+            speech_start_ms: 10,
+            silence_timeout_ms: 100,
+            ..Default::default()
+        };
+        let (control, mode_update_tx) = test_control(DEFAULT_FRAME_QUEUE_CAPACITY);
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(60));
+            let _ = mode_update_tx.send(Some(Mode::Raw));
+        });
+        let ctx = SttContext {
+            mode: Some(Mode::Memo),
+            ..test_ctx()
+        };
+        let engine: Arc<dyn SttEngine> = Arc::new(FixedEngine::new("テスト"));
+        let (event_tx, event_rx) = mpsc::channel();
+        AudioPipeline::processing_loop(
+            source,
+            control,
+            Some(engine),
+            event_tx,
+            vad_config,
+            CaptureOptions::default(),
+            ctx,
+        );
+
+        let modes: Vec<_> = event_rx
+            .try_iter()
+            .filter_map(|e| match e {
+                PipelineEvent::TranscriptFinal { mode, .. } => Some(mode),
+                _ => None,
+            })
+            .collect();
+        // 録音中だったセグメントは切り替え前のモードのまま、次のセグメントから新しいモードになる
+        assert_eq!(modes, vec![Some(Mode::Memo), Some(Mode::Raw)]);
+    }
+
+    #[test]
+    fn test_slow_device_init_times_out() {
+        // ストリームの生成に時間のかかるデバイスの代わり
+        let slow_init = |delay_ms: u64| {
+            let (tx, rx) = mpsc::channel();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(delay_ms));
+                let _ = tx.send(());
+            });
+            rx
+        };
+
+        let rx = slow_init(500);
+        let started = Instant::now();
+        assert!(!wait_for_device_init(&rx, Some(Duration::from_millis(50))));
+        assert!(started.elapsed() < Duration::from_millis(400));
+
+        assert!(wait_for_device_init(
+            &slow_init(10),
+            Some(Duration::from_secs(5))
+        ));
+        assert!(wait_for_device_init(&slow_init(10), None));
+
+        // 処理スレッドが知らせる前に終了していれば待たない
+        let (tx, rx) = mpsc::channel::<()>();
+        drop(tx);
+        assert!(wait_for_device_init(&rx, Some(Duration::from_secs(5))));
+    }
+
+    #[test]
+    fn test_stall_watchdog_detects_stopped_frame_counter() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut watchdog = StallWatchdog::new(3, start);
+
+        // フレームが進んでいる間は検出しない
+        for (sec, count) in [(1, 50), (2, 100), (3, 150)] {
+            assert!(!watchdog.check(count, at(sec)));
+        }
+        // 150 で止まってから 3 秒で検出する（監視間隔より短い呼び出しは数えない）
+        assert!(!watchdog.check(150, at(4)));
+        assert!(!watchdog.check(150, at(5)));
+        assert!(!watchdog.check(150, start + Duration::from_millis(5_500)));
+        assert!(watchdog.check(150, at(6)));
+
+        // 検出後は計り直し、開き直したストリームが進めば検出しない
+        assert!(!watchdog.check(150, at(7)));
+        assert!(!watchdog.check(200, at(9)));
+        assert!(!watchdog.check(200, at(11)));
+        assert!(watchdog.check(200, at(12)));
+
+        // 0 秒なら監視しない
+        let mut disabled = StallWatchdog::new(0, start);
+        assert!(!disabled.check(0, at(3_600)));
+    }
+}
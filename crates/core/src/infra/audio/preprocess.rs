@@ -1,3 +1,6 @@
+/// RMS ゲイン適用後に許容するピーク振幅（クリッピング防止）
+const MAX_PEAK: f32 = 0.99;
+
 /// 音声前処理設定
 #[derive(Debug, Clone)]
 pub struct PreprocessConfig {
@@ -52,6 +55,25 @@ impl AudioPreprocessor {
         }
     }
 
+    /// RMS ゲイン（RMS を target_rms に合わせる。ピークが MAX_PEAK を超えない範囲に制限）
+    pub fn apply_target_rms(samples: &mut [f32], target_rms: f32) {
+        if samples.is_empty() {
+            return;
+        }
+        let rms = (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt();
+        if rms < 1e-6 {
+            return; // ほぼ無音 — ノイズを持ち上げない
+        }
+        let peak = samples.iter().map(|s| s.abs()).fold(0.0_f32, f32::max);
+        let gain = (target_rms / rms).min(MAX_PEAK / peak);
+        if (gain - 1.0).abs() < 0.01 {
+            return;
+        }
+        for s in samples.iter_mut() {
+            *s *= gain;
+        }
+    }
+
     /// 全前処理を in-place 適用
     pub fn process(samples: &mut [f32], config: &PreprocessConfig) {
         if config.remove_dc_offset {
@@ -63,6 +85,29 @@ impl AudioPreprocessor {
     }
 }
 
+/// 線形補間でサンプルレートを変換する（同じレートならそのまま返す）
+pub fn resample_linear(samples: &[f32], source_rate: u32, target_rate: u32) -> Vec<f32> {
+    if source_rate == target_rate || samples.is_empty() {
+        return samples.to_vec();
+    }
+
+    let ratio = f64::from(source_rate) / f64::from(target_rate);
+    let output_len = (samples.len() as f64 / ratio) as usize;
+    let mut output = Vec::with_capacity(output_len);
+
+    for i in 0..output_len {
+        let src_idx = i as f64 * ratio;
+        let idx_floor = src_idx.floor() as usize;
+        let idx_ceil = (idx_floor + 1).min(samples.len() - 1);
+        let frac = (src_idx - idx_floor as f64) as f32;
+
+        let sample = samples[idx_floor] * (1.0 - frac) + samples[idx_ceil] * frac;
+        output.push(sample);
+    }
+
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,6 +120,16 @@ mod tests {
         assert!(mean.abs() < 1e-6, "DC offset should be ~0, got {mean}");
     }
 
+    #[test]
+    fn test_resample_linear_changes_length_by_ratio() {
+        let samples: Vec<f32> = (0..4410).map(|i| (i as f32) / 4410.0).collect();
+        let result = resample_linear(&samples, 44100, 16000);
+        assert_eq!(result.len(), 1600);
+        // 単調増加の入力は補間後も単調増加
+        assert!(result.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(resample_linear(&samples, 16000, 16000), samples);
+    }
+
     #[test]
     fn test_remove_dc_offset_empty() {
         let mut samples: Vec<f32> = vec![];
@@ -105,6 +160,24 @@ mod tests {
         assert!(samples.is_empty());
     }
 
+    #[test]
+    fn test_apply_target_rms_quiet_segment() {
+        let mut samples: Vec<f32> = (0..1600).map(|i| (i as f32 * 0.1).sin() * 0.01).collect();
+        AudioPreprocessor::apply_target_rms(&mut samples, 0.1);
+        let rms = (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt();
+        assert!((rms - 0.1).abs() < 0.005, "RMS should be ~0.1, got {rms}");
+    }
+
+    #[test]
+    fn test_apply_target_rms_limited_by_peak() {
+        // RMS は低いがピークが大きい（クリック音を含む）→ ピーク上限でゲインを制限
+        let mut samples = vec![0.01; 1600];
+        samples[800] = 0.8;
+        AudioPreprocessor::apply_target_rms(&mut samples, 0.1);
+        let peak = samples.iter().map(|s| s.abs()).fold(0.0_f32, f32::max);
+        assert!(peak <= MAX_PEAK + 1e-6, "Peak should not clip, got {peak}");
+    }
+
     #[test]
     fn test_process_applies_both() {
         let mut samples = vec![0.6, 0.7, 0.8, 0.5];
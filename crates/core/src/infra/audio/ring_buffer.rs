@@ -0,0 +1,182 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use super::agc::AutomaticGainControl;
+use super::capture::{self, AudioCaptureError, ChannelMode};
+use super::frame_queue::{self, FrameReceiver, DEFAULT_FRAME_QUEUE_CAPACITY};
+
+/// 直近 `capacity` サンプルだけを保持するリングバッファ
+///
+/// 容量を超えたら古いサンプルから捨てるため、メモリ使用量は容量で頭打ちになる。
+#[derive(Debug, Clone)]
+pub struct RingAudioBuffer {
+    samples: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl RingAudioBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// サンプルを末尾に追加する（溢れた分は先頭から捨てる）
+    pub fn push(&mut self, samples: &[f32]) {
+        let keep = samples.len().min(self.capacity);
+        let overflow = (self.samples.len() + keep).saturating_sub(self.capacity);
+        self.samples.drain(..overflow);
+        self.samples.extend(&samples[samples.len() - keep..]);
+    }
+
+    /// 保持しているサンプルを古い順に取り出し、バッファを空にする
+    pub fn take(&mut self) -> Vec<f32> {
+        self.samples.drain(..).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+/// 事前キャプチャで取り溜めた音声
+#[derive(Debug, Clone)]
+pub struct PrebufferedAudio {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+/// 録音開始前の直近数秒を取り溜める常時キャプチャ
+///
+/// 待機中もマイクを開いたまま [`RingAudioBuffer`] に書き込み続け、
+/// 録音開始時に [`PreCapture::finish`] で取り出して最初のセグメントの先頭に付ける。
+pub struct PreCapture {
+    stop_flag: Arc<AtomicBool>,
+    ring: Arc<Mutex<RingAudioBuffer>>,
+    sample_rate: u32,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl PreCapture {
+    /// 既定の入力デバイスで事前キャプチャを開始する（直近 `secs` 秒を保持）
+    pub fn start(
+        secs: u32,
+        agc: Option<AutomaticGainControl>,
+        channel_mode: ChannelMode,
+    ) -> Result<Self, AudioCaptureError> {
+        let (sample_tx, sample_rx) = frame_queue::frame_channel(DEFAULT_FRAME_QUEUE_CAPACITY);
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let config = capture::start_capture(sample_tx, stop_flag.clone(), agc, channel_mode)?;
+        Ok(Self::from_source(
+            sample_rx,
+            stop_flag,
+            config.sample_rate,
+            secs,
+        ))
+    }
+
+    /// 任意のフレーム供給元から事前キャプチャを行う
+    ///
+    /// 受信スレッドは `stop_flag` が立つか送信側が閉じるまでフレームをリングに書き込む。
+    pub fn from_source(
+        sample_rx: FrameReceiver,
+        stop_flag: Arc<AtomicBool>,
+        sample_rate: u32,
+        secs: u32,
+    ) -> Self {
+        let capacity = sample_rate as usize * secs as usize;
+        let ring = Arc::new(Mutex::new(RingAudioBuffer::new(capacity)));
+        let thread = {
+            let ring = ring.clone();
+            let stop_flag = stop_flag.clone();
+            thread::spawn(move || {
+                while !stop_flag.load(Ordering::Relaxed) {
+                    match sample_rx.recv_timeout(Duration::from_millis(100)) {
+                        Ok(frame) => ring.lock().unwrap().push(&frame),
+                        Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                // 停止までに届いていたフレームも取りこぼさない
+                let mut ring = ring.lock().unwrap();
+                for frame in sample_rx.drain() {
+                    ring.push(&frame);
+                }
+            })
+        };
+
+        Self {
+            stop_flag,
+            ring,
+            sample_rate,
+            thread: Some(thread),
+        }
+    }
+
+    /// キャプチャを停止し、取り溜めた音声を返す
+    pub fn finish(mut self) -> PrebufferedAudio {
+        self.stop();
+        PrebufferedAudio {
+            samples: self.ring.lock().unwrap().take(),
+            sample_rate: self.sample_rate,
+        }
+    }
+
+    fn stop(&mut self) {
+        self.stop_flag.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for PreCapture {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_keeps_only_latest_samples() {
+        let mut ring = RingAudioBuffer::new(4);
+        ring.push(&[1.0, 2.0, 3.0]);
+        ring.push(&[4.0, 5.0]);
+        assert_eq!(ring.len(), 4);
+        assert_eq!(ring.take(), vec![2.0, 3.0, 4.0, 5.0]);
+        assert!(ring.is_empty());
+
+        // 1 回の push が容量を超えても末尾だけ残す
+        ring.push(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(ring.take(), vec![3.0, 4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn test_precapture_returns_samples_received_before_finish() {
+        let (tx, rx) = frame_queue::frame_channel(DEFAULT_FRAME_QUEUE_CAPACITY);
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        // 4Hz × 1 秒 = 4 サンプル保持
+        let precapture = PreCapture::from_source(rx, stop_flag, 4, 1);
+        tx.send(vec![0.1, 0.2, 0.3]);
+        tx.send(vec![0.4, 0.5]);
+
+        let audio = precapture.finish();
+        assert_eq!(audio.sample_rate, 4);
+        assert_eq!(audio.samples, vec![0.2, 0.3, 0.4, 0.5]);
+    }
+}
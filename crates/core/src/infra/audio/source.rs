@@ -0,0 +1,131 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use super::agc::AutomaticGainControl;
+use super::capture::{self, AudioCaptureError, CaptureConfig, ChannelMode};
+use super::frame_queue::FrameSender;
+
+/// パイプラインへ mono フレームを供給する音声入力
+///
+/// 実機では cpal のマイク入力（[`CpalAudioSource`]）を使う。
+/// テストではデバイスを開かずに固定フレームを流す [`VecAudioSource`] に差し替える。
+pub trait AudioSource: Send + Sync {
+    /// 開始前の事前チェック（処理スレッドを起動する前に呼ばれ、エラーなら即座に返す）
+    fn check(&self) -> Result<(), AudioCaptureError>;
+
+    /// キャプチャを開始し、mono フレームを `sample_tx` へ送る
+    ///
+    /// パイプラインの処理スレッド上で呼ばれる。`stop_flag` が立ったら送出をやめること。
+    /// フレームが途絶えて開き直すときは、古いストリームの `stop_flag` を立ててから新しい送信側で再び呼ばれる。
+    /// `sample_tx` を drop するとパイプラインは残りのフレームを処理して終了する。
+    fn start(
+        &self,
+        sample_tx: FrameSender,
+        stop_flag: Arc<AtomicBool>,
+        agc: Option<AutomaticGainControl>,
+        channel_mode: ChannelMode,
+    ) -> Result<CaptureConfig, AudioCaptureError>;
+}
+
+/// 既定の入力デバイスからキャプチャする（cpal）
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CpalAudioSource;
+
+impl AudioSource for CpalAudioSource {
+    fn check(&self) -> Result<(), AudioCaptureError> {
+        capture::check_device().map(|_| ())
+    }
+
+    fn start(
+        &self,
+        sample_tx: FrameSender,
+        stop_flag: Arc<AtomicBool>,
+        agc: Option<AutomaticGainControl>,
+        channel_mode: ChannelMode,
+    ) -> Result<CaptureConfig, AudioCaptureError> {
+        capture::start_capture(sample_tx, stop_flag, agc, channel_mode)
+    }
+}
+
+/// 固定の mono フレームを開始のたびに先頭から流す入力（テスト・再現用）
+///
+/// 全フレームを開始時にまとめてキューへ入れてから送信側を閉じるため、
+/// パイプラインは最後のフレームまで処理すると録音停止を待たずに終了する。
+/// キューの容量を超えた分は古いフレームから捨てられるので、
+/// フレーム数は [`DEFAULT_FRAME_QUEUE_CAPACITY`](super::frame_queue::DEFAULT_FRAME_QUEUE_CAPACITY) 以下にすること。
+#[derive(Debug, Clone)]
+pub struct VecAudioSource {
+    frames: Vec<Vec<f32>>,
+    sample_rate: u32,
+}
+
+impl VecAudioSource {
+    pub fn new(frames: Vec<Vec<f32>>, sample_rate: u32) -> Self {
+        Self {
+            frames,
+            sample_rate,
+        }
+    }
+}
+
+impl AudioSource for VecAudioSource {
+    fn check(&self) -> Result<(), AudioCaptureError> {
+        Ok(())
+    }
+
+    fn start(
+        &self,
+        sample_tx: FrameSender,
+        stop_flag: Arc<AtomicBool>,
+        mut agc: Option<AutomaticGainControl>,
+        _channel_mode: ChannelMode,
+    ) -> Result<CaptureConfig, AudioCaptureError> {
+        for frame in &self.frames {
+            if stop_flag.load(Ordering::Relaxed) {
+                break;
+            }
+            let mut frame = frame.clone();
+            if let Some(agc) = agc.as_mut() {
+                agc.process(&mut frame);
+            }
+            sample_tx.send(frame);
+        }
+
+        Ok(CaptureConfig {
+            sample_rate: self.sample_rate,
+            channels: 1,
+            device_name: "vec".to_string(),
+            channel_mode: ChannelMode::Mix,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::audio::frame_queue;
+    use std::sync::mpsc::RecvTimeoutError;
+    use std::time::Duration;
+
+    #[test]
+    fn test_vec_source_replays_frames_then_disconnects() {
+        let source = VecAudioSource::new(vec![vec![0.1; 4], vec![0.2; 4]], 16000);
+
+        for _ in 0..2 {
+            let (tx, rx) = frame_queue::frame_channel(8);
+            let config = source
+                .start(tx, Arc::new(AtomicBool::new(false)), None, ChannelMode::Mix)
+                .unwrap();
+            assert_eq!(config.sample_rate, 16000);
+            assert_eq!(config.channels, 1);
+
+            let timeout = Duration::from_millis(10);
+            assert_eq!(rx.recv_timeout(timeout), Ok(vec![0.1; 4]));
+            assert_eq!(rx.recv_timeout(timeout), Ok(vec![0.2; 4]));
+            assert_eq!(
+                rx.recv_timeout(timeout),
+                Err(RecvTimeoutError::Disconnected)
+            );
+        }
+    }
+}
@@ -1,62 +1,13 @@
-use crate::domain::settings::SttEngineChoice;
-
-/// VAD（Voice Activity Detection）設定
-#[derive(Debug, Clone)]
-pub struct VadConfig {
-    /// RMS エネルギーしきい値（これ以上で発話とみなす）
-    pub energy_threshold: f32,
-    /// 無音タイムアウト（ms）：この長さ無音が続いたらセグメント終了
-    pub silence_timeout_ms: u64,
-    /// セグメント最大長（ms）：強制カット
-    pub max_segment_ms: u64,
-    /// 発話開始に必要な連続音声時間（ms）：ヒステリシス
-    pub speech_start_ms: u64,
-    /// 動的しきい値を有効にする（キャリブレーション）
-    pub adaptive_threshold: bool,
-    /// キャリブレーション期間（ms）
-    pub calibration_duration_ms: u64,
-    /// ノイズフロアに対する倍率（動的しきい値 = noise_floor * multiplier）
-    pub threshold_multiplier: f32,
-    /// ZCR（ゼロクロッシング率）を発話判定に使用する
-    pub use_zcr: bool,
-    /// ZCR しきい値（これ以上で発話候補）
-    pub zcr_threshold: f32,
-}
-
-impl Default for VadConfig {
-    fn default() -> Self {
-        Self {
-            energy_threshold: 0.02,
-            silence_timeout_ms: 700,
-            max_segment_ms: 30_000,
-            speech_start_ms: 50,
-            adaptive_threshold: true,
-            calibration_duration_ms: 2000,
-            threshold_multiplier: 3.0,
-            use_zcr: false,
-            zcr_threshold: 0.3,
-        }
-    }
-}
+use super::preprocess::AudioPreprocessor;
+pub use crate::domain::settings::VadConfig;
+use crate::domain::settings::MIN_VAD_FRAME_MS;
 
-impl VadConfig {
-    /// STT エンジンに応じた最適なデフォルト設定を返す
-    pub fn for_engine(engine: SttEngineChoice) -> Self {
-        match engine {
-            SttEngineChoice::Whisper => Self {
-                max_segment_ms: 20_000,
-                ..Default::default()
-            },
-            SttEngineChoice::Apple => Self {
-                max_segment_ms: 60_000,
-                silence_timeout_ms: 1000,
-                ..Default::default()
-            },
-            SttEngineChoice::Cloud => Self::default(),
-            SttEngineChoice::Soniox => Self::default(),
-        }
-    }
-}
+/// 無音トリム時に発話の前後に残す余白（ms）
+const TRIM_GUARD_MS: u64 = 100;
+/// 無音トリムの判定単位（ms）
+const TRIM_FRAME_MS: u64 = 10;
+/// MIN_VAD_FRAME_MS のフレームに 1 サンプル以上入る最小のサンプルレート
+const MIN_SAMPLE_RATE: u32 = (1000 / MIN_VAD_FRAME_MS) as u32;
 
 /// VAD イベント
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -94,10 +45,16 @@ pub struct VadProcessor {
     sample_rate: u32,
     /// 動的しきい値（キャリブレーション後に設定）
     effective_threshold: Option<f32>,
+    /// キャリブレーションで計測したノイズフロア（設定更新時のしきい値再計算用）
+    noise_floor: Option<f32>,
+    /// ミュート中は受け取ったフレームを捨て、状態を変えない（スピーカー再生中の誤検出防止）
+    muted: bool,
 }
 
 impl VadProcessor {
     pub fn new(config: VadConfig, sample_rate: u32) -> Self {
+        let config = Self::checked_config(config, sample_rate);
+        let sample_rate = sample_rate.max(MIN_SAMPLE_RATE);
         let initial_state = if config.adaptive_threshold {
             VadState::Calibrating {
                 samples_rms: Vec::new(),
@@ -112,7 +69,87 @@ impl VadProcessor {
             state: initial_state,
             sample_rate,
             effective_threshold: None,
+            noise_floor: None,
+            muted: false,
+        }
+    }
+
+    /// 設定を返す
+    pub fn config(&self) -> &VadConfig {
+        &self.config
+    }
+
+    /// 設定を差し替える（録音中でも次のチャンクから反映）
+    ///
+    /// キャリブレーション済みのノイズフロアは保持し、動的しきい値を新しい倍率で再計算する。
+    pub fn update_config(&mut self, config: VadConfig) {
+        let config = Self::checked_config(config, self.sample_rate);
+        self.effective_threshold = match self.noise_floor {
+            Some(noise_floor) if config.adaptive_threshold => {
+                Some((noise_floor * config.threshold_multiplier).max(config.energy_threshold))
+            }
+            _ => None,
+        };
+        self.config = config;
+    }
+
+    /// 設定を検証し、不正な項目があれば警告を出して動作可能な値に丸める
+    fn checked_config(config: VadConfig, sample_rate: u32) -> VadConfig {
+        match config.validate(sample_rate) {
+            Ok(()) => config,
+            Err(errors) => {
+                for e in &errors {
+                    log::warn!("VAD 設定が不正なため補正します: {e}");
+                }
+                config.clamped()
+            }
+        }
+    }
+
+    /// 確定したセグメントに後処理（無音トリム → RMS ゲイン）を適用する
+    pub fn finalize_segment(&self, mut samples: Vec<f32>) -> Vec<f32> {
+        if self.config.trim_silence {
+            samples = self.trim_trailing_silence(samples);
+        }
+        if self.config.normalize_gain {
+            AudioPreprocessor::apply_target_rms(&mut samples, self.config.target_rms);
+        }
+        samples
+    }
+
+    /// 前後のしきい値未満の区間を切り詰める（発話の前後に TRIM_GUARD_MS の余白を残す）
+    ///
+    /// 結果が `min_segment_ms` より短くなる場合は、その長さまで後ろ→前の順に広げる。
+    /// しきい値以上のフレームが無い場合はそのまま返す。
+    fn trim_trailing_silence(&self, samples: Vec<f32>) -> Vec<f32> {
+        let ms_to_samples = |ms: u64| (ms * self.sample_rate as u64 / 1000) as usize;
+        let frame_len = ms_to_samples(TRIM_FRAME_MS).max(1);
+        let threshold = self.current_threshold();
+
+        let voiced: Vec<usize> = samples
+            .chunks(frame_len)
+            .enumerate()
+            .filter(|(_, frame)| Self::rms(frame) >= threshold)
+            .map(|(i, _)| i)
+            .collect();
+        let (Some(&first), Some(&last)) = (voiced.first(), voiced.last()) else {
+            return samples;
+        };
+
+        let guard = ms_to_samples(TRIM_GUARD_MS);
+        let mut start = (first * frame_len).saturating_sub(guard);
+        let mut end = ((last + 1) * frame_len + guard).min(samples.len());
+
+        let min_len = ms_to_samples(self.config.min_segment_ms).min(samples.len());
+        if end - start < min_len {
+            end = (start + min_len).min(samples.len());
+            start = end.saturating_sub(min_len);
+        }
+
+        if start == 0 && end == samples.len() {
+            return samples;
         }
+        samples[start..end].to_vec()
     }
 
     /// 現在有効なしきい値を返す
@@ -142,8 +179,23 @@ impl VadProcessor {
         crossings as f32 / (samples.len() - 1) as f32
     }
 
-    /// サンプルを処理し、VAD イベントを返す
+    /// ミュートを切り替える
+    ///
+    /// TTS などの再生中にマイクが拾った音で発話を検出しないよう、既知の再生区間だけ VAD を止める。
+    /// ミュート中のフレームはキャリブレーション・発話判定のどちらにも使わない。
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    /// サンプルを処理し、VAD イベントを返す（ミュート中は何もしない）
     pub fn process(&mut self, samples: &[f32]) -> Vec<VadEvent> {
+        if self.muted {
+            return Vec::new();
+        }
         let rms = Self::rms(samples);
         let chunk_duration_ms = (samples.len() as u64 * 1000) / self.sample_rate as u64;
 
@@ -163,6 +215,7 @@ impl VadProcessor {
                 } else {
                     samples_rms.iter().sum::<f32>() / samples_rms.len() as f32
                 };
+                self.noise_floor = Some(noise_floor);
                 let dynamic_threshold = noise_floor * self.config.threshold_multiplier;
                 // 最低限 energy_threshold は下回らない
                 self.effective_threshold =
@@ -249,11 +302,22 @@ impl VadProcessor {
     pub fn is_in_speech(&self) -> bool {
         matches!(self.state, VadState::Speech { .. })
     }
+
+    /// 発話中に続いている無音の長さ（ms、発話中でなければ 0）
+    pub fn trailing_silence_ms(&self) -> u64 {
+        match self.state {
+            VadState::Speech {
+                silence_count_ms, ..
+            } => silence_count_ms,
+            _ => 0,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::settings::SttEngineChoice;
 
     fn make_samples(rms_target: f32, count: usize) -> Vec<f32> {
         // 一定振幅のサンプルを生成（RMS ≈ amplitude / sqrt(2) for sine, = amplitude for DC）
@@ -478,6 +542,123 @@ mod tests {
         assert!(!vad.is_in_speech());
     }
 
+    #[test]
+    fn test_update_config_changes_threshold_while_recording() {
+        let config = VadConfig {
+            energy_threshold: 0.01,
+            speech_start_ms: 10,
+            ..test_config()
+        };
+        let mut vad = VadProcessor::new(config.clone(), 16000);
+        let voice = make_samples(0.05, 160);
+
+        vad.process(&voice);
+        assert_eq!(vad.process(&voice), vec![VadEvent::SpeechStart]);
+        vad.reset();
+
+        // しきい値を上げると同じ入力は発話とみなされない
+        vad.update_config(VadConfig {
+            energy_threshold: 0.1,
+            ..config
+        });
+        assert!((vad.current_threshold() - 0.1).abs() < f32::EPSILON);
+        assert!(vad.process(&voice).is_empty());
+        assert!(vad.process(&voice).is_empty());
+        assert!(!vad.is_in_speech());
+    }
+
+    #[test]
+    fn test_update_config_recomputes_adaptive_threshold() {
+        let config = VadConfig {
+            adaptive_threshold: true,
+            calibration_duration_ms: 20,
+            threshold_multiplier: 2.0,
+            energy_threshold: 0.001,
+            ..test_config()
+        };
+        let mut vad = VadProcessor::new(config.clone(), 16000);
+        let noise = make_samples(0.01, 160);
+        vad.process(&noise);
+        vad.process(&noise); // ノイズフロア 0.01 → しきい値 0.02
+
+        vad.update_config(VadConfig {
+            threshold_multiplier: 5.0,
+            ..config
+        });
+        assert!((vad.current_threshold() - 0.05).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_finalize_segment_gain() {
+        let config = VadConfig {
+            normalize_gain: true,
+            target_rms: 0.1,
+            ..test_config()
+        };
+        let vad = VadProcessor::new(config, 16000);
+
+        // 小さい声のセグメントは目標 RMS に近づく
+        let quiet: Vec<f32> = (0..1600).map(|i| (i as f32 * 0.1).sin() * 0.02).collect();
+        let boosted = vad.finalize_segment(quiet);
+        let rms = VadProcessor::rms(&boosted);
+        assert!(
+            (rms - 0.1).abs() < 0.01,
+            "RMS should approach 0.1, got {rms}"
+        );
+
+        // 大きい声のセグメントはクリップするほど増幅されない
+        let loud: Vec<f32> = (0..1600).map(|i| (i as f32 * 0.1).sin() * 0.95).collect();
+        let out = vad.finalize_segment(loud);
+        assert!(out.iter().all(|s| s.abs() <= 1.0));
+        assert!(VadProcessor::rms(&out) <= VadProcessor::rms(&[0.95]) + 1e-3);
+    }
+
+    #[test]
+    fn test_finalize_segment_disabled_by_default() {
+        let vad = VadProcessor::new(test_config(), 16000);
+        let samples = vec![0.01; 160];
+        assert_eq!(vad.finalize_segment(samples.clone()), samples);
+    }
+
+    #[test]
+    fn test_trim_silence_shortens_padded_segment() {
+        let config = VadConfig {
+            energy_threshold: 0.02,
+            trim_silence: true,
+            min_segment_ms: 300,
+            ..test_config()
+        };
+        let vad = VadProcessor::new(config, 16000);
+
+        // 無音 300ms + 発話 1s + 無音 500ms
+        let mut samples = make_samples(0.0, 4800);
+        samples.extend(make_samples(0.1, 16000));
+        samples.extend(make_samples(0.001, 8000));
+        let trimmed = vad.finalize_segment(samples);
+        // 発話 1s + 前後の余白 100ms ずつ
+        assert_eq!(trimmed.len(), 16000 + 2 * 1600);
+
+        // 全体が発話なら変わらない
+        let voiced = make_samples(0.1, 16000);
+        assert_eq!(vad.finalize_segment(voiced.clone()), voiced);
+    }
+
+    #[test]
+    fn test_trim_silence_keeps_min_segment_length() {
+        let config = VadConfig {
+            energy_threshold: 0.02,
+            trim_silence: true,
+            min_segment_ms: 500,
+            ..test_config()
+        };
+        let vad = VadProcessor::new(config, 16000);
+
+        // 発話 50ms + 無音 1s → 500ms までしか切り詰めない
+        let mut samples = make_samples(0.1, 800);
+        samples.extend(make_samples(0.0, 16000));
+        assert_eq!(vad.finalize_segment(samples).len(), 8000);
+    }
+
     #[test]
     fn test_for_engine_whisper() {
         let config = VadConfig::for_engine(SttEngineChoice::Whisper);
@@ -490,4 +671,82 @@ mod tests {
         assert_eq!(config.max_segment_ms, 60_000);
         assert_eq!(config.silence_timeout_ms, 1000);
     }
+
+    #[test]
+    fn test_validate_rejects_invalid_combinations() {
+        assert!(VadConfig::default().validate(16_000).is_ok());
+
+        let errors = VadConfig::default().validate(100).unwrap_err();
+        assert_eq!(errors[0].field, "vad.sample_rate");
+
+        let config = VadConfig {
+            speech_start_ms: 0,
+            silence_timeout_ms: 40_000,
+            max_segment_ms: 30_000,
+            ..Default::default()
+        };
+        let fields: Vec<_> = config
+            .validate(16_000)
+            .unwrap_err()
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(
+            fields,
+            vec!["vad.speech_start_ms", "vad.silence_timeout_ms"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_config_is_clamped_instead_of_panicking() {
+        let config = VadConfig {
+            energy_threshold: 0.0,
+            speech_start_ms: 0,
+            silence_timeout_ms: 0,
+            max_segment_ms: 0,
+            ..test_config()
+        };
+        let mut vad = VadProcessor::new(config, 0);
+        assert!(vad.config().validate(16_000).is_ok());
+
+        // 0Hz 指定でもゼロ除算せずに処理できる
+        assert!(vad.process(&make_samples(0.5, 160)).len() <= 1);
+
+        vad.update_config(VadConfig {
+            silence_timeout_ms: 60_000,
+            ..test_config()
+        });
+        assert!(vad.config().silence_timeout_ms < vad.config().max_segment_ms);
+    }
+
+    #[test]
+    fn test_muted_frames_produce_no_events_and_keep_state() {
+        let config = VadConfig {
+            speech_start_ms: 10,
+            silence_timeout_ms: 30,
+            ..test_config()
+        };
+        let mut vad = VadProcessor::new(config, 16000);
+        let loud = make_samples(0.5, 160);
+        let quiet = make_samples(0.001, 160);
+
+        // 無音中にミュート: 大きな音でも発話を始めない
+        vad.set_muted(true);
+        for _ in 0..10 {
+            assert!(vad.process(&loud).is_empty());
+        }
+        assert!(!vad.is_in_speech());
+
+        // 解除後は通常どおり検出する
+        vad.set_muted(false);
+        vad.process(&loud);
+        assert_eq!(vad.process(&loud), vec![VadEvent::SpeechStart]);
+
+        // 発話中にミュート: 無音が続いても発話終了にならず、状態はそのまま
+        vad.set_muted(true);
+        for _ in 0..10 {
+            assert!(vad.process(&quiet).is_empty());
+        }
+        assert!(vad.is_in_speech());
+    }
 }
@@ -0,0 +1,359 @@
+use std::io::{BufReader, Seek, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::infra::stt::{AudioSegment, SttError};
+
+/// f32 モノラルサンプルを WAV ファイルとして書き出す
+///
+/// `bits_per_sample` が 16 なら 16bit PCM（±1.0 でクリップ）、32 なら 32bit float（値をそのまま保存）。
+/// それ以外は `hound::Error::Unsupported` を返す。
+pub fn write_wav(
+    path: &Path,
+    samples: &[f32],
+    sample_rate: u32,
+    bits_per_sample: u16,
+) -> Result<(), hound::Error> {
+    let sample_format = match bits_per_sample {
+        16 => hound::SampleFormat::Int,
+        32 => hound::SampleFormat::Float,
+        _ => return Err(hound::Error::Unsupported),
+    };
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample,
+        sample_format,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)?;
+    match sample_format {
+        hound::SampleFormat::Int => {
+            for &s in samples {
+                writer.write_sample((s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+            }
+        }
+        hound::SampleFormat::Float => {
+            for &s in samples {
+                writer.write_sample(s)?;
+            }
+        }
+    }
+    writer.finalize()
+}
+
+/// 所有者だけがアクセスできるディレクトリを作る（unix では 0700、既存ディレクトリも権限を絞る）
+pub fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+/// ファイルの中身をゼロで上書きしてから削除する
+///
+/// SSD のウェアレベリングやスナップショットに残った複製までは消せないが、
+/// 削除後のファイル復元で音声を読まれることは防げる。
+pub fn secure_remove(path: &Path) -> std::io::Result<()> {
+    let len = std::fs::metadata(path)?.len();
+    {
+        let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+        let zeros = [0u8; 8192];
+        let mut remaining = len;
+        while remaining > 0 {
+            let n = remaining.min(zeros.len() as u64) as usize;
+            file.write_all(&zeros[..n])?;
+            remaining -= n as u64;
+        }
+        file.sync_all()?;
+    }
+    std::fs::remove_file(path)
+}
+
+/// ディレクトリ直下の WAV ファイル
+#[derive(Debug, Clone)]
+pub struct WavFile {
+    pub path: PathBuf,
+    pub len: u64,
+    pub modified: SystemTime,
+}
+
+/// ディレクトリ直下の WAV ファイル（拡張子 .wav、大文字小文字を区別しない）を列挙する
+///
+/// ディレクトリが無ければ空を返す。サブディレクトリは辿らない。
+pub fn list_wav_files(dir: &Path) -> std::io::Result<Vec<WavFile>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        let is_wav = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"));
+        let metadata = entry.metadata()?;
+        if !is_wav || !metadata.is_file() {
+            continue;
+        }
+        files.push(WavFile {
+            path,
+            len: metadata.len(),
+            modified: metadata.modified()?,
+        });
+    }
+    Ok(files)
+}
+
+/// STT に渡せる WAV のサンプルレート（Hz）
+const WAV_SAMPLE_RATE_RANGE: RangeInclusive<u32> = 4_000..=192_000;
+
+/// WAV のヘッダを読み、STT に渡せる形式かを確かめる（サンプルは読まない）
+///
+/// 空ファイルや途中で書き込みが止まったファイル（ディスク不足など）を、
+/// STT エンジンの分かりにくいエラーになる前に `SttError::AudioFormat` として弾く。
+pub fn validate_wav(path: &Path) -> Result<hound::WavSpec, SttError> {
+    let invalid = |reason: String| {
+        SttError::AudioFormat(format!("WAV が不正です ({}): {reason}", path.display()))
+    };
+    let file = std::fs::File::open(path).map_err(|e| invalid(format!("開けません: {e}")))?;
+    let file_len = file
+        .metadata()
+        .map_err(|e| invalid(format!("サイズを取得できません: {e}")))?
+        .len();
+    let reader = hound::WavReader::new(BufReader::new(file))
+        .map_err(|e| invalid(format!("ヘッダを読めません: {e}")))?;
+    let spec = reader.spec();
+    let declared_samples = u64::from(reader.len());
+
+    if !WAV_SAMPLE_RATE_RANGE.contains(&spec.sample_rate) {
+        return Err(invalid(format!(
+            "サンプルレートが範囲外です: {}Hz",
+            spec.sample_rate
+        )));
+    }
+    if spec.channels == 0 {
+        return Err(invalid("チャンネル数が 0 です".to_string()));
+    }
+    let bits_ok = match spec.sample_format {
+        hound::SampleFormat::Int => matches!(spec.bits_per_sample, 8 | 16 | 24 | 32),
+        hound::SampleFormat::Float => spec.bits_per_sample == 32,
+    };
+    if !bits_ok {
+        return Err(invalid(format!(
+            "未対応のビット深度です: {}bit {:?}",
+            spec.bits_per_sample, spec.sample_format
+        )));
+    }
+    if declared_samples == 0 {
+        return Err(invalid("音声データが空です".to_string()));
+    }
+
+    // ヘッダが示すデータ長だけの中身がファイルに残っているか（途中で切れた書き込みの検出）
+    let data_start = reader
+        .into_inner()
+        .stream_position()
+        .map_err(|e| invalid(format!("データ位置を取得できません: {e}")))?;
+    let data_len = declared_samples * u64::from(spec.bits_per_sample / 8);
+    if file_len < data_start + data_len {
+        return Err(invalid(format!(
+            "データが途中で切れています（{} / {data_len} バイト）",
+            file_len.saturating_sub(data_start)
+        )));
+    }
+
+    Ok(spec)
+}
+
+/// WAV ファイルを読み込み、f32 モノラルの AudioSegment に変換する
+///
+/// 読み込む前に [`validate_wav`] で形式を確かめる。多チャンネルの場合は全チャンネルの平均を取る。
+pub fn read_wav(path: &Path) -> Result<AudioSegment, SttError> {
+    validate_wav(path)?;
+    let mut reader = hound::WavReader::open(path).map_err(|e| {
+        SttError::AudioFormat(format!("WAV を開けません ({}): {e}", path.display()))
+    })?;
+    let spec = reader.spec();
+
+    let interleaved: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>(),
+        hound::SampleFormat::Int => {
+            let scale = (1_i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|v| v as f32 / scale))
+                .collect::<Result<_, _>>()
+        }
+    }
+    .map_err(|e| SttError::AudioFormat(format!("WAV の読み込みに失敗しました: {e}")))?;
+
+    let channels = spec.channels as usize;
+    let samples = interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+
+    Ok(AudioSegment {
+        samples,
+        sample_rate: spec.sample_rate,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_wav_path() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("vt-wav-{}.wav", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_write_read_roundtrip() {
+        let path = temp_wav_path();
+        let samples: Vec<f32> = (0..1600).map(|i| ((i as f32) * 0.05).sin() * 0.5).collect();
+
+        write_wav(&path, &samples, 16000, 16).unwrap();
+        let segment = read_wav(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(segment.sample_rate, 16000);
+        assert_eq!(segment.samples.len(), samples.len());
+        for (a, b) in segment.samples.iter().zip(&samples) {
+            assert!((a - b).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_float_wav_keeps_samples_beyond_full_scale() {
+        let path = temp_wav_path();
+        let samples = vec![0.25, -0.5, 1.5, -2.0, 0.999_9];
+
+        write_wav(&path, &samples, 16000, 32).unwrap();
+        let spec = validate_wav(&path);
+        let segment = read_wav(&path);
+        std::fs::remove_file(&path).ok();
+
+        let spec = spec.unwrap();
+        assert_eq!(spec.bits_per_sample, 32);
+        assert_eq!(spec.sample_format, hound::SampleFormat::Float);
+        // i16 変換を通らないので ±1.0 を超える値も量子化もされずに残る
+        assert_eq!(segment.unwrap().samples, samples);
+
+        assert!(matches!(
+            write_wav(&path, &samples, 16000, 24),
+            Err(hound::Error::Unsupported)
+        ));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_read_stereo_is_downmixed() {
+        let path = temp_wav_path();
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 48000,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for _ in 0..10 {
+            writer.write_sample(0.2_f32).unwrap();
+            writer.write_sample(0.4_f32).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let segment = read_wav(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(segment.sample_rate, 48000);
+        assert_eq!(segment.samples.len(), 10);
+        assert!(segment.samples.iter().all(|&s| (s - 0.3).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_secure_remove_deletes_file() {
+        let path = temp_wav_path();
+        write_wav(&path, &[0.5; 1000], 16000, 16).unwrap();
+
+        secure_remove(&path).unwrap();
+        assert!(!path.exists());
+        // 既に無いファイルは NotFound
+        let err = secure_remove(&path).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_private_dir_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir()
+            .join(format!("vt-private-{}", uuid::Uuid::new_v4()))
+            .join("segments");
+        create_private_dir(&dir).unwrap();
+        let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+        std::fs::remove_dir_all(dir.parent().unwrap()).ok();
+
+        assert_eq!(mode & 0o777, 0o700);
+    }
+
+    #[test]
+    fn test_validate_accepts_written_wav() {
+        let path = temp_wav_path();
+        write_wav(&path, &[0.1; 1600], 16000, 16).unwrap();
+        let spec = validate_wav(&path);
+        std::fs::remove_file(&path).ok();
+
+        let spec = spec.unwrap();
+        assert_eq!(spec.sample_rate, 16000);
+        assert_eq!(spec.channels, 1);
+        assert_eq!(spec.bits_per_sample, 16);
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_and_truncated_files() {
+        // 0 バイトのファイル
+        let empty = temp_wav_path();
+        std::fs::write(&empty, b"").unwrap();
+        let empty_err = validate_wav(&empty);
+        std::fs::remove_file(&empty).ok();
+        assert!(matches!(empty_err, Err(SttError::AudioFormat(_))));
+
+        // ヘッダはあるがデータの途中で切れたファイル
+        let truncated = temp_wav_path();
+        write_wav(&truncated, &[0.1; 1600], 16000, 16).unwrap();
+        let len = std::fs::metadata(&truncated).unwrap().len();
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(&truncated)
+            .unwrap()
+            .set_len(len - 1000)
+            .unwrap();
+        let truncated_err = validate_wav(&truncated);
+        let read_err = read_wav(&truncated);
+        std::fs::remove_file(&truncated).ok();
+        match truncated_err {
+            Err(SttError::AudioFormat(message)) => assert!(message.contains("途中で切れて")),
+            other => panic!("unexpected: {other:?}"),
+        }
+        assert!(matches!(read_err, Err(SttError::AudioFormat(_))));
+
+        // サンプルの無い WAV
+        let silent = temp_wav_path();
+        write_wav(&silent, &[], 16000, 16).unwrap();
+        let silent_err = validate_wav(&silent);
+        std::fs::remove_file(&silent).ok();
+        assert!(matches!(silent_err, Err(SttError::AudioFormat(_))));
+    }
+
+    #[test]
+    fn test_read_missing_file_is_audio_format_error() {
+        let err = read_wav(&temp_wav_path()).unwrap_err();
+        assert!(matches!(err, SttError::AudioFormat(_)));
+    }
+}
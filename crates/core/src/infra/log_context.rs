@@ -0,0 +1,57 @@
+use std::fmt;
+
+/// パイプライン処理のログターゲット（`RUST_LOG=vt_core::pipeline=debug` で絞り込める）
+pub const TARGET_PIPELINE: &str = "vt_core::pipeline";
+/// STT エンジン呼び出しのログターゲット
+pub const TARGET_STT: &str = "vt_core::stt";
+/// AppService（セッション・保存処理）のログターゲット
+pub const TARGET_SERVICE: &str = "vt_core::service";
+
+/// ログ相関用のコンテキスト（session_id / segment_id）
+///
+/// `Display` で `[session=… segment=…]` 形式になり、ログ行の先頭に付けて使う。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogContext {
+    pub session_id: Option<String>,
+    pub segment_id: Option<String>,
+}
+
+impl LogContext {
+    pub fn session(session_id: impl Into<String>) -> Self {
+        Self {
+            session_id: Some(session_id.into()),
+            segment_id: None,
+        }
+    }
+
+    /// segment_id を付与したコピーを返す
+    pub fn with_segment(&self, segment_id: impl Into<String>) -> Self {
+        Self {
+            session_id: self.session_id.clone(),
+            segment_id: Some(segment_id.into()),
+        }
+    }
+}
+
+impl fmt::Display for LogContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[session={} segment={}]",
+            self.session_id.as_deref().unwrap_or("-"),
+            self.segment_id.as_deref().unwrap_or("-")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_includes_ids() {
+        let ctx = LogContext::session("s1").with_segment("seg1");
+        assert_eq!(ctx.to_string(), "[session=s1 segment=seg1]");
+        assert_eq!(LogContext::default().to_string(), "[session=- segment=-]");
+    }
+}
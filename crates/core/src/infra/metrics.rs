@@ -1,6 +1,9 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
 
+/// CSV 出力のヘッダ行
+const LATENCY_CSV_HEADER: &str = "phase,duration_ms,timestamp";
+
 /// ローカルメトリクス収集器
 pub struct Metrics {
     counters: Mutex<MetricsCounters>,
@@ -13,6 +16,7 @@ struct MetricsCounters {
     segments_transcribed: u64,
     segments_rewritten: u64,
     segments_delivered: u64,
+    frames_dropped: u64,
     errors_permission: u64,
     errors_device: u64,
     errors_stt: u64,
@@ -34,6 +38,8 @@ pub struct MetricsSummary {
     pub segments_transcribed: u64,
     pub segments_rewritten: u64,
     pub segments_delivered: u64,
+    /// 処理が追いつかずキャプチャキューから捨てた音声フレーム数
+    pub frames_dropped: u64,
     pub error_counts: ErrorCounts,
     pub avg_latency_ms: AvgLatency,
     pub recent_latencies: Vec<LatencyRecord>,
@@ -48,6 +54,16 @@ pub struct ErrorCounts {
     pub internal: u64,
 }
 
+/// メトリクスのエクスポート形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricsExportFormat {
+    /// 保持中のレイテンシ記録（1 行 1 記録）
+    Csv,
+    /// MetricsSummary 全体
+    Json,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct AvgLatency {
     pub transcribe: Option<f64>,
@@ -79,6 +95,10 @@ impl Metrics {
         self.counters.lock().unwrap().segments_delivered += 1;
     }
 
+    pub fn add_frames_dropped(&self, frames: u64) {
+        self.counters.lock().unwrap().frames_dropped += frames;
+    }
+
     pub fn inc_error(&self, code: &str) {
         let mut c = self.counters.lock().unwrap();
         match code {
@@ -105,6 +125,17 @@ impl Metrics {
         }
     }
 
+    /// 保持中のレイテンシ記録を古い順に CSV で返す（ヘッダ付き）
+    pub fn latencies_csv(&self) -> String {
+        let latencies = self.latencies.lock().unwrap();
+        let mut csv = String::from(LATENCY_CSV_HEADER);
+        csv.push('\n');
+        for r in latencies.iter() {
+            csv.push_str(&format!("{},{},{}\n", r.phase, r.duration_ms, r.timestamp));
+        }
+        csv
+    }
+
     pub fn summary(&self) -> MetricsSummary {
         let c = self.counters.lock().unwrap();
         let latencies = self.latencies.lock().unwrap();
@@ -129,6 +160,7 @@ impl Metrics {
             segments_transcribed: c.segments_transcribed,
             segments_rewritten: c.segments_rewritten,
             segments_delivered: c.segments_delivered,
+            frames_dropped: c.frames_dropped,
             error_counts: ErrorCounts {
                 permission: c.errors_permission,
                 device: c.errors_device,
@@ -162,6 +194,8 @@ mod tests {
         m.inc_sessions_started();
         m.inc_sessions_started();
         m.inc_segments_transcribed();
+        m.add_frames_dropped(3);
+        m.add_frames_dropped(0);
         m.inc_error("E_DEVICE");
         m.inc_error("E_PERMISSION");
         m.inc_error("E_INTERNAL");
@@ -169,6 +203,7 @@ mod tests {
         let s = m.summary();
         assert_eq!(s.sessions_started, 2);
         assert_eq!(s.segments_transcribed, 1);
+        assert_eq!(s.frames_dropped, 3);
         assert_eq!(s.error_counts.device, 1);
         assert_eq!(s.error_counts.permission, 1);
         assert_eq!(s.error_counts.internal, 1);
@@ -188,6 +223,23 @@ mod tests {
         assert_eq!(s.recent_latencies.len(), 3);
     }
 
+    #[test]
+    fn test_latencies_csv() {
+        let m = Metrics::new();
+        assert_eq!(m.latencies_csv(), "phase,duration_ms,timestamp\n");
+
+        m.record_latency("transcribe", 120);
+        m.record_latency("rewrite", 200);
+        m.record_latency("deliver", 5);
+
+        let csv = m.latencies_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "phase,duration_ms,timestamp");
+        assert_eq!(lines.len(), 1 + 3);
+        assert!(lines[1].starts_with("transcribe,120,"));
+        assert!(lines[3].starts_with("deliver,5,"));
+    }
+
     #[test]
     fn test_latency_cap() {
         let m = Metrics::new();
@@ -1,4 +1,6 @@
 pub mod audio;
+pub mod diagnostics;
+pub mod log_context;
 pub mod metrics;
 pub mod os_integration;
 pub mod output;
@@ -6,3 +8,5 @@ pub mod post_processor;
 pub mod rewriter;
 pub mod storage;
 pub mod stt;
+pub mod term_frequency;
+pub mod voice_command;
@@ -0,0 +1,88 @@
+use crate::domain::settings::AccumulateSeparator;
+
+/// 連続メモ用の蓄積バッファ
+///
+/// 配信のたびにテキストを区切り付きで追記し、クリップボードには蓄積済みの全文を書き込む。
+#[derive(Debug, Default)]
+pub struct AccumulationBuffer {
+    text: String,
+}
+
+impl AccumulationBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// テキストを追記し、蓄積済みの全文を返す
+    pub fn append(&mut self, text: &str, separator: AccumulateSeparator) -> &str {
+        let (join, prefix) = match separator {
+            AccumulateSeparator::Newline => ("\n", ""),
+            AccumulateSeparator::Space => (" ", ""),
+            AccumulateSeparator::Bullet => ("\n", "- "),
+        };
+        if !self.text.is_empty() {
+            self.text.push_str(join);
+        }
+        self.text.push_str(prefix);
+        self.text.push_str(text);
+        &self.text
+    }
+
+    /// 蓄積済みの全文を取り出し、バッファを空にする
+    pub fn take(&mut self) -> String {
+        std::mem::take(&mut self.text)
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_with_separators() {
+        let mut buf = AccumulationBuffer::new();
+        buf.append("一つ目", AccumulateSeparator::Newline);
+        assert_eq!(
+            buf.append("二つ目", AccumulateSeparator::Newline),
+            "一つ目\n二つ目"
+        );
+
+        let mut buf = AccumulationBuffer::new();
+        buf.append("first", AccumulateSeparator::Space);
+        assert_eq!(
+            buf.append("second", AccumulateSeparator::Space),
+            "first second"
+        );
+
+        let mut buf = AccumulationBuffer::new();
+        buf.append("買い物", AccumulateSeparator::Bullet);
+        assert_eq!(
+            buf.append("電話", AccumulateSeparator::Bullet),
+            "- 買い物\n- 電話"
+        );
+    }
+
+    #[test]
+    fn test_take_and_clear() {
+        let mut buf = AccumulationBuffer::new();
+        buf.append("a", AccumulateSeparator::Newline);
+        assert_eq!(buf.take(), "a");
+        assert!(buf.is_empty());
+
+        buf.append("b", AccumulateSeparator::Newline);
+        buf.clear();
+        assert_eq!(buf.as_str(), "");
+    }
+}
@@ -0,0 +1,42 @@
+use std::sync::mpsc;
+
+use serde::Serialize;
+
+use super::OutputTarget;
+use crate::domain::error::AppError;
+
+/// deliver_to_editor イベントのペイロード
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeliverToEditorPayload {
+    pub text: String,
+}
+
+/// アプリ内エディタ向けのイベントとして出力する
+///
+/// クリップボードには触れない（アプリ内の口述でユーザーのクリップボードを上書きしないため）。
+/// 受信側はペイロードを deliver_to_editor イベントとしてフロントエンドへ送る。
+pub struct AppEventOutput {
+    tx: mpsc::Sender<DeliverToEditorPayload>,
+}
+
+impl AppEventOutput {
+    pub fn new(tx: mpsc::Sender<DeliverToEditorPayload>) -> Self {
+        Self { tx }
+    }
+}
+
+impl OutputTarget for AppEventOutput {
+    fn deliver(&self, text: &str) -> Result<(), AppError> {
+        self.tx
+            .send(DeliverToEditorPayload {
+                text: text.to_string(),
+            })
+            .map_err(|_| AppError::internal("アプリ内エディタへの配信先がありません"))?;
+        log::info!("アプリ内エディタに出力: {} 文字", text.len());
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "app_event"
+    }
+}
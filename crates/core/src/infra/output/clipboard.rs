@@ -1,23 +1,121 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
 use super::OutputTarget;
 use crate::domain::error::AppError;
 
+/// クリップボードの読み書き
+///
+/// 本番は [`ArboardBackend`]、ヘッドレス環境やテストでは [`MemoryClipboard`] を使う。
+pub trait ClipboardBackend: Send + Sync {
+    fn set_text(&self, text: &str) -> Result<(), AppError>;
+    fn get_text(&self) -> Result<String, AppError>;
+}
+
+/// arboard によるシステムクリップボード
+pub struct ArboardBackend;
+
+impl ArboardBackend {
+    fn open() -> Result<arboard::Clipboard, AppError> {
+        arboard::Clipboard::new()
+            .map_err(|e| AppError::internal(format!("クリップボード初期化失敗: {e}")))
+    }
+}
+
+impl ClipboardBackend for ArboardBackend {
+    fn set_text(&self, text: &str) -> Result<(), AppError> {
+        Self::open()?
+            .set_text(text)
+            .map_err(|e| AppError::internal(format!("クリップボード書き込み失敗: {e}")))
+    }
+
+    fn get_text(&self) -> Result<String, AppError> {
+        Self::open()?
+            .get_text()
+            .map_err(|e| AppError::internal(format!("クリップボード読み取り失敗: {e}")))
+    }
+}
+
+/// メモリ上に保持する（ヘッドレス環境・テスト用）
+#[derive(Default)]
+pub struct MemoryClipboard {
+    text: Mutex<String>,
+}
+
+impl MemoryClipboard {
+    /// 最後に書き込まれたテキスト
+    pub fn text(&self) -> String {
+        self.text.lock().unwrap().clone()
+    }
+}
+
+impl ClipboardBackend for MemoryClipboard {
+    fn set_text(&self, text: &str) -> Result<(), AppError> {
+        *self.text.lock().unwrap() = text.to_string();
+        Ok(())
+    }
+
+    fn get_text(&self) -> Result<String, AppError> {
+        Ok(self.text())
+    }
+}
+
 /// クリップボード出力
+///
+/// 読み戻し確認が有効な場合、書き込み後にクリップボードを読み戻して一致を確かめる
+/// （サンドボックス下では書き込みが成功扱いでも反映されないことがある）。
+/// 書き込みと読み戻しの間に他アプリが上書きした可能性があるため、不一致なら 1 回だけ書き直す。
 pub struct ClipboardOutput {
-    // Phase2: arboard を使う。テスト時はモックに切り替え可能。
+    backend: Arc<dyn ClipboardBackend>,
+    verify: Arc<AtomicBool>,
 }
 
 impl ClipboardOutput {
     pub fn new() -> Self {
-        Self {}
+        Self::with_backend(Arc::new(ArboardBackend))
+    }
+
+    pub fn with_backend(backend: Arc<dyn ClipboardBackend>) -> Self {
+        Self {
+            backend,
+            verify: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// 読み戻し確認の有効/無効を切り替えるフラグ（clone して配信元と共有する）
+    pub fn verify_flag(&self) -> Arc<AtomicBool> {
+        self.verify.clone()
+    }
+
+    /// 書き込んだテキストがクリップボードに残っているか確認する（不一致なら 1 回だけ書き直す）
+    fn verify_written(&self, text: &str) -> Result<(), AppError> {
+        for attempt in 0..2 {
+            if self.backend.get_text()? == text {
+                return Ok(());
+            }
+            if attempt == 0 {
+                log::warn!("クリップボードの読み戻しが一致しないため再書き込みします");
+                self.backend.set_text(text)?;
+            }
+        }
+        Err(AppError::internal(
+            "クリップボードへの書き込みを確認できませんでした",
+        ))
+    }
+}
+
+impl Default for ClipboardOutput {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl OutputTarget for ClipboardOutput {
     fn deliver(&self, text: &str) -> Result<(), AppError> {
-        let mut ctx = arboard::Clipboard::new()
-            .map_err(|e| AppError::internal(format!("クリップボード初期化失敗: {e}")))?;
-        ctx.set_text(text)
-            .map_err(|e| AppError::internal(format!("クリップボード書き込み失敗: {e}")))?;
+        self.backend.set_text(text)?;
+        if self.verify.load(Ordering::Relaxed) {
+            self.verify_written(text)?;
+        }
         log::info!("クリップボードに出力: {} 文字", text.len());
         Ok(())
     }
@@ -26,3 +124,72 @@ impl OutputTarget for ClipboardOutput {
         "clipboard"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// メモリ上のクリップボード。`overwrites` 回だけ読み取り時に他アプリの上書きを模す
+    #[derive(Default)]
+    struct MockClipboard {
+        text: Mutex<String>,
+        drop_writes: bool,
+        overwrites: Mutex<u32>,
+        writes: Mutex<u32>,
+    }
+
+    impl ClipboardBackend for MockClipboard {
+        fn set_text(&self, text: &str) -> Result<(), AppError> {
+            *self.writes.lock().unwrap() += 1;
+            if !self.drop_writes {
+                *self.text.lock().unwrap() = text.to_string();
+            }
+            Ok(())
+        }
+
+        fn get_text(&self) -> Result<String, AppError> {
+            let mut overwrites = self.overwrites.lock().unwrap();
+            if *overwrites > 0 {
+                *overwrites -= 1;
+                *self.text.lock().unwrap() = "他アプリのテキスト".to_string();
+            }
+            Ok(self.text.lock().unwrap().clone())
+        }
+    }
+
+    fn output(mock: &Arc<MockClipboard>, verify: bool) -> ClipboardOutput {
+        let output = ClipboardOutput::with_backend(mock.clone());
+        output.verify_flag().store(verify, Ordering::Relaxed);
+        output
+    }
+
+    #[test]
+    fn test_verify_passes_when_read_back_matches() {
+        let mock = Arc::new(MockClipboard::default());
+        output(&mock, true).deliver("議事メモ").unwrap();
+        assert_eq!(*mock.text.lock().unwrap(), "議事メモ");
+        assert_eq!(*mock.writes.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_verify_retries_once_after_overwrite() {
+        let mock = Arc::new(MockClipboard {
+            overwrites: Mutex::new(1),
+            ..Default::default()
+        });
+        output(&mock, true).deliver("議事メモ").unwrap();
+        assert_eq!(*mock.text.lock().unwrap(), "議事メモ");
+        assert_eq!(*mock.writes.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_verify_fails_when_write_is_not_reflected() {
+        let mock = Arc::new(MockClipboard {
+            drop_writes: true,
+            ..Default::default()
+        });
+        assert!(output(&mock, true).deliver("議事メモ").is_err());
+        // 確認が無効なら書き込みの成否は見ない
+        assert!(output(&mock, false).deliver("議事メモ").is_ok());
+    }
+}
@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use super::OutputTarget;
+use crate::domain::error::AppError;
+use crate::infra::os_integration::PasteResult;
+#[cfg(target_os = "macos")]
+use crate::infra::os_integration::{OsIntegration, PermissionState};
+
+/// 1 イベントで送る最大 UTF-16 コード単位数（CGEvent の Unicode 文字列上限に合わせる）
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+const MAX_UTF16_PER_EVENT: usize = 16;
+/// イベント間の待ち時間のデフォルト（速すぎると取りこぼすアプリがある）
+const DEFAULT_EVENT_INTERVAL: Duration = Duration::from_millis(10);
+
+#[cfg(target_os = "macos")]
+extern "C" {
+    fn swift_keystroke_type(text: *const std::os::raw::c_char) -> bool;
+}
+
+/// キー入力としてカーソル位置にテキストを打ち込む出力（macOS 専用）
+///
+/// 貼り付けを受け付けないアプリ向け。アクセシビリティ権限が必要。
+pub struct KeystrokeOutput {
+    event_interval: Duration,
+}
+
+impl KeystrokeOutput {
+    pub fn new() -> Self {
+        Self {
+            event_interval: DEFAULT_EVENT_INTERVAL,
+        }
+    }
+
+    /// イベント間の待ち時間を指定する
+    pub fn with_event_interval(event_interval: Duration) -> Self {
+        Self { event_interval }
+    }
+
+    /// テキストを打鍵する
+    ///
+    /// 権限が無い・非対応 OS の場合は打鍵せず `FallbackClipboard` を返す（クリップボード出力は呼び出し側）。
+    #[cfg(target_os = "macos")]
+    pub fn type_text(&self, text: &str) -> Result<PasteResult, AppError> {
+        if OsIntegration::check_accessibility_permission() != PermissionState::Granted {
+            return Ok(PasteResult::FallbackClipboard {
+                reason: "アクセシビリティ権限がないためキー入力できません".to_string(),
+            });
+        }
+
+        for (i, chunk) in keystroke_chunks(text, MAX_UTF16_PER_EVENT)
+            .into_iter()
+            .enumerate()
+        {
+            if i > 0 {
+                std::thread::sleep(self.event_interval);
+            }
+            let chunk_c = std::ffi::CString::new(chunk)
+                .map_err(|e| AppError::internal(format!("キー入力文字列が不正です: {e}")))?;
+            if !unsafe { swift_keystroke_type(chunk_c.as_ptr()) } {
+                return Err(AppError::internal("キーボードイベントの生成に失敗しました"));
+            }
+        }
+
+        log::info!("キー入力で出力: {} 文字", text.chars().count());
+        Ok(PasteResult::Pasted {
+            app_bundle_id: OsIntegration::get_active_app_bundle_id().unwrap_or_default(),
+        })
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    pub fn type_text(&self, _text: &str) -> Result<PasteResult, AppError> {
+        let _ = self.event_interval;
+        Ok(PasteResult::FallbackClipboard {
+            reason: "キー入力出力は macOS のみ対応しています".to_string(),
+        })
+    }
+}
+
+impl Default for KeystrokeOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OutputTarget for KeystrokeOutput {
+    fn deliver(&self, text: &str) -> Result<(), AppError> {
+        match self.type_text(text)? {
+            PasteResult::FallbackClipboard { reason } => Err(AppError::invalid_state(reason)),
+            _ => Ok(()),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "keystroke"
+    }
+}
+
+/// テキストを 1 イベントあたり max_utf16 コード単位以下の塊に分割する（文字の途中では切らない）
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn keystroke_chunks(text: &str, max_utf16: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+
+    for ch in text.chars() {
+        let len = ch.len_utf16();
+        if current_len + len > max_utf16 && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+        current.push(ch);
+        current_len += len;
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keystroke_chunks_respects_limit() {
+        let text = "こんにちは、世界。Hello, world! 🎉";
+        let chunks = keystroke_chunks(text, 4);
+        assert_eq!(chunks.concat(), text);
+        assert!(chunks
+            .iter()
+            .all(|c| c.chars().map(char::len_utf16).sum::<usize>() <= 4));
+    }
+
+    #[test]
+    fn test_keystroke_chunks_keeps_surrogate_pairs() {
+        // 絵文字（サロゲートペア）は分割しない
+        let chunks = keystroke_chunks("a🎉b", 2);
+        assert_eq!(chunks, vec!["a", "🎉", "b"]);
+    }
+
+    #[test]
+    fn test_keystroke_chunks_empty() {
+        assert!(keystroke_chunks("", MAX_UTF16_PER_EVENT).is_empty());
+    }
+}
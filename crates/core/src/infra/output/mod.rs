@@ -1,9 +1,22 @@
+mod accumulator;
+mod app_event;
 mod clipboard;
+mod keystroke;
+mod normalize;
 
-pub use clipboard::ClipboardOutput;
+pub use accumulator::AccumulationBuffer;
+pub use app_event::{AppEventOutput, DeliverToEditorPayload};
+pub use clipboard::{ArboardBackend, ClipboardBackend, ClipboardOutput, MemoryClipboard};
+pub use keystroke::KeystrokeOutput;
+pub use normalize::normalize_for_delivery;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use crate::domain::error::AppError;
 use crate::domain::types::DeliverTarget;
+use crate::infra::os_integration::PasteResult;
 
 /// 出力先 trait
 pub trait OutputTarget: Send + Sync {
@@ -11,33 +24,70 @@ pub trait OutputTarget: Send + Sync {
     fn name(&self) -> &str;
 }
 
-/// 出力ルーター: DeliverPolicy に基づいてテキストを配信
+/// 配信の通知を受け取る trait（組み込み側が配信済みテキストを独自の索引などへ流す用途）
+///
+/// 配信に成功するたびに、改行変換・切り詰め後の実際に配信したテキストで呼ばれる。
+/// 配信処理のスレッドで同期的に呼ばれるため、重い処理は別スレッドへ渡すこと。
+pub trait DeliveryObserver: Send + Sync {
+    /// `session_id` はテキストの元になったセッション（アクティブセッションが無ければ None）
+    fn on_delivered(&self, session_id: Option<&str>, text: &str, target: &DeliverTarget);
+}
+
+/// 出力ルーター: 名前で登録された出力先にテキストを配信する
+///
+/// clipboard / keystroke は既定で登録される。組み込み側は [`OutputRouter::register`] で
+/// 独自の出力先を追加でき、`DeliverPolicy::Custom { name }` から参照できる。
 pub struct OutputRouter {
-    clipboard: ClipboardOutput,
+    targets: HashMap<String, Box<dyn OutputTarget>>,
+    /// clipboard 出力の読み戻し確認フラグ
+    verify_clipboard: Arc<AtomicBool>,
 }
 
 impl OutputRouter {
     pub fn new() -> Self {
-        Self {
-            clipboard: ClipboardOutput::new(),
-        }
+        Self::with_clipboard_backend(Arc::new(ArboardBackend))
     }
 
-    /// テキストをクリップボードに出力（Phase2はclipboardのみ）
-    pub fn deliver_clipboard(&self, text: &str) -> Result<(), AppError> {
-        self.clipboard.deliver(text)
+    /// clipboard 出力（keystroke のフォールバック含む）のバックエンドを指定して作る
+    pub fn with_clipboard_backend(backend: Arc<dyn ClipboardBackend>) -> Self {
+        let clipboard = ClipboardOutput::with_backend(backend.clone());
+        let mut router = Self {
+            targets: HashMap::new(),
+            verify_clipboard: clipboard.verify_flag(),
+        };
+        router.register(DeliverTarget::Clipboard.as_str(), clipboard);
+        router.register(
+            DeliverTarget::Keystroke.as_str(),
+            KeystrokeWithFallback {
+                keystroke: KeystrokeOutput::new(),
+                clipboard: ClipboardOutput::with_backend(backend),
+            },
+        );
+        router
     }
 
-    /// DeliverTarget に従って配信する
-    pub fn deliver(&self, target: DeliverTarget, text: &str) -> Result<(), AppError> {
-        match target {
-            DeliverTarget::Clipboard => self.deliver_clipboard(text),
-            DeliverTarget::Paste => Err(AppError::invalid_state("paste target は未実装です")),
-            DeliverTarget::FileAppend => {
-                Err(AppError::invalid_state("file_append target は未実装です"))
-            }
-            DeliverTarget::Webhook => Err(AppError::invalid_state("webhook target は未実装です")),
-        }
+    /// 出力先を登録する（同名の出力先は置き換える）
+    pub fn register(&mut self, name: impl Into<String>, target: impl OutputTarget + 'static) {
+        self.targets.insert(name.into(), Box::new(target));
+    }
+
+    /// clipboard 出力で書き込み後に読み戻して確認するか（設定 verify_clipboard）
+    pub fn set_verify_clipboard(&self, verify: bool) {
+        self.verify_clipboard.store(verify, Ordering::Relaxed);
+    }
+
+    /// 出力先が登録されているか
+    pub fn contains(&self, name: &str) -> bool {
+        self.targets.contains_key(name)
+    }
+
+    /// 指定した名前の出力先に配信する
+    pub fn deliver(&self, name: &str, text: &str) -> Result<(), AppError> {
+        let target = self
+            .targets
+            .get(name)
+            .ok_or_else(|| AppError::invalid_state(format!("{name} target は未実装です")))?;
+        target.deliver(text)
     }
 }
 
@@ -46,3 +96,83 @@ impl Default for OutputRouter {
         Self::new()
     }
 }
+
+/// キー入力で出力する。打鍵できない場合はクリップボードにフォールバック
+struct KeystrokeWithFallback {
+    keystroke: KeystrokeOutput,
+    clipboard: ClipboardOutput,
+}
+
+impl OutputTarget for KeystrokeWithFallback {
+    fn deliver(&self, text: &str) -> Result<(), AppError> {
+        match self.keystroke.type_text(text)? {
+            PasteResult::FallbackClipboard { reason } => {
+                log::warn!("キー入力できないためクリップボードに出力: {}", reason);
+                self.clipboard.deliver(text)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn name(&self) -> &str {
+        self.keystroke.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// 配信されたテキストを記録する出力先
+    struct RecordingOutput {
+        delivered: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl OutputTarget for RecordingOutput {
+        fn deliver(&self, text: &str) -> Result<(), AppError> {
+            self.delivered.lock().unwrap().push(text.to_string());
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "recording"
+        }
+    }
+
+    #[test]
+    fn test_registered_target_receives_delivery() {
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        let mut router = OutputRouter::new();
+        router.register(
+            "notes",
+            RecordingOutput {
+                delivered: delivered.clone(),
+            },
+        );
+
+        assert!(router.contains("notes"));
+        router.deliver("notes", "買い物メモ").unwrap();
+        assert_eq!(*delivered.lock().unwrap(), vec!["買い物メモ"]);
+    }
+
+    #[test]
+    fn test_clipboard_delivery_uses_injected_backend() {
+        let backend = Arc::new(MemoryClipboard::default());
+        let router = OutputRouter::with_clipboard_backend(backend.clone());
+
+        router.deliver("clipboard", "メモ").unwrap();
+        assert_eq!(backend.text(), "メモ");
+    }
+
+    #[test]
+    fn test_unregistered_target_is_error() {
+        let router = OutputRouter::new();
+        assert!(router.contains("clipboard"));
+        assert!(router.contains("keystroke"));
+
+        let err = router.deliver("webhook", "text").unwrap_err();
+        assert!(err.message.contains("webhook"));
+    }
+}
@@ -0,0 +1,81 @@
+use crate::domain::settings::LineEnding;
+
+/// 配信直前にテキストの改行コードを揃え、必要なら行末の空白を取り除く
+///
+/// `\r\n` と `\n` が混在していても、すべて `line_ending` の改行に置き換える。
+/// `trim` 有効時は各行の行末空白とテキスト末尾の空白・空行を取り除く。
+pub fn normalize_for_delivery(text: &str, line_ending: LineEnding, trim: bool) -> String {
+    let eol = line_ending.as_str();
+    let normalized = text
+        .split('\n')
+        .map(|line| {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if trim {
+                line.trim_end()
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(eol);
+    if trim {
+        normalized.trim_end().to_string()
+    } else {
+        normalized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIXED: &str = "一行目\r\n二行目\n三行目\r\n";
+
+    #[test]
+    fn test_lf_converts_crlf() {
+        assert_eq!(
+            normalize_for_delivery(MIXED, LineEnding::Lf, false),
+            "一行目\n二行目\n三行目\n"
+        );
+    }
+
+    #[test]
+    fn test_crlf_converts_lf() {
+        assert_eq!(
+            normalize_for_delivery(MIXED, LineEnding::CrLf, false),
+            "一行目\r\n二行目\r\n三行目\r\n"
+        );
+    }
+
+    #[test]
+    fn test_native_matches_platform() {
+        let expected = if cfg!(windows) {
+            "一行目\r\n二行目\r\n三行目\r\n"
+        } else {
+            "一行目\n二行目\n三行目\n"
+        };
+        assert_eq!(
+            normalize_for_delivery(MIXED, LineEnding::Native, false),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_trim_strips_trailing_whitespace() {
+        let text = "一行目  \r\n二行目\t\n\n  ";
+        assert_eq!(
+            normalize_for_delivery(text, LineEnding::CrLf, true),
+            "一行目\r\n二行目"
+        );
+        // trim 無効なら行末空白は残す
+        assert_eq!(
+            normalize_for_delivery(text, LineEnding::Lf, false),
+            "一行目  \n二行目\t\n\n  "
+        );
+        // 先頭のインデントは残す
+        assert_eq!(
+            normalize_for_delivery("  コード ", LineEnding::Lf, true),
+            "  コード"
+        );
+    }
+}
@@ -1,6 +1,9 @@
+use regex::Regex;
+
+use crate::domain::settings::RedactionRule;
 use crate::domain::types::DictionaryEntry;
 
-/// テキスト後処理パイプライン: 正規化 → 辞書置換
+/// テキスト後処理パイプライン: 正規化 → 辞書置換（→ マスキングは [`Redactor`]）
 pub struct PostProcessor;
 
 impl PostProcessor {
@@ -31,22 +34,360 @@ impl PostProcessor {
         compressed.trim().to_string()
     }
 
-    /// 辞書エントリを優先度順に適用（単純文字列置換）
+    /// 辞書エントリを優先度順に適用（単純文字列置換、`word_boundary` のエントリは単語単位）
     pub fn apply_dictionary(text: &str, entries: &[DictionaryEntry]) -> String {
         let mut result = text.to_string();
 
-        // entries は priority DESC でソート済みの前提
+        // entries は priority DESC（同順位は mode エントリ → global、id 順）でソート済みの前提
         for entry in entries {
             if !entry.enabled {
                 continue;
             }
-            result = result.replace(&entry.pattern, &entry.replacement);
+            result = if entry.word_boundary {
+                replace_whole_words(&result, &entry.pattern, &entry.replacement)
+            } else {
+                result.replace(&entry.pattern, &entry.replacement)
+            };
         }
 
         result
     }
 }
 
+/// 前後が単語の続きでない一致だけを置換する（"AI" は "AISLE" の中では置換しない）
+///
+/// 単語の続きとみなすのは CJK 以外の英数字どうしが隣り合う場合だけ。
+/// 日本語は単語を空白で区切らないため、CJK 文字との境目は常に単語境界として扱う。
+fn replace_whole_words(text: &str, pattern: &str, replacement: &str) -> String {
+    let (Some(first), Some(last)) = (pattern.chars().next(), pattern.chars().next_back()) else {
+        return text.to_string();
+    };
+
+    let mut result = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut search = 0;
+    while let Some(offset) = text[search..].find(pattern) {
+        let start = search + offset;
+        let end = start + pattern.len();
+        let joins_before = text[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|prev| is_word_char(prev) && is_word_char(first));
+        let joins_after = text[end..]
+            .chars()
+            .next()
+            .is_some_and(|next| is_word_char(next) && is_word_char(last));
+        if joins_before || joins_after {
+            // 重なった位置の一致も調べるため 1 文字だけ進める
+            search = start + text[start..].chars().next().map_or(1, char::len_utf8);
+            continue;
+        }
+        result.push_str(&text[copied..start]);
+        result.push_str(replacement);
+        copied = end;
+        search = end;
+    }
+    result.push_str(&text[copied..]);
+    result
+}
+
+/// 単語の一部になる文字（CJK 以外の英数字と `_`）
+fn is_word_char(c: char) -> bool {
+    (c.is_alphanumeric() || c == '_') && !is_cjk(c)
+}
+
+/// ひらがな・カタカナ・漢字・ハングル（全角記号を含む CJK の主なブロック）
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3000}'..='\u{30FF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{AC00}'..='\u{D7AF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{FF00}'..='\u{FFEF}'
+    )
+}
+
+/// 数詞の種類
+#[derive(Debug, Clone, Copy)]
+enum Numeral {
+    /// 一〜九
+    Digit(u64),
+    /// 十・百・千
+    Small(u64),
+    /// 万・億
+    Large(u64),
+}
+
+/// ひらがなの数詞（前方一致で長いものから照合する）
+const KANA_NUMERALS: &[(&str, Numeral)] = &[
+    ("きゅう", Numeral::Digit(9)),
+    ("じゅう", Numeral::Small(10)),
+    ("じゅっ", Numeral::Small(10)),
+    ("ひゃく", Numeral::Small(100)),
+    ("びゃく", Numeral::Small(100)),
+    ("ぴゃく", Numeral::Small(100)),
+    ("いち", Numeral::Digit(1)),
+    ("いっ", Numeral::Digit(1)),
+    ("さん", Numeral::Digit(3)),
+    ("よん", Numeral::Digit(4)),
+    ("ろく", Numeral::Digit(6)),
+    ("ろっ", Numeral::Digit(6)),
+    ("なな", Numeral::Digit(7)),
+    ("しち", Numeral::Digit(7)),
+    ("はち", Numeral::Digit(8)),
+    ("はっ", Numeral::Digit(8)),
+    ("じっ", Numeral::Small(10)),
+    ("せん", Numeral::Small(1000)),
+    ("ぜん", Numeral::Small(1000)),
+    ("まん", Numeral::Large(10_000)),
+    ("おく", Numeral::Large(100_000_000)),
+    ("に", Numeral::Digit(2)),
+    ("ご", Numeral::Digit(5)),
+];
+
+/// ひらがなの助数詞と置換後の漢字
+const KANA_COUNTERS: &[(&str, &str)] = &[
+    ("えん", "円"),
+    ("ねん", "年"),
+    ("がつ", "月"),
+    ("にち", "日"),
+    ("ふん", "分"),
+    ("ぷん", "分"),
+    ("びょう", "秒"),
+    ("にん", "人"),
+    ("かい", "回"),
+    ("さい", "歳"),
+    ("ばい", "倍"),
+];
+
+/// 漢数字の直後に来たときだけ数字に変換する助数詞
+const KANJI_COUNTERS: &[char] = &[
+    '円', '年', '月', '日', '時', '分', '秒', '人', '個', '回', '件', '歳', '倍',
+];
+
+/// 数詞列を値に変換する（"にせんにじゅうさん" → 2023）
+#[derive(Debug, Default)]
+struct NumeralParser {
+    total: u64,
+    section: u64,
+    digit: Option<u64>,
+    last_small: Option<u64>,
+    last_large: Option<u64>,
+    has_multiplier: bool,
+}
+
+impl NumeralParser {
+    /// 数詞を 1 つ取り込む（数詞として不正な並びなら false）
+    fn push(&mut self, numeral: Numeral) -> bool {
+        match numeral {
+            Numeral::Digit(d) => {
+                if self.digit.is_some() {
+                    return false;
+                }
+                self.digit = Some(d);
+            }
+            Numeral::Small(m) => {
+                if matches!(self.last_small, Some(last) if m >= last) {
+                    return false;
+                }
+                self.section += self.digit.take().unwrap_or(1) * m;
+                self.last_small = Some(m);
+                self.has_multiplier = true;
+            }
+            Numeral::Large(m) => {
+                let value = self.section + self.digit.take().unwrap_or(0);
+                if value == 0 || matches!(self.last_large, Some(last) if m >= last) {
+                    return false;
+                }
+                self.total += value * m;
+                self.section = 0;
+                self.last_small = None;
+                self.last_large = Some(m);
+                self.has_multiplier = true;
+            }
+        }
+        true
+    }
+
+    fn value(&self) -> u64 {
+        self.total + self.section + self.digit.unwrap_or(0)
+    }
+}
+
+/// 日本語の数詞を算用数字に変換する（助数詞が続く場合のみ）
+///
+/// 誤変換を避けるため保守的に変換する:
+/// - ひらがな: 十・百・千・万・億を含む数詞 + 助数詞（"ひゃくにじゅうえん" → "120円"）
+/// - 算用数字 + ひらがなの助数詞（"2023ねん" → "2023年"）
+/// - 漢数字: 2 文字以上の数詞 + 助数詞（"百二十円" → "120円"、"二〇二三年" → "2023年"）
+pub struct NumberNormalizer;
+
+impl NumberNormalizer {
+    pub fn normalize(text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut result = String::with_capacity(text.len());
+        let mut i = 0;
+
+        while i < chars.len() {
+            let matched = if chars[i].is_ascii_digit() {
+                Self::digits_with_counter(&chars, i)
+            } else if kanji_numeral(chars[i]).is_some() {
+                Self::kanji_with_counter(&chars, i)
+            } else {
+                Self::kana_with_counter(&chars, i)
+            };
+            match matched {
+                Ok((consumed, replaced)) => {
+                    result.push_str(&replaced);
+                    i += consumed;
+                }
+                Err(skip) => {
+                    result.extend(&chars[i..i + skip]);
+                    i += skip;
+                }
+            }
+        }
+        result
+    }
+
+    /// 成功時は (消費した文字数, 置換文字列)、失敗時はそのまま出力する文字数を返す
+    fn digits_with_counter(chars: &[char], start: usize) -> Result<(usize, String), usize> {
+        let end = start
+            + chars[start..]
+                .iter()
+                .take_while(|c| c.is_ascii_digit())
+                .count();
+        let (len, kanji) = counter_at(chars, end).ok_or(end - start)?;
+        let digits: String = chars[start..end].iter().collect();
+        Ok((end - start + len, format!("{digits}{kanji}")))
+    }
+
+    fn kanji_with_counter(chars: &[char], start: usize) -> Result<(usize, String), usize> {
+        let run: Vec<Numeral> = chars[start..]
+            .iter()
+            .map_while(|&c| kanji_numeral(c))
+            .collect();
+        let end = start + run.len();
+        let has_counter = chars.get(end).is_some_and(|c| KANJI_COUNTERS.contains(c));
+        if run.len() < 2 || !has_counter {
+            return Err(run.len());
+        }
+
+        // 位取り表記（二〇二三）は各桁を並べる
+        let value = if run.iter().all(|n| matches!(n, Numeral::Digit(_))) {
+            run.iter()
+                .map(|n| match n {
+                    Numeral::Digit(d) => d.to_string(),
+                    _ => unreachable!(),
+                })
+                .collect::<String>()
+        } else {
+            let mut parser = NumeralParser::default();
+            if !run.iter().all(|&n| parser.push(n)) {
+                return Err(run.len());
+            }
+            parser.value().to_string()
+        };
+        Ok((run.len() + 1, format!("{value}{}", chars[end])))
+    }
+
+    fn kana_with_counter(chars: &[char], start: usize) -> Result<(usize, String), usize> {
+        let mut parser = NumeralParser::default();
+        let mut pos = start;
+        let mut best = None;
+
+        while let Some((len, numeral)) = kana_numeral_at(chars, pos) {
+            if !parser.push(numeral) {
+                break;
+            }
+            pos += len;
+            if parser.has_multiplier {
+                if let Some((counter_len, kanji)) = counter_at(chars, pos) {
+                    best = Some((
+                        pos + counter_len - start,
+                        format!("{}{kanji}", parser.value()),
+                    ));
+                }
+            }
+        }
+        best.ok_or(1)
+    }
+}
+
+fn kanji_numeral(c: char) -> Option<Numeral> {
+    let numeral = match c {
+        '〇' => Numeral::Digit(0),
+        '一' => Numeral::Digit(1),
+        '二' => Numeral::Digit(2),
+        '三' => Numeral::Digit(3),
+        '四' => Numeral::Digit(4),
+        '五' => Numeral::Digit(5),
+        '六' => Numeral::Digit(6),
+        '七' => Numeral::Digit(7),
+        '八' => Numeral::Digit(8),
+        '九' => Numeral::Digit(9),
+        '十' => Numeral::Small(10),
+        '百' => Numeral::Small(100),
+        '千' => Numeral::Small(1000),
+        '万' => Numeral::Large(10_000),
+        '億' => Numeral::Large(100_000_000),
+        _ => return None,
+    };
+    Some(numeral)
+}
+
+/// chars[pos..] が pattern で始まるか
+fn starts_with_at(chars: &[char], pos: usize, pattern: &str) -> bool {
+    let mut rest = chars.get(pos..).unwrap_or_default().iter();
+    pattern.chars().all(|p| rest.next() == Some(&p))
+}
+
+fn kana_numeral_at(chars: &[char], pos: usize) -> Option<(usize, Numeral)> {
+    KANA_NUMERALS
+        .iter()
+        .find(|(reading, _)| starts_with_at(chars, pos, reading))
+        .map(|(reading, numeral)| (reading.chars().count(), *numeral))
+}
+
+fn counter_at(chars: &[char], pos: usize) -> Option<(usize, &'static str)> {
+    KANA_COUNTERS
+        .iter()
+        .find(|(reading, _)| starts_with_at(chars, pos, reading))
+        .map(|(reading, kanji)| (reading.chars().count(), *kanji))
+}
+
+/// 機密情報マスキング: 正規表現規則にマッチした部分を置換文字列で置き換える
+///
+/// 辞書置換の後段で適用する。規則は上から順に適用される。
+pub struct Redactor {
+    rules: Vec<(Regex, String)>,
+}
+
+impl Redactor {
+    /// 規則をコンパイルする（不正なパターンはエラー）
+    pub fn new(rules: &[RedactionRule]) -> Result<Self, regex::Error> {
+        let rules = rules
+            .iter()
+            .map(|rule| Ok((Regex::new(&rule.pattern)?, rule.replacement.clone())))
+            .collect::<Result<Vec<_>, regex::Error>>()?;
+        Ok(Self { rules })
+    }
+
+    /// マスキング済みテキストを返す
+    pub fn redact(&self, text: &str) -> String {
+        let mut result = text.to_string();
+        for (re, replacement) in &self.rules {
+            // "$1" などのキャプチャ展開はせず、置換文字列をそのまま使う
+            result = re
+                .replace_all(&result, regex::NoExpand(replacement))
+                .into_owned();
+        }
+        result
+    }
+}
+
 fn compress_whitespace(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
     let mut prev_space = false;
@@ -70,6 +411,7 @@ fn compress_whitespace(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::settings::default_redaction_rules;
     use crate::domain::types::{DictionaryEntry, DictionaryScope};
 
     #[test]
@@ -108,6 +450,7 @@ mod tests {
                 replacement: "Claude".into(),
                 priority: 10,
                 enabled: true,
+                word_boundary: false,
             },
             DictionaryEntry {
                 id: Some("2".into()),
@@ -117,6 +460,7 @@ mod tests {
                 replacement: "Rust".into(),
                 priority: 5,
                 enabled: true,
+                word_boundary: false,
             },
         ];
 
@@ -136,6 +480,7 @@ mod tests {
             replacement: "bar".into(),
             priority: 10,
             enabled: false,
+            word_boundary: false,
         }];
 
         assert_eq!(
@@ -156,6 +501,7 @@ mod tests {
                 replacement: "XYZ".into(),
                 priority: 10,
                 enabled: true,
+                word_boundary: false,
             },
             DictionaryEntry {
                 id: Some("2".into()),
@@ -165,6 +511,7 @@ mod tests {
                 replacement: "123".into(),
                 priority: 5,
                 enabled: true,
+                word_boundary: false,
             },
         ];
 
@@ -175,6 +522,55 @@ mod tests {
         );
     }
 
+    fn word_entry(pattern: &str, replacement: &str) -> DictionaryEntry {
+        DictionaryEntry {
+            id: None,
+            scope: DictionaryScope::Global,
+            mode: None,
+            pattern: pattern.into(),
+            replacement: replacement.into(),
+            priority: 0,
+            enabled: true,
+            word_boundary: true,
+        }
+    }
+
+    #[test]
+    fn test_word_boundary_skips_matches_inside_words() {
+        let entries = vec![word_entry("AI", "人工知能")];
+        assert_eq!(
+            PostProcessor::apply_dictionary("AI and AISLE, MAIL, AI_X, (AI)", &entries),
+            "人工知能 and AISLE, MAIL, AI_X, (人工知能)"
+        );
+
+        // 単語境界を指定しなければ従来どおり部分一致も置換する
+        let substring = vec![DictionaryEntry {
+            word_boundary: false,
+            ..word_entry("AI", "人工知能")
+        }];
+        assert_eq!(
+            PostProcessor::apply_dictionary("AISLE", &substring),
+            "人工知能SLE"
+        );
+    }
+
+    #[test]
+    fn test_word_boundary_treats_cjk_neighbors_as_boundaries() {
+        // 日本語は空白で区切らないため、かな・漢字に挟まれていても置換する
+        let entries = vec![word_entry("AI", "人工知能")];
+        assert_eq!(
+            PostProcessor::apply_dictionary("これはAIの話です。AIが", &entries),
+            "これは人工知能の話です。人工知能が"
+        );
+
+        // CJK のパターンは前後の文字に関係なく置換する
+        let entries = vec![word_entry("くろーど", "Claude")];
+        assert_eq!(
+            PostProcessor::apply_dictionary("xくろーどy と くろーど", &entries),
+            "xClaudey と Claude"
+        );
+    }
+
     #[test]
     fn test_full_pipeline() {
         let entries = vec![DictionaryEntry {
@@ -185,12 +581,100 @@ mod tests {
             replacement: "Claude".into(),
             priority: 10,
             enabled: true,
+            word_boundary: false,
         }];
 
         let input = "　くろーど　は　すごい　";
         assert_eq!(PostProcessor::process(input, &entries), "Claude は すごい");
     }
 
+    #[test]
+    fn test_number_normalizer_converts_numerals_with_counters() {
+        let cases = [
+            ("ひゃくにじゅうえん", "120円"),
+            (
+                "これはにせんにじゅうさんねんのはなし",
+                "これは2023年のはなし",
+            ),
+            ("じゅうにがつにじゅうにち", "12月20日"),
+            ("さんびゃくにん", "300人"),
+            ("2023ねん", "2023年"),
+            ("百二十円です", "120円です"),
+            ("二〇二三年", "2023年"),
+            ("一万五千円", "15000円"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(NumberNormalizer::normalize(input), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_number_normalizer_leaves_non_numeric_text() {
+        for text in [
+            "十分です",
+            "にほんごでじゅうぶんです",
+            "さんかいめのかいぎ",
+            "万年筆を買った",
+            "いちにちじゅう",
+            "Rust 2021",
+        ] {
+            assert_eq!(NumberNormalizer::normalize(text), text);
+        }
+    }
+
+    #[test]
+    fn test_redactor_masks_email_and_card_number() {
+        let redactor = Redactor::new(&default_redaction_rules()).unwrap();
+
+        assert_eq!(
+            redactor.redact("連絡先は taro.yamada@example.co.jp です"),
+            "連絡先は [EMAIL] です"
+        );
+        assert_eq!(
+            redactor.redact("カード番号は4111111111111111です"),
+            "カード番号は[NUMBER]です"
+        );
+        assert_eq!(
+            redactor.redact("番号 4111 1111 1111 1111 で決済"),
+            "番号 [NUMBER] で決済"
+        );
+        assert_eq!(
+            redactor.redact("電話は090-1234-5678まで"),
+            "電話は[PHONE]まで"
+        );
+    }
+
+    #[test]
+    fn test_redactor_leaves_ordinary_text() {
+        let redactor = Redactor::new(&default_redaction_rules()).unwrap();
+
+        for text in [
+            "今日は良い天気です",
+            "2025-01-15 の会議は 3 名で 10:30 から",
+            "予算は 1500000 円です",
+        ] {
+            assert_eq!(redactor.redact(text), text);
+        }
+    }
+
+    #[test]
+    fn test_redactor_custom_rule_and_invalid_pattern() {
+        let rules = vec![RedactionRule {
+            name: "project".into(),
+            pattern: r"PRJ-\d+".into(),
+            replacement: "$PROJECT".into(),
+        }];
+        let redactor = Redactor::new(&rules).unwrap();
+        assert_eq!(redactor.redact("PRJ-42 を確認"), "$PROJECT を確認");
+
+        let broken = vec![RedactionRule {
+            name: "broken".into(),
+            pattern: "[a-".into(),
+            replacement: String::new(),
+        }];
+        assert!(Redactor::new(&broken).is_err());
+    }
+
     #[test]
     fn test_empty_text() {
         assert_eq!(PostProcessor::normalize(""), "");
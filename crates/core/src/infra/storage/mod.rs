@@ -1,3 +1,4 @@
 mod repository;
+pub mod secret_store;
 
 pub use repository::Storage;
@@ -1,14 +1,25 @@
-use rusqlite::{params, Connection};
+use std::path::Path;
 
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::secret_store::{default_secret_store, MemorySecretStore, SecretStore};
 use crate::domain::error::AppError;
 use crate::domain::settings::AppSettings;
 use crate::domain::types::{
-    DictionaryEntry, DictionaryScope, HistoryPage, Mode, Segment, SessionDetail, SessionSummary,
+    DeleteSessionResult, DictionaryEntry, DictionaryScope, HistoryPage, Mode, Segment,
+    SegmentTiming, SessionDetail, SessionSummary, TranscriptTiming, WhisperTask,
 };
+use crate::infra::audio::wav;
+
+/// シークレットストアに保存する設定キー（settings テーブルには参照だけを残す）
+const SECRET_SETTING_KEYS: &[&str] = &["claude_api_key"];
+/// settings テーブル上のシークレット参照の接頭辞
+const SECRET_REF_PREFIX: &str = "secret:";
 
 /// SQLiteストレージ（sessions + segments）
 pub struct Storage {
     conn: Connection,
+    secrets: Box<dyn SecretStore>,
 }
 
 impl Storage {
@@ -16,8 +27,12 @@ impl Storage {
     pub fn open(path: &str) -> Result<Self, AppError> {
         let conn =
             Connection::open(path).map_err(|e| AppError::storage(format!("DB接続に失敗: {e}")))?;
-        let storage = Self { conn };
+        let storage = Self {
+            conn,
+            secrets: default_secret_store(path),
+        };
         storage.migrate()?;
+        storage.migrate_plaintext_secrets()?;
         Ok(storage)
     }
 
@@ -25,11 +40,20 @@ impl Storage {
     pub fn open_in_memory() -> Result<Self, AppError> {
         let conn = Connection::open_in_memory()
             .map_err(|e| AppError::storage(format!("in-memory DB作成に失敗: {e}")))?;
-        let storage = Self { conn };
+        let storage = Self {
+            conn,
+            secrets: Box::new(MemorySecretStore::default()),
+        };
         storage.migrate()?;
         Ok(storage)
     }
 
+    /// シークレットストアを差し替える
+    pub fn with_secret_store(mut self, secrets: Box<dyn SecretStore>) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
     /// スキーママイグレーション
     fn migrate(&self) -> Result<(), AppError> {
         self.conn
@@ -76,12 +100,102 @@ impl Storage {
                     key   TEXT PRIMARY KEY,
                     value TEXT NOT NULL
                 );
+
+                CREATE TABLE IF NOT EXISTS metrics_snapshots (
+                    saved_at TEXT NOT NULL,
+                    summary  TEXT NOT NULL
+                );
+
+                CREATE TABLE IF NOT EXISTS tags (
+                    session_id TEXT NOT NULL,
+                    tag        TEXT NOT NULL,
+                    PRIMARY KEY (session_id, tag),
+                    FOREIGN KEY (session_id) REFERENCES sessions(session_id)
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_tags_tag
+                    ON tags(tag);
                 ",
             )
             .map_err(|e| AppError::storage(format!("マイグレーション失敗: {e}")))?;
+
+        // 既存DB向けの列追加
+        self.add_column_if_missing("sessions", "pinned", "INTEGER NOT NULL DEFAULT 0")?;
+        // 録音時のモード（NULL は列追加前のセグメント → セッションのモードを使う）
+        self.add_column_if_missing("segments", "mode", "TEXT")?;
+        // STT の単語・区間タイミング（JSON 配列、NULL はタイミング無し）
+        self.add_column_if_missing("segments", "timings", "TEXT")?;
+        // セグメントを生成した Whisper タスク（NULL は列追加前 → transcribe）
+        self.add_column_if_missing("segments", "task", "TEXT")?;
+        // 後処理（辞書置換など）前の書き起こし（NULL は列追加前のセグメント → 後処理をやり直せない）
+        self.add_column_if_missing("segments", "original_text", "TEXT")?;
+        // 確定直前の部分書き起こし（store_partials 有効時のみ、NULL は保存していない）
+        self.add_column_if_missing("segments", "last_partial", "TEXT")?;
+        // 単語単位で置換する辞書エントリ（列追加前のエントリは部分一致のまま）
+        self.add_column_if_missing(
+            "dictionary_entries",
+            "word_boundary",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        Ok(())
+    }
+
+    /// テーブルに列が無ければ追加する（既存DBの後方互換マイグレーション）
+    fn add_column_if_missing(
+        &self,
+        table: &str,
+        column: &str,
+        definition: &str,
+    ) -> Result<(), AppError> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("PRAGMA table_info({table})"))
+            .map_err(|e| AppError::storage(format!("テーブル情報取得失敗: {e}")))?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>(1))
+            .map_err(|e| AppError::storage(format!("テーブル情報取得失敗: {e}")))?
+            .filter_map(|r| r.ok())
+            .any(|name| name == column);
+        if !exists {
+            self.conn
+                .execute_batch(&format!(
+                    "ALTER TABLE {table} ADD COLUMN {column} {definition};"
+                ))
+                .map_err(|e| AppError::storage(format!("マイグレーション失敗: {e}")))?;
+        }
         Ok(())
     }
 
+    /// クロージャ内の書き込みを1トランザクションで実行する
+    ///
+    /// クロージャが `Err` を返した場合はロールバックする。
+    /// 既にトランザクション内で呼ばれた場合は外側のトランザクションに合流する。
+    pub fn transaction<T, F>(&self, f: F) -> Result<T, AppError>
+    where
+        F: FnOnce(&Self) -> Result<T, AppError>,
+    {
+        if !self.conn.is_autocommit() {
+            return f(self);
+        }
+
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| AppError::storage(format!("トランザクション開始失敗: {e}")))?;
+        let value = f(self)?;
+        tx.commit()
+            .map_err(|e| AppError::storage(format!("コミット失敗: {e}")))?;
+        Ok(value)
+    }
+
+    /// SQL を直接実行する（テストで書き込みを失敗させるトリガーを仕掛けるため）
+    #[cfg(test)]
+    pub(crate) fn execute_batch(&self, sql: &str) -> Result<(), AppError> {
+        self.conn
+            .execute_batch(sql)
+            .map_err(|e| AppError::storage(e.to_string()))
+    }
+
     // --- Sessions ---
 
     pub fn insert_session(&self, session_id: &str, mode: Mode, now: &str) -> Result<(), AppError> {
@@ -111,18 +225,179 @@ impl Storage {
         Ok(())
     }
 
+    /// before より前に更新されたまま idle 以外で残っているセッションを idle に戻す
+    ///
+    /// クラッシュ等で録音・書き起こし途中のまま残ったセッションの復旧用。戻した件数を返す。
+    pub fn reset_stale_sessions(&self, before: &str) -> Result<u32, AppError> {
+        let affected = self
+            .conn
+            .execute(
+                "UPDATE sessions SET state = 'idle' WHERE state != 'idle' AND updated_at < ?1",
+                params![before],
+            )
+            .map_err(|e| AppError::storage(format!("セッション状態復旧失敗: {e}")))?;
+        Ok(affected as u32)
+    }
+
+    /// セッションのピン留め状態を設定する（該当セッションが無ければ false）
+    pub fn set_session_pinned(&self, session_id: &str, pinned: bool) -> Result<bool, AppError> {
+        let affected = self
+            .conn
+            .execute(
+                "UPDATE sessions SET pinned = ?1 WHERE session_id = ?2",
+                params![pinned as i32, session_id],
+            )
+            .map_err(|e| AppError::storage(format!("ピン留め更新失敗: {e}")))?;
+        Ok(affected > 0)
+    }
+
+    /// 指定セッションとその全セグメントを削除する
+    ///
+    /// DB 行はトランザクション内で segments → sessions の順に削除し、
+    /// コミット後に `audio_path` が設定されていた音声ファイルを削除する。
+    pub fn delete_session(&self, session_id: &str) -> Result<DeleteSessionResult, AppError> {
+        let (audio_paths, segments_deleted, sessions_deleted) = self.transaction(|s| {
+            let mut stmt = s
+                .conn
+                .prepare(
+                    "SELECT audio_path FROM segments
+                     WHERE session_id = ?1 AND audio_path IS NOT NULL",
+                )
+                .map_err(|e| AppError::storage(format!("クエリ準備失敗: {e}")))?;
+            let audio_paths: Vec<String> = stmt
+                .query_map(params![session_id], |row| row.get(0))
+                .map_err(|e| AppError::storage(format!("クエリ実行失敗: {e}")))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| AppError::storage(format!("行読み取り失敗: {e}")))?;
+
+            let segments_deleted = s
+                .conn
+                .execute(
+                    "DELETE FROM segments WHERE session_id = ?1",
+                    params![session_id],
+                )
+                .map_err(|e| AppError::storage(format!("セグメント削除失敗: {e}")))?;
+            s.conn
+                .execute(
+                    "DELETE FROM tags WHERE session_id = ?1",
+                    params![session_id],
+                )
+                .map_err(|e| AppError::storage(format!("タグ削除失敗: {e}")))?;
+            let sessions_deleted = s
+                .conn
+                .execute(
+                    "DELETE FROM sessions WHERE session_id = ?1",
+                    params![session_id],
+                )
+                .map_err(|e| AppError::storage(format!("セッション削除失敗: {e}")))?;
+            Ok((audio_paths, segments_deleted, sessions_deleted))
+        })?;
+
+        Ok(DeleteSessionResult {
+            sessions_deleted: sessions_deleted as u32,
+            segments_deleted: segments_deleted as u32,
+            audio_files_deleted: self.remove_audio_files(&audio_paths),
+        })
+    }
+
+    /// 新しい順に max 件を超えたセッションを古いものから削除する（ピン留めは対象外、0=無制限）
+    pub fn prune_to_limit(&self, max: u32) -> Result<DeleteSessionResult, AppError> {
+        let mut result = DeleteSessionResult::default();
+        if max == 0 {
+            return Ok(result);
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT session_id FROM sessions
+                 WHERE pinned = 0 AND session_id NOT IN (
+                     SELECT session_id FROM sessions ORDER BY created_at DESC LIMIT ?1
+                 )",
+            )
+            .map_err(|e| AppError::storage(format!("クエリ準備失敗: {e}")))?;
+        let session_ids: Vec<String> = stmt
+            .query_map(params![max], |row| row.get(0))
+            .map_err(|e| AppError::storage(format!("クエリ実行失敗: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::storage(format!("行読み取り失敗: {e}")))?;
+        drop(stmt);
+
+        for session_id in &session_ids {
+            let deleted = self.delete_session(session_id)?;
+            result.sessions_deleted += deleted.sessions_deleted;
+            result.segments_deleted += deleted.segments_deleted;
+            result.audio_files_deleted += deleted.audio_files_deleted;
+        }
+        Ok(result)
+    }
+
+    // --- Tags ---
+
+    /// セッションにタグを付ける（付与済みなら何もしない。セッションが無ければ false）
+    pub fn add_tag(&self, session_id: &str, tag: &str) -> Result<bool, AppError> {
+        let exists: bool = self
+            .conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM sessions WHERE session_id = ?1)",
+                params![session_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| AppError::storage(format!("セッション確認失敗: {e}")))?;
+        if !exists {
+            return Ok(false);
+        }
+        self.conn
+            .execute(
+                "INSERT OR IGNORE INTO tags (session_id, tag) VALUES (?1, ?2)",
+                params![session_id, tag],
+            )
+            .map_err(|e| AppError::storage(format!("タグ追加失敗: {e}")))?;
+        Ok(true)
+    }
+
+    /// セッションからタグを外す（付いていなければ false）
+    pub fn remove_tag(&self, session_id: &str, tag: &str) -> Result<bool, AppError> {
+        let affected = self
+            .conn
+            .execute(
+                "DELETE FROM tags WHERE session_id = ?1 AND tag = ?2",
+                params![session_id, tag],
+            )
+            .map_err(|e| AppError::storage(format!("タグ削除失敗: {e}")))?;
+        Ok(affected > 0)
+    }
+
+    /// セッションのタグを名前順に返す
+    pub fn list_tags(&self, session_id: &str) -> Result<Vec<String>, AppError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT tag FROM tags WHERE session_id = ?1 ORDER BY tag")
+            .map_err(|e| AppError::storage(format!("クエリ準備失敗: {e}")))?;
+        let tags = stmt
+            .query_map(params![session_id], |row| row.get(0))
+            .map_err(|e| AppError::storage(format!("クエリ実行失敗: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::storage(format!("行読み取り失敗: {e}")))?;
+        Ok(tags)
+    }
+
     // --- Segments ---
 
+    /// セグメントを追加する（mode は録音時にアクティブだったモード）
     pub fn insert_segment(
         &self,
         segment_id: &str,
         session_id: &str,
+        mode: Mode,
         now: &str,
     ) -> Result<(), AppError> {
+        let mode_str = serde_json::to_value(mode)
+            .map_err(|e| AppError::internal(format!("mode serialize: {e}")))?;
         self.conn
             .execute(
-                "INSERT INTO segments (segment_id, session_id, created_at) VALUES (?1, ?2, ?3)",
-                params![segment_id, session_id, now],
+                "INSERT INTO segments (segment_id, session_id, mode, created_at) VALUES (?1, ?2, ?3, ?4)",
+                params![segment_id, session_id, mode_str.as_str().unwrap_or("raw"), now],
             )
             .map_err(|e| AppError::storage(format!("セグメント挿入失敗: {e}")))?;
         Ok(())
@@ -143,6 +418,77 @@ impl Storage {
         Ok(())
     }
 
+    /// セグメントの raw_text を更新する（ユーザーによる修正）
+    ///
+    /// 修正後のテキストを後処理前のテキストとしても保存し、後処理のやり直しで修正が失われないようにする。
+    pub fn update_segment_raw(&self, segment_id: &str, raw_text: &str) -> Result<(), AppError> {
+        self.conn
+            .execute(
+                "UPDATE segments SET raw_text = ?1, original_text = ?1 WHERE segment_id = ?2",
+                params![raw_text, segment_id],
+            )
+            .map_err(|e| AppError::storage(format!("セグメントテキスト更新失敗: {e}")))?;
+        Ok(())
+    }
+
+    /// 後処理をやり直した結果で raw_text だけを更新する（後処理前のテキストは変えない）
+    pub fn update_segment_processed_text(
+        &self,
+        segment_id: &str,
+        raw_text: &str,
+    ) -> Result<(), AppError> {
+        self.conn
+            .execute(
+                "UPDATE segments SET raw_text = ?1 WHERE segment_id = ?2",
+                params![raw_text, segment_id],
+            )
+            .map_err(|e| AppError::storage(format!("セグメントテキスト更新失敗: {e}")))?;
+        Ok(())
+    }
+
+    /// セグメントに後処理前の書き起こしを記録する
+    pub fn set_segment_original_text(
+        &self,
+        segment_id: &str,
+        original_text: &str,
+    ) -> Result<(), AppError> {
+        self.conn
+            .execute(
+                "UPDATE segments SET original_text = ?1 WHERE segment_id = ?2",
+                params![original_text, segment_id],
+            )
+            .map_err(|e| AppError::storage(format!("後処理前テキスト更新失敗: {e}")))?;
+        Ok(())
+    }
+
+    /// セッション内で後処理前の書き起こしが記録されているセグメントの (segment_id, 録音時のモード, 後処理前テキスト) を返す
+    pub fn list_segment_original_texts(
+        &self,
+        session_id: &str,
+    ) -> Result<Vec<(String, Mode, String)>, AppError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT seg.segment_id, COALESCE(seg.mode, s.mode), seg.original_text
+                 FROM segments seg JOIN sessions s ON s.session_id = seg.session_id
+                 WHERE seg.session_id = ?1 AND seg.original_text IS NOT NULL
+                 ORDER BY seg.created_at ASC",
+            )
+            .map_err(|e| AppError::storage(format!("クエリ準備失敗: {e}")))?;
+        let rows = stmt
+            .query_map(params![session_id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    parse_mode(&row.get::<_, String>(1)?),
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(|e| AppError::storage(format!("クエリ実行失敗: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::storage(format!("行読み取り失敗: {e}")))?;
+        Ok(rows)
+    }
+
     pub fn update_segment_rewritten(
         &self,
         segment_id: &str,
@@ -157,6 +503,199 @@ impl Storage {
         Ok(())
     }
 
+    /// セグメントが属するセッションの updated_at を更新する（該当セグメントが無ければ false）
+    pub fn touch_segment_session(&self, segment_id: &str, now: &str) -> Result<bool, AppError> {
+        let affected = self
+            .conn
+            .execute(
+                "UPDATE sessions SET updated_at = ?1
+                 WHERE session_id = (SELECT session_id FROM segments WHERE segment_id = ?2)",
+                params![now, segment_id],
+            )
+            .map_err(|e| AppError::storage(format!("セッション更新日時の更新失敗: {e}")))?;
+        Ok(affected > 0)
+    }
+
+    /// セグメントに音声ファイルのパスを記録する
+    pub fn set_segment_audio_path(
+        &self,
+        segment_id: &str,
+        audio_path: &str,
+    ) -> Result<(), AppError> {
+        self.conn
+            .execute(
+                "UPDATE segments SET audio_path = ?1 WHERE segment_id = ?2",
+                params![audio_path, segment_id],
+            )
+            .map_err(|e| AppError::storage(format!("音声パス更新失敗: {e}")))?;
+        Ok(())
+    }
+
+    /// 音声ファイルが記録されているセグメントの音声パスをすべて返す
+    pub fn list_segment_audio_paths(&self) -> Result<Vec<String>, AppError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT audio_path FROM segments WHERE audio_path IS NOT NULL")
+            .map_err(|e| AppError::storage(format!("クエリ準備失敗: {e}")))?;
+        let paths = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| AppError::storage(format!("クエリ実行失敗: {e}")))?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|e| AppError::storage(format!("行読み取り失敗: {e}")))?;
+        Ok(paths)
+    }
+
+    /// セグメントの音声ファイルのパスを取得する（未保存・セグメント無しは None）
+    pub fn get_segment_audio_path(&self, segment_id: &str) -> Result<Option<String>, AppError> {
+        let path: Option<Option<String>> = self
+            .conn
+            .query_row(
+                "SELECT audio_path FROM segments WHERE segment_id = ?1",
+                params![segment_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| AppError::storage(format!("音声パス取得失敗: {e}")))?;
+        Ok(path.flatten())
+    }
+
+    /// セグメントを生成した Whisper タスクを記録する
+    pub fn set_segment_task(&self, segment_id: &str, task: WhisperTask) -> Result<(), AppError> {
+        self.conn
+            .execute(
+                "UPDATE segments SET task = ?1 WHERE segment_id = ?2",
+                params![task.as_str(), segment_id],
+            )
+            .map_err(|e| AppError::storage(format!("タスク更新失敗: {e}")))?;
+        Ok(())
+    }
+
+    /// 確定直前の部分書き起こしを記録する
+    pub fn set_segment_last_partial(
+        &self,
+        segment_id: &str,
+        last_partial: &str,
+    ) -> Result<(), AppError> {
+        self.conn
+            .execute(
+                "UPDATE segments SET last_partial = ?1 WHERE segment_id = ?2",
+                params![last_partial, segment_id],
+            )
+            .map_err(|e| AppError::storage(format!("部分書き起こし更新失敗: {e}")))?;
+        Ok(())
+    }
+
+    /// セグメントに STT のタイミングを記録する（空なら NULL に戻す）
+    pub fn set_segment_timings(
+        &self,
+        segment_id: &str,
+        timings: &[TranscriptTiming],
+    ) -> Result<(), AppError> {
+        let json = if timings.is_empty() {
+            None
+        } else {
+            Some(
+                serde_json::to_string(timings)
+                    .map_err(|e| AppError::internal(format!("timings serialize: {e}")))?,
+            )
+        };
+        self.conn
+            .execute(
+                "UPDATE segments SET timings = ?1 WHERE segment_id = ?2",
+                params![json, segment_id],
+            )
+            .map_err(|e| AppError::storage(format!("タイミング更新失敗: {e}")))?;
+        Ok(())
+    }
+
+    /// 新しい順に最大 `limit` 件のセグメントの raw_text を返す（空のセグメントは除く）
+    pub fn list_recent_segment_texts(&self, limit: u32) -> Result<Vec<String>, AppError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT raw_text FROM segments
+                 WHERE raw_text != ''
+                 ORDER BY created_at DESC
+                 LIMIT ?1",
+            )
+            .map_err(|e| AppError::storage(format!("クエリ準備失敗: {e}")))?;
+        let texts = stmt
+            .query_map(params![limit], |row| row.get(0))
+            .map_err(|e| AppError::storage(format!("クエリ実行失敗: {e}")))?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|e| AppError::storage(format!("行読み取り失敗: {e}")))?;
+        Ok(texts)
+    }
+
+    /// セグメントのタイミングを確率付きで返す（タイミング未記録・セグメント無しは空）
+    ///
+    /// 確率を持たない区間はセグメント全体の confidence で補う。
+    pub fn get_segment_timings(&self, segment_id: &str) -> Result<Vec<SegmentTiming>, AppError> {
+        let row: Option<(Option<String>, f32)> = self
+            .conn
+            .query_row(
+                "SELECT timings, confidence FROM segments WHERE segment_id = ?1",
+                params![segment_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| AppError::storage(format!("タイミング取得失敗: {e}")))?;
+        let Some((Some(json), confidence)) = row else {
+            return Ok(Vec::new());
+        };
+        let timings: Vec<TranscriptTiming> = serde_json::from_str(&json).unwrap_or_else(|e| {
+            log::warn!("セグメント {segment_id} のタイミングを読めません: {e}");
+            Vec::new()
+        });
+        Ok(timings
+            .into_iter()
+            .map(|t| SegmentTiming {
+                text: t.text,
+                start_ms: t.start_ms,
+                end_ms: t.end_ms,
+                probability: t.probability.unwrap_or(confidence),
+            })
+            .collect())
+    }
+
+    /// セッションに保存されているセグメント数
+    pub fn count_session_segments(&self, session_id: &str) -> Result<u32, AppError> {
+        self.conn
+            .query_row(
+                "SELECT COUNT(*) FROM segments WHERE session_id = ?1",
+                params![session_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| AppError::storage(format!("セグメント数の取得失敗: {e}")))
+    }
+
+    /// セグメントを録音したときのモード（未記録ならセッションのモード、セグメントが無ければ None）
+    pub fn get_segment_mode(&self, segment_id: &str) -> Result<Option<Mode>, AppError> {
+        self.conn
+            .query_row(
+                "SELECT COALESCE(seg.mode, s.mode)
+                 FROM segments seg JOIN sessions s ON s.session_id = seg.session_id
+                 WHERE seg.segment_id = ?1",
+                params![segment_id],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .map(|mode| mode.as_deref().map(parse_mode))
+            .map_err(|e| AppError::storage(format!("セグメントのモード取得失敗: {e}")))
+    }
+
+    /// セグメントが属するセッションの ID（セグメントが無ければ None）
+    pub fn get_segment_session_id(&self, segment_id: &str) -> Result<Option<String>, AppError> {
+        self.conn
+            .query_row(
+                "SELECT session_id FROM segments WHERE segment_id = ?1",
+                params![segment_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| AppError::storage(format!("セグメントのセッション取得失敗: {e}")))
+    }
+
     // --- Dictionary ---
 
     pub fn upsert_dictionary_entry(&self, entry: &DictionaryEntry) -> Result<String, AppError> {
@@ -177,15 +716,17 @@ impl Storage {
 
         self.conn
             .execute(
-                "INSERT INTO dictionary_entries (id, scope, mode, pattern, replacement, priority, enabled)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                "INSERT INTO dictionary_entries
+                    (id, scope, mode, pattern, replacement, priority, enabled, word_boundary)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
                  ON CONFLICT(id) DO UPDATE SET
                     scope = excluded.scope,
                     mode = excluded.mode,
                     pattern = excluded.pattern,
                     replacement = excluded.replacement,
                     priority = excluded.priority,
-                    enabled = excluded.enabled",
+                    enabled = excluded.enabled,
+                    word_boundary = excluded.word_boundary",
                 params![
                     id,
                     scope_str,
@@ -194,6 +735,7 @@ impl Storage {
                     entry.replacement,
                     entry.priority,
                     entry.enabled as i32,
+                    entry.word_boundary as i32,
                 ],
             )
             .map_err(|e| AppError::storage(format!("辞書エントリ保存失敗: {e}")))?;
@@ -212,7 +754,7 @@ impl Storage {
             stmt = self
                 .conn
                 .prepare(
-                    "SELECT id, scope, mode, pattern, replacement, priority, enabled
+                    "SELECT id, scope, mode, pattern, replacement, priority, enabled, word_boundary
                      FROM dictionary_entries
                      WHERE scope = ?1
                      ORDER BY priority DESC",
@@ -227,7 +769,7 @@ impl Storage {
             stmt = self
                 .conn
                 .prepare(
-                    "SELECT id, scope, mode, pattern, replacement, priority, enabled
+                    "SELECT id, scope, mode, pattern, replacement, priority, enabled, word_boundary
                      FROM dictionary_entries
                      ORDER BY priority DESC",
                 )
@@ -250,11 +792,11 @@ impl Storage {
         let mut stmt = self
             .conn
             .prepare(
-                "SELECT id, scope, mode, pattern, replacement, priority, enabled
+                "SELECT id, scope, mode, pattern, replacement, priority, enabled, word_boundary
                  FROM dictionary_entries
                  WHERE enabled = 1
                    AND (scope = 'global' OR (scope = ?1 AND (mode IS NULL OR mode = ?2)))
-                 ORDER BY priority DESC",
+                 ORDER BY priority DESC, (scope = 'mode') DESC, id",
             )
             .map_err(|e| AppError::storage(format!("クエリ準備失敗: {e}")))?;
 
@@ -281,6 +823,7 @@ impl Storage {
         let scope_str: String = row.get(1)?;
         let mode_str: Option<String> = row.get(2)?;
         let enabled_int: i32 = row.get(6)?;
+        let word_boundary_int: i32 = row.get(7)?;
 
         Ok(DictionaryEntry {
             id: Some(row.get(0)?),
@@ -293,173 +836,160 @@ impl Storage {
             replacement: row.get(4)?,
             priority: row.get(5)?,
             enabled: enabled_int != 0,
+            word_boundary: word_boundary_int != 0,
         })
     }
 
     // --- Queries ---
 
+    /// 履歴一覧を取得する
+    ///
+    /// `pinned_first` が true の場合はピン留めセッションを created_at に関係なく先頭に並べる。
+    /// その場合カーソルは `"{pinned}|{created_at}"` 形式になる。
+    /// created_at は `Z` と `+09:00` 等のオフセット表記が混在しうるため、文字列ではなく
+    /// julianday（UTC に換算したミリ秒精度の日時）で並べ替え・比較する。
     pub fn list_history(
         &self,
         limit: u32,
         cursor: Option<&str>,
         query: Option<&str>,
+        pinned_first: bool,
+    ) -> Result<HistoryPage, AppError> {
+        self.query_history(limit, cursor, query, pinned_first, None)
+    }
+
+    /// 指定タグの付いたセッションを新しい順に返す（カーソルは `list_history` と同じ created_at）
+    pub fn list_history_by_tag(
+        &self,
+        tag: &str,
+        limit: u32,
+        cursor: Option<&str>,
+    ) -> Result<HistoryPage, AppError> {
+        self.query_history(limit, cursor, None, false, Some(tag))
+    }
+
+    fn query_history(
+        &self,
+        limit: u32,
+        cursor: Option<&str>,
+        query: Option<&str>,
+        pinned_first: bool,
+        tag: Option<&str>,
     ) -> Result<HistoryPage, AppError> {
         let search_pattern = query
             .map(str::trim)
             .filter(|q| !q.is_empty())
             .map(|q| format!("%{q}%"));
-        let mut stmt;
-        let rows: Vec<SessionSummary>;
-
-        if let Some(cursor_ts) = cursor {
-            if let Some(pattern) = search_pattern.as_deref() {
-                stmt = self
-                    .conn
-                    .prepare(
-                        "SELECT s.session_id, s.state, s.mode, s.created_at, s.updated_at,
-                                (SELECT COUNT(*) FROM segments seg WHERE seg.session_id = s.session_id) as seg_count,
-                            (SELECT COALESCE(seg2.rewritten_text, seg2.raw_text)
-                             FROM segments seg2 WHERE seg2.session_id = s.session_id
-                             ORDER BY seg2.created_at LIMIT 1) as preview
-                         FROM sessions s
-                         WHERE s.created_at < ?1
-                           AND (
-                             s.session_id LIKE ?2 OR
-                             EXISTS (
-                               SELECT 1
-                               FROM segments seg
-                               WHERE seg.session_id = s.session_id
-                                 AND (
-                                   seg.raw_text LIKE ?2 OR
-                                   COALESCE(seg.rewritten_text, '') LIKE ?2
-                                 )
-                             )
-                           )
-                         ORDER BY s.created_at DESC
-                         LIMIT ?3",
-                    )
-                    .map_err(|e| AppError::storage(format!("クエリ準備失敗: {e}")))?;
-                rows = stmt
-                    .query_map(params![cursor_ts, pattern, limit + 1], |row| {
-                        Ok(SessionSummary {
-                            session_id: row.get(0)?,
-                            state: row.get(1)?,
-                            mode: parse_mode(row.get::<_, String>(2)?.as_str()),
-                            created_at: row.get(3)?,
-                            updated_at: row.get(4)?,
-                            segment_count: row.get(5)?,
-                            preview_text: row.get(6)?,
-                        })
-                    })
-                    .map_err(|e| AppError::storage(format!("クエリ実行失敗: {e}")))?
-                    .collect::<Result<Vec<_>, _>>()
-                    .map_err(|e| AppError::storage(format!("行読み取り失敗: {e}")))?;
-            } else {
-                stmt = self
-                    .conn
-                    .prepare(
-                        "SELECT s.session_id, s.state, s.mode, s.created_at, s.updated_at,
-                                (SELECT COUNT(*) FROM segments seg WHERE seg.session_id = s.session_id) as seg_count,
-                            (SELECT COALESCE(seg2.rewritten_text, seg2.raw_text)
-                             FROM segments seg2 WHERE seg2.session_id = s.session_id
-                             ORDER BY seg2.created_at LIMIT 1) as preview
-                         FROM sessions s
-                         WHERE s.created_at < ?1
-                         ORDER BY s.created_at DESC
-                         LIMIT ?2",
-                    )
-                    .map_err(|e| AppError::storage(format!("クエリ準備失敗: {e}")))?;
-                rows = stmt
-                    .query_map(params![cursor_ts, limit + 1], |row| {
-                        Ok(SessionSummary {
-                            session_id: row.get(0)?,
-                            state: row.get(1)?,
-                            mode: parse_mode(row.get::<_, String>(2)?.as_str()),
-                            created_at: row.get(3)?,
-                            updated_at: row.get(4)?,
-                            segment_count: row.get(5)?,
-                            preview_text: row.get(6)?,
-                        })
-                    })
-                    .map_err(|e| AppError::storage(format!("クエリ実行失敗: {e}")))?
-                    .collect::<Result<Vec<_>, _>>()
-                    .map_err(|e| AppError::storage(format!("行読み取り失敗: {e}")))?;
+
+        let mut conditions: Vec<String> = Vec::new();
+        let mut values: Vec<rusqlite::types::Value> = Vec::new();
+
+        if let Some(cursor) = cursor {
+            match (pinned_first, cursor.split_once('|')) {
+                (true, Some((pinned, ts))) => {
+                    values.push(i64::from(pinned == "1").into());
+                    values.push(ts.to_string().into());
+                    let (p, t) = (values.len() - 1, values.len());
+                    conditions.push(format!(
+                        "(s.pinned < ?{p} OR (s.pinned = ?{p} AND julianday(s.created_at) < julianday(?{t})))"
+                    ));
+                }
+                _ => {
+                    values.push(cursor.to_string().into());
+                    conditions.push(format!(
+                        "julianday(s.created_at) < julianday(?{})",
+                        values.len()
+                    ));
+                }
             }
-        } else if let Some(pattern) = search_pattern.as_deref() {
-            stmt = self
-                .conn
-                .prepare(
-                    "SELECT s.session_id, s.state, s.mode, s.created_at, s.updated_at,
-                            (SELECT COUNT(*) FROM segments seg WHERE seg.session_id = s.session_id) as seg_count,
-                            (SELECT COALESCE(seg2.rewritten_text, seg2.raw_text)
-                             FROM segments seg2 WHERE seg2.session_id = s.session_id
-                             ORDER BY seg2.created_at LIMIT 1) as preview
-                     FROM sessions s
-                     WHERE
-                       s.session_id LIKE ?1 OR
-                       EXISTS (
-                         SELECT 1
-                         FROM segments seg
-                         WHERE seg.session_id = s.session_id
-                           AND (
-                             seg.raw_text LIKE ?1 OR
-                             COALESCE(seg.rewritten_text, '') LIKE ?1
-                           )
-                       )
-                     ORDER BY s.created_at DESC
-                     LIMIT ?2",
-                )
-                .map_err(|e| AppError::storage(format!("クエリ準備失敗: {e}")))?;
-            rows = stmt
-                .query_map(params![pattern, limit + 1], |row| {
-                    Ok(SessionSummary {
-                        session_id: row.get(0)?,
-                        state: row.get(1)?,
-                        mode: parse_mode(row.get::<_, String>(2)?.as_str()),
-                        created_at: row.get(3)?,
-                        updated_at: row.get(4)?,
-                        segment_count: row.get(5)?,
-                    preview_text: row.get(6)?,
-                    })
-                })
-                .map_err(|e| AppError::storage(format!("クエリ実行失敗: {e}")))?
-                .collect::<Result<Vec<_>, _>>()
-                .map_err(|e| AppError::storage(format!("行読み取り失敗: {e}")))?;
+        }
+
+        if let Some(pattern) = search_pattern {
+            values.push(pattern.into());
+            let n = values.len();
+            conditions.push(format!(
+                "(s.session_id LIKE ?{n} OR
+                  EXISTS (
+                    SELECT 1
+                    FROM segments seg
+                    WHERE seg.session_id = s.session_id
+                      AND (
+                        seg.raw_text LIKE ?{n} OR
+                        COALESCE(seg.rewritten_text, '') LIKE ?{n}
+                      )
+                  ))"
+            ));
+        }
+
+        if let Some(tag) = tag {
+            values.push(tag.to_string().into());
+            conditions.push(format!(
+                "EXISTS (SELECT 1 FROM tags t WHERE t.session_id = s.session_id AND t.tag = ?{})",
+                values.len()
+            ));
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
         } else {
-            stmt = self
-                .conn
-                .prepare(
-                    "SELECT s.session_id, s.state, s.mode, s.created_at, s.updated_at,
-                            (SELECT COUNT(*) FROM segments seg WHERE seg.session_id = s.session_id) as seg_count,
-                            (SELECT COALESCE(seg2.rewritten_text, seg2.raw_text)
-                             FROM segments seg2 WHERE seg2.session_id = s.session_id
-                             ORDER BY seg2.created_at LIMIT 1) as preview
-                     FROM sessions s
-                     ORDER BY s.created_at DESC
-                     LIMIT ?1",
-                )
-                .map_err(|e| AppError::storage(format!("クエリ準備失敗: {e}")))?;
-            rows = stmt
-                .query_map(params![limit + 1], |row| {
-                    Ok(SessionSummary {
-                        session_id: row.get(0)?,
-                        state: row.get(1)?,
-                        mode: parse_mode(row.get::<_, String>(2)?.as_str()),
-                        created_at: row.get(3)?,
-                        updated_at: row.get(4)?,
-                        segment_count: row.get(5)?,
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let order_clause = if pinned_first {
+            "ORDER BY s.pinned DESC, julianday(s.created_at) DESC"
+        } else {
+            "ORDER BY julianday(s.created_at) DESC"
+        };
+        values.push(i64::from(limit + 1).into());
+        let limit_idx = values.len();
+
+        let sql = format!(
+            "SELECT s.session_id, s.state, s.mode, s.created_at, s.updated_at,
+                    (SELECT COUNT(*) FROM segments seg WHERE seg.session_id = s.session_id) as seg_count,
+                    (SELECT COALESCE(seg2.rewritten_text, seg2.raw_text)
+                     FROM segments seg2 WHERE seg2.session_id = s.session_id
+                     ORDER BY seg2.created_at LIMIT 1) as preview,
+                    s.pinned
+             FROM sessions s
+             {where_clause}
+             {order_clause}
+             LIMIT ?{limit_idx}"
+        );
+
+        let mut stmt = self
+            .conn
+            .prepare(&sql)
+            .map_err(|e| AppError::storage(format!("クエリ準備失敗: {e}")))?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(values), |row| {
+                Ok(SessionSummary {
+                    session_id: row.get(0)?,
+                    state: row.get(1)?,
+                    mode: parse_mode(row.get::<_, String>(2)?.as_str()),
+                    created_at: row.get(3)?,
+                    updated_at: row.get(4)?,
+                    segment_count: row.get(5)?,
                     preview_text: row.get(6)?,
-                    })
+                    pinned: row.get::<_, i32>(7)? != 0,
+                    tags: Vec::new(),
                 })
-                .map_err(|e| AppError::storage(format!("クエリ実行失敗: {e}")))?
-                .collect::<Result<Vec<_>, _>>()
-                .map_err(|e| AppError::storage(format!("行読み取り失敗: {e}")))?;
-        }
+            })
+            .map_err(|e| AppError::storage(format!("クエリ実行失敗: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::storage(format!("行読み取り失敗: {e}")))?;
 
         let has_next = rows.len() > limit as usize;
-        let items: Vec<SessionSummary> = rows.into_iter().take(limit as usize).collect();
+        let mut items: Vec<SessionSummary> = rows.into_iter().take(limit as usize).collect();
+        for item in &mut items {
+            item.tags = self.list_tags(&item.session_id)?;
+        }
         let next_cursor = if has_next {
-            items.last().map(|s| s.created_at.clone())
+            items.last().map(|s| {
+                if pinned_first {
+                    format!("{}|{}", u8::from(s.pinned), s.created_at)
+                } else {
+                    s.created_at.clone()
+                }
+            })
         } else {
             None
         };
@@ -493,13 +1023,14 @@ impl Storage {
         let mut seg_stmt = self
             .conn
             .prepare(
-                "SELECT segment_id, session_id, raw_text, rewritten_text, confidence, created_at
+                "SELECT segment_id, session_id, raw_text, rewritten_text, confidence, created_at,
+                        COALESCE(mode, ?2), task, last_partial
                  FROM segments WHERE session_id = ?1 ORDER BY created_at",
             )
             .map_err(|e| AppError::storage(format!("クエリ準備失敗: {e}")))?;
 
         let segments = seg_stmt
-            .query_map(params![session_id], |row| {
+            .query_map(params![session_id, mode_str], |row| {
                 Ok(Segment {
                     segment_id: row.get(0)?,
                     session_id: row.get(1)?,
@@ -507,6 +1038,9 @@ impl Storage {
                     rewritten_text: row.get(3)?,
                     confidence: row.get(4)?,
                     created_at: row.get(5)?,
+                    mode: parse_mode(&row.get::<_, String>(6)?),
+                    task: parse_task(row.get::<_, Option<String>>(7)?.as_deref()),
+                    last_partial: row.get(8)?,
                 })
             })
             .map_err(|e| AppError::storage(format!("クエリ実行失敗: {e}")))?
@@ -524,6 +1058,10 @@ impl Storage {
 
     // --- Settings ---
 
+    /// 設定を読み込む
+    ///
+    /// シークレットは `secret:<key>` 参照のまま返す（ストアは読まない）。
+    /// 実際の値が必要な箇所は [`Storage::get_secret`] で取得する。
     pub fn get_settings(&self) -> Result<AppSettings, AppError> {
         let mut stmt = self
             .conn
@@ -566,10 +1104,30 @@ impl Storage {
         let json = serde_json::to_value(settings)
             .map_err(|e| AppError::internal(format!("settings serialize: {e}")))?;
 
-        if let Some(obj) = json.as_object() {
+        let Some(mut obj) = json.as_object().cloned() else {
+            return Ok(());
+        };
+
+        // シークレットはストアへ移し、settings テーブルには参照のみ残す
+        for key in SECRET_SETTING_KEYS {
+            match obj.get(*key).and_then(|v| v.as_str()) {
+                Some(value) if value.starts_with(SECRET_REF_PREFIX) => {}
+                Some(value) if !value.is_empty() => {
+                    self.set_secret(key, value)?;
+                    obj.insert(key.to_string(), format!("{SECRET_REF_PREFIX}{key}").into());
+                }
+                _ => {
+                    self.secrets.delete(key)?;
+                    obj.insert(key.to_string(), serde_json::Value::Null);
+                }
+            }
+        }
+        let obj = &obj;
+
+        self.transaction(|s| {
             for (key, value) in obj {
                 let value_str = value.to_string();
-                self.conn
+                s.conn
                     .execute(
                         "INSERT INTO settings (key, value) VALUES (?1, ?2)
                          ON CONFLICT(key) DO UPDATE SET value = excluded.value",
@@ -577,22 +1135,149 @@ impl Storage {
                     )
                     .map_err(|e| AppError::storage(format!("設定保存失敗: {e}")))?;
             }
-        }
+            Ok(())
+        })
+    }
+
+    // --- Metrics ---
 
+    /// 終了時のメトリクス（MetricsSummary の JSON）を保存する
+    pub fn save_metrics_snapshot(
+        &self,
+        summary_json: &str,
+        saved_at: &str,
+    ) -> Result<(), AppError> {
+        self.conn
+            .execute(
+                "INSERT INTO metrics_snapshots (saved_at, summary) VALUES (?1, ?2)",
+                params![saved_at, summary_json],
+            )
+            .map_err(|e| AppError::storage(format!("メトリクス保存失敗: {e}")))?;
+        Ok(())
+    }
+
+    /// 最後に保存したメトリクスの JSON
+    pub fn latest_metrics_snapshot(&self) -> Result<Option<String>, AppError> {
+        self.conn
+            .query_row(
+                "SELECT summary FROM metrics_snapshots ORDER BY saved_at DESC, rowid DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| AppError::storage(format!("メトリクス読み取り失敗: {e}")))
+    }
+
+    // --- Secrets ---
+
+    /// シークレットを保存する
+    pub fn set_secret(&self, key: &str, value: &str) -> Result<(), AppError> {
+        self.secrets.set(key, value)
+    }
+
+    /// シークレットを取得する
+    pub fn get_secret(&self, key: &str) -> Result<Option<String>, AppError> {
+        self.secrets.get(key)
+    }
+
+    /// settings テーブルに平文で残っているシークレットをストアへ移行する
+    fn migrate_plaintext_secrets(&self) -> Result<(), AppError> {
+        for key in SECRET_SETTING_KEYS {
+            let value: Option<String> = self
+                .conn
+                .query_row(
+                    "SELECT value FROM settings WHERE key = ?1",
+                    params![key],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| AppError::storage(format!("設定読み取り失敗: {e}")))?;
+            let plaintext = value
+                .and_then(|v| serde_json::from_str::<Option<String>>(&v).ok().flatten())
+                .filter(|v| !v.is_empty() && !v.starts_with(SECRET_REF_PREFIX));
+            if let Some(secret) = plaintext {
+                self.set_secret(key, &secret)?;
+                let reference = serde_json::Value::from(format!("{SECRET_REF_PREFIX}{key}"));
+                self.conn
+                    .execute(
+                        "UPDATE settings SET value = ?1 WHERE key = ?2",
+                        params![reference.to_string(), key],
+                    )
+                    .map_err(|e| AppError::storage(format!("設定保存失敗: {e}")))?;
+                log::info!("{key} をシークレットストアへ移行しました");
+            }
+        }
         Ok(())
     }
 
     // --- Data cleanup ---
 
-    pub fn delete_old_segments(&self, before_date: &str) -> Result<u32, AppError> {
-        let affected = self
+    /// 指定日時より前のセグメントを削除する
+    ///
+    /// `delete_session` と同様に、行の削除をコミットしてから音声ファイルを削除する。
+    pub fn delete_old_segments(&self, before_date: &str) -> Result<DeleteSessionResult, AppError> {
+        let (audio_paths, segments_deleted) =
+            self.transaction(|s| s.delete_old_segment_rows(before_date))?;
+
+        Ok(DeleteSessionResult {
+            segments_deleted,
+            audio_files_deleted: self.remove_audio_files(&audio_paths),
+            ..Default::default()
+        })
+    }
+
+    /// 指定日時より前のセグメント行を削除し、その音声ファイルのパスと削除した行数を返す
+    ///
+    /// 音声ファイルは消さない。呼び出し側がコミット後に [`Storage::remove_audio_files`] で消す。
+    pub fn delete_old_segment_rows(
+        &self,
+        before_date: &str,
+    ) -> Result<(Vec<String>, u32), AppError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT audio_path FROM segments
+                 WHERE created_at < ?1 AND audio_path IS NOT NULL",
+            )
+            .map_err(|e| AppError::storage(format!("クエリ準備失敗: {e}")))?;
+        let audio_paths: Vec<String> = stmt
+            .query_map(params![before_date], |row| row.get(0))
+            .map_err(|e| AppError::storage(format!("クエリ実行失敗: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::storage(format!("行読み取り失敗: {e}")))?;
+
+        let segments_deleted = self
             .conn
             .execute(
                 "DELETE FROM segments WHERE created_at < ?1",
                 params![before_date],
             )
             .map_err(|e| AppError::storage(format!("セグメント削除失敗: {e}")))?;
-        Ok(affected as u32)
+        Ok((audio_paths, segments_deleted as u32))
+    }
+
+    /// 音声ファイルを削除し、削除できた数を返す（既に無いファイルは数えずに無視する）
+    ///
+    /// `secure_delete` 設定が有効ならゼロで上書きしてから削除する。
+    pub fn remove_audio_files(&self, paths: &[String]) -> u32 {
+        let secure = self
+            .get_settings()
+            .map(|s| s.secure_delete)
+            .unwrap_or(false);
+        let mut removed = 0;
+        for path in paths {
+            let result = if secure {
+                wav::secure_remove(Path::new(path))
+            } else {
+                std::fs::remove_file(path)
+            };
+            match result {
+                Ok(()) => removed += 1,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => log::warn!("音声ファイル削除失敗: {path}: {e}"),
+            }
+        }
+        removed
     }
 
     pub fn delete_old_sessions(&self, before_date: &str) -> Result<u32, AppError> {
@@ -605,6 +1290,12 @@ impl Storage {
                 params![before_date],
             )
             .map_err(|e| AppError::storage(format!("セッション削除失敗: {e}")))?;
+        self.conn
+            .execute(
+                "DELETE FROM tags WHERE session_id NOT IN (SELECT session_id FROM sessions)",
+                [],
+            )
+            .map_err(|e| AppError::storage(format!("タグ削除失敗: {e}")))?;
         Ok(affected as u32)
     }
 }
@@ -620,8 +1311,16 @@ fn parse_mode(s: &str) -> Mode {
     }
 }
 
+fn parse_task(s: Option<&str>) -> WhisperTask {
+    match s {
+        Some("translate") => WhisperTask::Translate,
+        _ => WhisperTask::Transcribe,
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::super::secret_store::FileSecretStore;
     use super::*;
 
     fn now() -> String {
@@ -633,39 +1332,309 @@ mod tests {
         let storage = Storage::open_in_memory().unwrap();
         storage.insert_session("s1", Mode::Memo, &now()).unwrap();
 
-        let detail = storage.get_session_detail("s1").unwrap().unwrap();
-        assert_eq!(detail.session_id, "s1");
-        assert_eq!(detail.state, "idle");
-        assert_eq!(detail.mode, Mode::Memo);
-        assert!(detail.segments.is_empty());
+        let detail = storage.get_session_detail("s1").unwrap().unwrap();
+        assert_eq!(detail.session_id, "s1");
+        assert_eq!(detail.state, "idle");
+        assert_eq!(detail.mode, Mode::Memo);
+        assert!(detail.segments.is_empty());
+    }
+
+    #[test]
+    fn test_insert_segment_and_update() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage.insert_session("s1", Mode::Raw, &now()).unwrap();
+        storage
+            .insert_segment("seg1", "s1", Mode::Raw, &now())
+            .unwrap();
+        storage
+            .update_segment_text("seg1", "テスト書き起こし", 0.95)
+            .unwrap();
+        storage
+            .update_segment_rewritten("seg1", "テスト整形済み")
+            .unwrap();
+
+        let detail = storage.get_session_detail("s1").unwrap().unwrap();
+        assert_eq!(detail.segments.len(), 1);
+        assert_eq!(detail.segments[0].raw_text, "テスト書き起こし");
+        assert_eq!(
+            detail.segments[0].rewritten_text.as_deref(),
+            Some("テスト整形済み")
+        );
+        assert!((detail.segments[0].confidence - 0.95).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_segment_timings_round_trip() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage.insert_session("s1", Mode::Raw, &now()).unwrap();
+        storage
+            .insert_segment("seg1", "s1", Mode::Raw, &now())
+            .unwrap();
+        storage
+            .update_segment_text("seg1", "今日は晴れ", 0.7)
+            .unwrap();
+
+        // 未記録なら空
+        assert!(storage.get_segment_timings("seg1").unwrap().is_empty());
+        assert!(storage.get_segment_timings("missing").unwrap().is_empty());
+
+        let timings = vec![
+            TranscriptTiming {
+                text: "今日は".to_string(),
+                start_ms: 0,
+                end_ms: 480,
+                probability: Some(0.95),
+            },
+            TranscriptTiming {
+                text: "晴れ".to_string(),
+                start_ms: 480,
+                end_ms: 900,
+                probability: None,
+            },
+        ];
+        storage.set_segment_timings("seg1", &timings).unwrap();
+
+        let stored = storage.get_segment_timings("seg1").unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[0].text, "今日は");
+        assert_eq!((stored[0].start_ms, stored[0].end_ms), (0, 480));
+        assert!((stored[0].probability - 0.95).abs() < f32::EPSILON);
+        // 確率の無い区間はセグメントの confidence で補う
+        assert_eq!((stored[1].start_ms, stored[1].end_ms), (480, 900));
+        assert!((stored[1].probability - 0.7).abs() < f32::EPSILON);
+
+        storage.set_segment_timings("seg1", &[]).unwrap();
+        assert!(storage.get_segment_timings("seg1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_segments_retain_mode_at_capture_time() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage.insert_session("s1", Mode::Memo, &now()).unwrap();
+        storage
+            .insert_segment("seg1", "s1", Mode::Memo, "2025-01-15T10:30:00Z")
+            .unwrap();
+        storage
+            .insert_segment("seg2", "s1", Mode::Tech, "2025-01-15T10:31:00Z")
+            .unwrap();
+
+        let detail = storage.get_session_detail("s1").unwrap().unwrap();
+        let modes: Vec<_> = detail.segments.iter().map(|s| s.mode).collect();
+        assert_eq!(modes, vec![Mode::Memo, Mode::Tech]);
+        assert_eq!(storage.get_segment_mode("seg2").unwrap(), Some(Mode::Tech));
+        assert_eq!(storage.get_segment_mode("missing").unwrap(), None);
+        assert_eq!(
+            storage.get_segment_session_id("seg2").unwrap().as_deref(),
+            Some("s1")
+        );
+        assert_eq!(storage.get_segment_session_id("missing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_segment_task_round_trip() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage.insert_session("s1", Mode::Memo, &now()).unwrap();
+        storage
+            .insert_segment("seg1", "s1", Mode::Memo, "2025-01-15T10:30:00Z")
+            .unwrap();
+        storage
+            .insert_segment("seg2", "s1", Mode::Memo, "2025-01-15T10:31:00Z")
+            .unwrap();
+        storage
+            .set_segment_task("seg2", WhisperTask::Translate)
+            .unwrap();
+
+        let detail = storage.get_session_detail("s1").unwrap().unwrap();
+        let tasks: Vec<_> = detail.segments.iter().map(|s| s.task).collect();
+        assert_eq!(tasks, vec![WhisperTask::Transcribe, WhisperTask::Translate]);
+    }
+
+    #[test]
+    fn test_list_recent_segment_texts() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage.insert_session("s1", Mode::Memo, &now()).unwrap();
+        for (i, text) in ["一つ目", "", "三つ目"].iter().enumerate() {
+            let id = format!("seg{i}");
+            storage
+                .insert_segment(&id, "s1", Mode::Memo, &format!("2025-01-15T10:3{i}:00Z"))
+                .unwrap();
+            storage.update_segment_text(&id, text, 0.9).unwrap();
+        }
+
+        assert_eq!(
+            storage.list_recent_segment_texts(10).unwrap(),
+            vec!["三つ目", "一つ目"]
+        );
+        assert_eq!(
+            storage.list_recent_segment_texts(1).unwrap(),
+            vec!["三つ目"]
+        );
+    }
+
+    #[test]
+    fn test_list_history_pagination() {
+        let storage = Storage::open_in_memory().unwrap();
+        for i in 0..5 {
+            storage
+                .insert_session(
+                    &format!("s{i}"),
+                    Mode::Memo,
+                    &format!("2025-01-15T10:3{i}:00Z"),
+                )
+                .unwrap();
+        }
+
+        // Page 1: limit=2
+        let page1 = storage.list_history(2, None, None, false).unwrap();
+        assert_eq!(page1.items.len(), 2);
+        assert!(page1.next_cursor.is_some());
+        // 最新が先頭
+        assert_eq!(page1.items[0].session_id, "s4");
+        assert_eq!(page1.items[1].session_id, "s3");
+
+        // Page 2
+        let page2 = storage
+            .list_history(2, page1.next_cursor.as_deref(), None, false)
+            .unwrap();
+        assert_eq!(page2.items.len(), 2);
+        assert_eq!(page2.items[0].session_id, "s2");
+
+        // Page 3
+        let page3 = storage
+            .list_history(2, page2.next_cursor.as_deref(), None, false)
+            .unwrap();
+        assert_eq!(page3.items.len(), 1);
+        assert!(page3.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_tag_untag_and_filter_history_by_tag() {
+        let storage = Storage::open_in_memory().unwrap();
+        for i in 0..3 {
+            storage
+                .insert_session(
+                    &format!("s{i}"),
+                    Mode::Memo,
+                    &format!("2025-01-15T10:3{i}:00Z"),
+                )
+                .unwrap();
+        }
+        assert!(storage.add_tag("s0", "project-a").unwrap());
+        assert!(storage.add_tag("s2", "project-a").unwrap());
+        assert!(storage.add_tag("s2", "meeting").unwrap());
+        // 付与済みのタグは重複しない。存在しないセッションには付けない
+        assert!(storage.add_tag("s2", "meeting").unwrap());
+        assert!(!storage.add_tag("missing", "meeting").unwrap());
+        assert_eq!(
+            storage.list_tags("s2").unwrap(),
+            vec!["meeting", "project-a"]
+        );
+
+        let page = storage.list_history_by_tag("project-a", 1, None).unwrap();
+        assert_eq!(page.items[0].session_id, "s2");
+        assert_eq!(page.items[0].tags, vec!["meeting", "project-a"]);
+        let page = storage
+            .list_history_by_tag("project-a", 1, page.next_cursor.as_deref())
+            .unwrap();
+        let ids: Vec<_> = page.items.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, vec!["s0"]);
+        assert!(page.next_cursor.is_none());
+
+        // 外したタグでは絞り込まれない
+        assert!(storage.remove_tag("s0", "project-a").unwrap());
+        assert!(!storage.remove_tag("s0", "project-a").unwrap());
+        let page = storage.list_history_by_tag("project-a", 10, None).unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert!(storage.list_tags("s0").unwrap().is_empty());
+
+        // セッションを削除するとタグも消える
+        storage.delete_session("s2").unwrap();
+        assert!(storage.list_tags("s2").unwrap().is_empty());
+        assert!(storage
+            .list_history_by_tag("meeting", 10, None)
+            .unwrap()
+            .items
+            .is_empty());
+    }
+
+    #[test]
+    fn test_update_session_state() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage.insert_session("s1", Mode::Memo, &now()).unwrap();
+        storage
+            .update_session_state("s1", "recording", &now())
+            .unwrap();
+
+        let detail = storage.get_session_detail("s1").unwrap().unwrap();
+        assert_eq!(detail.state, "recording");
+    }
+
+    #[test]
+    fn test_reset_stale_sessions() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .insert_session("s_stale", Mode::Raw, "2025-01-01T00:00:00Z")
+            .unwrap();
+        storage
+            .update_session_state("s_stale", "recording", "2025-01-01T00:00:00Z")
+            .unwrap();
+        storage
+            .insert_session("s_recent", Mode::Raw, "2025-06-01T00:00:00Z")
+            .unwrap();
+        storage
+            .update_session_state("s_recent", "transcribing", "2025-06-01T00:00:00Z")
+            .unwrap();
+
+        let reset = storage
+            .reset_stale_sessions("2025-03-01T00:00:00Z")
+            .unwrap();
+        assert_eq!(reset, 1);
+
+        let stale = storage.get_session_detail("s_stale").unwrap().unwrap();
+        assert_eq!(stale.state, "idle");
+        let recent = storage.get_session_detail("s_recent").unwrap().unwrap();
+        assert_eq!(recent.state, "transcribing");
     }
 
     #[test]
-    fn test_insert_segment_and_update() {
+    fn test_edit_segment_round_trip() {
         let storage = Storage::open_in_memory().unwrap();
-        storage.insert_session("s1", Mode::Raw, &now()).unwrap();
-        storage.insert_segment("seg1", "s1", &now()).unwrap();
         storage
-            .update_segment_text("seg1", "テスト書き起こし", 0.95)
+            .insert_session("s1", Mode::Memo, "2025-01-15T10:00:00Z")
             .unwrap();
         storage
-            .update_segment_rewritten("seg1", "テスト整形済み")
+            .insert_segment("seg1", "s1", Mode::Raw, "2025-01-15T10:00:00Z")
+            .unwrap();
+        storage
+            .update_segment_text("seg1", "きょうは", 0.9)
+            .unwrap();
+        storage.update_segment_rewritten("seg1", "今日は").unwrap();
+
+        storage
+            .transaction(|s| {
+                s.update_segment_raw("seg1", "今日は晴れ")?;
+                s.update_segment_rewritten("seg1", "今日は晴れです。")?;
+                s.touch_segment_session("seg1", "2025-01-16T09:00:00Z")
+            })
             .unwrap();
+        assert!(!storage
+            .touch_segment_session("missing", "2025-01-16T09:00:00Z")
+            .unwrap());
 
         let detail = storage.get_session_detail("s1").unwrap().unwrap();
-        assert_eq!(detail.segments.len(), 1);
-        assert_eq!(detail.segments[0].raw_text, "テスト書き起こし");
+        assert_eq!(detail.segments[0].raw_text, "今日は晴れ");
         assert_eq!(
             detail.segments[0].rewritten_text.as_deref(),
-            Some("テスト整形済み")
+            Some("今日は晴れです。")
         );
-        assert!((detail.segments[0].confidence - 0.95).abs() < f32::EPSILON);
+        let page = storage.list_history(10, None, None, false).unwrap();
+        assert_eq!(page.items[0].updated_at, "2025-01-16T09:00:00Z");
     }
 
     #[test]
-    fn test_list_history_pagination() {
+    fn test_prune_to_limit_removes_oldest_unpinned() {
         let storage = Storage::open_in_memory().unwrap();
-        for i in 0..5 {
+        for i in 0..3 {
             storage
                 .insert_session(
                     &format!("s{i}"),
@@ -673,51 +1642,175 @@ mod tests {
                     &format!("2025-01-15T10:3{i}:00Z"),
                 )
                 .unwrap();
+            storage
+                .insert_segment(
+                    &format!("seg{i}"),
+                    &format!("s{i}"),
+                    Mode::Raw,
+                    "2025-01-15T10:30:00Z",
+                )
+                .unwrap();
         }
+        storage.set_session_pinned("s0", true).unwrap();
+        assert_eq!(storage.prune_to_limit(0).unwrap().sessions_deleted, 0);
 
-        // Page 1: limit=2
-        let page1 = storage.list_history(2, None, None).unwrap();
-        assert_eq!(page1.items.len(), 2);
-        assert!(page1.next_cursor.is_some());
-        // 最新が先頭
-        assert_eq!(page1.items[0].session_id, "s4");
-        assert_eq!(page1.items[1].session_id, "s3");
+        storage
+            .insert_session("s3", Mode::Memo, "2025-01-15T10:33:00Z")
+            .unwrap();
+        let result = storage.prune_to_limit(2).unwrap();
+        assert_eq!(result.sessions_deleted, 1);
+        assert_eq!(result.segments_deleted, 1);
 
-        // Page 2
+        assert!(storage.get_session_detail("s1").unwrap().is_none());
+        assert!(storage.get_session_detail("s0").unwrap().is_some());
+        assert!(storage.get_session_detail("s3").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_pinned_sessions_listed_first() {
+        let storage = Storage::open_in_memory().unwrap();
+        for i in 0..4 {
+            storage
+                .insert_session(
+                    &format!("s{i}"),
+                    Mode::Memo,
+                    &format!("2025-01-15T10:3{i}:00Z"),
+                )
+                .unwrap();
+        }
+        assert!(storage.set_session_pinned("s0", true).unwrap());
+        assert!(!storage.set_session_pinned("missing", true).unwrap());
+
+        // 通常順序ではピン留めは影響しない
+        let page = storage.list_history(10, None, None, false).unwrap();
+        assert_eq!(page.items[0].session_id, "s3");
+        assert!(page.items[3].pinned);
+
+        // pinned_first: 最古の s0 が先頭、ページングも連続する
+        let page1 = storage.list_history(2, None, None, true).unwrap();
+        let ids: Vec<_> = page1.items.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, vec!["s0", "s3"]);
         let page2 = storage
-            .list_history(2, page1.next_cursor.as_deref(), None)
+            .list_history(2, page1.next_cursor.as_deref(), None, true)
             .unwrap();
-        assert_eq!(page2.items.len(), 2);
-        assert_eq!(page2.items[0].session_id, "s2");
+        let ids: Vec<_> = page2.items.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, vec!["s2", "s1"]);
+        assert!(page2.next_cursor.is_none());
 
-        // Page 3
-        let page3 = storage
-            .list_history(2, page2.next_cursor.as_deref(), None)
-            .unwrap();
-        assert_eq!(page3.items.len(), 1);
-        assert!(page3.next_cursor.is_none());
+        storage.set_session_pinned("s0", false).unwrap();
+        let page = storage.list_history(10, None, None, true).unwrap();
+        assert_eq!(page.items[0].session_id, "s3");
     }
 
     #[test]
-    fn test_update_session_state() {
+    fn test_migrate_adds_pinned_to_existing_db() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE sessions (
+                session_id TEXT PRIMARY KEY,
+                state      TEXT NOT NULL DEFAULT 'idle',
+                mode       TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+            INSERT INTO sessions VALUES ('old', 'idle', 'memo', '2025-01-01T00:00:00Z', '2025-01-01T00:00:00Z');",
+        )
+        .unwrap();
+        let storage = Storage {
+            conn,
+            secrets: Box::new(MemorySecretStore::default()),
+        };
+        storage.migrate().unwrap();
+
+        let page = storage.list_history(10, None, None, true).unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert!(!page.items[0].pinned);
+    }
+
+    #[test]
+    fn test_migrate_segment_without_mode_uses_session_mode() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE sessions (
+                session_id TEXT PRIMARY KEY,
+                state      TEXT NOT NULL DEFAULT 'idle',
+                mode       TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+            CREATE TABLE segments (
+                segment_id     TEXT PRIMARY KEY,
+                session_id     TEXT NOT NULL,
+                raw_text       TEXT NOT NULL DEFAULT '',
+                rewritten_text TEXT,
+                confidence     REAL NOT NULL DEFAULT 0.0,
+                audio_path     TEXT,
+                created_at     TEXT NOT NULL
+            );
+            INSERT INTO sessions VALUES ('old', 'idle', 'minutes', '2025-01-01T00:00:00Z', '2025-01-01T00:00:00Z');
+            INSERT INTO segments (segment_id, session_id, created_at) VALUES ('seg-old', 'old', '2025-01-01T00:00:00Z');",
+        )
+        .unwrap();
+        let storage = Storage {
+            conn,
+            secrets: Box::new(MemorySecretStore::default()),
+        };
+        storage.migrate().unwrap();
+
+        let detail = storage.get_session_detail("old").unwrap().unwrap();
+        assert_eq!(detail.segments[0].mode, Mode::Minutes);
+        assert_eq!(
+            storage.get_segment_mode("seg-old").unwrap(),
+            Some(Mode::Minutes)
+        );
+    }
+
+    #[test]
+    fn test_list_history_orders_mixed_offsets_chronologically() {
         let storage = Storage::open_in_memory().unwrap();
-        storage.insert_session("s1", Mode::Memo, &now()).unwrap();
-        storage
-            .update_session_state("s1", "recording", &now())
-            .unwrap();
+        // 文字列順では +09:00 のものが最新に見えるが、UTC では最も古い
+        let sessions = [
+            ("jst", "2025-01-15T18:30:00+09:00"),
+            ("zulu", "2025-01-15T10:00:00Z"),
+            ("millis", "2025-01-15T10:00:00.500Z"),
+            ("offset", "2025-01-15T10:15:00+00:00"),
+        ];
+        for (id, created_at) in sessions {
+            storage.insert_session(id, Mode::Memo, created_at).unwrap();
+        }
 
-        let detail = storage.get_session_detail("s1").unwrap().unwrap();
-        assert_eq!(detail.state, "recording");
+        let page1 = storage.list_history(2, None, None, false).unwrap();
+        let ids: Vec<&str> = page1.items.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, vec!["offset", "millis"]);
+
+        let page2 = storage
+            .list_history(2, page1.next_cursor.as_deref(), None, false)
+            .unwrap();
+        let ids: Vec<&str> = page2.items.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, vec!["zulu", "jst"]);
+        assert!(page2.next_cursor.is_none());
+
+        // ピン留め優先でも同じ順序で辿れる
+        let pinned = storage.list_history(3, None, None, true).unwrap();
+        let rest = storage
+            .list_history(3, pinned.next_cursor.as_deref(), None, true)
+            .unwrap();
+        assert_eq!(rest.items.len(), 1);
+        assert_eq!(rest.items[0].session_id, "jst");
     }
 
     #[test]
     fn test_segment_count_in_history() {
         let storage = Storage::open_in_memory().unwrap();
         storage.insert_session("s1", Mode::Memo, &now()).unwrap();
-        storage.insert_segment("seg1", "s1", &now()).unwrap();
-        storage.insert_segment("seg2", "s1", &now()).unwrap();
+        storage
+            .insert_segment("seg1", "s1", Mode::Raw, &now())
+            .unwrap();
+        storage
+            .insert_segment("seg2", "s1", Mode::Raw, &now())
+            .unwrap();
 
-        let page = storage.list_history(10, None, None).unwrap();
+        let page = storage.list_history(10, None, None, false).unwrap();
         assert_eq!(page.items[0].segment_count, 2);
     }
 
@@ -733,20 +1826,20 @@ mod tests {
             .unwrap();
 
         storage
-            .insert_segment("seg-1", "s-apple", "2025-01-15T10:31:30Z")
+            .insert_segment("seg-1", "s-apple", Mode::Raw, "2025-01-15T10:31:30Z")
             .unwrap();
         storage
             .update_segment_text("seg-1", "apple speech", 0.9)
             .unwrap();
 
         storage
-            .insert_segment("seg-2", "s-rust", "2025-01-15T10:32:30Z")
+            .insert_segment("seg-2", "s-rust", Mode::Raw, "2025-01-15T10:32:30Z")
             .unwrap();
         storage
             .update_segment_text("seg-2", "rust tauri", 0.95)
             .unwrap();
 
-        let page = storage.list_history(10, None, Some("rust")).unwrap();
+        let page = storage.list_history(10, None, Some("rust"), false).unwrap();
         assert_eq!(page.items.len(), 1);
         assert_eq!(page.items[0].session_id, "s-rust");
     }
@@ -771,6 +1864,7 @@ mod tests {
             replacement: "Claude".into(),
             priority: 10,
             enabled: true,
+            word_boundary: false,
         };
         let id = storage.upsert_dictionary_entry(&entry).unwrap();
         assert!(!id.is_empty());
@@ -794,6 +1888,7 @@ mod tests {
             replacement: "bar".into(),
             priority: 5,
             enabled: true,
+            word_boundary: false,
         };
         storage.upsert_dictionary_entry(&entry).unwrap();
 
@@ -806,6 +1901,7 @@ mod tests {
             replacement: "baz".into(),
             priority: 10,
             enabled: true,
+            word_boundary: true,
         };
         storage.upsert_dictionary_entry(&updated).unwrap();
 
@@ -813,6 +1909,7 @@ mod tests {
         assert_eq!(entries.len(), 1);
         assert_eq!(entries[0].replacement, "baz");
         assert_eq!(entries[0].priority, 10);
+        assert!(entries[0].word_boundary);
     }
 
     #[test]
@@ -827,6 +1924,7 @@ mod tests {
                 replacement: "b".into(),
                 priority: 1,
                 enabled: true,
+                word_boundary: false,
             })
             .unwrap();
         storage
@@ -838,6 +1936,7 @@ mod tests {
                 replacement: "d".into(),
                 priority: 1,
                 enabled: true,
+                word_boundary: false,
             })
             .unwrap();
 
@@ -862,6 +1961,7 @@ mod tests {
                 replacement: "b".into(),
                 priority: 10,
                 enabled: true,
+                word_boundary: false,
             })
             .unwrap();
         storage
@@ -873,6 +1973,7 @@ mod tests {
                 replacement: "d".into(),
                 priority: 5,
                 enabled: false, // disabled
+                word_boundary: false,
             })
             .unwrap();
 
@@ -883,6 +1984,45 @@ mod tests {
         assert_eq!(entries[0].id.as_deref(), Some("e1"));
     }
 
+    #[test]
+    fn test_mode_entry_wins_over_global_at_equal_priority() {
+        let storage = Storage::open_in_memory().unwrap();
+        for (id, scope, mode, replacement) in [
+            ("a_global", DictionaryScope::Global, None, "グローバル"),
+            ("z_mode", DictionaryScope::Mode, Some(Mode::Memo), "メモ用"),
+        ] {
+            storage
+                .upsert_dictionary_entry(&DictionaryEntry {
+                    id: Some(id.into()),
+                    scope,
+                    mode,
+                    pattern: "てすと".into(),
+                    replacement: replacement.into(),
+                    priority: 10,
+                    enabled: true,
+                    word_boundary: false,
+                })
+                .unwrap();
+        }
+
+        let entries = storage
+            .get_enabled_dictionary_entries("mode", Some("memo"))
+            .unwrap();
+        let ids: Vec<_> = entries.iter().map(|e| e.id.as_deref().unwrap()).collect();
+        assert_eq!(ids, vec!["z_mode", "a_global"]);
+        assert_eq!(
+            crate::infra::post_processor::PostProcessor::apply_dictionary("てすと", &entries),
+            "メモ用"
+        );
+
+        // 他モードでは mode エントリは対象外
+        let entries = storage
+            .get_enabled_dictionary_entries("mode", Some("tech"))
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id.as_deref(), Some("a_global"));
+    }
+
     #[test]
     fn test_delete_dictionary_entry() {
         let storage = Storage::open_in_memory().unwrap();
@@ -895,6 +2035,7 @@ mod tests {
                 replacement: "y".into(),
                 priority: 1,
                 enabled: true,
+                word_boundary: false,
             })
             .unwrap();
 
@@ -905,6 +2046,22 @@ mod tests {
         assert!(entries.is_empty());
     }
 
+    #[test]
+    fn test_latest_metrics_snapshot() {
+        let storage = Storage::open_in_memory().unwrap();
+        assert_eq!(storage.latest_metrics_snapshot().unwrap(), None);
+        storage
+            .save_metrics_snapshot(r#"{"sessions_started":1}"#, "2025-01-15T10:30:00Z")
+            .unwrap();
+        storage
+            .save_metrics_snapshot(r#"{"sessions_started":2}"#, "2025-01-15T11:30:00Z")
+            .unwrap();
+        assert_eq!(
+            storage.latest_metrics_snapshot().unwrap().as_deref(),
+            Some(r#"{"sessions_started":2}"#)
+        );
+    }
+
     // --- Settings tests ---
 
     #[test]
@@ -932,6 +2089,124 @@ mod tests {
         assert_eq!(loaded.paste_allowlist, vec!["com.apple.Terminal"]);
     }
 
+    #[test]
+    fn test_claude_api_key_stored_in_secret_store() {
+        let path = std::env::temp_dir().join(format!("vt-secrets-{}.json", uuid::Uuid::new_v4()));
+        let storage = Storage::open_in_memory()
+            .unwrap()
+            .with_secret_store(Box::new(FileSecretStore::new(&path)));
+
+        let settings = AppSettings {
+            claude_api_key: Some("sk-ant-secret".to_string()),
+            ..Default::default()
+        };
+        storage.save_settings(&settings).unwrap();
+
+        // SQLite には参照だけが残る
+        let raw: String = storage
+            .conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = 'claude_api_key'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(!raw.contains("sk-ant-secret"));
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("sk-ant-secret"));
+
+        // 設定の読み込みではストアを読まず、参照のまま返す
+        assert_eq!(
+            storage.get_settings().unwrap().claude_api_key.as_deref(),
+            Some("secret:claude_api_key")
+        );
+        assert_eq!(
+            storage.get_secret("claude_api_key").unwrap().as_deref(),
+            Some("sk-ant-secret")
+        );
+
+        // 参照のまま保存し直してもシークレットは残る
+        let loaded = storage.get_settings().unwrap();
+        storage.save_settings(&loaded).unwrap();
+        assert_eq!(
+            storage.get_secret("claude_api_key").unwrap().as_deref(),
+            Some("sk-ant-secret")
+        );
+
+        // キーを消すとストアからも削除される
+        storage.save_settings(&AppSettings::default()).unwrap();
+        assert_eq!(storage.get_settings().unwrap().claude_api_key, None);
+        assert_eq!(storage.get_secret("claude_api_key").unwrap(), None);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// 読み取りが常に失敗するシークレットストア
+    struct UnreadableSecretStore;
+
+    impl SecretStore for UnreadableSecretStore {
+        fn set(&self, _key: &str, _value: &str) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        fn get(&self, _key: &str) -> Result<Option<String>, AppError> {
+            Err(AppError::storage("キーチェーン読み取り失敗"))
+        }
+
+        fn delete(&self, _key: &str) -> Result<(), AppError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_settings_load_does_not_read_secret_store() {
+        let storage = Storage::open_in_memory()
+            .unwrap()
+            .with_secret_store(Box::new(UnreadableSecretStore));
+        let settings = AppSettings {
+            claude_api_key: Some("sk-ant-secret".to_string()),
+            segment_ttl_days: 30,
+            ..Default::default()
+        };
+        storage.save_settings(&settings).unwrap();
+
+        let loaded = storage.get_settings().unwrap();
+        assert_eq!(loaded.segment_ttl_days, 30);
+        assert_eq!(
+            loaded.claude_api_key.as_deref(),
+            Some("secret:claude_api_key")
+        );
+        assert!(storage.get_secret("claude_api_key").is_err());
+    }
+
+    #[test]
+    fn test_plaintext_secret_migrated_on_open() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .conn
+            .execute(
+                "INSERT INTO settings (key, value) VALUES ('claude_api_key', '\"sk-old\"')",
+                [],
+            )
+            .unwrap();
+
+        storage.migrate_plaintext_secrets().unwrap();
+
+        let raw: String = storage
+            .conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = 'claude_api_key'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(raw, "\"secret:claude_api_key\"");
+        assert_eq!(
+            storage.get_secret("claude_api_key").unwrap().as_deref(),
+            Some("sk-old")
+        );
+    }
+
     // --- Data cleanup tests ---
 
     #[test]
@@ -941,20 +2216,176 @@ mod tests {
             .insert_session("s1", Mode::Raw, "2025-01-01T00:00:00Z")
             .unwrap();
         storage
-            .insert_segment("seg_old", "s1", "2025-01-01T00:00:00Z")
+            .insert_segment("seg_old", "s1", Mode::Raw, "2025-01-01T00:00:00Z")
             .unwrap();
         storage
-            .insert_segment("seg_new", "s1", "2025-06-01T00:00:00Z")
+            .insert_segment("seg_new", "s1", Mode::Raw, "2025-06-01T00:00:00Z")
             .unwrap();
 
         let deleted = storage.delete_old_segments("2025-03-01T00:00:00Z").unwrap();
-        assert_eq!(deleted, 1);
+        assert_eq!(deleted.segments_deleted, 1);
 
         let detail = storage.get_session_detail("s1").unwrap().unwrap();
         assert_eq!(detail.segments.len(), 1);
         assert_eq!(detail.segments[0].segment_id, "seg_new");
     }
 
+    #[test]
+    fn test_delete_old_segments_removes_audio_files() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .insert_session("s1", Mode::Raw, "2025-01-01T00:00:00Z")
+            .unwrap();
+        for (segment_id, created_at) in [
+            ("seg_old", "2025-01-01T00:00:00Z"),
+            ("seg_missing", "2025-01-02T00:00:00Z"),
+            ("seg_new", "2025-06-01T00:00:00Z"),
+        ] {
+            storage
+                .insert_segment(segment_id, "s1", Mode::Raw, created_at)
+                .unwrap();
+        }
+
+        let wav_path = |name: &str| {
+            std::env::temp_dir().join(format!("vt-test-{name}-{}.wav", uuid::Uuid::new_v4()))
+        };
+        let old_wav = wav_path("old");
+        let new_wav = wav_path("new");
+        std::fs::write(&old_wav, b"RIFF").unwrap();
+        std::fs::write(&new_wav, b"RIFF").unwrap();
+        storage
+            .set_segment_audio_path("seg_old", &old_wav.to_string_lossy())
+            .unwrap();
+        // 既に消えているファイルはエラーにせず数えない
+        storage
+            .set_segment_audio_path("seg_missing", &wav_path("missing").to_string_lossy())
+            .unwrap();
+        storage
+            .set_segment_audio_path("seg_new", &new_wav.to_string_lossy())
+            .unwrap();
+
+        let deleted = storage.delete_old_segments("2025-03-01T00:00:00Z").unwrap();
+        assert_eq!(
+            deleted,
+            DeleteSessionResult {
+                sessions_deleted: 0,
+                segments_deleted: 2,
+                audio_files_deleted: 1,
+            }
+        );
+        assert!(!old_wav.exists());
+        assert!(new_wav.exists());
+        std::fs::remove_file(&new_wav).unwrap();
+    }
+
+    #[test]
+    fn test_delete_session_with_secure_delete_removes_audio_files() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .save_settings(&AppSettings {
+                secure_delete: true,
+                ..Default::default()
+            })
+            .unwrap();
+        storage.insert_session("s1", Mode::Raw, &now()).unwrap();
+        storage
+            .insert_segment("seg1", "s1", Mode::Raw, &now())
+            .unwrap();
+        let wav_path =
+            std::env::temp_dir().join(format!("vt-test-secure-{}.wav", uuid::Uuid::new_v4()));
+        std::fs::write(&wav_path, vec![0x7f; 4096]).unwrap();
+        storage
+            .set_segment_audio_path("seg1", &wav_path.to_string_lossy())
+            .unwrap();
+
+        let deleted = storage.delete_session("s1").unwrap();
+        assert_eq!(deleted.audio_files_deleted, 1);
+        assert!(!wav_path.exists());
+    }
+
+    #[test]
+    fn test_delete_session_keeps_other_sessions() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage.insert_session("s1", Mode::Memo, &now()).unwrap();
+        storage.insert_session("s2", Mode::Memo, &now()).unwrap();
+        storage
+            .insert_segment("seg1", "s1", Mode::Raw, &now())
+            .unwrap();
+        storage
+            .insert_segment("seg2", "s1", Mode::Raw, &now())
+            .unwrap();
+        storage
+            .insert_segment("seg3", "s2", Mode::Raw, &now())
+            .unwrap();
+
+        let wav = std::env::temp_dir().join(format!("vt-test-{}.wav", uuid::Uuid::new_v4()));
+        std::fs::write(&wav, b"RIFF").unwrap();
+        storage
+            .conn
+            .execute(
+                "UPDATE segments SET audio_path = ?1 WHERE segment_id = 'seg1'",
+                params![wav.to_string_lossy()],
+            )
+            .unwrap();
+
+        let result = storage.delete_session("s1").unwrap();
+        assert_eq!(
+            result,
+            DeleteSessionResult {
+                sessions_deleted: 1,
+                segments_deleted: 2,
+                audio_files_deleted: 1,
+            }
+        );
+        assert!(!wav.exists());
+        assert!(storage.get_session_detail("s1").unwrap().is_none());
+
+        let orphaned: u32 = storage
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM segments WHERE session_id NOT IN (SELECT session_id FROM sessions)",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(orphaned, 0);
+
+        let other = storage.get_session_detail("s2").unwrap().unwrap();
+        assert_eq!(other.segments.len(), 1);
+
+        // 存在しないセッションは 0 件
+        let result = storage.delete_session("s1").unwrap();
+        assert_eq!(result, DeleteSessionResult::default());
+    }
+
+    #[test]
+    fn test_transaction_rolls_back_on_error() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage.insert_session("s1", Mode::Memo, &now()).unwrap();
+
+        let result: Result<(), AppError> = storage.transaction(|s| {
+            s.insert_segment("seg1", "s1", Mode::Raw, &now())?;
+            s.update_session_state("s1", "transcribing", &now())?;
+            Err(AppError::internal("意図的な失敗"))
+        });
+        assert!(result.is_err());
+
+        let detail = storage.get_session_detail("s1").unwrap().unwrap();
+        assert_eq!(detail.state, "idle");
+        assert!(detail.segments.is_empty());
+
+        // 成功時はコミットされ、ネストしても外側に合流する
+        storage
+            .transaction(|s| {
+                s.insert_segment("seg1", "s1", Mode::Raw, &now())?;
+                s.transaction(|inner| inner.update_session_state("s1", "transcribing", &now()))
+            })
+            .unwrap();
+        let detail = storage.get_session_detail("s1").unwrap().unwrap();
+        assert_eq!(detail.state, "transcribing");
+        assert_eq!(detail.segments.len(), 1);
+    }
+
     #[test]
     fn test_delete_old_sessions() {
         let storage = Storage::open_in_memory().unwrap();
@@ -966,7 +2397,7 @@ mod tests {
             .unwrap();
         // s_new has a segment, so it shouldn't be deleted even if old
         storage
-            .insert_segment("seg1", "s_new", "2025-06-01T00:00:00Z")
+            .insert_segment("seg1", "s_new", Mode::Raw, "2025-06-01T00:00:00Z")
             .unwrap();
 
         let deleted = storage.delete_old_sessions("2025-12-01T00:00:00Z").unwrap();
@@ -991,6 +2422,7 @@ mod tests {
                     replacement: id.into(),
                     priority: pri,
                     enabled: true,
+                    word_boundary: false,
                 })
                 .unwrap();
         }
@@ -1013,7 +2445,7 @@ mod tests {
             .insert_session("s2", Mode::Raw, "2025-01-15T11:00:00Z")
             .unwrap();
 
-        let page = storage.list_history(10, None, None).unwrap();
+        let page = storage.list_history(10, None, None, false).unwrap();
         assert_eq!(page.items.len(), 2);
     }
 
@@ -1028,20 +2460,20 @@ mod tests {
             .unwrap();
 
         storage
-            .insert_segment("seg1", "s1", "2025-01-15T10:01:00Z")
+            .insert_segment("seg1", "s1", Mode::Raw, "2025-01-15T10:01:00Z")
             .unwrap();
         storage
             .update_segment_text("seg1", "Rustのテスト", 0.9)
             .unwrap();
 
         storage
-            .insert_segment("seg2", "s2", "2025-01-15T11:01:00Z")
+            .insert_segment("seg2", "s2", Mode::Raw, "2025-01-15T11:01:00Z")
             .unwrap();
         storage
             .update_segment_text("seg2", "Pythonのコード", 0.8)
             .unwrap();
 
-        let page = storage.list_history(10, None, Some("Rust")).unwrap();
+        let page = storage.list_history(10, None, Some("Rust"), false).unwrap();
         assert_eq!(page.items.len(), 1);
         assert_eq!(page.items[0].session_id, "s1");
     }
@@ -1053,7 +2485,7 @@ mod tests {
             .insert_session("s1", Mode::Memo, "2025-01-15T10:00:00Z")
             .unwrap();
         storage
-            .insert_segment("seg1", "s1", "2025-01-15T10:01:00Z")
+            .insert_segment("seg1", "s1", Mode::Raw, "2025-01-15T10:01:00Z")
             .unwrap();
         storage
             .update_segment_text("seg1", "元のテキスト", 0.9)
@@ -1063,13 +2495,15 @@ mod tests {
             .unwrap();
 
         // raw_text にはマッチしないが rewritten_text にマッチ
-        let page = storage.list_history(10, None, Some("リライト")).unwrap();
+        let page = storage
+            .list_history(10, None, Some("リライト"), false)
+            .unwrap();
         assert_eq!(page.items.len(), 1);
         assert_eq!(page.items[0].session_id, "s1");
 
         // マッチしないクエリ
         let page = storage
-            .list_history(10, None, Some("存在しない"))
+            .list_history(10, None, Some("存在しない"), false)
             .unwrap();
         assert_eq!(page.items.len(), 0);
     }
@@ -1078,12 +2512,14 @@ mod tests {
     fn list_history_returns_preview_text_when_segment_exists() {
         let storage = Storage::open_in_memory().unwrap();
         storage.insert_session("s1", Mode::Raw, &now()).unwrap();
-        storage.insert_segment("seg1", "s1", &now()).unwrap();
+        storage
+            .insert_segment("seg1", "s1", Mode::Raw, &now())
+            .unwrap();
         storage
             .update_segment_text("seg1", "hello world", 0.9)
             .unwrap();
 
-        let page = storage.list_history(10, None, None).unwrap();
+        let page = storage.list_history(10, None, None, false).unwrap();
         assert_eq!(page.items[0].preview_text.as_deref(), Some("hello world"));
     }
 
@@ -1091,7 +2527,9 @@ mod tests {
     fn list_history_returns_rewritten_text_as_preview_when_available() {
         let storage = Storage::open_in_memory().unwrap();
         storage.insert_session("s1", Mode::Raw, &now()).unwrap();
-        storage.insert_segment("seg1", "s1", &now()).unwrap();
+        storage
+            .insert_segment("seg1", "s1", Mode::Raw, &now())
+            .unwrap();
         storage
             .update_segment_text("seg1", "raw text", 0.9)
             .unwrap();
@@ -1099,7 +2537,7 @@ mod tests {
             .update_segment_rewritten("seg1", "rewritten text")
             .unwrap();
 
-        let page = storage.list_history(10, None, None).unwrap();
+        let page = storage.list_history(10, None, None, false).unwrap();
         assert_eq!(
             page.items[0].preview_text.as_deref(),
             Some("rewritten text")
@@ -1111,8 +2549,7 @@ mod tests {
         let storage = Storage::open_in_memory().unwrap();
         storage.insert_session("s1", Mode::Raw, &now()).unwrap();
 
-        let page = storage.list_history(10, None, None).unwrap();
+        let page = storage.list_history(10, None, None, false).unwrap();
         assert_eq!(page.items[0].preview_text, None);
     }
-
 }
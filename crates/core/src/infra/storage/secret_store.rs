@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::domain::error::AppError;
+
+/// API キーなどの秘密情報の保存先
+pub trait SecretStore: Send + Sync {
+    fn set(&self, key: &str, value: &str) -> Result<(), AppError>;
+    fn get(&self, key: &str) -> Result<Option<String>, AppError>;
+    fn delete(&self, key: &str) -> Result<(), AppError>;
+}
+
+/// OS のキーチェーンに保存する（macOS Keychain）
+#[cfg(target_os = "macos")]
+pub struct KeychainSecretStore {
+    service: String,
+}
+
+#[cfg(target_os = "macos")]
+impl KeychainSecretStore {
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+        }
+    }
+
+    fn entry(&self, key: &str) -> Result<keyring::Entry, AppError> {
+        keyring::Entry::new(&self.service, key)
+            .map_err(|e| AppError::storage(format!("キーチェーン参照失敗: {e}")))
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl SecretStore for KeychainSecretStore {
+    fn set(&self, key: &str, value: &str) -> Result<(), AppError> {
+        self.entry(key)?
+            .set_password(value)
+            .map_err(|e| AppError::storage(format!("キーチェーン保存失敗: {e}")))
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+        match self.entry(key)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(AppError::storage(format!("キーチェーン読み取り失敗: {e}"))),
+        }
+    }
+
+    fn delete(&self, key: &str) -> Result<(), AppError> {
+        match self.entry(key)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(AppError::storage(format!("キーチェーン削除失敗: {e}"))),
+        }
+    }
+}
+
+/// 平文 JSON ファイルに保存する（キーチェーンの無い開発環境向けフォールバック）
+///
+/// ファイルは所有者のみ読み書き可能（0600）で作成する。
+pub struct FileSecretStore {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileSecretStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn load(&self) -> Result<HashMap<String, String>, AppError> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| AppError::storage(format!("シークレットファイル解析失敗: {e}"))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(AppError::storage(format!(
+                "シークレットファイル読み取り失敗: {e}"
+            ))),
+        }
+    }
+
+    fn save(&self, secrets: &HashMap<String, String>) -> Result<(), AppError> {
+        let json = serde_json::to_string_pretty(secrets)
+            .map_err(|e| AppError::internal(format!("secrets serialize: {e}")))?;
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options
+            .open(&self.path)
+            .map_err(|e| AppError::storage(format!("シークレットファイル書き込み失敗: {e}")))?;
+        std::io::Write::write_all(&mut file, json.as_bytes())
+            .map_err(|e| AppError::storage(format!("シークレットファイル書き込み失敗: {e}")))
+    }
+}
+
+impl SecretStore for FileSecretStore {
+    fn set(&self, key: &str, value: &str) -> Result<(), AppError> {
+        let _guard = self.lock.lock().unwrap();
+        let mut secrets = self.load()?;
+        secrets.insert(key.to_string(), value.to_string());
+        self.save(&secrets)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+        let _guard = self.lock.lock().unwrap();
+        Ok(self.load()?.remove(key))
+    }
+
+    fn delete(&self, key: &str) -> Result<(), AppError> {
+        let _guard = self.lock.lock().unwrap();
+        let mut secrets = self.load()?;
+        if secrets.remove(key).is_some() {
+            self.save(&secrets)?;
+        }
+        Ok(())
+    }
+}
+
+/// メモリ上に保持する（in-memory DB・テスト用）
+#[derive(Default)]
+pub struct MemorySecretStore {
+    secrets: Mutex<HashMap<String, String>>,
+}
+
+impl SecretStore for MemorySecretStore {
+    fn set(&self, key: &str, value: &str) -> Result<(), AppError> {
+        self.secrets
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+        Ok(self.secrets.lock().unwrap().get(key).cloned())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), AppError> {
+        self.secrets.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// プラットフォームに応じたデフォルトのシークレットストア
+///
+/// macOS はキーチェーン、それ以外は DB と同じディレクトリの `secrets.json`。
+pub fn default_secret_store(db_path: &str) -> Box<dyn SecretStore> {
+    #[cfg(target_os = "macos")]
+    {
+        let _ = db_path;
+        Box::new(KeychainSecretStore::new("voiceTypeless"))
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let dir = std::path::Path::new(db_path)
+            .parent()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_default();
+        Box::new(FileSecretStore::new(dir.join("secrets.json")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_store_roundtrip() {
+        let path = std::env::temp_dir().join(format!("vt-secrets-{}.json", uuid::Uuid::new_v4()));
+        let store = FileSecretStore::new(&path);
+
+        assert_eq!(store.get("claude_api_key").unwrap(), None);
+        store.set("claude_api_key", "sk-test").unwrap();
+        assert_eq!(
+            FileSecretStore::new(&path).get("claude_api_key").unwrap(),
+            Some("sk-test".to_string())
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        store.delete("claude_api_key").unwrap();
+        assert_eq!(store.get("claude_api_key").unwrap(), None);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -9,7 +9,7 @@ use std::os::raw::c_char;
 
 use async_trait::async_trait;
 
-use super::{AudioSegment, SttContext, SttEngine, SttError, TranscriptResult};
+use super::{AudioSegment, EngineCapabilities, SttContext, SttEngine, SttError, TranscriptResult};
 
 extern "C" {
     fn swift_speech_is_available() -> bool;
@@ -83,6 +83,7 @@ impl SttEngine for AppleSttEngine {
             text: parsed.text,
             confidence: parsed.confidence,
             is_partial: false,
+            timings: Vec::new(),
         })
     }
 
@@ -90,6 +91,19 @@ impl SttEngine for AppleSttEngine {
         false
     }
 
+    /// ファイル単位の認識のため部分結果・タイミングは返さない（confidence は Speech.framework の値）
+    ///
+    /// 言語は SFSpeechRecognizer の locale にそのまま渡すため、対応可否は OS に任せる。
+    fn capabilities(&self) -> EngineCapabilities {
+        EngineCapabilities {
+            partial: false,
+            timings: false,
+            confidence: true,
+            translate: false,
+            languages: Vec::new(),
+        }
+    }
+
     fn name(&self) -> &str {
         "apple"
     }
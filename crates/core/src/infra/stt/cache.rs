@@ -0,0 +1,298 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+
+use super::{AudioSegment, EngineCapabilities, SttContext, SttEngine, SttError, TranscriptResult};
+use crate::domain::settings::AppSettings;
+use crate::infra::log_context::TARGET_STT;
+
+/// メモリ上に保持する書き起こし結果の件数
+pub const TRANSCRIPT_CACHE_CAPACITY: usize = 256;
+
+type CacheKey = [u8; 32];
+
+/// 同じ音声の書き起こし結果を再利用するラッパー（開発時に同じ WAV を繰り返し流す用途）
+///
+/// キーは音声サンプルとサンプルレート、エンジン名とモデル、結果に影響するコンテキスト
+/// （言語・タスク・initial_prompt）の SHA-256。モデルを切り替えるとキーが変わるため古い結果は使われない。
+/// 無効時（既定）は常にエンジンを呼び、キャッシュにも保存しない。
+pub struct CachingSttEngine {
+    inner: Arc<dyn SttEngine>,
+    enabled: AtomicBool,
+    cache: Mutex<LruCache>,
+}
+
+impl CachingSttEngine {
+    pub fn new(inner: Arc<dyn SttEngine>) -> Self {
+        Self {
+            inner,
+            enabled: AtomicBool::new(false),
+            cache: Mutex::new(LruCache::new(TRANSCRIPT_CACHE_CAPACITY)),
+        }
+    }
+
+    /// キャッシュの有効/無効を切り替える（無効にすると保持中の結果も捨てる）
+    pub fn set_enabled(&self, enabled: bool) {
+        let was_enabled = self.enabled.swap(enabled, Ordering::SeqCst);
+        if was_enabled && !enabled {
+            self.cache.lock().unwrap().clear();
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    fn key(&self, audio: &AudioSegment, ctx: &SttContext) -> CacheKey {
+        let mut hasher = Sha256::new();
+        hasher.update(self.inner.name().as_bytes());
+        hasher.update([0]);
+        hasher.update(self.inner.model_id().unwrap_or_default().as_bytes());
+        hasher.update([0]);
+        hasher.update(ctx.language.as_bytes());
+        hasher.update([0]);
+        hasher.update(ctx.task.as_str().as_bytes());
+        hasher.update([0]);
+        hasher.update(ctx.build_initial_prompt().unwrap_or_default().as_bytes());
+        hasher.update([0]);
+        hasher.update(audio.sample_rate.to_le_bytes());
+        for sample in &audio.samples {
+            hasher.update(sample.to_le_bytes());
+        }
+        hasher.finalize().into()
+    }
+}
+
+#[async_trait]
+impl SttEngine for CachingSttEngine {
+    async fn transcribe(
+        &self,
+        audio: AudioSegment,
+        ctx: SttContext,
+    ) -> Result<TranscriptResult, SttError> {
+        if !self.is_enabled() {
+            return self.inner.transcribe(audio, ctx).await;
+        }
+
+        let key = self.key(&audio, &ctx);
+        let hit = self.cache.lock().unwrap().get(&key);
+        if let Some(hit) = hit {
+            log::debug!(target: TARGET_STT, "{} Transcript cache hit", ctx.log_ctx);
+            return Ok(hit);
+        }
+
+        let result = self.inner.transcribe(audio, ctx).await?;
+        if !result.is_partial {
+            self.cache.lock().unwrap().insert(key, result.clone());
+        }
+        Ok(result)
+    }
+
+    fn supports_partial(&self) -> bool {
+        self.inner.supports_partial()
+    }
+
+    fn capabilities(&self) -> EngineCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn model_id(&self) -> Option<String> {
+        self.inner.model_id()
+    }
+
+    /// デコード設定が変わると同じ音声でも結果が変わりうるため、保持中の結果は捨てる
+    fn apply_settings(&self, settings: &AppSettings) {
+        self.inner.apply_settings(settings);
+        self.cache.lock().unwrap().clear();
+    }
+
+    fn switch_model(&self, model_path: &Path) -> Result<(), SttError> {
+        self.inner.switch_model(model_path)
+    }
+}
+
+/// 最近使った順に `capacity` 件まで保持するキャッシュ
+struct LruCache {
+    capacity: usize,
+    entries: HashMap<CacheKey, TranscriptResult>,
+    /// 先頭が最も古い
+    order: VecDeque<CacheKey>,
+}
+
+impl LruCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn get(&mut self, key: &CacheKey) -> Option<TranscriptResult> {
+        let value = self.entries.get(key)?.clone();
+        self.touch(key);
+        Some(value)
+    }
+
+    fn insert(&mut self, key: CacheKey, value: TranscriptResult) {
+        if self.entries.insert(key, value).is_some() {
+            self.touch(&key);
+            return;
+        }
+        self.order.push_back(key);
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+
+    fn touch(&mut self, key: &CacheKey) {
+        if let Some(pos) = self.order.iter().position(|k| k == key) {
+            self.order.remove(pos);
+        }
+        self.order.push_back(*key);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    /// 呼び出し回数を数え、モデル切り替えを記録するエンジン
+    #[derive(Default)]
+    struct CountingEngine {
+        calls: AtomicUsize,
+        model: Mutex<String>,
+    }
+
+    #[async_trait]
+    impl SttEngine for CountingEngine {
+        async fn transcribe(
+            &self,
+            _audio: AudioSegment,
+            _ctx: SttContext,
+        ) -> Result<TranscriptResult, SttError> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(TranscriptResult {
+                text: format!("call {n}"),
+                confidence: 0.9,
+                is_partial: false,
+                timings: Vec::new(),
+            })
+        }
+
+        fn supports_partial(&self) -> bool {
+            false
+        }
+
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        fn model_id(&self) -> Option<String> {
+            Some(self.model.lock().unwrap().clone())
+        }
+
+        fn switch_model(&self, model_path: &Path) -> Result<(), SttError> {
+            *self.model.lock().unwrap() = model_path.display().to_string();
+            Ok(())
+        }
+    }
+
+    fn audio(value: f32) -> AudioSegment {
+        AudioSegment {
+            samples: vec![value; 160],
+            sample_rate: 16000,
+        }
+    }
+
+    fn ctx() -> SttContext {
+        SttContext {
+            language: "ja-JP".to_string(),
+            dictionary: vec![],
+            mode: None,
+            prompt_prefix: None,
+            max_prompt_chars: 0,
+            task: Default::default(),
+            log_ctx: Default::default(),
+            cancel: Default::default(),
+        }
+    }
+
+    fn cached() -> (Arc<CountingEngine>, CachingSttEngine) {
+        let inner = Arc::new(CountingEngine::default());
+        let engine = CachingSttEngine::new(inner.clone());
+        engine.set_enabled(true);
+        (inner, engine)
+    }
+
+    #[tokio::test]
+    async fn test_identical_audio_hits_cache() {
+        let (inner, engine) = cached();
+        let first = engine.transcribe(audio(0.1), ctx()).await.unwrap();
+        let second = engine.transcribe(audio(0.1), ctx()).await.unwrap();
+
+        assert_eq!(second.text, first.text);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+
+        // 音声が違えばエンジンを呼ぶ
+        engine.transcribe(audio(0.2), ctx()).await.unwrap();
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_model_switch_invalidates() {
+        let (inner, engine) = cached();
+        engine.transcribe(audio(0.1), ctx()).await.unwrap();
+        engine
+            .switch_model(Path::new("/models/ggml-small.bin"))
+            .unwrap();
+
+        let after = engine.transcribe(audio(0.1), ctx()).await.unwrap();
+        assert_eq!(after.text, "call 2");
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_always_calls_engine() {
+        let inner = Arc::new(CountingEngine::default());
+        let engine = CachingSttEngine::new(inner.clone());
+        engine.transcribe(audio(0.1), ctx()).await.unwrap();
+        engine.transcribe(audio(0.1), ctx()).await.unwrap();
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_lru_evicts_least_recently_used() {
+        let result = |text: &str| TranscriptResult {
+            text: text.to_string(),
+            confidence: 1.0,
+            is_partial: false,
+            timings: Vec::new(),
+        };
+        let mut lru = LruCache::new(2);
+        lru.insert([1; 32], result("a"));
+        lru.insert([2; 32], result("b"));
+        // a を使ってから c を入れると、最も古い b が捨てられる
+        assert!(lru.get(&[1; 32]).is_some());
+        lru.insert([3; 32], result("c"));
+        assert!(lru.get(&[2; 32]).is_none());
+        assert!(lru.get(&[1; 32]).is_some());
+        assert!(lru.get(&[3; 32]).is_some());
+    }
+}
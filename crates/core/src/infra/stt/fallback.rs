@@ -0,0 +1,256 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{AudioSegment, EngineCapabilities, SttContext, SttEngine, SttError, TranscriptResult};
+use crate::domain::settings::{AppSettings, SttEngineChoice};
+use crate::infra::log_context::TARGET_STT;
+
+/// 利用可能なエンジンが無い場合の名前
+pub const UNAVAILABLE_ENGINE_NAME: &str = "unavailable";
+
+/// 選択中のエンジンを先頭に、フォールバックで試すエンジンを優先順に返す
+///
+/// フォールバックはローカルエンジン（Apple → Whisper）の間だけで行う。
+/// 音声を外部へ送るクラウドは、選択中か `allow_cloud_fallback` が有効な場合のみ加える。
+/// Cloud は Soniox バックエンドなので同一エンジン（[`SttEngineChoice::Soniox`]）として扱う。
+pub fn fallback_order(settings: &AppSettings) -> Vec<SttEngineChoice> {
+    let selected = match settings.stt_engine {
+        SttEngineChoice::Cloud => SttEngineChoice::Soniox,
+        other => other,
+    };
+    let mut order = vec![selected];
+    let mut fallbacks = vec![SttEngineChoice::Apple, SttEngineChoice::Whisper];
+    if settings.allow_cloud_fallback {
+        fallbacks.push(SttEngineChoice::Soniox);
+    }
+    for choice in fallbacks {
+        if !order.contains(&choice) {
+            order.push(choice);
+        }
+    }
+    order
+}
+
+/// 複数の STT エンジンを優先順に試すラッパー
+///
+/// `EngineNotAvailable` の場合のみ次のエンジンへフォールスルーする。
+/// 書き起こし失敗やタイムアウトは音声側の問題を隠さないようそのまま返す。
+pub struct FallbackSttEngine {
+    engines: Vec<Arc<dyn SttEngine>>,
+}
+
+impl FallbackSttEngine {
+    pub fn new(engines: Vec<Arc<dyn SttEngine>>) -> Self {
+        Self { engines }
+    }
+
+    /// チェーン内のエンジン名（優先順）
+    pub fn engine_names(&self) -> Vec<&str> {
+        self.engines.iter().map(|e| e.name()).collect()
+    }
+}
+
+#[async_trait]
+impl SttEngine for FallbackSttEngine {
+    async fn transcribe(
+        &self,
+        audio: AudioSegment,
+        ctx: SttContext,
+    ) -> Result<TranscriptResult, SttError> {
+        let mut unavailable = Vec::new();
+        for engine in &self.engines {
+            match engine.transcribe(audio.clone(), ctx.clone()).await {
+                Err(SttError::EngineNotAvailable(msg)) => {
+                    log::warn!(
+                        target: TARGET_STT,
+                        "{} {} not available, trying next engine: {msg}",
+                        ctx.log_ctx,
+                        engine.name()
+                    );
+                    unavailable.push(format!("{}: {msg}", engine.name()));
+                }
+                result => return result,
+            }
+        }
+
+        Err(SttError::EngineNotAvailable(if unavailable.is_empty() {
+            "No STT engine configured".to_string()
+        } else {
+            unavailable.join("; ")
+        }))
+    }
+
+    fn supports_partial(&self) -> bool {
+        self.engines
+            .first()
+            .is_some_and(|engine| engine.supports_partial())
+    }
+
+    /// 先頭（優先）エンジンの機能（エンジンが無ければ全て非対応）
+    fn capabilities(&self) -> EngineCapabilities {
+        self.engines
+            .first()
+            .map(|engine| engine.capabilities())
+            .unwrap_or_default()
+    }
+
+    /// 先頭（優先）エンジンの名前を返す
+    fn name(&self) -> &str {
+        self.engines
+            .first()
+            .map(|engine| engine.name())
+            .unwrap_or(UNAVAILABLE_ENGINE_NAME)
+    }
+
+    /// 先頭（優先）エンジンのモデル
+    fn model_id(&self) -> Option<String> {
+        self.engines.first().and_then(|engine| engine.model_id())
+    }
+
+    /// チェーン内の全エンジンに反映する
+    fn apply_settings(&self, settings: &AppSettings) {
+        for engine in &self.engines {
+            engine.apply_settings(settings);
+        }
+    }
+
+    /// チェーン内でモデル切り替えに対応するエンジン全てに反映する（1 つも無ければエラー）
+    fn switch_model(&self, model_path: &Path) -> Result<(), SttError> {
+        let mut switched = false;
+        let mut last_err = None;
+        for engine in &self.engines {
+            match engine.switch_model(model_path) {
+                Ok(()) => switched = true,
+                Err(e) => last_err = Some(e),
+            }
+        }
+        match (switched, last_err) {
+            (true, _) => Ok(()),
+            (false, Some(e)) => Err(e),
+            (false, None) => Err(SttError::EngineNotAvailable(
+                "No STT engine configured".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MockEngine {
+        name: &'static str,
+        result: fn() -> Result<TranscriptResult, SttError>,
+    }
+
+    #[async_trait]
+    impl SttEngine for MockEngine {
+        async fn transcribe(
+            &self,
+            _audio: AudioSegment,
+            _ctx: SttContext,
+        ) -> Result<TranscriptResult, SttError> {
+            (self.result)()
+        }
+
+        fn supports_partial(&self) -> bool {
+            false
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+    }
+
+    fn unavailable() -> Result<TranscriptResult, SttError> {
+        Err(SttError::EngineNotAvailable("not installed".to_string()))
+    }
+
+    fn failed() -> Result<TranscriptResult, SttError> {
+        Err(SttError::TranscriptionFailed("bad audio".to_string()))
+    }
+
+    fn ok() -> Result<TranscriptResult, SttError> {
+        Ok(TranscriptResult {
+            text: "second".to_string(),
+            confidence: 0.9,
+            is_partial: false,
+            timings: Vec::new(),
+        })
+    }
+
+    fn audio() -> AudioSegment {
+        AudioSegment {
+            samples: vec![0.0; 160],
+            sample_rate: 16000,
+        }
+    }
+
+    fn ctx() -> SttContext {
+        SttContext {
+            language: "ja-JP".to_string(),
+            dictionary: vec![],
+            mode: None,
+            prompt_prefix: None,
+            max_prompt_chars: 0,
+            task: Default::default(),
+            log_ctx: Default::default(),
+            cancel: Default::default(),
+        }
+    }
+
+    fn engine(
+        name: &'static str,
+        result: fn() -> Result<TranscriptResult, SttError>,
+    ) -> Arc<dyn SttEngine> {
+        Arc::new(MockEngine { name, result })
+    }
+
+    #[tokio::test]
+    async fn test_falls_through_unavailable_engine() {
+        let chain =
+            FallbackSttEngine::new(vec![engine("first", unavailable), engine("second", ok)]);
+        let result = chain.transcribe(audio(), ctx()).await.unwrap();
+        assert_eq!(result.text, "second");
+        assert_eq!(chain.name(), "first");
+    }
+
+    #[tokio::test]
+    async fn test_transcription_failure_is_not_masked() {
+        let chain = FallbackSttEngine::new(vec![engine("first", failed), engine("second", ok)]);
+        let err = chain.transcribe(audio(), ctx()).await.unwrap_err();
+        assert!(matches!(err, SttError::TranscriptionFailed(_)));
+    }
+
+    #[tokio::test]
+    async fn test_all_unavailable_returns_not_available() {
+        let chain = FallbackSttEngine::new(vec![engine("first", unavailable)]);
+        let err = chain.transcribe(audio(), ctx()).await.unwrap_err();
+        assert!(matches!(err, SttError::EngineNotAvailable(_)));
+
+        let empty = FallbackSttEngine::new(vec![]);
+        assert_eq!(empty.name(), UNAVAILABLE_ENGINE_NAME);
+        assert!(empty.transcribe(audio(), ctx()).await.is_err());
+    }
+
+    #[test]
+    fn test_cloud_is_not_a_fallback_unless_selected_or_allowed() {
+        use SttEngineChoice::*;
+
+        let order = |stt_engine, allow_cloud_fallback| {
+            fallback_order(&AppSettings {
+                stt_engine,
+                allow_cloud_fallback,
+                ..Default::default()
+            })
+        };
+        // ローカルエンジンの間だけでフォールバックする
+        assert_eq!(order(Whisper, false), vec![Whisper, Apple]);
+        assert_eq!(order(Apple, false), vec![Apple, Whisper]);
+        // クラウドは選択中なら先頭、明示的に許可したときだけ末尾に加える
+        assert_eq!(order(Cloud, false), vec![Soniox, Apple, Whisper]);
+        assert_eq!(order(Whisper, true), vec![Whisper, Apple, Soniox]);
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashSet;
+
+/// 直前の文脈に関係する辞書ヒントを優先し、最大 `max_terms` 件に絞る（設定 smart_hints）
+///
+/// 文脈にそのまま含まれる語を最優先し、次に文字 bigram の一致率が高い語（表記揺れ・一部だけ認識された語）を並べる。
+/// 関係の無い語は元の順のまま後ろに残すため、文脈が空なら先頭から `max_terms` 件になる。
+/// 英字は大文字小文字を区別しない。
+pub fn select_hints(terms: &[String], context: &str, max_terms: usize) -> Vec<String> {
+    let context = context.to_lowercase();
+    let context_bigrams = bigrams(&context);
+
+    let mut scored: Vec<(f32, &String)> = terms
+        .iter()
+        .map(|term| (relevance(term, &context, &context_bigrams), term))
+        .collect();
+    // 安定ソートなので同じスコアの語は元の順を保つ
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored
+        .into_iter()
+        .take(max_terms)
+        .map(|(_, term)| term.clone())
+        .collect()
+}
+
+/// 語と文脈の関係の強さ（0.0〜1.0。部分文字列なら 1.0）
+fn relevance(term: &str, context: &str, context_bigrams: &HashSet<(char, char)>) -> f32 {
+    let term = term.to_lowercase();
+    if term.trim().is_empty() || context.is_empty() {
+        return 0.0;
+    }
+    if context.contains(&term) {
+        return 1.0;
+    }
+    let term_bigrams = bigrams(&term);
+    if term_bigrams.is_empty() {
+        return 0.0;
+    }
+    let shared = term_bigrams
+        .iter()
+        .filter(|b| context_bigrams.contains(b))
+        .count();
+    // 完全一致より必ず下に並べる
+    0.9 * shared as f32 / term_bigrams.len() as f32
+}
+
+/// 空白を除いた文字 bigram の集合
+fn bigrams(text: &str) -> HashSet<(char, char)> {
+    let chars: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    chars.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_term_in_prior_context_ranks_first() {
+        let dictionary = terms(&["Kubernetes", "議事録", "ステークホルダー", "Tauri"]);
+        let context = "次のスプリントで tauri のビルドを直します";
+
+        let hints = select_hints(&dictionary, context, 2);
+        assert_eq!(hints, vec!["Tauri", "Kubernetes"]);
+
+        // 一部だけ一致する語は、関係の無い語より前に並ぶ
+        let hints = select_hints(&dictionary, "ステークホルの確認", 4);
+        assert_eq!(hints[0], "ステークホルダー");
+    }
+
+    #[test]
+    fn test_empty_context_keeps_original_order() {
+        let dictionary = terms(&["a1", "b2", "c3"]);
+        assert_eq!(select_hints(&dictionary, "", 2), vec!["a1", "b2"]);
+        assert!(select_hints(&dictionary, "a1", 0).is_empty());
+    }
+}
@@ -0,0 +1,63 @@
+/// 区切りに空白を使わない言語か（"ja-JP" のような地域付きも可）
+fn is_cjk_language(lang: &str) -> bool {
+    ["ja", "zh", "ko"].iter().any(|p| lang.starts_with(p))
+}
+
+/// STT エンジンが返した複数セグメントのテキストを言語に応じて連結する
+///
+/// CJK 言語では境界の前後どちらかが非 ASCII 文字なら空白を入れない（"こんにちは" + "世界" → "こんにちは世界"）。
+/// それ以外は空白 1 つで連結する。各セグメントの前後空白は除去し、空のセグメントは無視する。
+pub fn join_segments<S: AsRef<str>>(lang: &str, segments: &[S]) -> String {
+    let cjk = is_cjk_language(lang);
+    let mut joined = String::new();
+
+    for segment in segments {
+        let text = segment.as_ref().trim();
+        let Some(first) = text.chars().next() else {
+            continue;
+        };
+        if let Some(last) = joined.chars().next_back() {
+            let needs_space = !cjk || (last.is_ascii() && first.is_ascii());
+            if needs_space {
+                joined.push(' ');
+            }
+        }
+        joined.push_str(text);
+    }
+    joined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_japanese_without_space() {
+        assert_eq!(
+            join_segments("ja", &[" こんにちは", " 世界。"]),
+            "こんにちは世界。"
+        );
+        // ASCII と日本語の境界にも空白を入れない
+        assert_eq!(
+            join_segments("ja-JP", &["Rust", "の話です"]),
+            "Rustの話です"
+        );
+    }
+
+    #[test]
+    fn test_join_japanese_keeps_space_between_ascii() {
+        assert_eq!(
+            join_segments("ja", &["version", "2 です"]),
+            "version 2 です"
+        );
+    }
+
+    #[test]
+    fn test_join_english_with_space() {
+        assert_eq!(
+            join_segments("en", &[" Hello there.", " How are you?"]),
+            "Hello there. How are you?"
+        );
+        assert_eq!(join_segments("auto", &["a", "", "  ", "b"]), "a b");
+    }
+}
@@ -1,15 +1,35 @@
 #[cfg(target_os = "macos")]
 pub mod apple_speech;
+mod cache;
+mod fallback;
+mod hint_filter;
+mod join;
+pub mod model_manager;
 mod noop;
 pub mod soniox;
+mod transcript_filter;
 #[cfg(feature = "whisper")]
 pub mod whisper;
 
+pub use cache::{CachingSttEngine, TRANSCRIPT_CACHE_CAPACITY};
+pub use fallback::{fallback_order, FallbackSttEngine, UNAVAILABLE_ENGINE_NAME};
+pub use hint_filter::select_hints;
+pub use join::join_segments;
 pub use noop::NoopSttEngine;
+pub use transcript_filter::TranscriptFilter;
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+use super::log_context::{LogContext, TARGET_STT};
+use crate::domain::error::AppError;
+use crate::domain::settings::AppSettings;
+use crate::domain::types::{Mode, TranscriptTiming, WhisperTask};
+
 /// 音声セグメント（STTへの入力）
 #[derive(Debug, Clone)]
 pub struct AudioSegment {
@@ -26,6 +46,100 @@ pub struct SttContext {
     pub language: String,
     /// Domain dictionary for recognition hints
     pub dictionary: Vec<String>,
+    /// 書き起こし中のモード（不明なら None）
+    #[serde(default)]
+    pub mode: Option<Mode>,
+    /// initial_prompt の先頭に置く文（モード別設定 whisper_prompt_prefix から選ぶ）
+    #[serde(default)]
+    pub prompt_prefix: Option<String>,
+    /// initial_prompt の最大文字数（0=無制限、設定 max_prompt_chars）
+    #[serde(default)]
+    pub max_prompt_chars: u32,
+    /// 書き起こしか英訳か（英訳に対応するのは Whisper のみ）
+    #[serde(default)]
+    pub task: WhisperTask,
+    /// ログ相関用の session_id / segment_id
+    #[serde(skip)]
+    pub log_ctx: LogContext,
+    /// 書き起こしの中断要求（セッション停止・ジョブキャンセルで立つ）
+    #[serde(skip)]
+    pub cancel: CancelToken,
+}
+
+/// 書き起こしの中断フラグ（clone したものは同じフラグを共有する）
+///
+/// Whisper の推論は await を挟まない同期処理のため、future の drop や `select!` では止まらない。
+/// エンジンは推論中にこのフラグを確認し、立っていれば [`SttError::Canceled`] を返す。
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 中断を要求する
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// フラグ本体（FFI のコールバックに渡す用）
+    pub fn flag(&self) -> &AtomicBool {
+        &self.0
+    }
+}
+
+impl SttContext {
+    /// Whisper の initial_prompt を組み立てる（プレフィックス → 辞書ヒントの順、どちらも無ければ None）
+    ///
+    /// `max_prompt_chars` を超える場合は辞書ヒントの区切り（読点）で切り、入りきらない残りのヒントを落とす。
+    /// `dictionary` は優先度の高い順に並んでいるため、優先度の高いヒントが残る。
+    /// プレフィックスだけで上限を超える場合はプレフィックスを上限の文字数で切る。
+    pub fn build_initial_prompt(&self) -> Option<String> {
+        let max_chars = match self.max_prompt_chars {
+            0 => usize::MAX,
+            n => n as usize,
+        };
+        let prefix = self
+            .prompt_prefix
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .unwrap_or_default();
+
+        let mut prompt: String = prefix.chars().take(max_chars).collect();
+        let mut chars = prompt.chars().count();
+        let mut truncated = chars < prefix.chars().count();
+        for (i, hint) in self.dictionary.iter().enumerate() {
+            let separator = match (i, chars) {
+                (0, 0) => "",
+                (0, _) => " ",
+                _ => "、",
+            };
+            let added = separator.chars().count() + hint.chars().count();
+            if chars + added > max_chars {
+                truncated = true;
+                break;
+            }
+            prompt.push_str(separator);
+            prompt.push_str(hint);
+            chars += added;
+        }
+
+        if truncated {
+            log::warn!(
+                target: TARGET_STT,
+                "{} Initial prompt truncated to {chars} chars (max_prompt_chars={})",
+                self.log_ctx,
+                self.max_prompt_chars
+            );
+        }
+        (!prompt.is_empty()).then_some(prompt)
+    }
 }
 
 /// 書き起こし結果
@@ -34,6 +148,24 @@ pub struct TranscriptResult {
     pub text: String,
     pub confidence: f32,
     pub is_partial: bool,
+    /// 単語・区間単位のタイミング（エンジンが返さない場合は空）
+    #[serde(default)]
+    pub timings: Vec<TranscriptTiming>,
+}
+
+/// エンジンが対応する機能（UI が効かない操作を隠すため）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EngineCapabilities {
+    /// 部分結果（`is_partial` な結果）を返すか
+    pub partial: bool,
+    /// 単語・区間単位のタイミングを返すか
+    pub timings: bool,
+    /// 意味のある confidence を返すか（false なら固定値）
+    pub confidence: bool,
+    /// 英訳タスク（[`WhisperTask::Translate`]）に対応するか
+    pub translate: bool,
+    /// 対応する言語（設定 language と同じ BCP 47 形式。空なら制限なし）
+    pub languages: Vec<String>,
 }
 
 /// STTエラー
@@ -47,6 +179,25 @@ pub enum SttError {
     TranscriptionFailed(String),
     #[error("Timeout")]
     Timeout,
+    #[error("No speech detected")]
+    NoSpeech,
+    #[error("Canceled")]
+    Canceled,
+}
+
+impl From<SttError> for AppError {
+    fn from(e: SttError) -> Self {
+        match e {
+            SttError::AudioFormat(msg) => AppError::audio_format(msg),
+            SttError::EngineNotAvailable(msg) => AppError::stt_unavailable(msg),
+            SttError::Timeout => AppError::timeout("STT 処理がタイムアウトしました"),
+            SttError::NoSpeech => AppError::invalid_state("音声が検出されませんでした"),
+            SttError::Canceled => AppError::canceled("書き起こしはキャンセルされました"),
+            SttError::TranscriptionFailed(msg) => {
+                AppError::internal(format!("書き起こしエラー: {msg}"))
+            }
+        }
+    }
 }
 
 /// STTエンジン trait（Agent B が実装する）
@@ -60,6 +211,95 @@ pub trait SttEngine: Send + Sync {
 
     fn supports_partial(&self) -> bool;
 
+    /// 対応する機能（既定は部分結果の対応のみを反映し、他は非対応とする）
+    fn capabilities(&self) -> EngineCapabilities {
+        EngineCapabilities {
+            partial: self.supports_partial(),
+            ..Default::default()
+        }
+    }
+
     /// エンジン名を返す（診断・設定画面向け）
     fn name(&self) -> &str;
+
+    /// 使用中のモデルの識別子（モデルファイルのパスなど。モデルを持たないエンジンは None）
+    fn model_id(&self) -> Option<String> {
+        None
+    }
+
+    /// 設定の変更をデコード設定に反映する（調整項目を持たないエンジンは何もしない）
+    fn apply_settings(&self, _settings: &AppSettings) {}
+
+    /// 実行中のエンジンのモデルを差し替える（モデルを持たないエンジンは未対応）
+    fn switch_model(&self, model_path: &Path) -> Result<(), SttError> {
+        Err(SttError::EngineNotAvailable(format!(
+            "{} does not support model switching ({})",
+            self.name(),
+            model_path.display()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(prefix: Option<&str>, dictionary: &[&str]) -> SttContext {
+        SttContext {
+            language: "ja-JP".to_string(),
+            dictionary: dictionary.iter().map(|s| s.to_string()).collect(),
+            mode: Some(Mode::Tech),
+            prompt_prefix: prefix.map(str::to_string),
+            max_prompt_chars: 0,
+            task: WhisperTask::Transcribe,
+            log_ctx: Default::default(),
+            cancel: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_prompt_prefix_precedes_hints() {
+        let prompt = ctx(
+            Some("Rust と Tauri の開発についての会話です。"),
+            &["Tokio", "serde"],
+        )
+        .build_initial_prompt()
+        .unwrap();
+        assert_eq!(
+            prompt,
+            "Rust と Tauri の開発についての会話です。 Tokio、serde"
+        );
+    }
+
+    #[test]
+    fn test_initial_prompt_without_prefix() {
+        assert_eq!(
+            ctx(None, &["Tokio", "serde"])
+                .build_initial_prompt()
+                .as_deref(),
+            Some("Tokio、serde")
+        );
+        assert_eq!(ctx(Some("  "), &[]).build_initial_prompt(), None);
+        assert_eq!(
+            ctx(Some("技術の話"), &[]).build_initial_prompt().as_deref(),
+            Some("技術の話")
+        );
+    }
+
+    #[test]
+    fn test_long_hint_list_is_cut_at_separator() {
+        let hints: Vec<String> = (0..100).map(|i| format!("用語{i:02}")).collect();
+        let hints: Vec<&str> = hints.iter().map(String::as_str).collect();
+        let mut ctx = ctx(Some("技術の話"), &hints);
+        ctx.max_prompt_chars = 30;
+
+        // "技術の話 用語00、用語01、用語02、用語03、用語04" は 29 文字。次のヒントを足すと 30 を超える
+        let prompt = ctx.build_initial_prompt().unwrap();
+        assert_eq!(prompt, "技術の話 用語00、用語01、用語02、用語03、用語04");
+        assert!(prompt.chars().count() <= 30);
+
+        // プレフィックスだけで超える場合はプレフィックスを切る
+        ctx.max_prompt_chars = 2;
+        assert_eq!(ctx.build_initial_prompt().as_deref(), Some("技術"));
+    }
 }
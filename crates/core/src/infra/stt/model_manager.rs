@@ -0,0 +1,529 @@
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::domain::settings::WhisperModelSize;
+use crate::infra::diagnostics::app_data_dir;
+
+/// 進捗通知の間隔（バイト）
+const PROGRESS_INTERVAL_BYTES: u64 = 1024 * 1024;
+
+/// 既知の GGML モデル（HuggingFace ggerganov/whisper.cpp の SHA256）
+const KNOWN_MODELS: &[(WhisperModelSize, &str)] = &[
+    (
+        WhisperModelSize::Base,
+        "60ed5bc3dd14eea856493d334349b405782ddcaf0028d4b5df4088345fba2efe",
+    ),
+    (
+        WhisperModelSize::Small,
+        "1be3a9b2063867b937e64e2ec7483364a79917e157fa98c5d94b5c1fffea987b",
+    ),
+    (
+        WhisperModelSize::Medium,
+        "6c14d5adee5f86394037b4e4e8b59f1673b6cee10e3cf0b11bbdbee79c156208",
+    ),
+    (
+        WhisperModelSize::Large,
+        "64d182b440b98d5203c4f9bd541544d84c605196c4f7b845dfa11fb23594d1e2",
+    ),
+];
+
+/// モデル管理エラー
+#[derive(Debug, thiserror::Error)]
+pub enum ModelError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Download failed: {0}")]
+    Download(String),
+    #[error("Checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+}
+
+/// モデルの状態（一覧表示用）
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelStatus {
+    pub size: WhisperModelSize,
+    pub filename: String,
+    pub url: String,
+    pub sha256: String,
+    /// モデルファイルが存在するか
+    pub present: bool,
+}
+
+/// インストール済みモデルの情報（モデル切り替え用）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModelInfo {
+    /// `ggml-<name>.bin` の `<name>` 部分（例: "base", "large-v3", "small.en"）
+    pub name: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// パラメータ数の目安（例: "74M"。名前から判別できなければ None）
+    pub params: Option<String>,
+}
+
+/// モデル名から系列名を返す（".en" や "-v3" などの派生も同じ系列として扱う）
+fn model_family(name: &str) -> &str {
+    name.split(['.', '-']).next().unwrap_or(name)
+}
+
+/// モデル名からパラメータ数の目安を返す
+fn params_for(name: &str) -> Option<&'static str> {
+    match model_family(name) {
+        "tiny" => Some("39M"),
+        "base" => Some("74M"),
+        "small" => Some("244M"),
+        "medium" => Some("769M"),
+        "large" => Some("1550M"),
+        _ => None,
+    }
+}
+
+/// 系列ごとの推論時メモリ使用量の目安（MB、whisper.cpp の README の値）
+const MODEL_MEMORY_MB: &[(&str, u64)] = &[
+    ("tiny", 273),
+    ("base", 388),
+    ("small", 852),
+    ("medium", 2100),
+    ("large", 3900),
+];
+
+/// モデル名から推論に必要なメモリの目安（バイト）を返す（判別できなければ None）
+pub fn required_memory_bytes(name: &str) -> Option<u64> {
+    let family = model_family(name);
+    MODEL_MEMORY_MB
+        .iter()
+        .find(|(f, _)| *f == family)
+        .map(|(_, mb)| mb * 1024 * 1024)
+}
+
+/// モデルファイルのパスからモデル名（`ggml-<name>.bin` の `<name>`）を返す
+pub fn model_name(path: &Path) -> Option<&str> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix("ggml-")?
+        .strip_suffix(".bin")
+}
+
+/// モデルを読み込む前のメモリ確認の結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoryCheck {
+    /// そのまま読み込める（必要量・空き容量が不明な場合も含む）
+    Fits,
+    /// 空きメモリに収まる、より小さいインストール済みモデルに切り替える
+    Downgrade(ModelInfo),
+    /// メモリが足りない（自動切り替えが無効か、収まるモデルが無い）
+    Insufficient {
+        required_bytes: u64,
+        available_bytes: u64,
+    },
+}
+
+/// 空きメモリとモデルの必要量を比べ、読み込むモデルを決める
+///
+/// `auto_downgrade` なら `installed` のうち空きメモリに収まる最も大きいモデルを選ぶ。
+pub fn check_model_memory(
+    name: &str,
+    available_bytes: Option<u64>,
+    installed: &[ModelInfo],
+    auto_downgrade: bool,
+) -> MemoryCheck {
+    let (Some(required_bytes), Some(available_bytes)) =
+        (required_memory_bytes(name), available_bytes)
+    else {
+        return MemoryCheck::Fits;
+    };
+    if required_bytes <= available_bytes {
+        return MemoryCheck::Fits;
+    }
+    if auto_downgrade {
+        let fallback = installed
+            .iter()
+            .filter_map(|m| required_memory_bytes(&m.name).map(|bytes| (bytes, m)))
+            .filter(|(bytes, _)| *bytes <= available_bytes)
+            .max_by_key(|(bytes, _)| *bytes);
+        if let Some((_, model)) = fallback {
+            return MemoryCheck::Downgrade(model.clone());
+        }
+    }
+    MemoryCheck::Insufficient {
+        required_bytes,
+        available_bytes,
+    }
+}
+
+/// 使えるメモリ量（バイト、取得できなければ None）
+///
+/// Linux は `/proc/meminfo` の MemAvailable を使う。
+/// macOS は圧縮メモリのため空きが少なく見えるので、物理メモリの総量（`hw.memsize`）を使う。
+pub fn available_memory_bytes() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+        parse_mem_available(&meminfo)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("sysctl")
+            .args(["-n", "hw.memsize"])
+            .output()
+            .ok()?;
+        String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        None
+    }
+}
+
+/// `/proc/meminfo` の `MemAvailable:  123456 kB` をバイトに変換する
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_mem_available(meminfo: &str) -> Option<u64> {
+    let kb: u64 = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+/// ダウンロード進捗
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DownloadProgress {
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>,
+}
+
+/// Whisper GGML モデルの一覧・ダウンロード・検証を行う
+pub struct WhisperModelManager {
+    models_dir: PathBuf,
+}
+
+impl Default for WhisperModelManager {
+    fn default() -> Self {
+        Self::new(Self::default_dir())
+    }
+}
+
+impl WhisperModelManager {
+    pub fn new(models_dir: PathBuf) -> Self {
+        Self { models_dir }
+    }
+
+    /// デフォルトのモデル保存ディレクトリ
+    pub fn default_dir() -> PathBuf {
+        app_data_dir().join("models")
+    }
+
+    /// 指定サイズのモデルファイルパス
+    pub fn model_path(&self, size: WhisperModelSize) -> PathBuf {
+        self.models_dir.join(size.filename())
+    }
+
+    /// 指定サイズのモデルが存在するか
+    pub fn is_available(&self, size: WhisperModelSize) -> bool {
+        self.model_path(size).is_file()
+    }
+
+    /// 既知モデルの SHA256
+    pub fn expected_sha256(size: WhisperModelSize) -> &'static str {
+        KNOWN_MODELS
+            .iter()
+            .find(|(s, _)| *s == size)
+            .map(|(_, sha)| *sha)
+            .unwrap_or_default()
+    }
+
+    /// 既知モデルの一覧と存在状況
+    pub fn list(&self) -> Vec<ModelStatus> {
+        KNOWN_MODELS
+            .iter()
+            .map(|(size, sha)| ModelStatus {
+                size: *size,
+                filename: size.filename().to_string(),
+                url: size.download_url(),
+                sha256: sha.to_string(),
+                present: self.is_available(*size),
+            })
+            .collect()
+    }
+
+    /// モデルディレクトリにある `ggml-*.bin` を名前順に列挙する（既知サイズ以外も含む）
+    pub fn list_installed(&self) -> Vec<ModelInfo> {
+        let Ok(entries) = std::fs::read_dir(&self.models_dir) else {
+            return Vec::new();
+        };
+        let mut models: Vec<ModelInfo> = entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let path = entry.path();
+                let name = path
+                    .file_name()?
+                    .to_str()?
+                    .strip_prefix("ggml-")?
+                    .strip_suffix(".bin")?
+                    .to_string();
+                let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+                Some(ModelInfo {
+                    params: params_for(&name).map(str::to_string),
+                    name,
+                    path,
+                    size_bytes: metadata.len(),
+                })
+            })
+            .collect();
+        models.sort_by(|a, b| a.name.cmp(&b.name));
+        models
+    }
+
+    /// 名前でインストール済みモデルを探す
+    pub fn find_installed(&self, name: &str) -> Option<ModelInfo> {
+        self.list_installed().into_iter().find(|m| m.name == name)
+    }
+
+    /// モデルをダウンロードし、チェックサム検証後に配置する
+    ///
+    /// `.part` ファイルに書き出してから検証し、一致した場合のみ本来のパスへ rename する。
+    /// 不一致の場合は `.part` を削除してエラーを返す。
+    pub async fn download<F>(
+        &self,
+        size: WhisperModelSize,
+        mut on_progress: F,
+    ) -> Result<PathBuf, ModelError>
+    where
+        F: FnMut(DownloadProgress),
+    {
+        let dest = self.model_path(size);
+        if dest.is_file() {
+            return Ok(dest);
+        }
+        std::fs::create_dir_all(&self.models_dir)?;
+
+        let mut response = reqwest::get(size.download_url())
+            .await
+            .map_err(|e| ModelError::Download(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(ModelError::Download(format!("HTTP {}", response.status())));
+        }
+
+        let total_bytes = response.content_length();
+        let part = dest.with_extension("bin.part");
+        let mut file = std::fs::File::create(&part)?;
+        let mut downloaded: u64 = 0;
+        let mut next_report: u64 = 0;
+
+        loop {
+            let chunk = match response.chunk().await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+                Err(e) => {
+                    drop(file);
+                    let _ = std::fs::remove_file(&part);
+                    return Err(ModelError::Download(e.to_string()));
+                }
+            };
+            file.write_all(&chunk)?;
+            downloaded += chunk.len() as u64;
+            if downloaded >= next_report {
+                on_progress(DownloadProgress {
+                    downloaded_bytes: downloaded,
+                    total_bytes,
+                });
+                next_report = downloaded + PROGRESS_INTERVAL_BYTES;
+            }
+        }
+        file.flush()?;
+        drop(file);
+
+        on_progress(DownloadProgress {
+            downloaded_bytes: downloaded,
+            total_bytes,
+        });
+
+        verify_and_install(&part, &dest, Self::expected_sha256(size))?;
+        Ok(dest)
+    }
+}
+
+/// ファイルの SHA256（小文字 hex）を計算する
+pub fn sha256_file(path: &Path) -> Result<String, ModelError> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect())
+}
+
+/// 一時ファイルを検証し、一致すれば `dest` に配置する（不一致なら一時ファイルを削除）
+pub fn verify_and_install(
+    part: &Path,
+    dest: &Path,
+    expected_sha256: &str,
+) -> Result<(), ModelError> {
+    let actual = match sha256_file(part) {
+        Ok(actual) => actual,
+        Err(e) => {
+            let _ = std::fs::remove_file(part);
+            return Err(e);
+        }
+    };
+    if !actual.eq_ignore_ascii_case(expected_sha256) {
+        let _ = std::fs::remove_file(part);
+        return Err(ModelError::ChecksumMismatch {
+            expected: expected_sha256.to_string(),
+            actual,
+        });
+    }
+    std::fs::rename(part, dest)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// "hello" の SHA256
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vt-models-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_sha256_file() {
+        let dir = temp_dir();
+        let path = dir.join("fixture.bin");
+        std::fs::write(&path, b"hello").unwrap();
+        assert_eq!(sha256_file(&path).unwrap(), HELLO_SHA256);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_verify_and_install_moves_file_on_match() {
+        let dir = temp_dir();
+        let part = dir.join("ggml-base.bin.part");
+        let dest = dir.join("ggml-base.bin");
+        std::fs::write(&part, b"hello").unwrap();
+
+        verify_and_install(&part, &dest, &HELLO_SHA256.to_uppercase()).unwrap();
+        assert!(!part.exists());
+        assert_eq!(std::fs::read(&dest).unwrap(), b"hello");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_verify_and_install_deletes_partial_on_mismatch() {
+        let dir = temp_dir();
+        let part = dir.join("ggml-base.bin.part");
+        let dest = dir.join("ggml-base.bin");
+        std::fs::write(&part, b"corrupted").unwrap();
+
+        let err = verify_and_install(&part, &dest, HELLO_SHA256).unwrap_err();
+        assert!(matches!(err, ModelError::ChecksumMismatch { .. }));
+        assert!(!part.exists());
+        assert!(!dest.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_list_reports_presence() {
+        let dir = temp_dir();
+        let manager = WhisperModelManager::new(dir.clone());
+        std::fs::write(manager.model_path(WhisperModelSize::Small), b"x").unwrap();
+
+        let list = manager.list();
+        assert_eq!(list.len(), 4);
+        for status in &list {
+            assert_eq!(status.present, status.size == WhisperModelSize::Small);
+            assert_eq!(status.sha256.len(), 64);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_list_installed_scans_ggml_files() {
+        let dir = temp_dir();
+        let manager = WhisperModelManager::new(dir.clone());
+        std::fs::write(dir.join("ggml-small.en.bin"), b"12345").unwrap();
+        std::fs::write(dir.join("ggml-base.bin"), b"123").unwrap();
+        std::fs::write(dir.join("ggml-custom.bin"), b"1").unwrap();
+        // ダウンロード途中・別形式のファイルは含めない
+        std::fs::write(dir.join("ggml-medium.bin.part"), b"1").unwrap();
+        std::fs::write(dir.join("model.bin"), b"1").unwrap();
+        std::fs::create_dir(dir.join("ggml-dir.bin")).unwrap();
+
+        let models = manager.list_installed();
+        let names: Vec<&str> = models.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["base", "custom", "small.en"]);
+        assert_eq!(models[0].size_bytes, 3);
+        assert_eq!(models[0].path, dir.join("ggml-base.bin"));
+        assert_eq!(models[0].params.as_deref(), Some("74M"));
+        assert_eq!(models[1].params, None);
+        assert_eq!(models[2].params.as_deref(), Some("244M"));
+
+        assert_eq!(manager.find_installed("small.en").unwrap().size_bytes, 5);
+        assert!(manager.find_installed("medium").is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn model(name: &str) -> ModelInfo {
+        ModelInfo {
+            name: name.to_string(),
+            path: PathBuf::from(format!("/models/ggml-{name}.bin")),
+            size_bytes: 0,
+            params: params_for(name).map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_memory_check_downgrades_large_model_on_low_memory() {
+        const GB: u64 = 1024 * 1024 * 1024;
+        let installed = vec![model("base"), model("small"), model("large-v3")];
+
+        assert_eq!(
+            check_model_memory("large-v3", Some(16 * GB), &installed, false),
+            MemoryCheck::Fits
+        );
+        // 空き容量が分からなければ確認しない
+        assert_eq!(
+            check_model_memory("large-v3", None, &installed, false),
+            MemoryCheck::Fits
+        );
+        assert!(matches!(
+            check_model_memory("large-v3", Some(2 * GB), &installed, false),
+            MemoryCheck::Insufficient { required_bytes, .. } if required_bytes == 3900 * 1024 * 1024
+        ));
+        // 収まる中で最も大きいインストール済みモデルを選ぶ
+        assert_eq!(
+            check_model_memory("large-v3", Some(2 * GB), &installed, true),
+            MemoryCheck::Downgrade(model("small"))
+        );
+        assert!(matches!(
+            check_model_memory("large-v3", Some(100 * 1024 * 1024), &installed, true),
+            MemoryCheck::Insufficient { .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_mem_available() {
+        let meminfo = "MemTotal:       16314248 kB\nMemFree:         1207932 kB\nMemAvailable:    8155432 kB\n";
+        assert_eq!(parse_mem_available(meminfo), Some(8155432 * 1024));
+        assert_eq!(parse_mem_available("MemTotal: 1 kB\n"), None);
+    }
+}
@@ -17,6 +17,7 @@ impl SttEngine for NoopSttEngine {
             text: "[STTスタブ] これはモック書き起こし結果です".to_string(),
             confidence: 1.0,
             is_partial: false,
+            timings: Vec::new(),
         })
     }
 
@@ -45,6 +46,12 @@ mod tests {
                 SttContext {
                     language: "ja-JP".to_string(),
                     dictionary: vec![],
+                    mode: None,
+                    prompt_prefix: None,
+                    max_prompt_chars: 0,
+                    task: Default::default(),
+                    log_ctx: Default::default(),
+                    cancel: Default::default(),
                 },
             )
             .await
@@ -3,7 +3,9 @@ use reqwest::multipart;
 use serde::Deserialize;
 use std::time::Duration;
 
-use super::{AudioSegment, SttContext, SttEngine, SttError, TranscriptResult};
+use super::{AudioSegment, EngineCapabilities, SttContext, SttEngine, SttError, TranscriptResult};
+use crate::domain::types::TranscriptTiming;
+use crate::infra::log_context::{LogContext, TARGET_STT};
 
 const SONIOX_API_BASE: &str = "https://api.soniox.com/v1";
 const SONIOX_MODEL: &str = "stt-async-v4";
@@ -35,6 +37,10 @@ struct TranscriptionStatusResponse {
 
 #[derive(Deserialize)]
 struct TranscriptToken {
+    #[serde(default)]
+    text: String,
+    start_ms: Option<u64>,
+    end_ms: Option<u64>,
     confidence: Option<f32>,
 }
 
@@ -167,6 +173,7 @@ impl SonioxSttEngine {
             .map_err(|e| SttError::TranscriptionFailed(format!("Parse transcription resp: {e}")))?;
 
         log::debug!(
+            target: TARGET_STT,
             "Soniox transcription created: id={}, status={}",
             tx_resp.id,
             tx_resp.status
@@ -176,7 +183,11 @@ impl SonioxSttEngine {
     }
 
     /// ステータスをポーリングして完了を待つ
-    async fn wait_for_completion(&self, transcription_id: &str) -> Result<(), SttError> {
+    async fn wait_for_completion(
+        &self,
+        transcription_id: &str,
+        log_ctx: &LogContext,
+    ) -> Result<(), SttError> {
         for attempt in 0..MAX_POLL_ATTEMPTS {
             tokio::time::sleep(POLL_INTERVAL).await;
 
@@ -211,7 +222,8 @@ impl SonioxSttEngine {
                 }
                 _ => {
                     log::debug!(
-                        "Soniox poll attempt {}/{}: status={}",
+                        target: TARGET_STT,
+                        "{log_ctx} Soniox poll attempt {}/{}: status={}",
                         attempt + 1,
                         MAX_POLL_ATTEMPTS,
                         status_resp.status
@@ -261,11 +273,13 @@ impl SonioxSttEngine {
                 }
             })
             .unwrap_or(0.9);
+        let timings = transcript.tokens.map(token_timings).unwrap_or_default();
 
         Ok(TranscriptResult {
             text: transcript.text,
             confidence,
             is_partial: false,
+            timings,
         })
     }
 
@@ -307,19 +321,28 @@ impl SttEngine for SonioxSttEngine {
         let wav_data = Self::encode_wav(&audio.samples, audio.sample_rate);
 
         // 2. ファイルアップロード
-        let file_id = self.upload_audio(wav_data).await?;
+        let log_ctx = &ctx.log_ctx;
+        let file_id = self.upload_audio(wav_data).await.inspect_err(|e| {
+            log::warn!(target: TARGET_STT, "{log_ctx} Soniox upload failed: {e}");
+        })?;
 
         // 3. 書き起こしジョブ作成
         let transcription_id = match self.create_transcription(&file_id, &ctx.language).await {
             Ok(id) => id,
             Err(e) => {
+                log::warn!(target: TARGET_STT, "{log_ctx} Soniox create failed: {e}");
                 self.cleanup_file(&file_id).await;
                 return Err(e);
             }
         };
+        log::debug!(
+            target: TARGET_STT,
+            "{log_ctx} Soniox job: file_id={file_id}, transcription_id={transcription_id}"
+        );
 
         // 4. 完了までポーリング
-        if let Err(e) = self.wait_for_completion(&transcription_id).await {
+        if let Err(e) = self.wait_for_completion(&transcription_id, log_ctx).await {
+            log::warn!(target: TARGET_STT, "{log_ctx} Soniox transcription failed: {e}");
             self.cleanup_file(&file_id).await;
             self.cleanup_transcription(&transcription_id).await;
             return Err(e);
@@ -339,6 +362,17 @@ impl SttEngine for SonioxSttEngine {
         false
     }
 
+    /// 非同期 API の結果からトークンのタイミングと confidence を返す（60 以上の言語に対応）
+    fn capabilities(&self) -> EngineCapabilities {
+        EngineCapabilities {
+            partial: false,
+            timings: true,
+            confidence: true,
+            translate: false,
+            languages: Vec::new(),
+        }
+    }
+
     fn name(&self) -> &str {
         "soniox"
     }
@@ -355,6 +389,21 @@ fn language_to_hint(lang: &str) -> &str {
     }
 }
 
+/// トークンのタイミングを変換する（時刻の無いトークンは捨てる）
+fn token_timings(tokens: Vec<TranscriptToken>) -> Vec<TranscriptTiming> {
+    tokens
+        .into_iter()
+        .filter_map(|t| {
+            Some(TranscriptTiming {
+                start_ms: t.start_ms?,
+                end_ms: t.end_ms?,
+                text: t.text,
+                probability: t.confidence,
+            })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -395,6 +444,24 @@ mod tests {
         assert_eq!(language_to_hint("fr-FR"), "");
     }
 
+    #[test]
+    fn test_token_timings_skip_tokens_without_times() {
+        let response: TranscriptResponse = serde_json::from_str(
+            r#"{"text":"今日は","tokens":[
+                {"text":"今日","start_ms":0,"end_ms":300,"confidence":0.9},
+                {"text":"は","start_ms":300,"end_ms":420},
+                {"text":"","confidence":0.5}
+            ]}"#,
+        )
+        .unwrap();
+        let timings = token_timings(response.tokens.unwrap());
+        assert_eq!(timings.len(), 2);
+        assert_eq!(timings[0].text, "今日");
+        assert_eq!(timings[0].probability, Some(0.9));
+        assert_eq!((timings[1].start_ms, timings[1].end_ms), (300, 420));
+        assert_eq!(timings[1].probability, None);
+    }
+
     #[test]
     fn soniox_name_returns_soniox() {
         let engine = SonioxSttEngine::new("test-key".to_string());
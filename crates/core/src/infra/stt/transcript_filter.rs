@@ -0,0 +1,149 @@
+/// 句の区切りとみなす文字
+const PHRASE_DELIMITERS: &[char] = &[
+    '、', '。', '，', '．', '！', '？', '…', ',', '.', '!', '?', ' ', '　', '\n',
+];
+
+/// 繰り返し検出で 1 単位とみなす最大の句数
+const MAX_REPEAT_UNIT: usize = 8;
+
+/// STT 結果の幻覚・繰り返し除去フィルタ
+///
+/// Whisper が無音やノイズから出力しがちなループ（「はい、はい、はい、…」）と
+/// 定型句（「ご視聴ありがとうございました」等）を取り除く。
+#[derive(Debug, Clone)]
+pub struct TranscriptFilter {
+    /// 同じ句がこの回数を超えて連続したら 1 つにまとめる
+    repetition_threshold: usize,
+    /// 除去する定型句
+    blocklist: Vec<String>,
+}
+
+impl TranscriptFilter {
+    pub fn new(repetition_threshold: usize, blocklist: Vec<String>) -> Self {
+        Self {
+            repetition_threshold: repetition_threshold.max(1),
+            blocklist,
+        }
+    }
+
+    /// フィルタ済みテキストを返す
+    pub fn apply(&self, text: &str) -> String {
+        let collapsed = self.collapse_repetitions(text);
+        self.strip_blocklist(&collapsed)
+    }
+
+    /// 連続する同一句（または句の並び）の繰り返しを 1 つにまとめる
+    fn collapse_repetitions(&self, text: &str) -> String {
+        let phrases = split_phrases(text);
+        let cores: Vec<&str> = phrases.iter().map(|p| phrase_core(p)).collect();
+        let mut result = String::with_capacity(text.len());
+
+        let mut i = 0;
+        while i < phrases.len() {
+            match self.find_repetition(&cores[i..]) {
+                Some((unit, repeats)) => {
+                    // 末尾の区切りを活かすため、繰り返しの最後の 1 回分を残す
+                    let last = i + unit * (repeats - 1);
+                    for phrase in &phrases[last..last + unit] {
+                        result.push_str(phrase);
+                    }
+                    i += unit * repeats;
+                }
+                None => {
+                    result.push_str(phrases[i]);
+                    i += 1;
+                }
+            }
+        }
+        result
+    }
+
+    /// 先頭から始まる繰り返しを探し、(単位の句数, 回数) を返す（しきい値以下なら None）
+    fn find_repetition(&self, cores: &[&str]) -> Option<(usize, usize)> {
+        let max_unit = MAX_REPEAT_UNIT.min(cores.len() / (self.repetition_threshold + 1));
+        (1..=max_unit).find_map(|unit| {
+            let block = &cores[..unit];
+            if block.iter().any(|c| c.is_empty()) {
+                return None;
+            }
+            let repeats = cores
+                .chunks_exact(unit)
+                .take_while(|chunk| *chunk == block)
+                .count();
+            (repeats > self.repetition_threshold).then_some((unit, repeats))
+        })
+    }
+
+    fn strip_blocklist(&self, text: &str) -> String {
+        let mut result = text.to_string();
+        for phrase in self.blocklist.iter().filter(|p| !p.is_empty()) {
+            result = result.replace(phrase.as_str(), "");
+        }
+        if result.len() == text.len() {
+            result
+        } else {
+            result.trim().to_string()
+        }
+    }
+}
+
+/// テキストを「句 + 後続の区切り文字」の単位に分割する
+fn split_phrases(text: &str) -> Vec<&str> {
+    let mut phrases = Vec::new();
+    let mut start = 0;
+    let mut in_delimiters = false;
+
+    for (idx, ch) in text.char_indices() {
+        let is_delim = PHRASE_DELIMITERS.contains(&ch);
+        if !is_delim && in_delimiters {
+            phrases.push(&text[start..idx]);
+            start = idx;
+        }
+        in_delimiters = is_delim;
+    }
+    if start < text.len() {
+        phrases.push(&text[start..]);
+    }
+    phrases
+}
+
+/// 句から区切り文字を除いた本体
+fn phrase_core(phrase: &str) -> &str {
+    phrase.trim_end_matches(PHRASE_DELIMITERS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter() -> TranscriptFilter {
+        TranscriptFilter::new(3, vec!["ご視聴ありがとうございました".to_string()])
+    }
+
+    #[test]
+    fn test_repeated_phrase_collapses_to_one() {
+        let looped = ["はい"; 10].join("、");
+        assert_eq!(filter().apply(&looped), "はい");
+
+        let looped = format!("{}次の議題です。", "はい、".repeat(10));
+        assert_eq!(filter().apply(&looped), "はい、次の議題です。");
+
+        assert_eq!(filter().apply(&"thank you. ".repeat(10)), "thank you. ");
+    }
+
+    #[test]
+    fn test_normal_text_passes_through() {
+        let text = "はい、はい、わかりました。今日は 2024 年の計画について話します。";
+        assert_eq!(filter().apply(text), text);
+        assert_eq!(filter().apply(""), "");
+    }
+
+    #[test]
+    fn test_blocklist_phrase_is_stripped() {
+        assert_eq!(
+            filter().apply("以上です。ご視聴ありがとうございました"),
+            "以上です。"
+        );
+        assert_eq!(filter().apply("ご視聴ありがとうございました"), "");
+    }
+}
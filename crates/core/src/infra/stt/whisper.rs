@@ -1,10 +1,23 @@
 use async_trait::async_trait;
-use std::path::PathBuf;
+use std::ffi::c_void;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
-use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
-
-use super::{AudioSegment, SttContext, SttEngine, SttError, TranscriptResult};
-use crate::domain::settings::WhisperModelSize;
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
+};
+
+use super::model_manager::{
+    available_memory_bytes, check_model_memory, model_name, MemoryCheck, WhisperModelManager,
+};
+use super::{
+    join_segments, AudioSegment, EngineCapabilities, SttContext, SttEngine, SttError,
+    TranscriptResult,
+};
+use crate::domain::settings::{AppSettings, WhisperModelSize};
+use crate::domain::types::{TranscriptTiming, WhisperTask};
+use crate::infra::audio::preprocess::resample_linear;
+use crate::infra::log_context::TARGET_STT;
 
 /// Whisper デコード設定
 #[derive(Debug, Clone)]
@@ -13,6 +26,21 @@ pub struct WhisperConfig {
     pub beam_size: usize,
     /// Greedy 時の best_of パラメータ
     pub best_of: usize,
+    /// whisper.cpp のデコードに渡す no_speech しきい値
+    ///
+    /// whisper-rs はセグメントごとの no_speech 確率を返さないため、アプリ側では
+    /// 全セグメントが空のときだけ [`SttError::NoSpeech`] とする（[`is_no_speech`]）。
+    pub no_speech_thold: f32,
+    /// これを超えるエントロピー（繰り返し気味）の結果は温度を上げて再デコードする
+    pub entropy_thold: f32,
+    /// これを下回る平均対数確率の結果は温度を上げて再デコードする
+    pub logprob_thold: f32,
+    /// 推論スレッド数（`auto_threads` が false のときに使う）
+    pub threads: usize,
+    /// CPU コア数から推論スレッド数を決める（コア数 - 1、最低 1）
+    pub auto_threads: bool,
+    /// 既定のタスク（Translate なら全セッションで英訳する）
+    pub task: WhisperTask,
 }
 
 impl Default for WhisperConfig {
@@ -20,14 +48,67 @@ impl Default for WhisperConfig {
         Self {
             beam_size: 5,
             best_of: 5,
+            no_speech_thold: 0.6,
+            entropy_thold: 2.4,
+            logprob_thold: -1.0,
+            threads: 4,
+            auto_threads: true,
+            task: WhisperTask::Transcribe,
+        }
+    }
+}
+
+impl WhisperConfig {
+    /// 設定画面で調整できる項目を反映した設定
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        let mut config = Self::default();
+        config.apply_settings(settings);
+        config
+    }
+
+    /// 設定画面で調整できる項目（エントロピー・対数確率しきい値）を上書きする
+    pub fn apply_settings(&mut self, settings: &AppSettings) {
+        self.entropy_thold = settings.whisper_entropy_thold;
+        self.logprob_thold = settings.whisper_logprob_thold;
+    }
+
+    /// 実際に使う推論スレッド数
+    ///
+    /// 自動時は UI・音声キャプチャ用に 1 コア残す。
+    pub fn effective_threads(&self) -> usize {
+        if self.auto_threads {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+                .saturating_sub(1)
+                .max(1)
+        } else {
+            self.threads.max(1)
+        }
+    }
+
+    /// 実際に使うタスク（設定かセッションのどちらかが英訳なら英訳）
+    pub fn effective_task(&self, requested: WhisperTask) -> WhisperTask {
+        if self.task == WhisperTask::Translate {
+            WhisperTask::Translate
+        } else {
+            requested
         }
     }
 }
 
 /// Whisper.cpp ベースの STT エンジン
+///
+/// 推論は `AudioSegment::samples` をメモリ上で直接渡して行い、一時 WAV ファイルは作らない。
+/// そのため `AudioRetention::None` の場合も音声がディスクに書き出されることはない。
 pub struct WhisperSttEngine {
     ctx: Mutex<WhisperContext>,
-    config: WhisperConfig,
+    /// 読み込み中のモデルファイル（`switch_model` で更新）
+    model_path: Mutex<PathBuf>,
+    /// デコード設定（しきい値は `apply_settings` で録音中にも更新される）
+    config: Mutex<WhisperConfig>,
+    /// 初期化時に決めた推論スレッド数
+    n_threads: usize,
 }
 
 impl WhisperSttEngine {
@@ -41,18 +122,54 @@ impl WhisperSttEngine {
         let ctx = WhisperContext::new_with_params(model_path, WhisperContextParameters::default())
             .map_err(|e| SttError::EngineNotAvailable(format!("Whisper model load failed: {e}")))?;
 
+        let n_threads = config.effective_threads();
+        log::info!(target: TARGET_STT, "Whisper threads: {n_threads}");
+
         Ok(Self {
             ctx: Mutex::new(ctx),
-            config,
+            model_path: Mutex::new(PathBuf::from(model_path)),
+            config: Mutex::new(config),
+            n_threads,
         })
     }
 
+    /// モデルを読み込む前に空きメモリを確認し、読み込むモデルのパスを返す
+    ///
+    /// 足りなければ案内付きの `EngineNotAvailable` を返す（読み込み中の OOM で落ちないように）。
+    /// `auto_downgrade` なら同じディレクトリにある、空きメモリに収まる小さいモデルを返す。
+    pub fn preflight(model_path: &Path, auto_downgrade: bool) -> Result<PathBuf, SttError> {
+        let Some(name) = model_name(model_path) else {
+            return Ok(model_path.to_path_buf());
+        };
+        let installed = match (auto_downgrade, model_path.parent()) {
+            (true, Some(dir)) => WhisperModelManager::new(dir.to_path_buf()).list_installed(),
+            _ => Vec::new(),
+        };
+        match check_model_memory(name, available_memory_bytes(), &installed, auto_downgrade) {
+            MemoryCheck::Fits => Ok(model_path.to_path_buf()),
+            MemoryCheck::Downgrade(model) => {
+                log::warn!(
+                    target: TARGET_STT,
+                    "Not enough memory for Whisper model {name}, using {} instead",
+                    model.name
+                );
+                Ok(model.path)
+            }
+            MemoryCheck::Insufficient {
+                required_bytes,
+                available_bytes,
+            } => Err(SttError::EngineNotAvailable(format!(
+                "Whisper model {name} needs about {} MB of memory but only {} MB is available; \
+                 choose a smaller model (e.g. small) or enable auto_downgrade_model",
+                required_bytes / (1024 * 1024),
+                available_bytes / (1024 * 1024)
+            ))),
+        }
+    }
+
     /// 指定モデルサイズのモデルパスを返す
     pub fn model_path_for(size: WhisperModelSize) -> PathBuf {
-        let base = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
-        base.join("voiceTypeless")
-            .join("models")
-            .join(size.filename())
+        WhisperModelManager::default().model_path(size)
     }
 
     /// デフォルトのモデルパスを返す（Base）
@@ -74,25 +191,7 @@ impl WhisperSttEngine {
 /// 線形補間リサンプラー（フォールバック）: 任意サンプルレート → 16kHz
 #[cfg(not(feature = "high-quality-resample"))]
 fn resample_to_16k(samples: &[f32], source_rate: u32) -> Vec<f32> {
-    if source_rate == 16000 {
-        return samples.to_vec();
-    }
-
-    let ratio = source_rate as f64 / 16000.0;
-    let output_len = (samples.len() as f64 / ratio) as usize;
-    let mut output = Vec::with_capacity(output_len);
-
-    for i in 0..output_len {
-        let src_idx = i as f64 * ratio;
-        let idx_floor = src_idx.floor() as usize;
-        let idx_ceil = (idx_floor + 1).min(samples.len() - 1);
-        let frac = (src_idx - idx_floor as f64) as f32;
-
-        let sample = samples[idx_floor] * (1.0 - frac) + samples[idx_ceil] * frac;
-        output.push(sample);
-    }
-
-    output
+    resample_linear(samples, source_rate, 16000)
 }
 
 /// 高品質 sinc 補間リサンプラー (rubato): 任意サンプルレート → 16kHz
@@ -138,21 +237,81 @@ fn resample_to_16k(samples: &[f32], source_rate: u32) -> Vec<f32> {
 /// rubato 失敗時の線形補間フォールバック
 #[cfg(feature = "high-quality-resample")]
 fn resample_linear_fallback(samples: &[f32], source_rate: u32) -> Vec<f32> {
-    let ratio = source_rate as f64 / 16000.0;
-    let output_len = (samples.len() as f64 / ratio) as usize;
-    let mut output = Vec::with_capacity(output_len);
+    resample_linear(samples, source_rate, 16000)
+}
 
-    for i in 0..output_len {
-        let src_idx = i as f64 * ratio;
-        let idx_floor = src_idx.floor() as usize;
-        let idx_ceil = (idx_floor + 1).min(samples.len() - 1);
-        let frac = (src_idx - idx_floor as f64) as f32;
+/// トークン確率から信頼度を出せない場合の値（whisper.cpp は信頼度を直接返さない）
+const FALLBACK_CONFIDENCE: f32 = 0.8;
+
+/// Whisper のセグメントごとのタイミングと平均トークン確率、および全トークンの平均対数確率
+///
+/// t0/t1 は 10ms 単位。確率は特殊トークン（`[_BEG_]` 等）を除いて平均する。
+fn segment_timings(
+    state: &WhisperState,
+    num_segments: i32,
+) -> (Vec<TranscriptTiming>, Option<f32>) {
+    let mut all_probs = Vec::new();
+    let timings = (0..num_segments)
+        .filter_map(|i| {
+            let text = state.full_get_segment_text(i).ok()?;
+            if text.trim().is_empty() {
+                return None;
+            }
+            let t0 = state.full_get_segment_t0(i).ok()?;
+            let t1 = state.full_get_segment_t1(i).ok()?;
+            let n_tokens = state.full_n_tokens(i).unwrap_or(0);
+            let probs: Vec<f32> = (0..n_tokens)
+                .filter(|&j| {
+                    state
+                        .full_get_token_text(i, j)
+                        .map(|t| !t.starts_with("[_"))
+                        .unwrap_or(false)
+                })
+                .filter_map(|j| state.full_get_token_prob(i, j).ok())
+                .collect();
+            let probability =
+                (!probs.is_empty()).then(|| probs.iter().sum::<f32>() / probs.len() as f32);
+            all_probs.extend_from_slice(&probs);
+            Some(TranscriptTiming {
+                text: text.trim().to_string(),
+                start_ms: t0.max(0) as u64 * 10,
+                end_ms: t1.max(0) as u64 * 10,
+                probability,
+            })
+        })
+        .collect();
+    (timings, avg_logprob(&all_probs))
+}
 
-        let sample = samples[idx_floor] * (1.0 - frac) + samples[idx_ceil] * frac;
-        output.push(sample);
+/// 無音・ノイズとみなす書き起こしか（セグメントがあり、すべて空白だけ）
+///
+/// whisper.cpp は無音と判定した区間のテキストを空にするため、それしか無ければ書き起こしを捨てる。
+fn is_no_speech(segments: &[String]) -> bool {
+    !segments.is_empty() && segments.iter().all(|s| s.trim().is_empty())
+}
+
+/// トークン確率の平均対数確率（確率が 1 つも無ければ None）
+fn avg_logprob(probs: &[f32]) -> Option<f32> {
+    if probs.is_empty() {
+        return None;
     }
+    let sum: f32 = probs.iter().map(|p| p.max(f32::MIN_POSITIVE).ln()).sum();
+    Some(sum / probs.len() as f32)
+}
 
-    output
+/// 平均対数確率を 0〜1 の信頼度に写す（有限でなければ None）
+///
+/// exp(avg_logprob) はトークン確率の幾何平均で、-0.1 ≒ 0.90、-0.7 ≒ 0.50、-2.3 ≒ 0.10 になる。
+fn confidence_from_avg_logprob(avg_logprob: f32) -> Option<f32> {
+    avg_logprob
+        .is_finite()
+        .then(|| avg_logprob.exp().clamp(0.0, 1.0))
+}
+
+/// whisper.cpp の中断コールバック（user_data は `CancelToken` のフラグ）
+unsafe extern "C" fn abort_when_canceled(user_data: *mut c_void) -> bool {
+    let flag = &*(user_data as *const AtomicBool);
+    flag.load(Ordering::SeqCst)
 }
 
 #[async_trait]
@@ -170,26 +329,37 @@ impl SttEngine for WhisperSttEngine {
                 text: String::new(),
                 confidence: 0.0,
                 is_partial: false,
+                timings: Vec::new(),
             });
         }
 
+        if ctx.cancel.is_canceled() {
+            return Err(SttError::Canceled);
+        }
+
         let whisper_ctx = self.ctx.lock().map_err(|e| {
             SttError::TranscriptionFailed(format!("Whisper context lock failed: {e}"))
         })?;
 
+        let config = self
+            .config
+            .lock()
+            .map_err(|e| SttError::TranscriptionFailed(format!("Whisper config lock failed: {e}")))?
+            .clone();
+
         let mut state = whisper_ctx.create_state().map_err(|e| {
             SttError::TranscriptionFailed(format!("Whisper state creation failed: {e}"))
         })?;
 
         // サンプリング戦略: beam_size > 0 → BeamSearch, 0 → Greedy
-        let strategy = if self.config.beam_size > 0 {
+        let strategy = if config.beam_size > 0 {
             SamplingStrategy::BeamSearch {
-                beam_size: self.config.beam_size as i32,
+                beam_size: config.beam_size as i32,
                 patience: -1.0,
             }
         } else {
             SamplingStrategy::Greedy {
-                best_of: self.config.best_of as i32,
+                best_of: config.best_of as i32,
             }
         };
         let mut params = FullParams::new(strategy);
@@ -207,42 +377,82 @@ impl SttEngine for WhisperSttEngine {
             "auto"
         };
         params.set_language(Some(lang));
+        params.set_n_threads(self.n_threads as i32);
+        params.set_translate(config.effective_task(ctx.task) == WhisperTask::Translate);
         params.set_print_special(false);
         params.set_print_progress(false);
         params.set_print_realtime(false);
         params.set_print_timestamps(false);
         params.set_suppress_blank(true);
+        params.set_no_speech_thold(config.no_speech_thold);
+        params.set_entropy_thold(config.entropy_thold);
+        params.set_logprob_thold(config.logprob_thold);
+        // 停止要求で推論を途中で打ち切る（フラグは ctx が保持するため推論中は生存している）
+        unsafe {
+            params.set_abort_callback(Some(abort_when_canceled));
+            params.set_abort_callback_user_data(
+                ctx.cancel.flag() as *const AtomicBool as *mut c_void,
+            );
+        }
 
-        // 辞書ヒントによる initial_prompt 設定
-        if !ctx.dictionary.is_empty() {
-            let hints = ctx.dictionary.join("、");
-            params.set_initial_prompt(&hints);
+        // モード別プレフィックス + 辞書ヒントによる initial_prompt 設定
+        let initial_prompt = ctx.build_initial_prompt();
+        if let Some(prompt) = &initial_prompt {
+            params.set_initial_prompt(prompt);
             params.set_no_context(false);
         } else {
             params.set_no_context(true);
         }
 
-        state
-            .full(params, &samples_16k)
-            .map_err(|e| SttError::TranscriptionFailed(format!("Whisper inference failed: {e}")))?;
+        log::debug!(
+            target: TARGET_STT,
+            "{} Whisper inference: {} samples, lang={lang}",
+            ctx.log_ctx,
+            samples_16k.len()
+        );
+        let started = std::time::Instant::now();
+        let inference = state.full(params, &samples_16k);
+        if ctx.cancel.is_canceled() {
+            log::info!(
+                target: TARGET_STT,
+                "{} Whisper inference aborted after {}ms",
+                ctx.log_ctx,
+                started.elapsed().as_millis()
+            );
+            return Err(SttError::Canceled);
+        }
+        inference.map_err(|e| {
+            log::error!(target: TARGET_STT, "{} Whisper inference failed: {e}", ctx.log_ctx);
+            SttError::TranscriptionFailed(format!("Whisper inference failed: {e}"))
+        })?;
 
         let num_segments = state
             .full_n_segments()
             .map_err(|e| SttError::TranscriptionFailed(format!("Failed to get segments: {e}")))?;
 
-        let mut text = String::new();
-        for i in 0..num_segments {
-            if let Ok(segment_text) = state.full_get_segment_text(i) {
-                text.push_str(&segment_text);
-            }
+        let segments: Vec<String> = (0..num_segments)
+            .filter_map(|i| state.full_get_segment_text(i).ok())
+            .collect();
+        if is_no_speech(&segments) {
+            return Err(SttError::NoSpeech);
         }
-
-        let text = text.trim().to_string();
+        let text = join_segments(lang, &segments);
+        let (timings, avg_logprob) = segment_timings(&state, num_segments);
+        let confidence = avg_logprob
+            .and_then(confidence_from_avg_logprob)
+            .unwrap_or(FALLBACK_CONFIDENCE);
+        log::debug!(
+            target: TARGET_STT,
+            "{} Whisper done in {}ms: {num_segments} segments",
+            ctx.log_ctx,
+            started.elapsed().as_millis()
+        );
 
         Ok(TranscriptResult {
             text,
-            confidence: 0.8, // Whisper.cpp は confidence を直接返さないため固定値
+            confidence,
             is_partial: false,
+            timings,
         })
     }
 
@@ -250,9 +460,72 @@ impl SttEngine for WhisperSttEngine {
         false
     }
 
+    fn capabilities(&self) -> EngineCapabilities {
+        match self.model_path.lock() {
+            Ok(path) => model_capabilities(&path),
+            Err(_) => model_capabilities(Path::new("")),
+        }
+    }
+
     fn name(&self) -> &str {
         "whisper"
     }
+
+    fn model_id(&self) -> Option<String> {
+        let path = self.model_path.lock().ok()?;
+        Some(path.display().to_string())
+    }
+
+    /// 次の推論からしきい値を反映する（実行中の推論には影響しない）
+    fn apply_settings(&self, settings: &AppSettings) {
+        if let Ok(mut config) = self.config.lock() {
+            config.apply_settings(settings);
+        }
+    }
+
+    /// 新しいモデルを読み込んでからコンテキストを差し替える（推論中ならその完了を待つ）
+    fn switch_model(&self, model_path: &Path) -> Result<(), SttError> {
+        Self::preflight(model_path, false)?;
+        let ctx = WhisperContext::new_with_params(
+            &model_path.to_string_lossy(),
+            WhisperContextParameters::default(),
+        )
+        .map_err(|e| SttError::EngineNotAvailable(format!("Whisper model load failed: {e}")))?;
+        *self.ctx.lock().map_err(|e| {
+            SttError::TranscriptionFailed(format!("Whisper context lock failed: {e}"))
+        })? = ctx;
+        if let Ok(mut current) = self.model_path.lock() {
+            *current = model_path.to_path_buf();
+        }
+        log::info!(target: TARGET_STT, "Whisper model switched: {}", model_path.display());
+        Ok(())
+    }
+}
+
+/// 言語を明示して推論する言語（これ以外は Whisper の自動判定に任せる）
+const WHISPER_LANGUAGES: [&str; 4] = ["ja-JP", "en-US", "zh-CN", "ko-KR"];
+
+/// モデルファイルから Whisper の対応機能を決める
+///
+/// 推論は一括のため部分結果は返さず、区間タイミングと avg_logprob 由来の confidence を返す。
+/// 英語専用モデル（`ggml-*.en.bin`）は英訳に対応せず、英語のみを扱う。
+fn model_capabilities(model_path: &Path) -> EngineCapabilities {
+    let english_only = model_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| stem.ends_with(".en"));
+    let languages = if english_only {
+        vec!["en-US".to_string()]
+    } else {
+        WHISPER_LANGUAGES.iter().map(|l| l.to_string()).collect()
+    };
+    EngineCapabilities {
+        partial: false,
+        timings: true,
+        confidence: true,
+        translate: !english_only,
+        languages,
+    }
 }
 
 #[cfg(test)]
@@ -275,6 +548,53 @@ mod tests {
         assert!(diff < 160, "Expected ~16000 samples, got {}", result.len());
     }
 
+    #[test]
+    fn test_capabilities_advertise_timings_but_not_partials() {
+        let caps = model_capabilities(Path::new("/models/ggml-base.bin"));
+        assert!(caps.timings);
+        assert!(!caps.partial);
+        assert!(caps.translate);
+        assert!(caps.languages.contains(&"ja-JP".to_string()));
+
+        // 英語専用モデルは英訳できない
+        let caps = model_capabilities(Path::new("/models/ggml-small.en.bin"));
+        assert!(caps.timings);
+        assert!(!caps.translate);
+        assert_eq!(caps.languages, vec!["en-US"]);
+    }
+
+    #[test]
+    fn test_effective_threads_auto_uses_spare_cores() {
+        let config = WhisperConfig {
+            auto_threads: true,
+            ..Default::default()
+        };
+        let cores = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        let threads = config.effective_threads();
+        assert!(threads >= 1);
+        assert!(threads <= cores);
+    }
+
+    #[test]
+    fn test_effective_threads_explicit_when_auto_is_off() {
+        let config = WhisperConfig {
+            threads: 3,
+            auto_threads: false,
+            ..Default::default()
+        };
+        assert_eq!(config.effective_threads(), 3);
+
+        // 0 は 1 に丸める
+        let config = WhisperConfig {
+            threads: 0,
+            auto_threads: false,
+            ..Default::default()
+        };
+        assert_eq!(config.effective_threads(), 1);
+    }
+
     #[test]
     fn test_default_model_path() {
         let path = WhisperSttEngine::default_model_path();
@@ -302,6 +622,80 @@ mod tests {
         let config = WhisperConfig::default();
         assert_eq!(config.beam_size, 5);
         assert_eq!(config.best_of, 5);
+        assert_eq!(config.no_speech_thold, 0.6);
+        assert_eq!(config.task, WhisperTask::Transcribe);
+    }
+
+    #[test]
+    fn test_blank_segments_are_no_speech() {
+        let segments = |texts: &[&str]| texts.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        assert!(is_no_speech(&segments(&["", " "])));
+        assert!(!is_no_speech(&segments(&["", " 今日の議題です。"])));
+        // セグメントが無いのは空の音声（呼び出し側で空の結果として扱う）
+        assert!(!is_no_speech(&[]));
+    }
+
+    #[test]
+    fn test_config_from_settings_uses_thresholds() {
+        let settings = AppSettings {
+            whisper_entropy_thold: 2.8,
+            whisper_logprob_thold: -0.5,
+            ..Default::default()
+        };
+        let mut config = WhisperConfig::from_settings(&settings);
+        assert_eq!(config.entropy_thold, 2.8);
+        assert_eq!(config.logprob_thold, -0.5);
+        // 調整対象外の項目は既定値のまま
+        assert_eq!(config.beam_size, WhisperConfig::default().beam_size);
+
+        // 既定の設定値は whisper.cpp の既定値と同じ
+        config.apply_settings(&AppSettings::default());
+        assert_eq!(config.entropy_thold, WhisperConfig::default().entropy_thold);
+        assert_eq!(config.logprob_thold, WhisperConfig::default().logprob_thold);
+    }
+
+    #[test]
+    fn test_confidence_from_avg_logprob_buckets() {
+        let bucket = |logprob: f32| (confidence_from_avg_logprob(logprob).unwrap() * 10.0).round();
+        assert_eq!(bucket(-0.105), 9.0);
+        assert_eq!(bucket(-0.693), 5.0);
+        assert_eq!(bucket(-2.3), 1.0);
+        assert_eq!(confidence_from_avg_logprob(0.5), Some(1.0));
+        assert_eq!(confidence_from_avg_logprob(f32::NAN), None);
+        assert_eq!(confidence_from_avg_logprob(f32::NEG_INFINITY), None);
+    }
+
+    #[test]
+    fn test_avg_logprob_of_token_probs() {
+        assert_eq!(avg_logprob(&[]), None);
+        // 幾何平均 0.5 → 信頼度 0.5
+        let logprob = avg_logprob(&[0.25, 1.0]).unwrap();
+        assert!((confidence_from_avg_logprob(logprob).unwrap() - 0.5).abs() < 1e-6);
+        // 確率 0 のトークンがあっても有限値になる
+        assert!(avg_logprob(&[0.0, 0.9]).unwrap().is_finite());
+    }
+
+    #[test]
+    fn test_effective_task_translates_only_when_requested() {
+        let config = WhisperConfig::default();
+        assert_eq!(
+            config.effective_task(WhisperTask::Transcribe),
+            WhisperTask::Transcribe
+        );
+        assert_eq!(
+            config.effective_task(WhisperTask::Translate),
+            WhisperTask::Translate
+        );
+
+        // 設定で英訳を指定したらセッションの指定にかかわらず英訳
+        let config = WhisperConfig {
+            task: WhisperTask::Translate,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.effective_task(WhisperTask::Transcribe),
+            WhisperTask::Translate
+        );
     }
 
     // WhisperSttEngine::name() は実際のモデルが必要なため、
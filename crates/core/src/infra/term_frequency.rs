@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use crate::domain::types::{DictionaryEntry, TermSuggestion};
+
+/// 候補とする語の最小文字数（「ト」のような 1 文字の断片を除く）
+const MIN_TERM_CHARS: usize = 2;
+
+/// 候補として挙げる最小出現回数（1 回きりの語は誤認識か固有の言い回しとみなす）
+const MIN_SUGGESTION_OCCURRENCES: u32 = 2;
+
+/// 書き起こしテキストに繰り返し現れる語（カタカナ連続・全大文字トークン）の出現回数を数える
+///
+/// 辞書登録の候補を出すための軽量な集計で、形態素解析は行わない。
+#[derive(Debug, Default)]
+pub struct TermFrequency {
+    counts: HashMap<String, u32>,
+}
+
+impl TermFrequency {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// テキスト中の候補語を数える
+    pub fn add_text(&mut self, text: &str) {
+        for term in extract_terms(text) {
+            *self.counts.entry(term).or_default() += 1;
+        }
+    }
+
+    /// 辞書でカバーされていない語を出現回数の多い順に最大 `limit` 件返す
+    ///
+    /// エントリの pattern または replacement に含まれる語はカバー済みとみなす。
+    pub fn suggestions(&self, covered: &[DictionaryEntry], limit: usize) -> Vec<TermSuggestion> {
+        let mut candidates: Vec<TermSuggestion> = self
+            .counts
+            .iter()
+            .filter(|(_, &occurrences)| occurrences >= MIN_SUGGESTION_OCCURRENCES)
+            .filter(|(term, _)| {
+                !covered.iter().any(|entry| {
+                    entry.pattern.contains(term.as_str())
+                        || entry.replacement.contains(term.as_str())
+                })
+            })
+            .map(|(term, &occurrences)| TermSuggestion {
+                term: term.clone(),
+                occurrences,
+            })
+            .collect();
+        candidates.sort_by(|a, b| {
+            b.occurrences
+                .cmp(&a.occurrences)
+                .then_with(|| a.term.cmp(&b.term))
+        });
+        candidates.truncate(limit);
+        candidates
+    }
+}
+
+/// カタカナの連続（長音符を含む）と、2 文字以上の全大文字トークンを取り出す
+fn extract_terms(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut katakana = String::new();
+    let mut ascii = String::new();
+
+    for ch in text.chars().chain(std::iter::once(' ')) {
+        if is_katakana(ch) {
+            katakana.push(ch);
+        } else {
+            push_term(&mut terms, &mut katakana, |_| true);
+        }
+        if ch.is_ascii_alphanumeric() {
+            ascii.push(ch);
+        } else {
+            push_term(&mut terms, &mut ascii, is_all_caps);
+        }
+    }
+    terms
+}
+
+fn push_term(terms: &mut Vec<String>, run: &mut String, accept: impl Fn(&str) -> bool) {
+    // 先頭の長音符は語の一部ではない（「ー」だけの連続も除く）
+    let term = run.trim_start_matches('ー');
+    if term.chars().count() >= MIN_TERM_CHARS && accept(term) {
+        terms.push(term.to_string());
+    }
+    run.clear();
+}
+
+fn is_katakana(ch: char) -> bool {
+    // ゠(U+30A0) と中黒(U+30FB) は区切りとして扱う
+    matches!(ch, '\u{30A1}'..='\u{30FA}' | '\u{30FC}')
+}
+
+/// 英大文字を 2 文字以上含み、小文字を含まないトークン（"API", "GPT4" など）
+fn is_all_caps(token: &str) -> bool {
+    token.chars().filter(char::is_ascii_uppercase).count() >= MIN_TERM_CHARS
+        && !token.chars().any(|c| c.is_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::types::DictionaryScope;
+
+    #[test]
+    fn test_extract_katakana_runs_and_caps_tokens() {
+        assert_eq!(
+            extract_terms("テストのAPIをクロードとGPT4で試すOk"),
+            vec!["テスト", "API", "クロード", "GPT4"]
+        );
+    }
+
+    #[test]
+    fn test_suggestions_skip_covered_and_rare_terms() {
+        let mut tf = TermFrequency::new();
+        tf.add_text("ヴォイスタイプレスでAPIを呼ぶ");
+        tf.add_text("ヴォイスタイプレスのAPIとクロード");
+        tf.add_text("ヴォイスタイプレスは速い");
+        tf.add_text("クロード");
+        tf.add_text("ワンオフ");
+
+        let covered = vec![DictionaryEntry {
+            id: None,
+            scope: DictionaryScope::Global,
+            mode: None,
+            pattern: "くろーど".into(),
+            replacement: "クロード".into(),
+            priority: 0,
+            enabled: true,
+            word_boundary: false,
+        }];
+        let suggestions = tf.suggestions(&covered, 10);
+        assert_eq!(
+            suggestions,
+            vec![
+                TermSuggestion {
+                    term: "ヴォイスタイプレス".into(),
+                    occurrences: 3,
+                },
+                TermSuggestion {
+                    term: "API".into(),
+                    occurrences: 2,
+                },
+            ]
+        );
+        assert_eq!(tf.suggestions(&covered, 1).len(), 1);
+    }
+}
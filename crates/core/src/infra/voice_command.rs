@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use crate::domain::types::Mode;
+
+/// 確定テキスト全体が音声コマンドのフレーズと一致すれば、そのフレーズと切り替え先のモードを返す
+///
+/// Whisper は「モード：議事録」を「モード、議事録。」や「モード 議事録」のように書き起こすため、
+/// 空白・句読点・記号を除いてから比較する。英字は大文字小文字を区別しない。
+/// 発話の一部にフレーズが含まれるだけでは一致とみなさない（通常の書き起こしを誤って消さないため）。
+pub fn match_mode_command<'a>(
+    commands: &'a HashMap<String, Mode>,
+    text: &str,
+) -> Option<(&'a str, Mode)> {
+    let spoken = normalize_command(text);
+    if spoken.is_empty() {
+        return None;
+    }
+    commands
+        .iter()
+        .find(|(phrase, _)| normalize_command(phrase) == spoken)
+        .map(|(phrase, &mode)| (phrase.as_str(), mode))
+}
+
+/// 比較用に空白・句読点・記号を除き、英字を小文字にする
+fn normalize_command(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::settings::default_voice_mode_commands;
+
+    #[test]
+    fn test_command_matches_despite_punctuation_and_spacing() {
+        let commands = default_voice_mode_commands();
+        for spoken in [
+            "モード：議事録",
+            "モード、議事録。",
+            " モード 議事録 ",
+            "モード:議事録",
+        ] {
+            assert_eq!(
+                match_mode_command(&commands, spoken),
+                Some(("モード：議事録", Mode::Minutes)),
+                "{spoken}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_ordinary_transcript_does_not_match() {
+        let commands = default_voice_mode_commands();
+        assert_eq!(
+            match_mode_command(&commands, "次のモード：議事録の話をします"),
+            None
+        );
+        assert_eq!(match_mode_command(&commands, "。"), None);
+    }
+}
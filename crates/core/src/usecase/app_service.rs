@@ -1,32 +1,115 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use crate::domain::error::AppError;
-use crate::domain::session::{SessionManager, SessionState, StateTransition};
-use crate::domain::settings::AppSettings;
+use crate::domain::error::{AppError, ErrorCode};
+use crate::domain::job::JobKind;
+use crate::domain::session::{
+    AppSnapshot, PipelineStatus, SavedTranscript, SessionDurationLimit, SessionManager,
+    SessionSegmentLimit, SessionState, StateTransition, TranscriptOutcome,
+};
+use crate::domain::settings::{
+    AppSettings, CaptureMode, SegmentLimitAction, SttEngineChoice, WhisperModelSize,
+};
 use crate::domain::types::{
-    DeliverPolicy, DeliverTarget, DictionaryEntry, HistoryPage, Mode, SessionDetail, SetupIssue,
-    SetupStatus,
+    AudioCacheStats, DeleteSessionResult, DeliverLastResult, DeliverPolicy, DeliverTarget,
+    DeliverText, DeliverTruncated, DiagnosticCheck, DiagnosticsReport, DictionaryEntry,
+    HistoryPage, JoinStyle, MicTestResult, Mode, ModeSwitchedByVoice, SegmentTiming, SessionDetail,
+    SetupIssue, SetupStatus, TermSuggestion, TranscribeFileResult, TranscribeProgress,
+    TranscribeSessionResult, TranscriptTiming, WhisperTask, WrapStyle,
 };
-use crate::infra::audio::pipeline::{AudioPipeline, PipelineEvent};
+use crate::infra::audio::agc::AutomaticGainControl;
+use crate::infra::audio::chunker::ChunkerConfig;
+use crate::infra::audio::frame_queue::{self, DEFAULT_FRAME_QUEUE_CAPACITY};
+use crate::infra::audio::level::LevelProfile;
+use crate::infra::audio::pipeline::{
+    split_into_segments, AudioPipeline, CaptureOptions, PipelineEvent,
+};
+use crate::infra::audio::preprocess::resample_linear;
+use crate::infra::audio::ring_buffer::{PreCapture, PrebufferedAudio};
+use crate::infra::audio::source::{AudioSource, CpalAudioSource};
 use crate::infra::audio::vad::VadConfig;
-use crate::infra::metrics::{Metrics, MetricsSummary};
+use crate::infra::audio::wav;
+use crate::infra::diagnostics;
+use crate::infra::log_context::{LogContext, TARGET_SERVICE};
+use crate::infra::metrics::{Metrics, MetricsExportFormat, MetricsSummary};
 use crate::infra::os_integration::{OsIntegration, PasteResult, PasteRouter, PermissionStatus};
-use crate::infra::output::OutputRouter;
-use crate::infra::post_processor::PostProcessor;
-use crate::infra::rewriter::{RewriteContext, Rewriter};
+use crate::infra::output::{
+    normalize_for_delivery, AccumulationBuffer, AppEventOutput, ArboardBackend, ClipboardBackend,
+    DeliverToEditorPayload, DeliveryObserver, OutputRouter, OutputTarget,
+};
+use crate::infra::post_processor::{NumberNormalizer, PostProcessor, Redactor};
+use crate::infra::rewriter::{RewriteContext, RewriteError, Rewriter};
 use crate::infra::storage::Storage;
-use crate::infra::stt::SttEngine;
+use crate::infra::stt::model_manager::{ModelInfo, WhisperModelManager};
+use crate::infra::stt::{
+    select_hints, AudioSegment, CachingSttEngine, CancelToken, EngineCapabilities, SttContext,
+    SttEngine, TranscriptFilter, UNAVAILABLE_ENGINE_NAME,
+};
+use crate::infra::term_frequency::TermFrequency;
+use crate::infra::voice_command::match_mode_command;
+use crate::usecase::job_queue::JobQueue;
+
+/// マイクテストの最大計測時間（ms）
+const MAX_MIC_TEST_MS: u64 = 10_000;
+
+/// 辞書候補の集計対象にする直近のセグメント数
+const TERM_SUGGESTION_SCAN_SEGMENTS: u32 = 2_000;
+
+/// 音声ファイルの書き起こしで VAD・STT に渡すサンプルレート
+const FILE_TRANSCRIBE_SAMPLE_RATE: u32 = 16_000;
+
+/// 終了処理で書き起こし・ジョブの完了を待つ上限
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 終了処理で完了を確認する間隔
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(20);
 
 /// アプリケーションサービス（Tauri State として管理される）
+///
+/// ロック順序: 複数のロックを同時に保持する場合は必ず
+/// `session_mgr` → `storage` → `pipeline` / `accumulator` の順に取得する。
+/// `output_router` は配信中だけ保持し、保持したまま他のロックを取らない。
+/// `precapture` / `last_partial` / `recorded_ms` / `last_toggle` も同様に単独で取得する。
+/// 逆順の取得はデッドロックの原因になるため、後ろのロックを保持したまま
+/// `current_mode()` 等の前のロックを取るヘルパーを呼ばないこと。
 pub struct AppService {
     session_mgr: Mutex<SessionManager>,
     storage: Mutex<Storage>,
-    output_router: OutputRouter,
+    output_router: Mutex<OutputRouter>,
+    /// app_event 出力先に配信されたテキスト（組み込み側が取り出して deliver_to_editor に転送する）
+    editor_events: Mutex<Option<mpsc::Receiver<DeliverToEditorPayload>>>,
+    /// 配信テキストを切り詰めた通知（組み込み側が取り出して deliver_truncated に転送する）
+    truncation_tx: mpsc::Sender<DeliverTruncated>,
+    truncation_events: Mutex<Option<mpsc::Receiver<DeliverTruncated>>>,
+    /// 配信に成功するたびに呼ぶ組み込み側のフック（既定は無し）
+    delivery_observer: Option<Arc<dyn DeliveryObserver>>,
     metrics: Metrics,
+    /// 書き起こしに使うエンジン（`transcript_cache` 経由で元のエンジンを呼ぶ）
     stt_engine: Arc<dyn SttEngine>,
+    /// `cache_transcripts` 設定で有効になる書き起こしキャッシュ
+    transcript_cache: Arc<CachingSttEngine>,
     rewriter: Arc<dyn Rewriter>,
+    /// 録音・マイクテストの音声入力（既定は cpal の入力デバイス）
+    audio_source: Arc<dyn AudioSource>,
     pipeline: Mutex<Option<AudioPipeline>>,
+    accumulator: Mutex<AccumulationBuffer>,
+    /// 待機中の事前キャプチャ（`prebuffer_enabled` のときだけ動作）
+    precapture: Mutex<Option<PreCapture>>,
+    /// 直近の部分書き起こし（スナップショット用）
+    last_partial: Mutex<Option<String>>,
+    /// セッションで録音した時間の累計（ms、停止済みのパイプラインの分。`max_session_duration_secs` 用）
+    recorded_ms: Mutex<Option<(String, u64)>>,
+    /// 最後に受け付けた録音トグルの時刻（`toggle_debounce_ms` の連打判定用）
+    last_toggle: Mutex<Option<Instant>>,
+    /// 転送側がまだ読み切っていないパイプラインイベントのチャネル数（終了処理で待つ）
+    open_event_streams: AtomicUsize,
+    /// VAD ミュート（音声再生中など、録音開始前に設定した場合も次のパイプラインに引き継ぐ）
+    vad_muted: AtomicBool,
+    job_queue: JobQueue,
 }
 
 impl AppService {
@@ -43,13 +126,64 @@ impl AppService {
             .active()
             .ok_or_else(|| AppError::internal("アクティブセッションがありません"))?;
 
-        let resolved = match session.deliver_policy {
-            DeliverPolicy::Clipboard => DeliverTarget::Clipboard,
-            DeliverPolicy::Paste => DeliverTarget::Paste,
-            DeliverPolicy::FileAppend => DeliverTarget::FileAppend,
-            DeliverPolicy::Webhook => DeliverTarget::Webhook,
+        Ok(session.deliver_policy.target())
+    }
+
+    /// 辞書の mode 列に使うモード名（serde 表現）
+    fn mode_key(mode: Mode) -> Option<String> {
+        serde_json::to_value(mode)
+            .ok()
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+    }
+
+    /// アクティブセッションのクリップボード整形設定を返す
+    fn clipboard_wrap(&self) -> Option<WrapStyle> {
+        let mgr = self.session_mgr.lock().unwrap();
+        match mgr.active()?.deliver_policy {
+            DeliverPolicy::Clipboard { wrap } => wrap,
+            _ => None,
+        }
+    }
+
+    /// STT 結果を保存用に整形する
+    ///
+    /// 繰り返し・定型句フィルタ（有効時）→ 正規化・辞書置換 → 数詞変換（有効時）
+    /// → マスキング（有効時）の順に適用する。
+    fn finalize_transcript(storage: &Storage, text: &str, entries: &[DictionaryEntry]) -> String {
+        let settings = storage.get_settings().unwrap_or_default();
+        let filtered = if settings.transcript_filter_enabled {
+            TranscriptFilter::new(
+                settings.repetition_threshold,
+                settings.hallucination_blocklist.clone(),
+            )
+            .apply(text)
+        } else {
+            text.to_string()
         };
-        Ok(resolved)
+        let mut processed = PostProcessor::process(&filtered, entries);
+        if settings.number_normalization_enabled {
+            processed = NumberNormalizer::normalize(&processed);
+        }
+        if !settings.redaction_enabled {
+            return processed;
+        }
+        match Redactor::new(&settings.redaction_rules) {
+            Ok(redactor) => redactor.redact(&processed),
+            Err(e) => {
+                log::error!(target: TARGET_SERVICE, "Redaction rules invalid: {e}");
+                processed
+            }
+        }
+    }
+
+    /// 出力ルーターを作り、アプリ内エディタ向け出力（app_event）の受信側と組にして返す
+    fn build_output_router(
+        backend: Arc<dyn ClipboardBackend>,
+    ) -> (OutputRouter, mpsc::Receiver<DeliverToEditorPayload>) {
+        let mut router = OutputRouter::with_clipboard_backend(backend);
+        let (tx, rx) = mpsc::channel();
+        router.register(DeliverTarget::AppEvent.as_str(), AppEventOutput::new(tx));
+        (router, rx)
     }
 
     pub fn new(
@@ -57,24 +191,84 @@ impl AppService {
         stt_engine: Arc<dyn SttEngine>,
         rewriter: Arc<dyn Rewriter>,
     ) -> Self {
+        let (output_router, editor_events) = Self::build_output_router(Arc::new(ArboardBackend));
+        let (truncation_tx, truncation_events) = mpsc::channel();
+        let transcript_cache = Arc::new(CachingSttEngine::new(stt_engine));
+        transcript_cache.set_enabled(
+            storage
+                .get_settings()
+                .map(|s| s.cache_transcripts)
+                .unwrap_or(false),
+        );
         Self {
             session_mgr: Mutex::new(SessionManager::new()),
             storage: Mutex::new(storage),
-            output_router: OutputRouter::new(),
+            output_router: Mutex::new(output_router),
+            editor_events: Mutex::new(Some(editor_events)),
+            truncation_tx,
+            truncation_events: Mutex::new(Some(truncation_events)),
+            delivery_observer: None,
             metrics: Metrics::new(),
-            stt_engine,
+            stt_engine: transcript_cache.clone(),
+            transcript_cache,
             rewriter,
+            audio_source: Arc::new(CpalAudioSource),
             pipeline: Mutex::new(None),
+            accumulator: Mutex::new(AccumulationBuffer::new()),
+            precapture: Mutex::new(None),
+            last_partial: Mutex::new(None),
+            recorded_ms: Mutex::new(None),
+            last_toggle: Mutex::new(None),
+            open_event_streams: AtomicUsize::new(0),
+            vad_muted: AtomicBool::new(false),
+            job_queue: JobQueue::new(),
         }
     }
 
+    /// clipboard 出力のバックエンドを差し替える（ヘッドレス環境・テスト用）
+    ///
+    /// 出力ルーターを作り直すため、`register_output_target` より前に呼ぶこと。
+    pub fn with_clipboard_backend(mut self, backend: Arc<dyn ClipboardBackend>) -> Self {
+        let (output_router, editor_events) = Self::build_output_router(backend);
+        self.output_router = Mutex::new(output_router);
+        self.editor_events = Mutex::new(Some(editor_events));
+        self
+    }
+
+    /// 音声入力を差し替える（デバイスを使わないテスト用）
+    pub fn with_audio_source(mut self, source: Arc<dyn AudioSource>) -> Self {
+        self.audio_source = source;
+        self
+    }
+
+    /// 配信に成功するたびに呼ぶフックを登録する
+    pub fn with_delivery_observer(mut self, observer: Arc<dyn DeliveryObserver>) -> Self {
+        self.delivery_observer = Some(observer);
+        self
+    }
+
+    /// app_event 出力先への配信を受け取るチャネルを取り出す（最初の 1 回だけ Some）
+    pub fn take_editor_events(&self) -> Option<mpsc::Receiver<DeliverToEditorPayload>> {
+        self.editor_events.lock().unwrap().take()
+    }
+
+    /// 配信テキストを切り詰めた通知を受け取るチャネルを取り出す（最初の 1 回だけ Some）
+    pub fn take_truncation_events(&self) -> Option<mpsc::Receiver<DeliverTruncated>> {
+        self.truncation_events.lock().unwrap().take()
+    }
+
     // ==================== Session ====================
 
+    /// セッションを開始する（出力ポリシーを省略した場合はモードの既定 `deliver_policy_by_mode` を使う）
     pub fn start_session(
         &self,
         mode: Mode,
-        deliver_policy: DeliverPolicy,
+        deliver_policy: impl Into<Option<DeliverPolicy>>,
     ) -> Result<(String, StateTransition), AppError> {
+        let deliver_policy = match deliver_policy.into() {
+            Some(policy) => policy,
+            None => self.get_settings()?.deliver_policy_for(mode),
+        };
         let session_id = uuid::Uuid::new_v4().to_string();
         let now = chrono::Utc::now().to_rfc3339();
 
@@ -84,7 +278,21 @@ impl AppService {
         let storage = self.storage.lock().unwrap();
         storage.insert_session(&session_id, mode, &now)?;
 
+        let max_history = storage.get_settings()?.max_history_sessions;
+        match storage.prune_to_limit(max_history) {
+            Ok(pruned) if pruned.sessions_deleted > 0 => log::info!(
+                target: TARGET_SERVICE,
+                "履歴上限 {max_history} を超えた {} セッションを削除しました",
+                pruned.sessions_deleted
+            ),
+            Ok(_) => {}
+            Err(e) => log::warn!(target: TARGET_SERVICE, "履歴の削除に失敗: {e}"),
+        }
+
         self.metrics.inc_sessions_started();
+        drop(storage);
+        drop(mgr);
+        self.start_precapture();
 
         let transition = StateTransition {
             session_id: session_id.clone(),
@@ -96,13 +304,26 @@ impl AppService {
     }
 
     pub fn stop_session(&self) -> Result<Option<StateTransition>, AppError> {
-        // パイプラインが動作中なら停止
-        self.stop_pipeline();
+        // パイプラインが動作中なら停止（書き起こし中のセグメントは完了を待たずに中断し、音声のみ保存する）
+        self.cancel_pipeline();
+        self.stop_precapture();
+        *self.last_partial.lock().unwrap() = None;
 
         let mut mgr = self.session_mgr.lock().unwrap();
         let session = mgr.stop_session()?;
 
         if let Some(ref s) = session {
+            // 実行中のリライト等を中断
+            let canceled = self.job_queue.cancel_session_now(&s.session_id);
+            if !canceled.is_empty() {
+                log::info!(
+                    target: TARGET_SERVICE,
+                    "{} Canceled {} running job(s)",
+                    LogContext::session(&s.session_id),
+                    canceled.len()
+                );
+            }
+
             let now = chrono::Utc::now().to_rfc3339();
             let storage = self.storage.lock().unwrap();
             storage.update_session_state(&s.session_id, "idle", &now)?;
@@ -117,22 +338,49 @@ impl AppService {
         Ok(None)
     }
 
+    /// 録音トグルを受け付けるか判定する（直前に受け付けてから `toggle_debounce_ms` 以内なら false）
+    ///
+    /// ホットキーのチャタリングやダブルクリックで、開始した録音をすぐ止めてしまうのを防ぐ。
+    /// コマンド層が状態を変える前に呼ぶ。受け付けたときだけ時刻を更新する。
+    pub fn admit_toggle(&self) -> bool {
+        let debounce_ms = self
+            .storage
+            .lock()
+            .unwrap()
+            .get_settings()
+            .map(|s| s.toggle_debounce_ms)
+            .unwrap_or_default();
+        self.admit_toggle_at(Instant::now(), debounce_ms)
+    }
+
+    fn admit_toggle_at(&self, now: Instant, debounce_ms: u32) -> bool {
+        let mut last = self.last_toggle.lock().unwrap();
+        let window = Duration::from_millis(u64::from(debounce_ms));
+        if last.is_some_and(|t| now.saturating_duration_since(t) < window) {
+            return false;
+        }
+        *last = Some(now);
+        true
+    }
+
     pub fn toggle_recording(&self) -> Result<StateTransition, AppError> {
         let now = chrono::Utc::now().to_rfc3339();
         let mut mgr = self.session_mgr.lock().unwrap();
-        let transition = mgr.toggle_recording(now.clone())?;
+        let mut next = mgr.clone();
+        let transition = next.toggle_recording(now.clone())?;
+        let mode = next.active().map(|s| s.mode).unwrap_or(Mode::Raw);
 
         let storage = self.storage.lock().unwrap();
-        storage.update_session_state(
-            &transition.session_id,
-            transition.new_state.as_str(),
-            &now,
-        )?;
+        storage.transaction(|s| {
+            s.update_session_state(&transition.session_id, transition.new_state.as_str(), &now)?;
 
-        if transition.new_state == SessionState::Transcribing {
-            let segment_id = uuid::Uuid::new_v4().to_string();
-            storage.insert_segment(&segment_id, &transition.session_id, &now)?;
-        }
+            if transition.new_state == SessionState::Transcribing {
+                let segment_id = uuid::Uuid::new_v4().to_string();
+                s.insert_segment(&segment_id, &transition.session_id, mode, &now)?;
+            }
+            Ok(())
+        })?;
+        *mgr = next;
 
         Ok(transition)
     }
@@ -152,42 +400,115 @@ impl AppService {
             transition.new_state.as_str(),
             &now,
         )?;
+        drop(storage);
+        drop(mgr);
+        self.start_precapture();
+
+        Ok(transition)
+    }
+
+    /// 録音を一時停止する: Recording → Paused
+    ///
+    /// パイプラインは止めずに受信フレームを捨てるだけなので、録音中のセグメントは確定せず、
+    /// `resume_capture` 後の音声が同じセグメントに続けて蓄積される。
+    pub fn pause_capture(&self) -> Result<StateTransition, AppError> {
+        self.set_capture_paused(true)
+    }
+
+    /// 一時停止を解除する: Paused → Recording
+    pub fn resume_capture(&self) -> Result<StateTransition, AppError> {
+        self.set_capture_paused(false)
+    }
+
+    fn set_capture_paused(&self, paused: bool) -> Result<StateTransition, AppError> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut mgr = self.session_mgr.lock().unwrap();
+        let transition = if paused {
+            mgr.pause_capture(now.clone())?
+        } else {
+            mgr.resume_capture(now.clone())?
+        };
+
+        let storage = self.storage.lock().unwrap();
+        storage.update_session_state(
+            &transition.session_id,
+            transition.new_state.as_str(),
+            &now,
+        )?;
+
+        if let Some(pipeline) = self.pipeline.lock().unwrap().as_ref() {
+            if paused {
+                pipeline.pause();
+            } else {
+                pipeline.resume();
+            }
+        }
 
         Ok(transition)
     }
 
+    /// モードを切り替える（`deliver_policy_by_mode` に設定があれば出力ポリシーも切り替える）
+    /// モードを切り替える（録音中なら次のセグメントから新しいモードで書き起こす）
     pub fn set_mode(&self, mode: Mode) -> Result<(), AppError> {
+        {
+            let mut mgr = self.session_mgr.lock().unwrap();
+            mgr.set_mode(mode)?;
+            let storage = self.storage.lock().unwrap();
+            if let Some(policy) = storage.get_settings()?.deliver_policy_by_mode.remove(&mode) {
+                mgr.set_deliver_policy(policy)?;
+            }
+        }
+        if let Some(pipeline) = self.pipeline.lock().unwrap().as_ref() {
+            pipeline.update_mode(Some(mode));
+        }
+        Ok(())
+    }
+
+    pub fn set_task(&self, task: WhisperTask) -> Result<(), AppError> {
         let mut mgr = self.session_mgr.lock().unwrap();
-        mgr.set_mode(mode)
+        mgr.set_task(task)
     }
 
     // ==================== Audio Pipeline ====================
 
     /// パイプラインを開始し、イベント受信チャネルを返す
     pub fn start_pipeline(&self) -> Result<mpsc::Receiver<PipelineEvent>, AppError> {
+        self.start_pipeline_inner(false)
+    }
+
+    /// 音声のみ録音のパイプラインを開始する（STT は行わず、セグメントを WAV として保存する）
+    pub fn start_audio_only_pipeline(&self) -> Result<mpsc::Receiver<PipelineEvent>, AppError> {
+        self.start_pipeline_inner(true)
+    }
+
+    fn start_pipeline_inner(
+        &self,
+        audio_only: bool,
+    ) -> Result<mpsc::Receiver<PipelineEvent>, AppError> {
+        // 録音開始前の直近音声（事前キャプチャが動いていれば停止して取り出す）
+        let prebuffer = self.take_prebuffer();
+        // session_mgr → storage の順序を守るため、セッション情報は先に取得する
+        let session_id = self.current_session_id();
+        let mode = self.current_mode();
+        let task = self.current_task();
+
         let storage = self.storage.lock().unwrap();
         let settings = storage.get_settings().unwrap_or_default();
 
-        // エンジンに応じた VAD 設定
-        let mut vad_config = VadConfig::for_engine(settings.stt_engine);
-        // ユーザーオーバーライド
-        if let Some(max_ms) = settings.vad_max_segment_ms {
-            vad_config.max_segment_ms = max_ms;
-        }
+        let vad_config = Self::resolve_vad_config(&settings);
 
-        let language = settings.language.clone();
+        let chunker = match settings.capture_mode {
+            CaptureMode::Vad => None,
+            CaptureMode::Continuous => Some(ChunkerConfig {
+                chunk_target_ms: settings.chunk_target_ms,
+                boundary_search_ms: settings.boundary_search_ms,
+            }),
+        };
 
         // 辞書ヒントを取得
-        let mode_str = {
-            let mgr = self.session_mgr.lock().unwrap();
-            mgr.active().and_then(|s| {
-                serde_json::to_value(s.mode)
-                    .ok()
-                    .and_then(|v| v.as_str().map(|s| s.to_string()))
-            })
-        };
+        let mode_str = mode.and_then(Self::mode_key);
         let dictionary_hints: Vec<String> = storage
-            .get_enabled_dictionary_entries("global", mode_str.as_deref())
+            .get_enabled_dictionary_entries("mode", mode_str.as_deref())
             .unwrap_or_default()
             .into_iter()
             .map(|e| e.replacement.clone())
@@ -195,612 +516,4171 @@ impl AppService {
 
         drop(storage);
 
+        let stt_ctx = SttContext {
+            language: settings.language.clone(),
+            dictionary: dictionary_hints,
+            mode,
+            prompt_prefix: settings.prompt_prefix_for(mode),
+            max_prompt_chars: settings.max_prompt_chars,
+            task,
+            log_ctx: session_id.map(LogContext::session).unwrap_or_default(),
+            cancel: CancelToken::new(),
+        };
+        log::info!(
+            target: TARGET_SERVICE,
+            "{} Starting pipeline: engine={}, capture_mode={:?}, audio_only={audio_only}",
+            stt_ctx.log_ctx,
+            self.stt_engine.name(),
+            settings.capture_mode
+        );
+
         let (event_tx, event_rx) = mpsc::channel();
         let pipeline = AudioPipeline::start(
-            self.stt_engine.clone(),
+            self.audio_source.clone(),
+            (!audio_only).then(|| self.stt_engine.clone()),
             event_tx,
             vad_config,
-            language,
-            dictionary_hints,
+            CaptureOptions {
+                chunker,
+                prebuffer,
+                session_idle_timeout_secs: settings.session_idle_timeout_secs,
+                channel_mode: settings.input_channel,
+                min_native_sample_rate: settings.min_native_sample_rate,
+                stall_timeout_secs: settings.capture_stall_timeout_secs,
+                device_init_timeout_secs: settings.device_init_timeout_secs,
+                max_duration_ms: self.remaining_recording_ms(
+                    session_id.as_deref(),
+                    settings.max_session_duration_secs,
+                ),
+                commit_on_sentence_end: settings.commit_on_sentence_end,
+                smart_hints: settings.smart_hints_limit(),
+                captions: settings.captions_enabled,
+                partials: settings.store_partials,
+            },
+            stt_ctx,
         )
-        .map_err(|e| AppError::device(e.to_string()))?;
+        .map_err(AppError::from)?;
+        pipeline.set_vad_muted(self.vad_muted.load(Ordering::SeqCst));
 
         *self.pipeline.lock().unwrap() = Some(pipeline);
+        *self.last_partial.lock().unwrap() = None;
+        self.open_event_streams.fetch_add(1, Ordering::SeqCst);
         Ok(event_rx)
     }
 
-    /// パイプラインを停止する
-    pub fn stop_pipeline(&self) {
-        if let Some(mut pipeline) = self.pipeline.lock().unwrap().take() {
-            pipeline.stop();
+    /// パイプラインのエラーをセッション状態に反映し、行った遷移を順に返す（転送側がエラーイベントを送った後に呼ぶ）
+    ///
+    /// キャプチャの開始失敗などでパイプラインが止まった場合だけ Error 状態にする。
+    /// 書き起こし 1 件の失敗など録音が続いているエラーでは状態を変えず、録音もそのまま続く。
+    /// `auto_recover` が有効で回復可能なエラーなら、続けて Idle に戻す。
+    pub fn on_pipeline_error(&self, error: &AppError) -> Result<Vec<StateTransition>, AppError> {
+        let stopped = self
+            .pipeline
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|pipeline| !pipeline.is_running());
+        if !stopped {
+            return Ok(Vec::new());
         }
-    }
-
-    /// パイプラインからの書き起こし結果を処理する
-    /// セグメントをDBに保存し、ポストプロセス済みテキストとsegment_idを返す
-    pub fn on_pipeline_transcript(
-        &self,
-        text: &str,
-        confidence: f32,
-    ) -> Result<(String, String), AppError> {
-        let session_id = self
-            .current_session_id()
-            .ok_or_else(|| AppError::internal("アクティブセッションがありません"))?;
+        // 終了済みのパイプラインを片付ける（取りこぼしフレーム数もここで集計される）
+        self.stop_pipeline();
+        let auto_recover = self.get_settings()?.auto_recover;
 
-        let segment_id = uuid::Uuid::new_v4().to_string();
         let now = chrono::Utc::now().to_rfc3339();
+        let mut mgr = self.session_mgr.lock().unwrap();
+        let Some(errored) = mgr.on_error(
+            error.code.to_string(),
+            error.message.clone(),
+            error.recoverable,
+            now.clone(),
+        ) else {
+            return Ok(Vec::new());
+        };
+        let mut transitions = vec![errored];
+        if error.recoverable && auto_recover {
+            transitions.push(mgr.recover_from_error(now.clone())?);
+        }
+        let last = transitions.last().expect("at least one transition");
 
         let storage = self.storage.lock().unwrap();
-        storage.insert_segment(&segment_id, &session_id, &now)?;
+        storage.update_session_state(&last.session_id, last.new_state.as_str(), &now)?;
+        drop(storage);
+        log::warn!(
+            target: TARGET_SERVICE,
+            "{} Pipeline stopped by {}: state={}",
+            LogContext::session(&last.session_id),
+            error.code,
+            last.new_state.as_str()
+        );
+        let recovered = last.new_state == SessionState::Idle;
+        drop(mgr);
+        if recovered {
+            self.start_precapture();
+        }
 
-        // ポストプロセス（正規化 + 辞書置換）
-        let mode_str = {
-            let mgr = self.session_mgr.lock().unwrap();
-            mgr.active().and_then(|s| {
-                serde_json::to_value(s.mode)
-                    .ok()
-                    .and_then(|v| v.as_str().map(|s| s.to_string()))
-            })
+        Ok(transitions)
+    }
+
+    /// パイプラインイベントを最後まで読み終えたことを知らせる（転送スレッドの終わりで呼ぶ）
+    ///
+    /// 終了処理は、停止時に書き起こした最後のセグメントが保存されるまでこれを待つ。
+    pub fn on_pipeline_events_drained(&self) {
+        let _ = self
+            .open_event_streams
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1));
+    }
+
+    /// 設定から VAD 設定を決定する（未設定ならエンジン別デフォルト + 最大長オーバーライド）
+    fn resolve_vad_config(settings: &AppSettings) -> VadConfig {
+        let mut vad_config = settings
+            .vad
+            .clone()
+            .unwrap_or_else(|| VadConfig::for_engine(settings.stt_engine));
+        // ユーザーオーバーライド
+        if let Some(max_ms) = settings.vad_max_segment_ms {
+            vad_config.max_segment_ms = max_ms;
+        }
+        if let Some(dir) = &settings.audio_dir {
+            vad_config.output_dir = dir.clone();
+        }
+        vad_config
+    }
+
+    /// VAD をミュートする（スピーカーで音声を再生する区間の前後で呼ぶ）
+    ///
+    /// ミュート中に受け取ったフレームは捨て、VAD の状態や蓄積中のセグメントは変えない。
+    /// 録音していない間の設定も保持し、次に開始したパイプラインに適用する。
+    pub fn set_vad_muted(&self, muted: bool) {
+        self.vad_muted.store(muted, Ordering::SeqCst);
+        if let Some(pipeline) = self.pipeline.lock().unwrap().as_ref() {
+            pipeline.set_vad_muted(muted);
+        }
+        log::info!(target: TARGET_SERVICE, "VAD muted: {muted}");
+    }
+
+    /// VAD 設定を保存し、録音中のパイプラインにも即時反映する
+    pub fn update_vad_config(&self, config: VadConfig) -> Result<(), AppError> {
+        let settings = AppSettings {
+            vad: Some(config),
+            ..self.get_settings()?
         };
-        let dict_entries = storage
-            .get_enabled_dictionary_entries("global", mode_str.as_deref())
-            .unwrap_or_default();
+        self.update_settings(settings)
+    }
+
+    /// マイクを duration_ms だけ開いて入力レベルを測る（セッション・DB には触れない）
+    pub fn test_microphone(&self, duration_ms: u64) -> Result<MicTestResult, AppError> {
+        let duration = Duration::from_millis(duration_ms.clamp(100, MAX_MIC_TEST_MS));
+        let (sample_tx, sample_rx) = frame_queue::frame_channel(DEFAULT_FRAME_QUEUE_CAPACITY);
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let channel_mode = self.get_settings()?.input_channel;
+        let config = self
+            .audio_source
+            .start(sample_tx, stop_flag.clone(), None, channel_mode)
+            .map_err(AppError::from)?;
+
+        let mut profile = LevelProfile::new();
+        let deadline = Instant::now() + duration;
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            match sample_rx.recv_timeout(remaining) {
+                Ok(frame) => profile.push(&frame),
+                Err(_) => break,
+            }
+        }
+        stop_flag.store(true, Ordering::Relaxed);
 
-        let processed_text = PostProcessor::process(text, &dict_entries);
-        storage.update_segment_text(&segment_id, &processed_text, confidence)?;
+        Ok(profile.finish(config.device_name))
+    }
 
-        self.metrics.inc_segments_transcribed();
+    /// 設定が有効なら待機中の事前キャプチャを開始する
+    ///
+    /// 失敗しても録音自体には影響しないため、ログに残すだけにする。
+    fn start_precapture(&self) {
+        let settings = self.get_settings().unwrap_or_default();
+        let mut precapture = self.precapture.lock().unwrap();
+        if !settings.prebuffer_enabled {
+            *precapture = None;
+            return;
+        }
+        if precapture.is_some() {
+            return;
+        }
+        let vad_config = Self::resolve_vad_config(&settings);
+        let agc = vad_config
+            .agc
+            .then(|| AutomaticGainControl::new(vad_config.agc_target_rms, vad_config.agc_max_gain));
+        match PreCapture::start(settings.prebuffer_secs, agc, settings.input_channel) {
+            Ok(started) => *precapture = Some(started),
+            Err(e) => log::warn!(target: TARGET_SERVICE, "事前キャプチャを開始できません: {e}"),
+        }
+    }
 
-        Ok((processed_text, segment_id))
+    /// 事前キャプチャを停止し、取り溜めた音声を破棄する
+    fn stop_precapture(&self) {
+        self.precapture.lock().unwrap().take();
     }
 
-    /// テキストをリライトする（Claude API 経由）
-    pub async fn rewrite_text(&self, text: &str, mode: Mode) -> Result<String, AppError> {
-        if mode == Mode::Raw {
-            return Ok(text.to_string());
+    /// 事前キャプチャを停止し、取り溜めた音声を返す
+    fn take_prebuffer(&self) -> Option<PrebufferedAudio> {
+        let precapture = self.precapture.lock().unwrap().take()?;
+        Some(precapture.finish())
+    }
+
+    /// パイプラインを停止する
+    pub fn stop_pipeline(&self) {
+        let session_id = self.current_session_id();
+        if let Some(mut pipeline) = self.pipeline.lock().unwrap().take() {
+            pipeline.stop();
+            self.collect_pipeline_stats(session_id, &pipeline);
         }
+    }
 
-        // 辞書ヒントを取得
-        let dictionary_hints = {
-            let storage = self.storage.lock().unwrap();
-            let mode_str = serde_json::to_value(mode)
-                .ok()
-                .and_then(|v| v.as_str().map(|s| s.to_string()));
-            storage
-                .get_enabled_dictionary_entries("global", mode_str.as_deref())
-                .unwrap_or_default()
-                .into_iter()
-                .map(|e| format!("{} → {}", e.pattern, e.replacement))
-                .collect::<Vec<_>>()
+    /// 実行中の書き起こしを中断してパイプラインを停止する
+    ///
+    /// 中断したセグメントと未処理の最終セグメントは捨てずに音声のみのセグメントとして登録し、
+    /// `transcribe_session` で後から書き起こせるようにする（セッションがアクティブなうちに呼ぶこと）。
+    pub fn cancel_pipeline(&self) {
+        let session_id = self.current_session_id();
+        let Some(mut pipeline) = self.pipeline.lock().unwrap().take() else {
+            return;
         };
+        let saved = pipeline.cancel();
+        self.collect_pipeline_stats(session_id, &pipeline);
+        for segment in saved {
+            if let Err(e) = self.on_pipeline_audio_segment(&segment.segment_id, &segment.audio_path)
+            {
+                log::warn!(
+                    target: TARGET_SERVICE,
+                    "中断したセグメントを登録できません: {}: {e}",
+                    segment.audio_path
+                );
+            }
+        }
+    }
 
-        let ctx = RewriteContext {
-            mode,
-            dictionary_hints,
+    /// 停止したパイプラインの取りこぼしフレーム数と、セッションの録音時間を集計する
+    fn collect_pipeline_stats(&self, session_id: Option<String>, pipeline: &AudioPipeline) {
+        self.metrics.add_frames_dropped(pipeline.frames_dropped());
+        let Some(session_id) = session_id else {
+            return;
         };
+        let mut recorded = self.recorded_ms.lock().unwrap();
+        match recorded.as_mut() {
+            Some((id, ms)) if *id == session_id => *ms += pipeline.recorded_ms(),
+            _ => *recorded = Some((session_id, pipeline.recorded_ms())),
+        }
+    }
 
-        self.rewriter
-            .rewrite(text, ctx)
-            .await
-            .map_err(|e| AppError::internal(format!("リライトエラー: {e}")))
+    /// セッションで録音済みの時間（ms、停止済みのパイプラインの分）
+    fn session_recorded_ms(&self, session_id: &str) -> u64 {
+        match self.recorded_ms.lock().unwrap().as_ref() {
+            Some((id, ms)) if id == session_id => *ms,
+            _ => 0,
+        }
     }
 
-    // ==================== Pipeline (legacy) ====================
+    /// 次のパイプラインで録音できる残り時間（ms、0=無制限）
+    ///
+    /// 上限を使い切っていても 0（無制限）にならないよう最低 1ms を返し、開始直後に止める。
+    fn remaining_recording_ms(&self, session_id: Option<&str>, limit_secs: u32) -> u64 {
+        if limit_secs == 0 {
+            return 0;
+        }
+        let recorded = session_id.map_or(0, |id| self.session_recorded_ms(id));
+        (u64::from(limit_secs) * 1000)
+            .saturating_sub(recorded)
+            .max(1)
+    }
 
-    pub fn on_transcript_done(
+    /// 録音時間が `max_session_duration_secs` に達したら録音を止める（転送側が SessionDurationLimit を受けて呼ぶ）
+    ///
+    /// 録音中のセグメントは停止時に確定する。既に録音を止めていれば（手動停止・無音による自動停止）何もせず None を返す。
+    pub fn on_session_duration_limit(
         &self,
-        segment_id: &str,
-        text: &str,
-        confidence: f32,
-    ) -> Result<(StateTransition, String), AppError> {
-        let start = std::time::Instant::now();
-        let now = chrono::Utc::now().to_rfc3339();
-
-        let storage = self.storage.lock().unwrap();
-        let mode_str = {
+    ) -> Result<Option<(SessionDurationLimit, StateTransition)>, AppError> {
+        let session_id = {
             let mgr = self.session_mgr.lock().unwrap();
-            mgr.active().and_then(|s| {
-                serde_json::to_value(s.mode)
-                    .ok()
-                    .and_then(|v| v.as_str().map(|s| s.to_string()))
-            })
+            match mgr.active() {
+                Some(s) if s.state == SessionState::Recording => s.session_id.clone(),
+                _ => return Ok(None),
+            }
         };
-        let dict_entries = storage
-            .get_enabled_dictionary_entries("global", mode_str.as_deref())
-            .unwrap_or_default();
-
-        let processed_text = PostProcessor::process(text, &dict_entries);
-        storage.update_segment_text(segment_id, &processed_text, confidence)?;
-
-        let mut mgr = self.session_mgr.lock().unwrap();
-        let transition = mgr.on_transcript_done(now.clone())?;
-        storage.update_session_state(
-            &transition.session_id,
-            transition.new_state.as_str(),
-            &now,
-        )?;
-
-        self.metrics.inc_segments_transcribed();
-        self.metrics
-            .record_latency("transcribe", start.elapsed().as_millis() as u64);
+        let transition = self.pause_recording()?;
+        let limit_secs = self.get_settings()?.max_session_duration_secs;
+        let recorded_secs = self.session_recorded_ms(&session_id) / 1000;
+        log::info!(
+            target: TARGET_SERVICE,
+            "{} Recording duration limit {limit_secs}s reached: recorded={recorded_secs}s",
+            LogContext::session(&session_id)
+        );
 
-        Ok((transition, processed_text))
+        Ok(Some((
+            SessionDurationLimit {
+                session_id,
+                recorded_secs,
+                limit_secs,
+            },
+            transition,
+        )))
     }
 
-    pub fn on_rewrite_done(
+    /// 音声のみ録音で保存されたセグメントを登録する（raw_text は空のまま）
+    ///
+    /// 書き起こしは後から保存済み音声に対して行う。
+    pub fn on_pipeline_audio_segment(
         &self,
         segment_id: &str,
-        rewritten_text: &str,
-    ) -> Result<StateTransition, AppError> {
-        let start = std::time::Instant::now();
+        audio_path: &str,
+    ) -> Result<(), AppError> {
+        let (session_id, mode) = {
+            let mgr = self.session_mgr.lock().unwrap();
+            let session = mgr
+                .active()
+                .ok_or_else(|| AppError::internal("アクティブセッションがありません"))?;
+            (session.session_id.clone(), session.mode)
+        };
         let now = chrono::Utc::now().to_rfc3339();
 
         let storage = self.storage.lock().unwrap();
-        storage.update_segment_rewritten(segment_id, rewritten_text)?;
+        storage.transaction(|s| {
+            s.insert_segment(segment_id, &session_id, mode, &now)?;
+            s.set_segment_audio_path(segment_id, audio_path)
+        })?;
+        log::info!(
+            target: TARGET_SERVICE,
+            "{} Audio-only segment saved: {audio_path}",
+            LogContext::session(&session_id).with_segment(segment_id)
+        );
+        Ok(())
+    }
 
-        let mut mgr = self.session_mgr.lock().unwrap();
-        let transition = mgr.on_rewrite_done(now.clone())?;
-        storage.update_session_state(
-            &transition.session_id,
-            transition.new_state.as_str(),
-            &now,
-        )?;
+    /// パイプラインからの部分書き起こしを記録する（スナップショットで返す）
+    pub fn on_pipeline_partial(&self, text: &str) {
+        *self.last_partial.lock().unwrap() = Some(text.to_string());
+    }
 
-        self.metrics.inc_segments_rewritten();
-        self.metrics
-            .record_latency("rewrite", start.elapsed().as_millis() as u64);
+    /// 確定テキストが音声コマンドならモードを切り替え、その内容を返す（セグメントの保存前に呼ぶ）
+    ///
+    /// 一致したセグメントは保存・配信しない（[`AppService::handle_pipeline_transcript`] は Some なら保存せずに返す）。
+    /// `voice_commands_enabled` が無効なら常に None を返す。
+    pub fn apply_voice_command(&self, text: &str) -> Result<Option<ModeSwitchedByVoice>, AppError> {
+        let settings = self.get_settings()?;
+        if !settings.voice_commands_enabled {
+            return Ok(None);
+        }
+        let Some((phrase, mode)) = match_mode_command(&settings.voice_mode_commands, text) else {
+            return Ok(None);
+        };
+        self.set_mode(mode)?;
+        *self.last_partial.lock().unwrap() = None;
 
-        Ok(transition)
+        let session_id = self.current_session_id().unwrap_or_default();
+        log::info!(
+            target: TARGET_SERVICE,
+            "{} Mode switched by voice command: {mode}",
+            LogContext::session(&session_id)
+        );
+        Ok(Some(ModeSwitchedByVoice {
+            session_id,
+            mode,
+            phrase: phrase.to_string(),
+        }))
     }
 
-    pub fn deliver(&self, text: &str) -> Result<StateTransition, AppError> {
+    /// パイプラインで確定した書き起こし 1 件を処理する（UI・ヘッドレスの転送側が共通で呼ぶ）
+    ///
+    /// 音声コマンドならモードを切り替えるだけで、セグメントは保存しない。
+    /// それ以外はセグメントとして保存し、セグメント数の上限を確認したうえで、確定字幕・レビュー待ちの要否と合わせて返す。
+    /// 自動リライトは、レビュー待ちでなければ転送側が [`AppService::auto_rewrite_segment`] で非同期に行う。
+    pub fn handle_pipeline_transcript(
+        &self,
+        segment_id: &str,
+        text: &str,
+        confidence: f32,
+        timings: &[TranscriptTiming],
+        mode: Option<Mode>,
+    ) -> Result<TranscriptOutcome, AppError> {
+        match self.apply_voice_command(text) {
+            Ok(Some(switched)) => return Ok(TranscriptOutcome::ModeSwitched(switched)),
+            Ok(None) => {}
+            Err(e) => log::error!(target: TARGET_SERVICE, "Voice command handling failed: {e}"),
+        }
+        let settings = self.get_settings()?;
+        let processed = self.on_pipeline_transcript(segment_id, text, confidence, timings, mode)?;
+        let segment_limit = self.enforce_segment_limit().unwrap_or_else(|e| {
+            log::error!(target: TARGET_SERVICE, "Segment limit handling failed: {e}");
+            None
+        });
+
+        Ok(TranscriptOutcome::Saved(SavedTranscript {
+            segment_id: segment_id.to_string(),
+            text: processed,
+            confidence,
+            caption: settings.captions_enabled,
+            for_review: settings.review_before_deliver,
+            segment_limit,
+        }))
+    }
+
+    /// パイプラインからの書き起こし結果を処理する
+    /// パイプラインが採番した segment_id でセグメントをDBに保存し、ポストプロセス済みテキストを返す
+    ///
+    /// `mode` はセグメントを録音したときのモード（`PipelineEvent::TranscriptFinal` の `mode`）。
+    /// None ならセッションの現在のモードで保存する。
+    pub fn on_pipeline_transcript(
+        &self,
+        segment_id: &str,
+        text: &str,
+        confidence: f32,
+        timings: &[TranscriptTiming],
+        mode: Option<Mode>,
+    ) -> Result<String, AppError> {
+        let (session_id, mode, task) = {
+            let mgr = self.session_mgr.lock().unwrap();
+            let session = mgr
+                .active()
+                .ok_or_else(|| AppError::internal("アクティブセッションがありません"))?;
+            (
+                session.session_id.clone(),
+                mode.unwrap_or(session.mode),
+                session.task,
+            )
+        };
+        let mode_str = Self::mode_key(mode);
+        let log_ctx = LogContext::session(&session_id).with_segment(segment_id);
+
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let storage = self.storage.lock().unwrap();
+
+        // ポストプロセス（正規化 + 辞書置換）
+        let dict_entries = storage
+            .get_enabled_dictionary_entries("mode", mode_str.as_deref())
+            .unwrap_or_default();
+
+        let processed_text = Self::finalize_transcript(&storage, text, &dict_entries);
+        // 認識の揺れを調べる用に、確定前に最後に届いた部分書き起こしも残す
+        let last_partial = if storage.get_settings()?.store_partials {
+            self.last_partial.lock().unwrap().clone()
+        } else {
+            None
+        };
+        storage.transaction(|s| {
+            s.insert_segment(segment_id, &session_id, mode, &now)?;
+            s.update_segment_text(segment_id, &processed_text, confidence)?;
+            s.set_segment_original_text(segment_id, text)?;
+            s.set_segment_task(segment_id, task)?;
+            if let Some(partial) = &last_partial {
+                s.set_segment_last_partial(segment_id, partial)?;
+            }
+            s.set_segment_timings(segment_id, timings)
+        })?;
+        log::info!(
+            target: TARGET_SERVICE,
+            "{log_ctx} Segment saved: confidence={confidence:.2}, {} chars",
+            processed_text.chars().count()
+        );
+
+        self.metrics.inc_segments_transcribed();
+        *self.last_partial.lock().unwrap() = None;
+
+        Ok(processed_text)
+    }
+
+    /// セッションのセグメント数が `max_segments_per_session` に達していれば設定どおりに処理する（セグメントの保存後に呼ぶ）
+    ///
+    /// `stop` ではセッションを停止し、`new_session` では新しいセッションへ切り替えて録音を続ける。
+    /// 上限未満・無制限（0）・アクティブセッションが無い場合は None を返す。
+    pub fn enforce_segment_limit(
+        &self,
+    ) -> Result<Option<(SessionSegmentLimit, Vec<StateTransition>)>, AppError> {
+        let settings = self.get_settings()?;
+        let limit = settings.max_segments_per_session;
+        if limit == 0 {
+            return Ok(None);
+        }
+        let Some(session_id) = self.current_session_id() else {
+            return Ok(None);
+        };
+        let segment_count = self
+            .storage
+            .lock()
+            .unwrap()
+            .count_session_segments(&session_id)?;
+        if segment_count < limit {
+            return Ok(None);
+        }
+
+        let action = settings.segment_limit_action;
+        let (new_session_id, transitions) = match action {
+            SegmentLimitAction::Stop => (None, self.stop_session()?.into_iter().collect()),
+            SegmentLimitAction::NewSession => {
+                let (new_session_id, transitions) = self.rollover_session()?;
+                (Some(new_session_id), transitions)
+            }
+        };
+        log::info!(
+            target: TARGET_SERVICE,
+            "{} Segment limit {limit} reached: action={action:?}",
+            LogContext::session(&session_id)
+        );
+
+        Ok(Some((
+            SessionSegmentLimit {
+                session_id,
+                segment_count,
+                limit,
+                action,
+                new_session_id,
+            },
+            transitions,
+        )))
+    }
+
+    /// アクティブセッションを終え、録音を続けたまま同じ設定の新しいセッションへ切り替える
+    ///
+    /// 新しいセッションの ID と、終えたセッション・新しいセッションの遷移を返す。
+    fn rollover_session(&self) -> Result<(String, Vec<StateTransition>), AppError> {
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let mut mgr = self.session_mgr.lock().unwrap();
+        let ended = mgr.rollover_session(session_id.clone(), now.clone())?;
+        let storage = self.storage.lock().unwrap();
+        storage.transaction(|s| {
+            s.update_session_state(&ended.session_id, "idle", &now)?;
+            s.insert_session(&session_id, ended.mode, &now)?;
+            s.update_session_state(&session_id, ended.state.as_str(), &now)
+        })?;
+        self.metrics.inc_sessions_started();
+
+        let transitions = vec![
+            StateTransition {
+                session_id: ended.session_id,
+                prev_state: ended.state.as_str().to_string(),
+                new_state: SessionState::Idle,
+            },
+            StateTransition {
+                session_id: session_id.clone(),
+                prev_state: "none".to_string(),
+                new_state: ended.state,
+            },
+        ];
+        Ok((session_id, transitions))
+    }
+
+    /// テキストをリライトする（Claude API 経由）
+    pub async fn rewrite_text(&self, text: &str, mode: Mode) -> Result<String, AppError> {
+        if mode == Mode::Raw {
+            return Ok(text.to_string());
+        }
+
+        // 辞書ヒントを取得
+        let dictionary_hints = {
+            let storage = self.storage.lock().unwrap();
+            let mode_str = Self::mode_key(mode);
+            storage
+                .get_enabled_dictionary_entries("mode", mode_str.as_deref())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|e| format!("{} → {}", e.pattern, e.replacement))
+                .collect::<Vec<_>>()
+        };
+
+        let ctx = RewriteContext {
+            mode,
+            dictionary_hints,
+        };
+        let timeout = std::time::Duration::from_secs(self.get_settings()?.rewrite_timeout_secs);
+
+        // セッション停止でキャンセルできるようジョブとして登録する
+        self.job_queue.cleanup_completed().await;
+        let session_id = self.current_session_id().unwrap_or_default();
+        let (job_id, cancel_rx) = self
+            .job_queue
+            .enqueue(session_id, None, JobKind::Rewrite)
+            .await;
+        self.job_queue.mark_running(&job_id).await;
+
+        let result = tokio::select! {
+            result = tokio::time::timeout(timeout, self.rewriter.rewrite(text, ctx)) => {
+                result.unwrap_or(Err(RewriteError::Timeout))
+            }
+            _ = cancel_rx => {
+                return Err(AppError::canceled("リライトはキャンセルされました"));
+            }
+        };
+
+        match result {
+            Ok(rewritten) => {
+                self.job_queue.mark_done(&job_id).await;
+                Ok(rewritten)
+            }
+            Err(e) => {
+                self.job_queue.mark_failed(&job_id, e.to_string()).await;
+                Err(match e {
+                    RewriteError::Timeout => AppError::timeout(format!(
+                        "リライトが {} 秒以内に完了しませんでした",
+                        timeout.as_secs()
+                    )),
+                    e => AppError::internal(format!("リライトエラー: {e}")),
+                })
+            }
+        }
+    }
+
+    /// パイプラインで確定したセグメントを、設定に応じて自動リライトする
+    ///
+    /// セグメント録音時のモードで自動リライトが無効（Raw は常に無効）なら `None` を返す。
+    /// 録音は継続中のため状態遷移は行わず、リライト結果の保存のみ行う。
+    pub async fn auto_rewrite_segment(
+        &self,
+        segment_id: &str,
+        text: &str,
+    ) -> Result<Option<(Mode, String)>, AppError> {
+        let segment_mode = self.storage.lock().unwrap().get_segment_mode(segment_id)?;
+        let Some(mode) = segment_mode.or_else(|| self.current_mode()) else {
+            return Ok(None);
+        };
+        let settings = self.get_settings()?;
+        if !settings.should_auto_rewrite(mode) {
+            return Ok(None);
+        }
+
+        let start = std::time::Instant::now();
+        let rewritten = self.rewrite_text(text, mode).await?;
+        self.storage
+            .lock()
+            .unwrap()
+            .update_segment_rewritten(segment_id, &rewritten)?;
+
+        self.metrics.inc_segments_rewritten();
+        self.metrics
+            .record_latency("rewrite", start.elapsed().as_millis() as u64);
+
+        Ok(Some((mode, rewritten)))
+    }
+
+    /// 保存済みの音声から、指定エンジン（別モデル等）でセグメントを書き起こし直す
+    ///
+    /// `raw_text` / `confidence` を更新し、後処理済みの新しいテキストを返す。
+    /// 音声ファイルが保存されていない・削除済みの場合は `E_AUDIO_FORMAT`。
+    pub async fn retranscribe_segment(
+        &self,
+        session_id: &str,
+        segment_id: &str,
+        engine: &dyn SttEngine,
+    ) -> Result<String, AppError> {
+        self.retranscribe_segment_with_cancel(session_id, segment_id, engine, CancelToken::new())
+            .await
+    }
+
+    /// `retranscribe_segment` の中断可能版（`cancel` が立つとエンジンは `E_CANCELED` で中断する）
+    async fn retranscribe_segment_with_cancel(
+        &self,
+        session_id: &str,
+        segment_id: &str,
+        engine: &dyn SttEngine,
+        cancel: CancelToken,
+    ) -> Result<String, AppError> {
+        let log_ctx = LogContext::session(session_id).with_segment(segment_id);
+        let (audio_path, mode, task, settings) = {
+            let storage = self.storage.lock().unwrap();
+            let detail = storage
+                .get_session_detail(session_id)?
+                .ok_or_else(|| AppError::invalid_state("セッションが見つかりません"))?;
+            let segment = detail
+                .segments
+                .iter()
+                .find(|s| s.segment_id == segment_id)
+                .ok_or_else(|| AppError::invalid_state("セグメントが見つかりません"))?;
+            let audio_path = storage.get_segment_audio_path(segment_id)?;
+            (
+                audio_path,
+                segment.mode,
+                segment.task,
+                storage.get_settings()?,
+            )
+        };
+
+        let audio_path = audio_path
+            .map(std::path::PathBuf::from)
+            .filter(|p| p.exists())
+            .ok_or_else(|| {
+                AppError::audio_format(
+                    "セグメントの音声ファイルがありません（未保存または削除済み）",
+                )
+            })?;
+        let audio = wav::read_wav(&audio_path)?;
+
+        let mode_str = mode.to_string();
+        let dict_entries = self
+            .storage
+            .lock()
+            .unwrap()
+            .get_enabled_dictionary_entries("mode", Some(&mode_str))
+            .unwrap_or_default();
+        let ctx = SttContext {
+            prompt_prefix: settings.prompt_prefix_for(Some(mode)),
+            max_prompt_chars: settings.max_prompt_chars,
+            language: settings.language,
+            dictionary: dict_entries.iter().map(|e| e.replacement.clone()).collect(),
+            mode: Some(mode),
+            task,
+            log_ctx: log_ctx.clone(),
+            cancel,
+        };
+
+        log::info!(
+            target: TARGET_SERVICE,
+            "{log_ctx} Re-transcribing segment with engine={}",
+            engine.name()
+        );
+        let result = engine.transcribe(audio, ctx).await?;
+
+        let storage = self.storage.lock().unwrap();
+        let processed_text = Self::finalize_transcript(&storage, &result.text, &dict_entries);
+        storage.transaction(|s| {
+            s.update_segment_text(segment_id, &processed_text, result.confidence)?;
+            s.set_segment_original_text(segment_id, &result.text)?;
+            s.set_segment_timings(segment_id, &result.timings)
+        })?;
+
+        Ok(processed_text)
+    }
+
+    /// セッション内の未書き起こしセグメント（音声あり・raw_text 空）を設定中のエンジンで一括書き起こしする
+    ///
+    /// セグメントごとに Transcribe ジョブを登録し、`stop_session` 等でキャンセルされたら中断する（`E_CANCELED`）。
+    /// 音声ファイルが無いセグメントは失敗として数え、残りの処理を続ける。
+    /// `on_progress` は各セグメントの処理後に呼ばれる。
+    pub async fn transcribe_session<F>(
+        &self,
+        session_id: &str,
+        mut on_progress: F,
+    ) -> Result<TranscribeSessionResult, AppError>
+    where
+        F: FnMut(TranscribeProgress),
+    {
+        let (pending, skipped) = {
+            let storage = self.storage.lock().unwrap();
+            let detail = storage
+                .get_session_detail(session_id)?
+                .ok_or_else(|| AppError::invalid_state("セッションが見つかりません"))?;
+            // 音声ファイルの無いセグメントも対象に含め、書き起こしの失敗として数える
+            let (skipped, pending): (Vec<_>, Vec<_>) = detail
+                .segments
+                .into_iter()
+                .partition(|segment| !segment.raw_text.is_empty());
+            let pending: Vec<_> = pending.into_iter().map(|s| s.segment_id).collect();
+            (pending, skipped.len() as u32)
+        };
+
+        let total = pending.len() as u32;
+        let mut result = TranscribeSessionResult {
+            skipped,
+            ..Default::default()
+        };
+        log::info!(
+            target: TARGET_SERVICE,
+            "{} Batch transcription: {total} segments pending, {skipped} skipped",
+            LogContext::session(session_id)
+        );
+
+        let engine = self.stt_engine.clone();
+        self.job_queue.cleanup_completed().await;
+        for (i, segment_id) in pending.into_iter().enumerate() {
+            let (job_id, cancel_rx) = self
+                .job_queue
+                .enqueue(
+                    session_id.to_string(),
+                    Some(segment_id.clone()),
+                    JobKind::Transcribe,
+                )
+                .await;
+            self.job_queue.mark_running(&job_id).await;
+            let cancel = self
+                .job_queue
+                .cancel_token(&job_id)
+                .await
+                .unwrap_or_default();
+
+            // await で止まらないエンジン（Whisper）は cancel トークンで中断される
+            let outcome = tokio::select! {
+                outcome = self.retranscribe_segment_with_cancel(session_id, &segment_id, engine.as_ref(), cancel) => outcome,
+                _ = cancel_rx => Err(AppError::canceled("一括書き起こしはキャンセルされました")),
+            };
+            if matches!(&outcome, Err(e) if e.code == ErrorCode::Canceled) {
+                return Err(AppError::canceled("一括書き起こしはキャンセルされました"));
+            }
+
+            let (text, error) = match outcome {
+                Ok(text) => {
+                    self.job_queue.mark_done(&job_id).await;
+                    result.transcribed += 1;
+                    (Some(text), None)
+                }
+                Err(e) => {
+                    log::warn!(
+                        target: TARGET_SERVICE,
+                        "{} Batch transcription failed: {e}",
+                        LogContext::session(session_id).with_segment(&segment_id)
+                    );
+                    self.job_queue.mark_failed(&job_id, e.to_string()).await;
+                    result.failed += 1;
+                    (None, Some(e.message))
+                }
+            };
+            on_progress(TranscribeProgress {
+                session_id: session_id.to_string(),
+                segment_id,
+                completed: i as u32 + 1,
+                total,
+                text,
+                error,
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// 録音済みの音声ファイルを書き起こし、新しいセッションとして履歴に保存する
+    ///
+    /// WAV をモノラル 16kHz に変換し、録音時と同じ VAD 設定で発話ごとに区切ってから、
+    /// 設定中のエンジンで各セグメントを書き起こす（辞書置換などの後処理も録音時と同じ）。
+    /// アクティブセッションには影響しない。書き起こしに失敗したセグメントは保存せず、失敗として数える。
+    /// 発話が見つからなければセッションを作らずに `E_AUDIO_FORMAT` を返す。
+    /// `on_progress` は各セグメントの処理後に呼ばれる。
+    pub async fn transcribe_file<F>(
+        &self,
+        path: &Path,
+        mode: Mode,
+        mut on_progress: F,
+    ) -> Result<TranscribeFileResult, AppError>
+    where
+        F: FnMut(TranscribeProgress),
+    {
+        let audio = wav::read_wav(path)?;
+        let samples = resample_linear(
+            &audio.samples,
+            audio.sample_rate,
+            FILE_TRANSCRIBE_SAMPLE_RATE,
+        );
+        let (settings, dict_entries) = {
+            let storage = self.storage.lock().unwrap();
+            let dict_entries = storage
+                .get_enabled_dictionary_entries("mode", Self::mode_key(mode).as_deref())
+                .unwrap_or_default();
+            (storage.get_settings()?, dict_entries)
+        };
+
+        let segments = split_into_segments(
+            &samples,
+            FILE_TRANSCRIBE_SAMPLE_RATE,
+            Self::resolve_vad_config(&settings),
+        );
+        if segments.is_empty() {
+            return Err(AppError::audio_format(format!(
+                "音声ファイルに発話が見つかりませんでした: {}",
+                path.display()
+            )));
+        }
+
+        let session_id = uuid::Uuid::new_v4().to_string();
+        self.storage.lock().unwrap().insert_session(
+            &session_id,
+            mode,
+            &chrono::Utc::now().to_rfc3339(),
+        )?;
+        let total = segments.len() as u32;
+        log::info!(
+            target: TARGET_SERVICE,
+            "{} Transcribing file {}: {total} segments",
+            LogContext::session(&session_id),
+            path.display()
+        );
+
+        let task = WhisperTask::default();
+        let mut result = TranscribeFileResult {
+            session_id: session_id.clone(),
+            transcribed: 0,
+            failed: 0,
+        };
+        let hints: Vec<String> = dict_entries.iter().map(|e| e.replacement.clone()).collect();
+        let mut previous_text = String::new();
+        for (i, samples) in segments.into_iter().enumerate() {
+            let segment_id = uuid::Uuid::new_v4().to_string();
+            let log_ctx = LogContext::session(&session_id).with_segment(&segment_id);
+            let dictionary = match settings.smart_hints_limit() {
+                Some(max_terms) => select_hints(&hints, &previous_text, max_terms),
+                None => hints.clone(),
+            };
+            let ctx = SttContext {
+                language: settings.language.clone(),
+                dictionary,
+                mode: Some(mode),
+                prompt_prefix: settings.prompt_prefix_for(Some(mode)),
+                max_prompt_chars: settings.max_prompt_chars,
+                task,
+                log_ctx: log_ctx.clone(),
+                cancel: CancelToken::new(),
+            };
+            let audio = AudioSegment {
+                samples,
+                sample_rate: FILE_TRANSCRIBE_SAMPLE_RATE,
+            };
+
+            let (text, error) = match self.stt_engine.transcribe(audio, ctx).await {
+                Ok(transcript) => {
+                    let storage = self.storage.lock().unwrap();
+                    let processed_text =
+                        Self::finalize_transcript(&storage, &transcript.text, &dict_entries);
+                    let now = chrono::Utc::now().to_rfc3339();
+                    storage.transaction(|s| {
+                        s.insert_segment(&segment_id, &session_id, mode, &now)?;
+                        s.update_segment_text(&segment_id, &processed_text, transcript.confidence)?;
+                        s.set_segment_original_text(&segment_id, &transcript.text)?;
+                        s.set_segment_task(&segment_id, task)?;
+                        s.set_segment_timings(&segment_id, &transcript.timings)
+                    })?;
+                    result.transcribed += 1;
+                    previous_text.clone_from(&processed_text);
+                    (Some(processed_text), None)
+                }
+                Err(e) => {
+                    let e = AppError::from(e);
+                    log::warn!(target: TARGET_SERVICE, "{log_ctx} File transcription failed: {e}");
+                    result.failed += 1;
+                    (None, Some(e.message))
+                }
+            };
+            on_progress(TranscribeProgress {
+                session_id: session_id.clone(),
+                segment_id,
+                completed: i as u32 + 1,
+                total,
+                text,
+                error,
+            });
+        }
+
+        Ok(result)
+    }
+
+    // ==================== Pipeline (legacy) ====================
+
+    pub fn on_transcript_done(
+        &self,
+        segment_id: &str,
+        text: &str,
+        confidence: f32,
+    ) -> Result<(StateTransition, String), AppError> {
+        let start = std::time::Instant::now();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let mut mgr = self.session_mgr.lock().unwrap();
+        let storage = self.storage.lock().unwrap();
+        let mode = storage
+            .get_segment_mode(segment_id)?
+            .or_else(|| mgr.active().map(|s| s.mode));
+        let mode_str = mode.and_then(Self::mode_key);
+        let dict_entries = storage
+            .get_enabled_dictionary_entries("mode", mode_str.as_deref())
+            .unwrap_or_default();
+
+        let processed_text = Self::finalize_transcript(&storage, text, &dict_entries);
+        let review = storage.get_settings()?.review_before_deliver;
+        // 遷移は写しで計算し、DB への書き込みがコミットされてから反映する
+        let mut next = mgr.clone();
+        let transition = if review {
+            next.start_review(now.clone())?
+        } else {
+            next.on_transcript_done(now.clone())?
+        };
+        storage.transaction(|s| {
+            s.update_segment_text(segment_id, &processed_text, confidence)?;
+            s.set_segment_original_text(segment_id, text)?;
+            s.update_session_state(&transition.session_id, transition.new_state.as_str(), &now)
+        })?;
+        *mgr = next;
+
+        self.metrics.inc_segments_transcribed();
+        self.metrics
+            .record_latency("transcribe", start.elapsed().as_millis() as u64);
+
+        Ok((transition, processed_text))
+    }
+
+    pub fn on_rewrite_done(
+        &self,
+        segment_id: &str,
+        rewritten_text: &str,
+    ) -> Result<StateTransition, AppError> {
+        let start = std::time::Instant::now();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let mut mgr = self.session_mgr.lock().unwrap();
+        let storage = self.storage.lock().unwrap();
+        let mut next = mgr.clone();
+        let transition = next.on_rewrite_done(now.clone())?;
+        storage.transaction(|s| {
+            s.update_segment_rewritten(segment_id, rewritten_text)?;
+            s.update_session_state(&transition.session_id, transition.new_state.as_str(), &now)
+        })?;
+        *mgr = next;
+
+        self.metrics.inc_segments_rewritten();
+        self.metrics
+            .record_latency("rewrite", start.elapsed().as_millis() as u64);
+
+        Ok(transition)
+    }
+
+    /// レビュー済みテキストを確定する
+    ///
+    /// 修正後のテキストを raw_text として保存し、Reviewing 状態なら次の状態へ遷移する。
+    /// セッションが Reviewing でない場合（パイプライン経由）は保存のみ行い None を返す。
+    pub fn commit_segment(
+        &self,
+        segment_id: &str,
+        edited_text: &str,
+    ) -> Result<Option<StateTransition>, AppError> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut mgr = self.session_mgr.lock().unwrap();
+        let storage = self.storage.lock().unwrap();
+        let reviewing =
+            matches!(mgr.active(), Some(session) if session.state == SessionState::Reviewing);
+        if !reviewing {
+            storage.update_segment_raw(segment_id, edited_text)?;
+            return Ok(None);
+        }
+        let mut next = mgr.clone();
+        let transition = next.commit_review(now.clone())?;
+        storage.transaction(|s| {
+            s.update_segment_raw(segment_id, edited_text)?;
+            s.update_session_state(&transition.session_id, transition.new_state.as_str(), &now)
+        })?;
+        *mgr = next;
+        Ok(Some(transition))
+    }
+
+    /// レビューで修正したテキストを確定し、配信まで進める（UI・ヘッドレスの `commit_segment`）
+    ///
+    /// 状態遷移を行うたびに `on_transition` を呼ぶ。配信した場合はそのセッションと配信先を返す。
+    /// リライトが必要なモード（Reviewing → Rewriting）では配信せず None を返し、rewrite_last → deliver_last に任せる。
+    pub fn commit_and_deliver(
+        &self,
+        segment_id: &str,
+        edited_text: &str,
+        mut on_transition: impl FnMut(&StateTransition),
+    ) -> Result<Option<(String, DeliverTarget)>, AppError> {
+        let Some(transition) = self.commit_segment(segment_id, edited_text)? else {
+            let target = self.deliver_text(edited_text)?;
+            let session_id = self.current_session_id().unwrap_or_default();
+            return Ok(Some((session_id, target)));
+        };
+        on_transition(&transition);
+        if transition.new_state != SessionState::Delivering {
+            return Ok(None);
+        }
+        let target = self.deliver_text(edited_text)?;
+        let delivered = self.on_deliver_done()?;
+        on_transition(&delivered);
+        Ok(Some((delivered.session_id, target)))
+    }
+
+    /// 状態遷移を伴わずにテキストを配信し、配信先を返す
+    pub fn deliver_text(&self, text: &str) -> Result<DeliverTarget, AppError> {
         let start = std::time::Instant::now();
         let target = self.resolve_deliver_target(None)?;
-        self.output_router.deliver(target, text)?;
+        let wrapped = match self.clipboard_wrap() {
+            Some(wrap) if target == DeliverTarget::Clipboard => {
+                let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M").to_string();
+                wrap.apply(text, &timestamp)
+            }
+            _ => text.to_string(),
+        };
+        let text = self.accumulate_for_delivery(&target, &wrapped)?;
+        self.route_delivery(self.current_session_id().as_deref(), &target, &text)?;
+
+        self.metrics.inc_segments_delivered();
+        self.metrics
+            .record_latency("deliver", start.elapsed().as_millis() as u64);
+        Ok(target)
+    }
+
+    /// 指定したポリシーで一度だけ配信し、配信先を返す
+    ///
+    /// セッションの出力ポリシーと状態は変えない。蓄積配信のバッファにも追記しない。
+    pub fn deliver_to(&self, text: &str, policy: DeliverPolicy) -> Result<DeliverTarget, AppError> {
+        let start = std::time::Instant::now();
+        let target = policy.target();
+        let text = match policy {
+            DeliverPolicy::Clipboard { wrap: Some(wrap) } => {
+                let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M").to_string();
+                wrap.apply(text, &timestamp)
+            }
+            _ => text.to_string(),
+        };
+        self.route_delivery(self.current_session_id().as_deref(), &target, &text)?;
+
+        self.metrics.inc_segments_delivered();
+        self.metrics
+            .record_latency("deliver", start.elapsed().as_millis() as u64);
+        Ok(target)
+    }
+
+    pub fn deliver(&self, text: &str) -> Result<StateTransition, AppError> {
+        self.deliver_text(text)?;
+        self.on_deliver_done()
+    }
 
+    /// 配信完了: Delivering→Idle を永続化する
+    pub fn on_deliver_done(&self) -> Result<StateTransition, AppError> {
         let now = chrono::Utc::now().to_rfc3339();
         let mut mgr = self.session_mgr.lock().unwrap();
         let transition = mgr.on_deliver_done(now.clone())?;
 
-        let storage = self.storage.lock().unwrap();
-        storage.update_session_state(
-            &transition.session_id,
-            transition.new_state.as_str(),
-            &now,
-        )?;
+        let storage = self.storage.lock().unwrap();
+        storage.update_session_state(
+            &transition.session_id,
+            transition.new_state.as_str(),
+            &now,
+        )?;
+
+        Ok(transition)
+    }
+
+    /// 出力ルーター経由で配信する
+    ///
+    /// 改行コードと末尾空白を設定に合わせて揃えてから配信する。
+    /// `max_deliver_chars` を超えるテキストは文字境界で切り詰め、その旨を通知する
+    /// （暴走した書き起こしで貼り付け先のアプリが固まらないようにするため）。
+    /// 配信に成功したら、登録済みの [`DeliveryObserver`] に配信したテキストを渡す。
+    fn route_delivery(
+        &self,
+        session_id: Option<&str>,
+        target: &DeliverTarget,
+        text: &str,
+    ) -> Result<(), AppError> {
+        let settings = self.get_settings()?;
+        let normalized =
+            normalize_for_delivery(text, settings.deliver_line_endings, settings.deliver_trim);
+        let text = normalized.as_str();
+        let text = match truncate_chars(text, settings.max_deliver_chars) {
+            Some(truncated) => {
+                let notice = DeliverTruncated {
+                    original_len: text.chars().count(),
+                    delivered_len: settings.max_deliver_chars as usize,
+                };
+                log::warn!(
+                    target: TARGET_SERVICE,
+                    "配信テキストを切り詰めました: {} → {} 文字",
+                    notice.original_len,
+                    notice.delivered_len
+                );
+                // 受信側が無くても配信自体は続ける
+                let _ = self.truncation_tx.send(notice);
+                truncated
+            }
+            None => text,
+        };
+        let router = self.output_router.lock().unwrap();
+        router.set_verify_clipboard(settings.verify_clipboard);
+        router.deliver(target.as_str(), text)?;
+        drop(router);
+
+        if let Some(observer) = &self.delivery_observer {
+            observer.on_delivered(session_id, text, target);
+        }
+        Ok(())
+    }
+
+    /// 独自の出力先を登録する（`DeliverPolicy::Custom { name }` で配信先に指定できる）
+    pub fn register_output_target(
+        &self,
+        name: impl Into<String>,
+        target: impl OutputTarget + 'static,
+    ) {
+        self.output_router.lock().unwrap().register(name, target);
+    }
+
+    /// 蓄積配信が有効ならテキストをバッファに追記し、クリップボードへ書き込む全文を返す
+    fn accumulate_for_delivery(
+        &self,
+        target: &DeliverTarget,
+        text: &str,
+    ) -> Result<String, AppError> {
+        let settings = self.get_settings()?;
+        if !settings.accumulate_deliveries || *target != DeliverTarget::Clipboard {
+            return Ok(text.to_string());
+        }
+        let mut buf = self.accumulator.lock().unwrap();
+        Ok(buf.append(text, settings.accumulate_separator).to_string())
+    }
+
+    /// 蓄積済みテキストをクリップボードへ書き込んでバッファを空にし、その全文を返す
+    pub fn flush_accumulated(&self) -> Result<String, AppError> {
+        let text = self.accumulator.lock().unwrap().take();
+        if !text.is_empty() {
+            self.route_delivery(
+                self.current_session_id().as_deref(),
+                &DeliverTarget::Clipboard,
+                &text,
+            )?;
+        }
+        Ok(text)
+    }
+
+    /// 蓄積済みテキストを破棄する
+    pub fn clear_accumulated(&self) {
+        self.accumulator.lock().unwrap().clear();
+    }
+
+    pub fn deliver_last(
+        &self,
+        target: Option<DeliverTarget>,
+        prefer: DeliverText,
+    ) -> Result<(StateTransition, DeliverLastResult), AppError> {
+        let start = std::time::Instant::now();
+        let target = self.resolve_deliver_target(target)?;
+
+        let (session_id, text, fell_back_to_raw) = self.last_segment_text(prefer)?;
+        if fell_back_to_raw {
+            log::info!(
+                target: TARGET_SERVICE,
+                "{} リライト済みテキストが無いため生テキストを出力します",
+                LogContext::session(&session_id)
+            );
+        }
+
+        let delivered = self.accumulate_for_delivery(&target, &text)?;
+        self.route_delivery(Some(&session_id), &target, &delivered)?;
+
+        let mgr = self.session_mgr.lock().unwrap();
+        let current_state = mgr.active().map(|s| s.state.as_str().to_string());
+        drop(mgr);
+
+        let result = DeliverLastResult {
+            text,
+            target,
+            fell_back_to_raw,
+        };
+
+        self.metrics.inc_segments_delivered();
+        self.metrics
+            .record_latency("deliver", start.elapsed().as_millis() as u64);
+
+        if current_state.as_deref() == Some("delivering") {
+            let now = chrono::Utc::now().to_rfc3339();
+            let mut mgr = self.session_mgr.lock().unwrap();
+            let transition = mgr.on_deliver_done(now.clone())?;
+            let storage = self.storage.lock().unwrap();
+            storage.update_session_state(
+                &transition.session_id,
+                transition.new_state.as_str(),
+                &now,
+            )?;
+            Ok((transition, result))
+        } else {
+            Ok((
+                StateTransition {
+                    session_id,
+                    prev_state: current_state.unwrap_or_default(),
+                    new_state: SessionState::Idle,
+                },
+                result,
+            ))
+        }
+    }
+
+    /// アクティブセッションの最終セグメントから出力テキストを選ぶ
+    ///
+    /// (session_id, テキスト, 生テキストへフォールバックしたか) を返す。
+    fn last_segment_text(&self, prefer: DeliverText) -> Result<(String, String, bool), AppError> {
+        let session_id = {
+            let mgr = self.session_mgr.lock().unwrap();
+            mgr.active()
+                .map(|s| s.session_id.clone())
+                .ok_or_else(|| AppError::internal("アクティブセッションがありません"))?
+        };
+
+        let storage = self.storage.lock().unwrap();
+        let detail = storage
+            .get_session_detail(&session_id)?
+            .ok_or_else(|| AppError::internal("セッション詳細が見つかりません"))?;
+        drop(storage);
+
+        let last_segment = detail
+            .segments
+            .last()
+            .ok_or_else(|| AppError::internal("セグメントがありません"))?;
+
+        let (text, fell_back) = prefer.select(
+            &last_segment.raw_text,
+            last_segment.rewritten_text.as_deref(),
+        );
+        Ok((session_id, text.to_string(), fell_back))
+    }
+
+    /// 最後のセグメントの (segment_id, raw_text, 録音時のモード) を返す
+    pub fn get_last_segment_for_rewrite(&self) -> Result<(String, String, Mode), AppError> {
+        let session_id = {
+            let mgr = self.session_mgr.lock().unwrap();
+            mgr.active()
+                .map(|s| s.session_id.clone())
+                .ok_or_else(|| AppError::internal("アクティブセッションがありません"))?
+        };
+
+        let storage = self.storage.lock().unwrap();
+        let detail = storage
+            .get_session_detail(&session_id)?
+            .ok_or_else(|| AppError::internal("セッション詳細が見つかりません"))?;
+
+        let last_segment = detail
+            .segments
+            .last()
+            .ok_or_else(|| AppError::internal("セグメントがありません"))?;
+
+        Ok((
+            last_segment.segment_id.clone(),
+            last_segment.raw_text.clone(),
+            last_segment.mode,
+        ))
+    }
+
+    // ==================== Paste Router ====================
+
+    pub fn paste_to_active_app(&self, text: &str) -> Result<PasteResult, AppError> {
+        let storage = self.storage.lock().unwrap();
+        let settings = storage.get_settings()?;
+        drop(storage);
+
+        PasteRouter::paste_if_allowlisted(text, &settings.paste_allowlist, settings.paste_confirm)
+    }
+
+    // ==================== Queries ====================
+
+    pub fn get_history(
+        &self,
+        limit: u32,
+        cursor: Option<&str>,
+        query: Option<&str>,
+        pinned_first: bool,
+    ) -> Result<HistoryPage, AppError> {
+        let storage = self.storage.lock().unwrap();
+        storage.list_history(limit, cursor, query, pinned_first)
+    }
+
+    /// セッションのピン留め状態を設定する
+    pub fn pin_session(&self, session_id: &str, pinned: bool) -> Result<(), AppError> {
+        let storage = self.storage.lock().unwrap();
+        if !storage.set_session_pinned(session_id, pinned)? {
+            return Err(AppError::invalid_state(format!(
+                "セッションが見つかりません: {session_id}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// セッションの各セグメントに、現在の辞書・設定で後処理をやり直す（辞書を追加した後の過去の書き起こし向け）
+    ///
+    /// 保存しておいた後処理前の書き起こしから処理し直し、テキストが変わったセグメントの raw_text を更新する。
+    /// 後処理前の書き起こしが記録されていないセグメント（列追加前に保存したもの）は変更しない。
+    /// 更新したセグメント数を返す。
+    pub fn reapply_post_processing(&self, session_id: &str) -> Result<u32, AppError> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let storage = self.storage.lock().unwrap();
+        let Some(detail) = storage.get_session_detail(session_id)? else {
+            return Err(AppError::invalid_state(format!(
+                "セッションが見つかりません: {session_id}"
+            )));
+        };
+        let segments = storage.list_segment_original_texts(session_id)?;
+
+        let mut dict_by_mode: HashMap<Mode, Vec<DictionaryEntry>> = HashMap::new();
+        let mut updates = Vec::new();
+        for (segment_id, mode, original) in segments {
+            let dict_entries = dict_by_mode.entry(mode).or_insert_with(|| {
+                storage
+                    .get_enabled_dictionary_entries("mode", Self::mode_key(mode).as_deref())
+                    .unwrap_or_default()
+            });
+            let processed = Self::finalize_transcript(&storage, &original, dict_entries);
+            let unchanged = detail
+                .segments
+                .iter()
+                .any(|segment| segment.segment_id == segment_id && segment.raw_text == processed);
+            if !unchanged {
+                updates.push((segment_id, processed));
+            }
+        }
+
+        storage.transaction(|s| {
+            for (segment_id, processed) in &updates {
+                s.update_segment_processed_text(segment_id, processed)?;
+                s.touch_segment_session(segment_id, &now)?;
+            }
+            Ok(())
+        })?;
+        log::info!(
+            target: TARGET_SERVICE,
+            "{} Post-processing reapplied: {} segments updated",
+            LogContext::session(session_id),
+            updates.len()
+        );
+        Ok(updates.len() as u32)
+    }
+
+    /// セッションにタグを付け、付与後のタグ一覧を返す（前後の空白は除く）
+    pub fn tag_session(&self, session_id: &str, tag: &str) -> Result<Vec<String>, AppError> {
+        let tag = Self::normalize_tag(tag)?;
+        let storage = self.storage.lock().unwrap();
+        if !storage.add_tag(session_id, tag)? {
+            return Err(AppError::invalid_state(format!(
+                "セッションが見つかりません: {session_id}"
+            )));
+        }
+        storage.list_tags(session_id)
+    }
+
+    /// セッションからタグを外し、残ったタグ一覧を返す（付いていなければ何もしない）
+    pub fn untag_session(&self, session_id: &str, tag: &str) -> Result<Vec<String>, AppError> {
+        let tag = Self::normalize_tag(tag)?;
+        let storage = self.storage.lock().unwrap();
+        storage.remove_tag(session_id, tag)?;
+        storage.list_tags(session_id)
+    }
+
+    /// 指定タグの付いたセッションの履歴を新しい順に返す
+    pub fn list_sessions_by_tag(
+        &self,
+        tag: &str,
+        limit: u32,
+        cursor: Option<&str>,
+    ) -> Result<HistoryPage, AppError> {
+        let tag = Self::normalize_tag(tag)?;
+        let storage = self.storage.lock().unwrap();
+        storage.list_history_by_tag(tag, limit, cursor)
+    }
+
+    fn normalize_tag(tag: &str) -> Result<&str, AppError> {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return Err(AppError::invalid_state("タグが空です"));
+        }
+        Ok(tag)
+    }
+
+    /// 履歴のセグメントのテキストを修正する（rewritten が None ならリライト結果は変更しない）
+    pub fn edit_segment(
+        &self,
+        segment_id: &str,
+        raw: &str,
+        rewritten: Option<&str>,
+    ) -> Result<(), AppError> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let storage = self.storage.lock().unwrap();
+        storage.transaction(|s| {
+            if !s.touch_segment_session(segment_id, &now)? {
+                return Err(AppError::invalid_state(format!(
+                    "セグメントが見つかりません: {segment_id}"
+                )));
+            }
+            s.update_segment_raw(segment_id, raw)?;
+            if let Some(rewritten) = rewritten {
+                s.update_segment_rewritten(segment_id, rewritten)?;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn get_session(&self, session_id: &str) -> Result<Option<SessionDetail>, AppError> {
+        let storage = self.storage.lock().unwrap();
+        storage.get_session_detail(session_id)
+    }
+
+    /// セッションの全セグメントのテキストを録音順に `join` の区切りで連結する
+    ///
+    /// 各セグメントは `prefer` に従って生テキストかリライト済みテキストを選ぶ。空のセグメントは飛ばす。
+    pub fn get_session_text(
+        &self,
+        session_id: &str,
+        prefer: DeliverText,
+        join: JoinStyle,
+    ) -> Result<String, AppError> {
+        let detail = self
+            .get_session(session_id)?
+            .ok_or_else(|| AppError::invalid_state("セッションが見つかりません"))?;
+        let texts: Vec<&str> = detail
+            .segments
+            .iter()
+            .map(|s| prefer.select(&s.raw_text, s.rewritten_text.as_deref()).0)
+            .filter(|text| !text.trim().is_empty())
+            .collect();
+        Ok(texts.join(join.separator()))
+    }
+
+    /// セッション全体のテキストをクリップボードへ出力し、出力したテキストを返す
+    pub fn copy_session(
+        &self,
+        session_id: &str,
+        prefer: DeliverText,
+        join: JoinStyle,
+    ) -> Result<String, AppError> {
+        let text = self.get_session_text(session_id, prefer, join)?;
+        if text.is_empty() {
+            return Err(AppError::invalid_state("出力できるテキストがありません"));
+        }
+        self.route_delivery(Some(session_id), &DeliverTarget::Clipboard, &text)?;
+        log::info!(
+            target: TARGET_SERVICE,
+            "{} Session copied: {} chars",
+            LogContext::session(session_id),
+            text.chars().count()
+        );
+        Ok(text)
+    }
+
+    /// セグメントの単語・区間タイミングを確率付きで返す（信頼度ヒートマップ用）
+    ///
+    /// タイミングを返さないエンジンで書き起こしたセグメントは空。
+    pub fn get_segment_timings(&self, segment_id: &str) -> Result<Vec<SegmentTiming>, AppError> {
+        let storage = self.storage.lock().unwrap();
+        storage.get_segment_timings(segment_id)
+    }
+
+    // ==================== Dictionary ====================
+
+    pub fn upsert_dictionary(&self, entry: DictionaryEntry) -> Result<String, AppError> {
+        let storage = self.storage.lock().unwrap();
+        storage.upsert_dictionary_entry(&entry)
+    }
+
+    pub fn list_dictionary(&self, scope: Option<&str>) -> Result<Vec<DictionaryEntry>, AppError> {
+        let storage = self.storage.lock().unwrap();
+        storage.list_dictionary_entries(scope)
+    }
+
+    /// 直近のセグメントで繰り返し現れ、辞書に無い語（カタカナ・全大文字）を多い順に返す
+    pub fn suggest_dictionary_terms(&self, limit: usize) -> Result<Vec<TermSuggestion>, AppError> {
+        let storage = self.storage.lock().unwrap();
+        let mut frequency = TermFrequency::new();
+        for text in storage.list_recent_segment_texts(TERM_SUGGESTION_SCAN_SEGMENTS)? {
+            frequency.add_text(&text);
+        }
+        let entries = storage.list_dictionary_entries(None)?;
+        Ok(frequency.suggestions(&entries, limit))
+    }
+
+    // ==================== Settings ====================
+
+    pub fn get_settings(&self) -> Result<AppSettings, AppError> {
+        let storage = self.storage.lock().unwrap();
+        storage.get_settings()
+    }
+
+    pub fn update_settings(&self, settings: AppSettings) -> Result<(), AppError> {
+        settings.validate().map_err(|errors| {
+            AppError::invalid_settings(
+                errors
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            )
+        })?;
+
+        self.storage.lock().unwrap().save_settings(&settings)?;
+        self.transcript_cache
+            .set_enabled(settings.cache_transcripts);
+        // Whisper のしきい値など、エンジン側の調整項目を次の書き起こしから反映する
+        self.stt_engine.apply_settings(&settings);
+
+        // 録音中なら VAD 設定を即時反映する
+        if let Some(pipeline) = self.pipeline.lock().unwrap().as_ref() {
+            pipeline.update_vad_config(Self::resolve_vad_config(&settings));
+        }
+        // 事前キャプチャの有効/無効は待機中のセッションにも即時反映する
+        if !settings.prebuffer_enabled {
+            self.stop_precapture();
+        } else if self.current_state().as_deref() == Some("idle") {
+            self.start_precapture();
+        }
+        Ok(())
+    }
+
+    /// インストール済みの Whisper モデルに実行中のエンジンを切り替える
+    ///
+    /// 既知サイズのモデルなら `whisper_model_size` にも保存し、再起動後も同じモデルを使う。
+    pub fn set_whisper_model(&self, name: &str) -> Result<ModelInfo, AppError> {
+        self.switch_whisper_model(&WhisperModelManager::default(), name)
+    }
+
+    fn switch_whisper_model(
+        &self,
+        manager: &WhisperModelManager,
+        name: &str,
+    ) -> Result<ModelInfo, AppError> {
+        let model = manager.find_installed(name).ok_or_else(|| {
+            AppError::invalid_settings(format!(
+                "Whisper モデル {name} はインストールされていません"
+            ))
+        })?;
+        self.stt_engine.switch_model(&model.path)?;
+
+        let filename = format!("ggml-{name}.bin");
+        let known = [
+            WhisperModelSize::Base,
+            WhisperModelSize::Small,
+            WhisperModelSize::Medium,
+            WhisperModelSize::Large,
+        ]
+        .into_iter()
+        .find(|size| size.filename() == filename);
+        if let Some(size) = known {
+            self.update_settings(AppSettings {
+                whisper_model_size: size,
+                ..self.get_settings()?
+            })?;
+        }
+        log::info!(target: TARGET_SERVICE, "Whisper model set: {name}");
+        Ok(model)
+    }
+
+    // ==================== Shutdown ====================
+
+    /// アプリ終了前に進行中の処理を片付ける
+    ///
+    /// パイプラインを停止して録音中のセグメントを書き起こし、その保存と実行中のジョブの完了を
+    /// `timeout` まで待つ（超えたら残りのジョブはキャンセルする）。
+    /// その後メトリクスを保存し、アクティブセッションを idle にする。
+    pub fn shutdown(&self, timeout: Duration) -> Result<(), AppError> {
+        let deadline = Instant::now() + timeout;
+        self.stop_precapture();
+        // 停止時に残りのセグメントを書き起こしてから戻る（cancel_pipeline と違い破棄しない）
+        self.stop_pipeline();
+
+        while self.open_event_streams.load(Ordering::SeqCst) > 0 || self.job_queue.has_active_jobs()
+        {
+            if Instant::now() >= deadline {
+                log::warn!(
+                    target: TARGET_SERVICE,
+                    "Shutdown timed out after {}ms waiting for in-flight work",
+                    timeout.as_millis()
+                );
+                break;
+            }
+            std::thread::sleep(SHUTDOWN_POLL_INTERVAL);
+        }
+        let canceled = self.job_queue.cancel_all_now();
+        if !canceled.is_empty() {
+            log::warn!(
+                target: TARGET_SERVICE,
+                "Canceled {} job(s) at shutdown",
+                canceled.len()
+            );
+        }
+
+        let summary = serde_json::to_string(&self.metrics.summary())
+            .map_err(|e| AppError::internal(format!("メトリクスのシリアライズ失敗: {e}")))?;
+        self.storage
+            .lock()
+            .unwrap()
+            .save_metrics_snapshot(&summary, &chrono::Utc::now().to_rfc3339())?;
+
+        self.stop_session()?;
+        log::info!(target: TARGET_SERVICE, "Shutdown complete");
+        Ok(())
+    }
+
+    // ==================== OS Integration ====================
+
+    pub fn check_permissions(&self) -> PermissionStatus {
+        OsIntegration::check_all_permissions()
+    }
+
+    // ==================== Metrics ====================
+
+    pub fn get_metrics(&self) -> MetricsSummary {
+        self.metrics.summary()
+    }
+
+    /// メトリクスを分析用に書き出す（CSV: レイテンシ記録、JSON: サマリー全体）
+    pub fn export_metrics(&self, format: MetricsExportFormat) -> Result<String, AppError> {
+        match format {
+            MetricsExportFormat::Csv => Ok(self.metrics.latencies_csv()),
+            MetricsExportFormat::Json => serde_json::to_string_pretty(&self.metrics.summary())
+                .map_err(|e| AppError::internal(format!("メトリクスのシリアライズ失敗: {e}"))),
+        }
+    }
+
+    pub fn record_error(&self, code: &str) {
+        self.metrics.inc_error(code);
+    }
+
+    // ==================== Data Protection ====================
+
+    /// TTL を過ぎたセグメント（と音声ファイル）、および空になった古いセッションを削除する
+    pub fn cleanup_old_data(&self, ttl_days: u32) -> Result<DeleteSessionResult, AppError> {
+        if ttl_days == 0 {
+            return Ok(DeleteSessionResult::default());
+        }
+
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(ttl_days as i64);
+        let cutoff_str = cutoff.to_rfc3339();
+
+        let storage = self.storage.lock().unwrap();
+        // セグメントとセッションの削除は 1 トランザクションで行い、音声ファイルはコミット後に消す
+        let (audio_paths, segments_deleted, sessions_deleted) = storage.transaction(|s| {
+            let (audio_paths, segments_deleted) = s.delete_old_segment_rows(&cutoff_str)?;
+            let sessions_deleted = s.delete_old_sessions(&cutoff_str)?;
+            Ok((audio_paths, segments_deleted, sessions_deleted))
+        })?;
+        let result = DeleteSessionResult {
+            sessions_deleted,
+            segments_deleted,
+            audio_files_deleted: storage.remove_audio_files(&audio_paths),
+        };
+
+        log::info!(
+            "データクリーンアップ: {} セグメント、{} 音声ファイル、{} セッション削除（TTL: {ttl_days}日）",
+            result.segments_deleted,
+            result.audio_files_deleted,
+            result.sessions_deleted
+        );
+
+        Ok(result)
+    }
+
+    /// 指定セッションを削除する（アクティブセッションは削除不可）
+    pub fn delete_session(&self, session_id: &str) -> Result<DeleteSessionResult, AppError> {
+        let mgr = self.session_mgr.lock().unwrap();
+        if mgr.active().is_some_and(|s| s.session_id == session_id) {
+            return Err(AppError::invalid_state(
+                "アクティブなセッションは削除できません",
+            ));
+        }
+        drop(mgr);
+
+        let storage = self.storage.lock().unwrap();
+        let result = storage.delete_session(session_id)?;
+        log::info!(
+            "セッション削除: {session_id}（{} セグメント、{} 音声ファイル）",
+            result.segments_deleted,
+            result.audio_files_deleted
+        );
+        Ok(result)
+    }
+
+    /// 音声の保存先（VAD の output_dir、設定 audio_dir があればそちら）にある WAV の件数・合計サイズ・最も古い日時
+    pub fn audio_cache_stats(&self) -> Result<AudioCacheStats, AppError> {
+        let settings = self
+            .storage
+            .lock()
+            .unwrap()
+            .get_settings()
+            .unwrap_or_default();
+        let files = Self::list_audio_cache(&settings)?;
+        Ok(AudioCacheStats {
+            file_count: files.len() as u32,
+            total_bytes: files.iter().map(|f| f.len).sum(),
+            oldest: files
+                .iter()
+                .map(|f| f.modified)
+                .min()
+                .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339()),
+        })
+    }
+
+    /// 音声の保存先にある WAV のうち、どのセグメントからも参照されていないものを削除し、削除した数を返す
+    ///
+    /// 保存済みセグメントの音声は保持設定（audio_retention・TTL）に従って消すため残す。
+    /// `secure_delete` 設定が有効ならゼロで上書きしてから削除する。
+    /// 録音中は書き出した直後でまだセグメントに記録されていない WAV を消しうるため拒否する。
+    pub fn clear_audio_cache(&self) -> Result<u32, AppError> {
+        if self.pipeline.lock().unwrap().is_some() {
+            return Err(AppError::invalid_state(
+                "録音中は音声キャッシュを削除できません",
+            ));
+        }
+
+        let storage = self.storage.lock().unwrap();
+        let settings = storage.get_settings().unwrap_or_default();
+        let referenced: HashSet<String> = storage.list_segment_audio_paths()?.into_iter().collect();
+        let orphans: Vec<String> = Self::list_audio_cache(&settings)?
+            .into_iter()
+            .map(|f| f.path.to_string_lossy().to_string())
+            .filter(|path| !referenced.contains(path))
+            .collect();
+        let removed = storage.remove_audio_files(&orphans);
+        log::info!(
+            target: TARGET_SERVICE,
+            "音声キャッシュ削除: {removed} / {} ファイル",
+            orphans.len()
+        );
+        Ok(removed)
+    }
+
+    fn list_audio_cache(settings: &AppSettings) -> Result<Vec<wav::WavFile>, AppError> {
+        let dir = Self::resolve_vad_config(settings).output_dir;
+        wav::list_wav_files(&dir).map_err(|e| {
+            AppError::storage(format!(
+                "音声の保存先を読み取れません ({}): {e}",
+                dir.display()
+            ))
+        })
+    }
+
+    // ==================== State Accessors ====================
+
+    pub fn current_state(&self) -> Option<String> {
+        let mgr = self.session_mgr.lock().unwrap();
+        mgr.active().map(|s| s.state.as_str().to_string())
+    }
+
+    /// 現在の状態をまとめて返す（UI がマウント時・フォーカス時に再同期する）
+    pub fn current_snapshot(&self) -> AppSnapshot {
+        let (session_id, state, mode) = {
+            let mgr = self.session_mgr.lock().unwrap();
+            match mgr.active() {
+                Some(s) => (
+                    Some(s.session_id.clone()),
+                    Some(s.state.clone()),
+                    Some(s.mode),
+                ),
+                None => (None, None, None),
+            }
+        };
+        let pipeline = match self.pipeline.lock().unwrap().as_ref() {
+            Some(p) if p.is_paused() => PipelineStatus::Paused,
+            Some(_) => PipelineStatus::Running,
+            None => PipelineStatus::Stopped,
+        };
+        let last_partial_text = self.last_partial.lock().unwrap().clone();
+
+        AppSnapshot {
+            session_id,
+            state,
+            mode,
+            pipeline,
+            last_partial_text,
+        }
+    }
+
+    pub fn current_session_id(&self) -> Option<String> {
+        let mgr = self.session_mgr.lock().unwrap();
+        mgr.active().map(|s| s.session_id.clone())
+    }
+
+    pub fn current_mode(&self) -> Option<Mode> {
+        let mgr = self.session_mgr.lock().unwrap();
+        mgr.active().map(|s| s.mode)
+    }
+
+    /// セグメントが属するセッションの ID（セッションを止めた後に終わる非同期処理でも引ける）
+    pub fn segment_session_id(&self, segment_id: &str) -> Result<Option<String>, AppError> {
+        self.storage
+            .lock()
+            .unwrap()
+            .get_segment_session_id(segment_id)
+    }
+
+    /// アクティブセッションの Whisper タスク（セッションが無ければ transcribe）
+    pub fn current_task(&self) -> WhisperTask {
+        let mgr = self.session_mgr.lock().unwrap();
+        mgr.active().map(|s| s.task).unwrap_or_default()
+    }
+
+    pub fn rewriter_name(&self) -> &str {
+        self.rewriter.name()
+    }
+
+    pub fn stt_engine_name(&self) -> &str {
+        self.stt_engine.name()
+    }
+
+    /// 使用中の STT エンジンが対応する機能（UI が非対応の操作を隠すため）
+    pub fn engine_capabilities(&self) -> EngineCapabilities {
+        self.stt_engine.capabilities()
+    }
+
+    // ==================== Setup Status ====================
+
+    /// STT セットアップ全体を診断する（権限・デバイス・エンジン・モデル・保存先）
+    pub fn diagnose(&self) -> DiagnosticsReport {
+        let settings = self
+            .storage
+            .lock()
+            .unwrap()
+            .get_settings()
+            .unwrap_or_default();
+
+        let stt_name = self.stt_engine.name();
+        let mut checks = vec![
+            diagnostics::check_microphone_permission(),
+            diagnostics::check_input_device(),
+            DiagnosticCheck {
+                name: "stt_engine".to_string(),
+                ok: !matches!(stt_name, "noop" | UNAVAILABLE_ENGINE_NAME),
+                detail: format!("selected={:?}, active={stt_name}", settings.stt_engine),
+            },
+        ];
+
+        match settings.stt_engine {
+            SttEngineChoice::Whisper => {
+                #[cfg(feature = "whisper")]
+                checks.push(diagnostics::check_file_exists(
+                    "whisper_model",
+                    &crate::infra::stt::whisper::WhisperSttEngine::model_path_for(
+                        settings.whisper_model_size,
+                    ),
+                ));
+                #[cfg(not(feature = "whisper"))]
+                checks.push(DiagnosticCheck {
+                    name: "whisper_model".to_string(),
+                    ok: false,
+                    detail: "whisper 機能無効でビルドされています".to_string(),
+                });
+            }
+            SttEngineChoice::Soniox | SttEngineChoice::Cloud => {
+                checks.push(diagnostics::check_api_key(
+                    "soniox_api_key",
+                    settings.soniox_api_key.as_deref(),
+                ));
+            }
+            SttEngineChoice::Apple => {
+                #[cfg(target_os = "macos")]
+                let available = crate::infra::stt::apple_speech::AppleSttEngine::is_available();
+                #[cfg(not(target_os = "macos"))]
+                let available = false;
+                checks.push(DiagnosticCheck {
+                    name: "apple_speech".to_string(),
+                    ok: available,
+                    detail: if available {
+                        "利用可能".to_string()
+                    } else {
+                        "Speech.framework が利用できません".to_string()
+                    },
+                });
+            }
+        }
+
+        checks.push(diagnostics::check_dir_writable(
+            "data_dir_writable",
+            &diagnostics::app_data_dir(),
+        ));
+
+        DiagnosticsReport {
+            ok: checks.iter().all(|c| c.ok),
+            checks,
+        }
+    }
+
+    pub fn check_setup_status(&self) -> SetupStatus {
+        let settings = self
+            .storage
+            .lock()
+            .unwrap()
+            .get_settings()
+            .unwrap_or_default();
+
+        let stt_name = self.stt_engine.name().to_string();
+        let rewriter_name = self.rewriter.name().to_string();
+
+        let mut issues = Vec::new();
+
+        // 1. STT が Noop / 利用不可 → 選択されたエンジンに応じたエラーメッセージ
+        if matches!(stt_name.as_str(), "noop" | UNAVAILABLE_ENGINE_NAME) {
+            let (message, action) = match settings.stt_engine {
+                crate::domain::settings::SttEngineChoice::Soniox
+                | crate::domain::settings::SttEngineChoice::Cloud => (
+                    "Soniox API キーが設定されていないため、音声認識が動作しません".to_string(),
+                    "Settings で Soniox API キーを入力してください（変更後はアプリ再起動が必要です）"
+                        .to_string(),
+                ),
+                crate::domain::settings::SttEngineChoice::Whisper => (
+                    format!(
+                        "Whisper モデル（{:?}）がダウンロードされていないため、音声認識が動作しません",
+                        settings.whisper_model_size
+                    ),
+                    "Settings で Whisper モデルをダウンロードしてください（変更後はアプリ再起動が必要です）"
+                        .to_string(),
+                ),
+                crate::domain::settings::SttEngineChoice::Apple => (
+                    "Apple Speech が利用できないため、音声認識が動作しません".to_string(),
+                    "macOS で Speech.framework が有効か確認してください".to_string(),
+                ),
+            };
+            issues.push(SetupIssue {
+                category: "stt".to_string(),
+                severity: "error".to_string(),
+                message,
+                action,
+                navigate_to: Some("settings".to_string()),
+            });
+        }
+
+        // 2. Rewriter が Noop かつ rewrite_enabled → 警告
+        if rewriter_name == "noop" && settings.rewrite_enabled {
+            issues.push(SetupIssue {
+                category: "rewriter".to_string(),
+                severity: "warning".to_string(),
+                message: "Claude API キーが未設定のため、リライト機能が動作しません".to_string(),
+                action: "Settings で Claude API キーを入力してください（変更後はアプリ再起動が必要です）"
+                    .to_string(),
+                navigate_to: Some("settings".to_string()),
+            });
+        }
+
+        // 3. 未実装の配信ターゲット → 警告
+        match settings.default_deliver_target.as_str() {
+            "file_append" => {
+                issues.push(SetupIssue {
+                    category: "delivery".to_string(),
+                    severity: "warning".to_string(),
+                    message: "ファイル追記（file_append）は現在未実装です".to_string(),
+                    action: "Clipboard または Paste をご利用ください".to_string(),
+                    navigate_to: Some("settings".to_string()),
+                });
+            }
+            "webhook" => {
+                issues.push(SetupIssue {
+                    category: "delivery".to_string(),
+                    severity: "warning".to_string(),
+                    message: "Webhook 配信は現在未実装です".to_string(),
+                    action: "Clipboard または Paste をご利用ください".to_string(),
+                    navigate_to: Some("settings".to_string()),
+                });
+            }
+            _ => {}
+        }
+
+        // 4. マイク権限チェック
+        use crate::infra::os_integration::PermissionState;
+        let permissions = self.check_permissions();
+        if permissions.microphone != PermissionState::Granted {
+            issues.push(SetupIssue {
+                category: "permission".to_string(),
+                severity: "error".to_string(),
+                message: "マイクのアクセス権限がありません".to_string(),
+                action: "システム環境設定でマイクへのアクセスを許可してください".to_string(),
+                navigate_to: Some("permissions".to_string()),
+            });
+        }
+
+        // 5. キー入力出力にはアクセシビリティ権限が必要
+        if settings.default_deliver_target == "keystroke"
+            && permissions.accessibility != PermissionState::Granted
+        {
+            issues.push(SetupIssue {
+                category: "delivery".to_string(),
+                severity: "warning".to_string(),
+                message: "アクセシビリティ権限がないため、キー入力出力はクリップボードにフォールバックします"
+                    .to_string(),
+                action: "システム環境設定でアクセシビリティへのアクセスを許可してください"
+                    .to_string(),
+                navigate_to: Some("permissions".to_string()),
+            });
+        }
+
+        let ready = !issues.iter().any(|i| i.severity == "error");
+
+        SetupStatus {
+            ready,
+            issues,
+            active_stt_engine: stt_name,
+            active_rewriter: rewriter_name,
+        }
+    }
+}
+
+/// `max_chars` 文字を超える場合だけ、先頭 `max_chars` 文字（UTF-8 の文字境界）で切り詰める（0=無制限）
+fn truncate_chars(text: &str, max_chars: u32) -> Option<&str> {
+    if max_chars == 0 {
+        return None;
+    }
+    text.char_indices()
+        .nth(max_chars as usize)
+        .map(|(end, _)| &text[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::audio::pipeline::save_segment_wav;
+    use crate::infra::output::MemoryClipboard;
+    use crate::infra::rewriter::NoopRewriter;
+    use crate::infra::stt::NoopSttEngine;
+    use std::path::Path;
+
+    fn make_service() -> AppService {
+        make_service_with_settings(AppSettings::default())
+    }
+
+    fn make_service_with_settings(settings: AppSettings) -> AppService {
+        make_service_with_clipboard(settings).0
+    }
+
+    /// テスト用クリップボード付きのサービス（実クリップボードに依存しない）
+    fn make_service_with_clipboard(settings: AppSettings) -> (AppService, Arc<MemoryClipboard>) {
+        let storage = Storage::open_in_memory().unwrap();
+        storage.save_settings(&settings).unwrap();
+        let stt: Arc<dyn SttEngine> = Arc::new(NoopSttEngine);
+        let rewriter: Arc<dyn Rewriter> = Arc::new(NoopRewriter);
+        let clipboard = Arc::new(MemoryClipboard::default());
+        let service =
+            AppService::new(storage, stt, rewriter).with_clipboard_backend(clipboard.clone());
+        (service, clipboard)
+    }
+
+    #[test]
+    fn check_setup_status_reports_noop_stt_as_error() {
+        let service = make_service();
+        let status = service.check_setup_status();
+
+        assert_eq!(status.active_stt_engine, "noop");
+        let stt_issues: Vec<_> = status
+            .issues
+            .iter()
+            .filter(|i| i.category == "stt")
+            .collect();
+        assert_eq!(stt_issues.len(), 1);
+        assert_eq!(stt_issues[0].severity, "error");
+    }
+
+    #[test]
+    fn check_setup_status_reports_noop_rewriter_warning_when_rewrite_enabled() {
+        let mut settings = AppSettings::default();
+        settings.rewrite_enabled = true;
+        let service = make_service_with_settings(settings);
+
+        let status = service.check_setup_status();
+
+        let rewriter_issues: Vec<_> = status
+            .issues
+            .iter()
+            .filter(|i| i.category == "rewriter")
+            .collect();
+        assert_eq!(rewriter_issues.len(), 1);
+        assert_eq!(rewriter_issues[0].severity, "warning");
+    }
+
+    #[test]
+    fn check_setup_status_no_rewriter_warning_when_rewrite_disabled() {
+        let service = make_service();
+        let status = service.check_setup_status();
+
+        let rewriter_issues: Vec<_> = status
+            .issues
+            .iter()
+            .filter(|i| i.category == "rewriter")
+            .collect();
+        assert!(rewriter_issues.is_empty());
+    }
+
+    #[test]
+    fn check_setup_status_reports_file_append_as_warning() {
+        let mut settings = AppSettings::default();
+        settings.default_deliver_target = "file_append".to_string();
+        let service = make_service_with_settings(settings);
+
+        let status = service.check_setup_status();
+
+        let delivery_issues: Vec<_> = status
+            .issues
+            .iter()
+            .filter(|i| i.category == "delivery")
+            .collect();
+        assert_eq!(delivery_issues.len(), 1);
+        assert_eq!(delivery_issues[0].severity, "warning");
+    }
+
+    #[test]
+    fn check_setup_status_warns_keystroke_without_accessibility() {
+        let mut settings = AppSettings::default();
+        settings.default_deliver_target = "keystroke".to_string();
+        let service = make_service_with_settings(settings);
+
+        let status = service.check_setup_status();
+        let has_warning = status
+            .issues
+            .iter()
+            .any(|i| i.category == "delivery" && i.severity == "warning");
+        let granted = service.check_permissions().accessibility
+            == crate::infra::os_integration::PermissionState::Granted;
+        assert_eq!(has_warning, !granted);
+    }
+
+    #[test]
+    fn check_setup_status_reports_webhook_as_warning() {
+        let mut settings = AppSettings::default();
+        settings.default_deliver_target = "webhook".to_string();
+        let service = make_service_with_settings(settings);
+
+        let status = service.check_setup_status();
+
+        let delivery_issues: Vec<_> = status
+            .issues
+            .iter()
+            .filter(|i| i.category == "delivery")
+            .collect();
+        assert_eq!(delivery_issues.len(), 1);
+        assert_eq!(delivery_issues[0].severity, "warning");
+    }
+
+    #[test]
+    fn check_setup_status_ready_false_when_stt_is_noop() {
+        let service = make_service();
+        let status = service.check_setup_status();
+
+        // noop STT → error exists → ready should be false
+        assert!(!status.ready);
+    }
+
+    #[test]
+    fn check_setup_status_engine_names() {
+        let service = make_service();
+        let status = service.check_setup_status();
+
+        assert_eq!(status.active_stt_engine, "noop");
+        assert_eq!(status.active_rewriter, "noop");
+    }
+
+    #[test]
+    fn delete_session_rejects_active_session() {
+        let service = make_service();
+        let (session_id, _) = service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+
+        let err = service.delete_session(&session_id).unwrap_err();
+        assert_eq!(err.code, crate::domain::error::ErrorCode::InvalidState);
+
+        service.stop_session().unwrap();
+        let result = service.delete_session(&session_id).unwrap();
+        assert_eq!(result.sessions_deleted, 1);
+        assert!(service.get_session(&session_id).unwrap().is_none());
+    }
+
+    #[test]
+    fn cleanup_old_data_keeps_rows_and_audio_when_session_delete_fails() {
+        let service = make_service();
+        let wav = std::env::temp_dir().join(format!("vt-test-{}.wav", uuid::Uuid::new_v4()));
+        std::fs::write(&wav, b"RIFF").unwrap();
+        {
+            let storage = service.storage.lock().unwrap();
+            storage
+                .insert_session("s-old", Mode::Raw, "2020-01-01T00:00:00Z")
+                .unwrap();
+            storage
+                .insert_segment("seg-old", "s-old", Mode::Raw, "2020-01-01T00:00:00Z")
+                .unwrap();
+            storage
+                .set_segment_audio_path("seg-old", &wav.to_string_lossy())
+                .unwrap();
+            // セグメント削除の後に行うセッション削除だけを失敗させる
+            storage
+                .execute_batch(
+                    "CREATE TRIGGER fail_session_delete BEFORE DELETE ON sessions
+                     BEGIN SELECT RAISE(ABORT, 'session delete failed'); END;",
+                )
+                .unwrap();
+        }
+
+        assert!(service.cleanup_old_data(30).is_err());
+
+        // セグメントの削除もロールバックされ、音声ファイルも残る
+        let detail = service.get_session("s-old").unwrap().unwrap();
+        assert_eq!(detail.segments.len(), 1);
+        assert!(wav.exists());
+
+        service
+            .storage
+            .lock()
+            .unwrap()
+            .execute_batch("DROP TRIGGER fail_session_delete;")
+            .unwrap();
+        let result = service.cleanup_old_data(30).unwrap();
+        assert_eq!(
+            result,
+            DeleteSessionResult {
+                sessions_deleted: 1,
+                segments_deleted: 1,
+                audio_files_deleted: 1,
+            }
+        );
+        assert!(!wav.exists());
+    }
+
+    #[test]
+    fn diagnose_reports_noop_engine_and_missing_api_key() {
+        let service = make_service();
+        let report = service.diagnose();
+
+        let find = |name: &str| report.checks.iter().find(|c| c.name == name).unwrap();
+        assert!(!find("stt_engine").ok);
+        // デフォルトは Soniox で API キー未設定
+        assert!(!find("soniox_api_key").ok);
+        assert!(report.checks.iter().any(|c| c.name == "input_device"));
+        assert!(!report.ok);
+    }
+
+    #[test]
+    fn update_settings_rejects_invalid_values_without_saving() {
+        let service = make_service();
+        let settings = AppSettings {
+            hotkey_toggle: String::new(),
+            segment_ttl_days: 99_999,
+            ..Default::default()
+        };
+
+        let err = service.update_settings(settings).unwrap_err();
+        assert_eq!(err.code, crate::domain::error::ErrorCode::InvalidSettings);
+        assert!(err.message.contains("hotkey_toggle"));
+        assert!(err.message.contains("segment_ttl_days"));
+
+        let saved = service.get_settings().unwrap();
+        assert_eq!(saved.hotkey_toggle, AppSettings::default().hotkey_toggle);
+    }
+
+    #[test]
+    fn pipeline_transcript_stores_redacted_text_when_enabled() {
+        let service = make_service_with_settings(AppSettings {
+            redaction_enabled: true,
+            ..Default::default()
+        });
+        let (session_id, _) = service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+
+        let text = service
+            .on_pipeline_transcript("seg-1", "メールは a@example.com です", 0.9, &[], None)
+            .unwrap();
+        assert_eq!(text, "メールは [EMAIL] です");
+
+        let detail = service.get_session(&session_id).unwrap().unwrap();
+        assert_eq!(detail.segments[0].raw_text, "メールは [EMAIL] です");
+    }
+
+    #[test]
+    fn pipeline_transcript_converts_numerals_when_enabled() {
+        let service = make_service_with_settings(AppSettings {
+            number_normalization_enabled: true,
+            ..Default::default()
+        });
+        service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+
+        let text = service
+            .on_pipeline_transcript("seg-1", "ひゃくにじゅうえんです", 0.9, &[], None)
+            .unwrap();
+        assert_eq!(text, "120円です");
+    }
+
+    #[test]
+    fn segments_keep_mode_active_at_capture_time() {
+        let service = make_service();
+        let (session_id, _) = service
+            .start_session(Mode::Memo, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        service
+            .on_pipeline_transcript("seg-memo", "買い物リスト", 0.9, &[], None)
+            .unwrap();
+        service.set_mode(Mode::Tech).unwrap();
+        service
+            .on_pipeline_transcript("seg-tech", "Tokio のランタイム", 0.9, &[], None)
+            .unwrap();
+
+        let detail = service.get_session(&session_id).unwrap().unwrap();
+        let modes: Vec<_> = detail
+            .segments
+            .iter()
+            .map(|s| (s.segment_id.as_str(), s.mode))
+            .collect();
+        assert!(modes.contains(&("seg-memo", Mode::Memo)));
+        assert!(modes.contains(&("seg-tech", Mode::Tech)));
+
+        // 後からモードを戻しても、記録済みセグメントのモードは変わらない
+        service.set_mode(Mode::Memo).unwrap();
+        let storage = service.storage.lock().unwrap();
+        assert_eq!(
+            storage.get_segment_mode("seg-tech").unwrap(),
+            Some(Mode::Tech)
+        );
+    }
+
+    #[test]
+    fn pipeline_transcript_collapses_repetitions_when_filter_enabled() {
+        let service = make_service_with_settings(AppSettings {
+            transcript_filter_enabled: true,
+            ..Default::default()
+        });
+        service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+
+        let looped = format!("{}ご視聴ありがとうございました", "はい、".repeat(10));
+        let text = service
+            .on_pipeline_transcript("seg-1", &looped, 0.9, &[], None)
+            .unwrap();
+        assert_eq!(text, "はい、");
+    }
+
+    /// 呼び出し回数を数えるリライター
+    struct CountingRewriter {
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl Rewriter for CountingRewriter {
+        async fn rewrite(
+            &self,
+            text: &str,
+            _ctx: RewriteContext,
+        ) -> Result<String, crate::infra::rewriter::RewriteError> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(format!("[rewritten] {text}"))
+        }
+
+        fn name(&self) -> &str {
+            "counting"
+        }
+    }
+
+    #[tokio::test]
+    async fn auto_rewrite_runs_only_for_enabled_modes() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .save_settings(&AppSettings {
+                auto_rewrite_by_mode: std::collections::HashMap::from([
+                    (Mode::Memo, true),
+                    (Mode::Raw, false),
+                ]),
+                ..Default::default()
+            })
+            .unwrap();
+        let rewriter = Arc::new(CountingRewriter {
+            calls: Default::default(),
+        });
+        let service = AppService::new(storage, Arc::new(NoopSttEngine), rewriter.clone());
+
+        let (memo_session, _) = service
+            .start_session(Mode::Memo, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        let text = service
+            .on_pipeline_transcript("seg-memo", "メモです", 0.9, &[], None)
+            .unwrap();
+        let result = service
+            .auto_rewrite_segment("seg-memo", &text)
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            Some((Mode::Memo, "[rewritten] メモです".to_string()))
+        );
+        let detail = service.get_session(&memo_session).unwrap().unwrap();
+        assert_eq!(
+            detail.segments[0].rewritten_text.as_deref(),
+            Some("[rewritten] メモです")
+        );
+        service.stop_session().unwrap();
+
+        service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        let text = service
+            .on_pipeline_transcript("seg-raw", "そのまま", 0.9, &[], None)
+            .unwrap();
+        let result = service
+            .auto_rewrite_segment("seg-raw", &text)
+            .await
+            .unwrap();
+        assert_eq!(result, None);
+
+        assert_eq!(rewriter.calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn auto_rewrite_follows_mode_captured_with_segment() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .save_settings(&AppSettings {
+                auto_rewrite_by_mode: std::collections::HashMap::from([
+                    (Mode::Memo, true),
+                    (Mode::Raw, false),
+                ]),
+                ..Default::default()
+            })
+            .unwrap();
+        let rewriter = Arc::new(CountingRewriter {
+            calls: Default::default(),
+        });
+        let service = AppService::new(storage, Arc::new(NoopSttEngine), rewriter.clone());
+        let (session_id, _) = service
+            .start_session(Mode::Memo, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+
+        // Memo で録音したセグメントの書き起こしが、Raw へ切り替えた後に届く
+        service.set_mode(Mode::Raw).unwrap();
+        let text = service
+            .on_pipeline_transcript("seg-memo", "メモです", 0.9, &[], Some(Mode::Memo))
+            .unwrap();
+        let result = service
+            .auto_rewrite_segment("seg-memo", &text)
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            Some((Mode::Memo, "[rewritten] メモです".to_string()))
+        );
+
+        // 逆に Raw で録音したセグメントは、Memo へ戻した後に届いてもリライトしない
+        service.set_mode(Mode::Memo).unwrap();
+        let text = service
+            .on_pipeline_transcript("seg-raw", "そのまま", 0.9, &[], Some(Mode::Raw))
+            .unwrap();
+        let result = service
+            .auto_rewrite_segment("seg-raw", &text)
+            .await
+            .unwrap();
+        assert_eq!(result, None);
+
+        let detail = service.get_session(&session_id).unwrap().unwrap();
+        let modes: Vec<_> = detail.segments.iter().map(|s| s.mode).collect();
+        assert_eq!(modes, vec![Mode::Memo, Mode::Raw]);
+        assert_eq!(rewriter.calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    /// テスト用の STT エンジン（返すテキスト・待ち時間・中断までのブロックを組み合わせる）
+    #[derive(Default)]
+    struct MockSttEngine {
+        /// 返すテキスト（None なら NoSpeech）
+        text: Option<&'static str>,
+        /// 結果を返す前に同期的に待つ時間
+        delay: Duration,
+        /// 中断されるまで同期的にブロックする（Whisper の推論を模す）
+        block_until_canceled: bool,
+        /// 書き起こしを始めた回数
+        started: AtomicUsize,
+        /// 切り替えたモデルのパス
+        model: Mutex<Option<std::path::PathBuf>>,
+    }
+
+    impl MockSttEngine {
+        /// 固定テキストを返すエンジン
+        fn fixed(text: &'static str) -> Self {
+            Self {
+                text: Some(text),
+                ..Default::default()
+            }
+        }
+
+        /// 中断されるまでブロックするエンジン
+        fn blocking() -> Self {
+            Self {
+                block_until_canceled: true,
+                ..Default::default()
+            }
+        }
+
+        fn with_delay(mut self, delay: Duration) -> Self {
+            self.delay = delay;
+            self
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl SttEngine for MockSttEngine {
+        async fn transcribe(
+            &self,
+            _audio: AudioSegment,
+            ctx: SttContext,
+        ) -> Result<crate::infra::stt::TranscriptResult, crate::infra::stt::SttError> {
+            use crate::infra::stt::SttError;
+
+            self.started.fetch_add(1, Ordering::SeqCst);
+            if self.block_until_canceled {
+                let started = Instant::now();
+                while !ctx.cancel.is_canceled() {
+                    if started.elapsed() > Duration::from_secs(30) {
+                        return Err(SttError::Timeout);
+                    }
+                    std::thread::sleep(Duration::from_millis(5));
+                }
+                return Err(SttError::Canceled);
+            }
+            std::thread::sleep(self.delay);
+            let text = self.text.ok_or(SttError::NoSpeech)?;
+            Ok(crate::infra::stt::TranscriptResult {
+                text: text.to_string(),
+                confidence: 0.95,
+                is_partial: false,
+                timings: Vec::new(),
+            })
+        }
+
+        fn supports_partial(&self) -> bool {
+            false
+        }
+
+        fn name(&self) -> &str {
+            "mock"
+        }
+
+        fn switch_model(&self, model_path: &Path) -> Result<(), crate::infra::stt::SttError> {
+            *self.model.lock().unwrap() = Some(model_path.to_path_buf());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn retranscribe_segment_updates_stored_text() {
+        let service = make_service();
+        let (session_id, _) = service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        service
+            .on_pipeline_transcript("seg-1", "聞き間違い", 0.5, &[], None)
+            .unwrap();
+
+        let wav_path =
+            std::env::temp_dir().join(format!("vt-retranscribe-{}.wav", uuid::Uuid::new_v4()));
+        wav::write_wav(&wav_path, &vec![0.1; 1600], 16000, 16).unwrap();
+        service
+            .storage
+            .lock()
+            .unwrap()
+            .set_segment_audio_path("seg-1", wav_path.to_str().unwrap())
+            .unwrap();
+
+        let text = service
+            .retranscribe_segment(&session_id, "seg-1", &MockSttEngine::fixed("正しい用語"))
+            .await
+            .unwrap();
+        assert_eq!(text, "正しい用語");
+
+        let detail = service.get_session(&session_id).unwrap().unwrap();
+        assert_eq!(detail.segments[0].raw_text, "正しい用語");
+        assert!((detail.segments[0].confidence - 0.95).abs() < f32::EPSILON);
+
+        // 音声が削除済みなら E_AUDIO_FORMAT
+        std::fs::remove_file(&wav_path).unwrap();
+        let err = service
+            .retranscribe_segment(&session_id, "seg-1", &MockSttEngine::fixed("x"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, crate::domain::error::ErrorCode::AudioFormat);
+    }
+
+    #[test]
+    fn reapply_post_processing_uses_new_dictionary_entries() {
+        let service = make_service();
+        let (session_id, _) = service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        service
+            .on_pipeline_transcript("seg-1", "くろーどで書く", 0.9, &[], None)
+            .unwrap();
+        service
+            .on_pipeline_transcript("seg-2", "手で書く", 0.9, &[], None)
+            .unwrap();
+
+        service
+            .upsert_dictionary(DictionaryEntry {
+                id: None,
+                scope: crate::domain::types::DictionaryScope::Global,
+                mode: None,
+                pattern: "くろーど".into(),
+                replacement: "Claude".into(),
+                priority: 0,
+                enabled: true,
+                word_boundary: false,
+            })
+            .unwrap();
+
+        // 変わったセグメントだけを更新する
+        assert_eq!(service.reapply_post_processing(&session_id).unwrap(), 1);
+        let detail = service.get_session(&session_id).unwrap().unwrap();
+        assert_eq!(detail.segments[0].raw_text, "Claudeで書く");
+        assert_eq!(detail.segments[1].raw_text, "手で書く");
 
-        self.metrics.inc_segments_delivered();
-        self.metrics
-            .record_latency("deliver", start.elapsed().as_millis() as u64);
+        // 後処理前のテキストから処理し直すので、何度やり直しても結果は変わらない
+        assert_eq!(service.reapply_post_processing(&session_id).unwrap(), 0);
 
-        Ok(transition)
+        let err = service.reapply_post_processing("missing").unwrap_err();
+        assert_eq!(err.code, crate::domain::error::ErrorCode::InvalidState);
     }
 
-    pub fn deliver_last(
-        &self,
-        target: Option<DeliverTarget>,
-    ) -> Result<(StateTransition, String, DeliverTarget), AppError> {
-        let start = std::time::Instant::now();
-        let target = self.resolve_deliver_target(target)?;
+    #[test]
+    fn pipeline_delivers_synthetic_audio_end_to_end() {
+        use crate::infra::audio::source::VecAudioSource;
+
+        let settings = AppSettings {
+            vad: Some(VadConfig {
+                energy_threshold: 0.01,
+                adaptive_threshold: false,
+                speech_start_ms: 10,
+                silence_timeout_ms: 30,
+                ..VadConfig::default()
+            }),
+            ..AppSettings::default()
+        };
+        let storage = Storage::open_in_memory().unwrap();
+        storage.save_settings(&settings).unwrap();
+        // 発話 200ms → 無音 100ms（10ms フレーム @16kHz）
+        let frames: Vec<Vec<f32>> =
+            [vec![vec![0.05; 160]; 20], vec![vec![0.001; 160]; 10]].concat();
+        let clipboard = Arc::new(MemoryClipboard::default());
+        let service = AppService::new(
+            storage,
+            Arc::new(MockSttEngine::fixed("合成音声のテスト")),
+            Arc::new(NoopRewriter),
+        )
+        .with_clipboard_backend(clipboard.clone())
+        .with_audio_source(Arc::new(VecAudioSource::new(frames, 16000)));
+
+        let (session_id, _) = service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        service.toggle_recording().unwrap();
+        let events = service.start_pipeline().unwrap();
+
+        // 転送スレッドと同じく、確定結果を保存してから配信する
+        let mut finals = 0;
+        for event in events {
+            if let PipelineEvent::TranscriptFinal {
+                segment_id,
+                text,
+                confidence,
+                timings,
+                mode,
+            } = event
+            {
+                let processed = service
+                    .on_pipeline_transcript(&segment_id, &text, confidence, &timings, mode)
+                    .unwrap();
+                service.deliver_text(&processed).unwrap();
+                finals += 1;
+            }
+        }
+        service.on_pipeline_events_drained();
+        service.pause_recording().unwrap();
+
+        assert_eq!(finals, 1);
+        assert_eq!(clipboard.text(), "合成音声のテスト");
+        let detail = service.get_session(&session_id).unwrap().unwrap();
+        assert_eq!(detail.segments.len(), 1);
+        assert_eq!(detail.segments[0].raw_text, "合成音声のテスト");
+    }
 
-        let session_id = {
-            let mgr = self.session_mgr.lock().unwrap();
-            mgr.active()
-                .map(|s| s.session_id.clone())
-                .ok_or_else(|| AppError::internal("アクティブセッションがありません"))?
+    #[test]
+    fn duration_limit_pauses_recording_once() {
+        use crate::infra::audio::source::VecAudioSource;
+
+        let settings = AppSettings {
+            max_session_duration_secs: 1,
+            ..AppSettings::default()
         };
+        let storage = Storage::open_in_memory().unwrap();
+        storage.save_settings(&settings).unwrap();
+        // 無音 1.5 秒（10ms フレーム @16kHz）
+        let frames = vec![vec![0.0; 160]; 150];
+        let service = AppService::new(storage, Arc::new(NoopSttEngine), Arc::new(NoopRewriter))
+            .with_audio_source(Arc::new(VecAudioSource::new(frames, 16000)));
+
+        let (session_id, _) = service.start_session(Mode::Raw, None).unwrap();
+        service.toggle_recording().unwrap();
+        let events = service.start_pipeline().unwrap();
+
+        // 転送スレッドと同じく、上限の通知を受けたら録音を止める
+        let mut limits = Vec::new();
+        for event in events {
+            if matches!(event, PipelineEvent::SessionDurationLimit) {
+                limits.push(service.on_session_duration_limit().unwrap());
+            }
+        }
+        service.on_pipeline_events_drained();
+
+        assert_eq!(limits.len(), 1);
+        let (limit, transition) = limits.remove(0).unwrap();
+        assert_eq!(
+            limit,
+            SessionDurationLimit {
+                session_id: session_id.clone(),
+                recorded_secs: 1,
+                limit_secs: 1,
+            }
+        );
+        assert_eq!(transition.new_state, SessionState::Idle);
+        assert_eq!(service.current_snapshot().state, Some(SessionState::Idle));
 
-        let storage = self.storage.lock().unwrap();
-        let detail = storage
-            .get_session_detail(&session_id)?
-            .ok_or_else(|| AppError::internal("セッション詳細が見つかりません"))?;
-        drop(storage);
+        // 録音を止めた後に届いた通知では何もしない
+        assert!(service.on_session_duration_limit().unwrap().is_none());
+        // 同じセッションでは上限を使い切っているため、次の録音はすぐに止める
+        assert_eq!(service.remaining_recording_ms(Some(&session_id), 1), 1);
+    }
 
-        let last_segment = detail
-            .segments
-            .last()
-            .ok_or_else(|| AppError::internal("セグメントがありません"))?;
+    #[tokio::test]
+    async fn transcribe_file_stores_segments_in_new_session() {
+        let settings = AppSettings {
+            vad: Some(VadConfig {
+                energy_threshold: 0.01,
+                adaptive_threshold: false,
+                speech_start_ms: 10,
+                silence_timeout_ms: 30,
+                ..VadConfig::default()
+            }),
+            ..AppSettings::default()
+        };
+        let storage = Storage::open_in_memory().unwrap();
+        storage.save_settings(&settings).unwrap();
+        let service = AppService::new(
+            storage,
+            Arc::new(MockSttEngine::fixed("ファイルの書き起こし")),
+            Arc::new(NoopRewriter),
+        );
 
-        let text = last_segment
-            .rewritten_text
-            .as_deref()
-            .unwrap_or(&last_segment.raw_text);
+        // 48kHz の録音: 発話 200ms → 無音 200ms → 発話 200ms → 無音 200ms
+        let utterance = [vec![0.05; 9600], vec![0.001; 9600]].concat();
+        let samples = utterance.repeat(2);
+        let path = std::env::temp_dir().join(format!("vt-file-{}.wav", uuid::Uuid::new_v4()));
+        wav::write_wav(&path, &samples, 48000, 16).unwrap();
 
-        self.output_router.deliver(target, text)?;
+        let mut progress = Vec::new();
+        let result = service
+            .transcribe_file(&path, Mode::Memo, |p| progress.push(p))
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((result.transcribed, result.failed), (2, 0));
+        assert_eq!(progress.len(), 2);
+        assert_eq!((progress[1].completed, progress[1].total), (2, 2));
+        let detail = service.get_session(&result.session_id).unwrap().unwrap();
+        assert_eq!(detail.segments.len(), 2);
+        assert_eq!(detail.segments[0].raw_text, "ファイルの書き起こし");
+        assert_eq!(detail.segments[0].mode, Mode::Memo);
+        // アクティブセッションは作らない
+        assert!(service.current_session_id().is_none());
+    }
 
-        let mgr = self.session_mgr.lock().unwrap();
-        let current_state = mgr.active().map(|s| s.state.as_str().to_string());
-        drop(mgr);
+    #[test]
+    fn switch_whisper_model_validates_and_persists_known_sizes() {
+        let dir = std::env::temp_dir().join(format!("vt-models-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("ggml-small.bin"), b"x").unwrap();
+        std::fs::write(dir.join("ggml-custom.bin"), b"x").unwrap();
+        let manager = WhisperModelManager::new(dir.clone());
+
+        let engine = Arc::new(MockSttEngine::default());
+        let service = AppService::new(
+            Storage::open_in_memory().unwrap(),
+            engine.clone(),
+            Arc::new(NoopRewriter),
+        );
 
-        let text_result = text.to_string();
+        let err = service
+            .switch_whisper_model(&manager, "medium")
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidSettings);
+        assert!(engine.model.lock().unwrap().is_none());
 
-        self.metrics.inc_segments_delivered();
-        self.metrics
-            .record_latency("deliver", start.elapsed().as_millis() as u64);
+        let model = service.switch_whisper_model(&manager, "small").unwrap();
+        assert_eq!(
+            *engine.model.lock().unwrap(),
+            Some(dir.join("ggml-small.bin"))
+        );
+        assert_eq!(model.name, "small");
+        assert_eq!(
+            service.get_settings().unwrap().whisper_model_size,
+            WhisperModelSize::Small
+        );
 
-        if current_state.as_deref() == Some("delivering") {
-            let now = chrono::Utc::now().to_rfc3339();
-            let mut mgr = self.session_mgr.lock().unwrap();
-            let transition = mgr.on_deliver_done(now.clone())?;
-            let storage = self.storage.lock().unwrap();
-            storage.update_session_state(
-                &transition.session_id,
-                transition.new_state.as_str(),
-                &now,
-            )?;
-            Ok((transition, text_result, target))
-        } else {
-            Ok((
-                StateTransition {
-                    session_id,
-                    prev_state: current_state.unwrap_or_default(),
-                    new_state: SessionState::Idle,
-                },
-                text_result,
-                target,
-            ))
-        }
+        // 既知サイズ以外はエンジンだけ切り替え、設定はそのまま
+        service.switch_whisper_model(&manager, "custom").unwrap();
+        assert_eq!(
+            *engine.model.lock().unwrap(),
+            Some(dir.join("ggml-custom.bin"))
+        );
+        assert_eq!(
+            service.get_settings().unwrap().whisper_model_size,
+            WhisperModelSize::Small
+        );
+
+        // モデル切り替えに対応しないエンジンは E_STT_UNAVAILABLE
+        let err = make_service()
+            .switch_whisper_model(&manager, "small")
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::SttUnavailable);
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 
-    pub fn get_last_segment_for_rewrite(&self) -> Result<(String, String, Mode), AppError> {
-        let (session_id, mode) = {
-            let mgr = self.session_mgr.lock().unwrap();
-            let s = mgr
-                .active()
-                .ok_or_else(|| AppError::internal("アクティブセッションがありません"))?;
-            (s.session_id.clone(), s.mode)
+    #[test]
+    fn stop_session_aborts_blocking_transcription_promptly() {
+        let storage = Storage::open_in_memory().unwrap();
+        let service = AppService::new(
+            storage,
+            Arc::new(MockSttEngine::blocking()),
+            Arc::new(NoopRewriter),
+        );
+        let (session_id, _) = service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        let dir = std::env::temp_dir().join(format!("vt-cancel-{}", uuid::Uuid::new_v4()));
+        let path = save_segment_wav(&dir, "seg-1", &[0.1; 1600], 16000, 16).unwrap();
+        service
+            .on_pipeline_audio_segment("seg-1", path.to_str().unwrap())
+            .unwrap();
+
+        let started = Instant::now();
+        let result = std::thread::scope(|scope| {
+            let worker = scope.spawn(|| {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap()
+                    .block_on(service.transcribe_session(&session_id, |_| {}))
+            });
+            std::thread::sleep(Duration::from_millis(100));
+            service.stop_session().unwrap();
+            worker.join().unwrap()
+        });
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(result.unwrap_err().code, ErrorCode::Canceled);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn stop_session_keeps_pending_segment_as_audio_only() {
+        use crate::infra::audio::source::VecAudioSource;
+
+        let dir = std::env::temp_dir().join(format!("vt-stop-{}", uuid::Uuid::new_v4()));
+        let settings = AppSettings {
+            vad: Some(VadConfig {
+                energy_threshold: 0.01,
+                adaptive_threshold: false,
+                speech_start_ms: 10,
+                silence_timeout_ms: 5000,
+                ..VadConfig::default()
+            }),
+            audio_dir: Some(dir.clone()),
+            ..AppSettings::default()
         };
+        let storage = Storage::open_in_memory().unwrap();
+        storage.save_settings(&settings).unwrap();
+        // 発話 200ms のまま入力が終わる（無音タイムアウト前なので最終セグメントとして書き起こす）
+        let frames = vec![vec![0.05; 160]; 20];
+        let engine = Arc::new(MockSttEngine::blocking());
+        let service = AppService::new(storage, engine.clone(), Arc::new(NoopRewriter))
+            .with_audio_source(Arc::new(VecAudioSource::new(frames, 16000)));
+
+        let (session_id, _) = service.start_session(Mode::Raw, None).unwrap();
+        service.toggle_recording().unwrap();
+        let _events = service.start_pipeline().unwrap();
+        let waiting = Instant::now();
+        while engine.started.load(Ordering::SeqCst) == 0 {
+            assert!(waiting.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        service.stop_session().unwrap();
 
-        let storage = self.storage.lock().unwrap();
-        let detail = storage
-            .get_session_detail(&session_id)?
-            .ok_or_else(|| AppError::internal("セッション詳細が見つかりません"))?;
+        // 書き起こし中だったセグメントは音声のみで残り、transcribe_session の対象になる
+        let detail = service.get_session(&session_id).unwrap().unwrap();
+        assert_eq!(detail.segments.len(), 1);
+        assert!(detail.segments[0].raw_text.is_empty());
+        let audio_path = service
+            .storage
+            .lock()
+            .unwrap()
+            .get_segment_audio_path(&detail.segments[0].segment_id)
+            .unwrap();
+        assert!(audio_path.is_some_and(|path| Path::new(&path).exists()));
+        std::fs::remove_dir_all(&dir).ok();
+    }
 
-        let last_segment = detail
-            .segments
-            .last()
-            .ok_or_else(|| AppError::internal("セグメントがありません"))?;
+    #[test]
+    fn rapid_toggles_change_state_once() {
+        let service = make_service();
+        service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+
+        // コマンド層と同じく、受け付けたトグルだけ状態を変える
+        let mut transitions = Vec::new();
+        for _ in 0..2 {
+            if service.admit_toggle() {
+                transitions.push(service.toggle_recording().unwrap());
+            }
+        }
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].new_state, SessionState::Recording);
+        assert_eq!(service.current_state().as_deref(), Some("recording"));
+
+        // 間隔が空けば受け付ける。0 なら間引かない
+        let later = Instant::now() + Duration::from_millis(301);
+        assert!(service.admit_toggle_at(later, 300));
+        assert!(service.admit_toggle_at(later, 0));
+    }
 
-        Ok((
-            last_segment.segment_id.clone(),
-            last_segment.raw_text.clone(),
-            mode,
-        ))
+    #[test]
+    fn audio_cache_stats_and_clear_keep_segment_audio() {
+        let dir = std::env::temp_dir().join(format!("vt-cache-{}", uuid::Uuid::new_v4()));
+        let service = make_service_with_settings(AppSettings {
+            audio_dir: Some(dir.clone()),
+            ..Default::default()
+        });
+        assert_eq!(
+            service.audio_cache_stats().unwrap(),
+            AudioCacheStats::default()
+        );
+
+        service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        let kept = save_segment_wav(&dir, "seg-1", &[0.1; 1600], 16000, 16).unwrap();
+        service
+            .on_pipeline_audio_segment("seg-1", kept.to_str().unwrap())
+            .unwrap();
+        let orphan = save_segment_wav(&dir, "orphan", &[0.1; 800], 16000, 16).unwrap();
+        std::fs::write(dir.join("notes.txt"), b"not audio").unwrap();
+
+        let stats = service.audio_cache_stats().unwrap();
+        assert_eq!(stats.file_count, 2);
+        let expected_bytes =
+            std::fs::metadata(&kept).unwrap().len() + std::fs::metadata(&orphan).unwrap().len();
+        assert_eq!(stats.total_bytes, expected_bytes);
+        assert!(stats.oldest.is_some());
+
+        // セグメントが参照している WAV と WAV 以外のファイルは残す
+        assert_eq!(service.clear_audio_cache().unwrap(), 1);
+        let after = service.audio_cache_stats().unwrap();
+        let (kept_exists, orphan_exists) = (kept.exists(), orphan.exists());
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(after.file_count, 1);
+        assert!(kept_exists && !orphan_exists);
     }
 
-    // ==================== Paste Router ====================
+    #[test]
+    fn shutdown_waits_for_in_flight_transcription() {
+        let storage = Storage::open_in_memory().unwrap();
+        let service = AppService::new(
+            storage,
+            Arc::new(MockSttEngine::fixed("終了前の発言").with_delay(Duration::from_millis(200))),
+            Arc::new(NoopRewriter),
+        );
+        let (session_id, _) = service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        let dir = std::env::temp_dir().join(format!("vt-shutdown-{}", uuid::Uuid::new_v4()));
+        let path = save_segment_wav(&dir, "seg-1", &[0.1; 1600], 16000, 16).unwrap();
+        service
+            .on_pipeline_audio_segment("seg-1", path.to_str().unwrap())
+            .unwrap();
+
+        let result = std::thread::scope(|scope| {
+            let worker = scope.spawn(|| {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap()
+                    .block_on(service.transcribe_session(&session_id, |_| {}))
+            });
+            while !service.job_queue.has_active_jobs() {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            service.shutdown(Duration::from_secs(5)).unwrap();
+            worker.join().unwrap()
+        });
+        std::fs::remove_dir_all(&dir).ok();
+
+        // キャンセルされずに書き起こしが保存され、セッションは idle で閉じられる
+        assert_eq!(result.unwrap().transcribed, 1);
+        let detail = service.get_session(&session_id).unwrap().unwrap();
+        assert_eq!(detail.segments[0].raw_text, "終了前の発言");
+        assert_eq!(detail.state, "idle");
+        assert!(service.current_session_id().is_none());
+        assert!(service
+            .storage
+            .lock()
+            .unwrap()
+            .latest_metrics_snapshot()
+            .unwrap()
+            .is_some());
+    }
 
-    pub fn paste_to_active_app(&self, text: &str) -> Result<PasteResult, AppError> {
-        let storage = self.storage.lock().unwrap();
-        let settings = storage.get_settings()?;
+    #[test]
+    fn shutdown_waits_for_final_pipeline_segment() {
+        let service = make_service();
+        let (session_id, _) = service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        // パイプライン停止時に書き起こした最後のセグメントを転送スレッドが保存する状況を模す
+        service.open_event_streams.fetch_add(1, Ordering::SeqCst);
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                std::thread::sleep(Duration::from_millis(100));
+                service
+                    .on_pipeline_transcript("seg-last", "最後の発言", 0.8, &[], None)
+                    .unwrap();
+                service.on_pipeline_events_drained();
+            });
+            service.shutdown(Duration::from_secs(5)).unwrap();
+        });
+
+        let detail = service.get_session(&session_id).unwrap().unwrap();
+        assert_eq!(detail.segments.len(), 1);
+        assert_eq!(detail.segments[0].raw_text, "最後の発言");
+        assert_eq!(detail.state, "idle");
+    }
+
+    #[test]
+    fn audio_only_segments_have_wav_path_and_no_transcript() {
+        let service = make_service();
+        let (session_id, _) = service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+
+        let dir = std::env::temp_dir().join(format!("vt-audio-only-{}", uuid::Uuid::new_v4()));
+        for segment_id in ["seg-1", "seg-2"] {
+            let path = save_segment_wav(&dir, segment_id, &[0.1; 1600], 16000, 16).unwrap();
+            service
+                .on_pipeline_audio_segment(segment_id, path.to_str().unwrap())
+                .unwrap();
+        }
+
+        let detail = service.get_session(&session_id).unwrap().unwrap();
+        assert_eq!(detail.segments.len(), 2);
+        let storage = service.storage.lock().unwrap();
+        for segment in &detail.segments {
+            assert_eq!(segment.raw_text, "");
+            let path = storage
+                .get_segment_audio_path(&segment.segment_id)
+                .unwrap()
+                .unwrap();
+            assert_eq!(wav::read_wav(Path::new(&path)).unwrap().samples.len(), 1600);
+        }
         drop(storage);
+        std::fs::remove_dir_all(&dir).ok();
+    }
 
-        PasteRouter::paste_if_allowlisted(text, &settings.paste_allowlist, settings.paste_confirm)
+    #[tokio::test]
+    async fn transcribe_session_processes_pending_audio_segments() {
+        let storage = Storage::open_in_memory().unwrap();
+        let stt: Arc<dyn SttEngine> = Arc::new(MockSttEngine::fixed("後から書き起こし"));
+        let service = AppService::new(storage, stt, Arc::new(NoopRewriter));
+        let (session_id, _) = service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+
+        let dir = std::env::temp_dir().join(format!("vt-batch-{}", uuid::Uuid::new_v4()));
+        for segment_id in ["seg-1", "seg-2"] {
+            let path = save_segment_wav(&dir, segment_id, &[0.1; 1600], 16000, 16).unwrap();
+            service
+                .on_pipeline_audio_segment(segment_id, path.to_str().unwrap())
+                .unwrap();
+        }
+        // 音声ファイルが消えたセグメント・音声を保存していないセグメントは失敗として続行
+        service
+            .on_pipeline_audio_segment("seg-missing", dir.join("missing.wav").to_str().unwrap())
+            .unwrap();
+        service
+            .storage
+            .lock()
+            .unwrap()
+            .insert_segment(
+                "seg-no-audio",
+                &session_id,
+                Mode::Raw,
+                &chrono::Utc::now().to_rfc3339(),
+            )
+            .unwrap();
+        // 書き起こし済みはスキップ
+        service
+            .on_pipeline_transcript("seg-done", "既存テキスト", 0.9, &[], None)
+            .unwrap();
+
+        let mut progress = Vec::new();
+        let result = service
+            .transcribe_session(&session_id, |p| progress.push(p))
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(
+            result,
+            TranscribeSessionResult {
+                transcribed: 2,
+                failed: 2,
+                skipped: 1,
+            }
+        );
+        assert_eq!(progress.len(), 4);
+        assert_eq!(progress.last().unwrap().completed, 4);
+        assert_eq!(progress.last().unwrap().total, 4);
+        let failed: Vec<_> = progress
+            .iter()
+            .filter(|p| p.error.is_some())
+            .map(|p| p.segment_id.as_str())
+            .collect();
+        assert_eq!(failed, vec!["seg-missing", "seg-no-audio"]);
+
+        let detail = service.get_session(&session_id).unwrap().unwrap();
+        let text_of = |id: &str| {
+            detail
+                .segments
+                .iter()
+                .find(|s| s.segment_id == id)
+                .unwrap()
+                .raw_text
+                .clone()
+        };
+        assert_eq!(text_of("seg-1"), "後から書き起こし");
+        assert_eq!(text_of("seg-2"), "後から書き起こし");
+        assert_eq!(text_of("seg-missing"), "");
+        assert_eq!(text_of("seg-no-audio"), "");
+        assert_eq!(text_of("seg-done"), "既存テキスト");
     }
 
-    // ==================== Queries ====================
+    #[test]
+    fn update_vad_config_persists_and_is_used_for_next_pipeline() {
+        let service = make_service();
+        assert_eq!(
+            AppService::resolve_vad_config(&service.get_settings().unwrap()),
+            VadConfig::for_engine(SttEngineChoice::Soniox)
+        );
 
-    pub fn get_history(
-        &self,
-        limit: u32,
-        cursor: Option<&str>,
-        query: Option<&str>,
-    ) -> Result<HistoryPage, AppError> {
-        let storage = self.storage.lock().unwrap();
-        storage.list_history(limit, cursor, query)
+        let config = VadConfig {
+            energy_threshold: 0.08,
+            silence_timeout_ms: 1200,
+            ..Default::default()
+        };
+        service.update_vad_config(config.clone()).unwrap();
+
+        let settings = service.get_settings().unwrap();
+        assert_eq!(settings.vad, Some(config.clone()));
+        assert_eq!(AppService::resolve_vad_config(&settings), config);
     }
 
-    pub fn get_session(&self, session_id: &str) -> Result<Option<SessionDetail>, AppError> {
-        let storage = self.storage.lock().unwrap();
-        storage.get_session_detail(session_id)
+    #[test]
+    fn accumulated_deliveries_are_joined_with_separator() {
+        let service = make_service_with_settings(AppSettings {
+            accumulate_deliveries: true,
+            accumulate_separator: crate::domain::settings::AccumulateSeparator::Bullet,
+            ..Default::default()
+        });
+
+        let first = service
+            .accumulate_for_delivery(&DeliverTarget::Clipboard, "牛乳を買う")
+            .unwrap();
+        assert_eq!(first, "- 牛乳を買う");
+        let second = service
+            .accumulate_for_delivery(&DeliverTarget::Clipboard, "銀行に電話")
+            .unwrap();
+        assert_eq!(second, "- 牛乳を買う\n- 銀行に電話");
+
+        service.clear_accumulated();
+        let after_clear = service
+            .accumulate_for_delivery(&DeliverTarget::Clipboard, "新しいメモ")
+            .unwrap();
+        assert_eq!(after_clear, "- 新しいメモ");
     }
 
-    // ==================== Dictionary ====================
+    #[test]
+    fn deliveries_are_not_accumulated_when_disabled() {
+        let service = make_service();
+        service
+            .accumulate_for_delivery(&DeliverTarget::Clipboard, "a")
+            .unwrap();
+        let text = service
+            .accumulate_for_delivery(&DeliverTarget::Clipboard, "b")
+            .unwrap();
+        assert_eq!(text, "b");
+    }
 
-    pub fn upsert_dictionary(&self, entry: DictionaryEntry) -> Result<String, AppError> {
-        let storage = self.storage.lock().unwrap();
-        storage.upsert_dictionary_entry(&entry)
+    /// 配信されたテキストを記録する出力先
+    struct RecordingOutput(Arc<Mutex<Vec<String>>>);
+
+    impl OutputTarget for RecordingOutput {
+        fn deliver(&self, text: &str) -> Result<(), AppError> {
+            self.0.lock().unwrap().push(text.to_string());
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "recording"
+        }
     }
 
-    pub fn list_dictionary(&self, scope: Option<&str>) -> Result<Vec<DictionaryEntry>, AppError> {
-        let storage = self.storage.lock().unwrap();
-        storage.list_dictionary_entries(scope)
+    #[test]
+    fn deliver_last_writes_transcript_to_clipboard_backend() {
+        let (service, clipboard) = make_service_with_clipboard(AppSettings {
+            verify_clipboard: true,
+            ..Default::default()
+        });
+        service
+            .start_session(
+                Mode::Raw,
+                DeliverPolicy::Clipboard {
+                    wrap: Some(WrapStyle::Quote),
+                },
+            )
+            .unwrap();
+        service
+            .on_pipeline_transcript("seg-1", "ＡＰＩの　仕様を確認", 0.9, &[], None)
+            .unwrap();
+
+        let target = service.deliver_text("APIの 仕様を確認").unwrap();
+        assert_eq!(target, DeliverTarget::Clipboard);
+        assert_eq!(clipboard.text(), "> APIの 仕様を確認");
+
+        let (_, result) = service.deliver_last(None, DeliverText::Raw).unwrap();
+        assert_eq!(result.text, "APIの 仕様を確認");
+        assert_eq!(clipboard.text(), "APIの 仕様を確認");
     }
 
-    // ==================== Settings ====================
+    #[test]
+    fn app_event_policy_emits_editor_payload_without_touching_clipboard() {
+        let (service, clipboard) = make_service_with_clipboard(AppSettings::default());
+        let editor_events = service.take_editor_events().unwrap();
+        service
+            .start_session(Mode::Raw, DeliverPolicy::AppEvent)
+            .unwrap();
+        service
+            .on_pipeline_transcript("seg-1", "議事録に追記", 0.9, &[], None)
+            .unwrap();
+
+        let (_, result) = service.deliver_last(None, DeliverText::Raw).unwrap();
+        assert_eq!(result.target, DeliverTarget::AppEvent);
+        assert_eq!(
+            editor_events.try_recv().unwrap(),
+            DeliverToEditorPayload {
+                text: "議事録に追記".to_string()
+            }
+        );
+        assert_eq!(clipboard.text(), "");
+        assert!(service.take_editor_events().is_none());
+    }
 
-    pub fn get_settings(&self) -> Result<AppSettings, AppError> {
-        let storage = self.storage.lock().unwrap();
-        storage.get_settings()
+    #[test]
+    fn deliver_text_dispatches_to_registered_custom_target() {
+        let service = make_service();
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        service.register_output_target("notes", RecordingOutput(delivered.clone()));
+        service
+            .start_session(
+                Mode::Raw,
+                DeliverPolicy::Custom {
+                    name: "notes".into(),
+                },
+            )
+            .unwrap();
+
+        let target = service.deliver_text("議事メモ").unwrap();
+        assert_eq!(target, DeliverTarget::Custom("notes".into()));
+        assert_eq!(*delivered.lock().unwrap(), vec!["議事メモ"]);
     }
 
-    pub fn update_settings(&self, settings: AppSettings) -> Result<(), AppError> {
-        let storage = self.storage.lock().unwrap();
-        storage.save_settings(&settings)
+    #[test]
+    fn deliver_to_overrides_target_once_without_changing_session() {
+        let (service, clipboard) = make_service_with_clipboard(AppSettings::default());
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        service.register_output_target(
+            DeliverTarget::FileAppend.as_str(),
+            RecordingOutput(delivered.clone()),
+        );
+        service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        let state_before = service.current_snapshot().state;
+
+        let target = service
+            .deliver_to("一度だけファイルへ", DeliverPolicy::FileAppend)
+            .unwrap();
+        assert_eq!(target, DeliverTarget::FileAppend);
+        assert_eq!(*delivered.lock().unwrap(), vec!["一度だけファイルへ"]);
+        assert_eq!(clipboard.text(), "");
+
+        // セッションのポリシーと状態はそのまま
+        assert_eq!(
+            service.resolve_deliver_target(None).unwrap(),
+            DeliverTarget::Clipboard
+        );
+        assert_eq!(service.current_snapshot().state, state_before);
+        service.deliver_text("次はクリップボード").unwrap();
+        assert_eq!(clipboard.text(), "次はクリップボード");
     }
 
-    // ==================== OS Integration ====================
+    /// 配信の通知を記録する observer
+    #[derive(Default)]
+    struct RecordingObserver(Mutex<Vec<(Option<String>, String, DeliverTarget)>>);
+
+    impl DeliveryObserver for RecordingObserver {
+        fn on_delivered(&self, session_id: Option<&str>, text: &str, target: &DeliverTarget) {
+            self.0.lock().unwrap().push((
+                session_id.map(str::to_string),
+                text.to_string(),
+                target.clone(),
+            ));
+        }
+    }
 
-    pub fn check_permissions(&self) -> PermissionStatus {
-        OsIntegration::check_all_permissions()
+    #[test]
+    fn delivery_observer_receives_delivered_text() {
+        let observer = Arc::new(RecordingObserver::default());
+        let (service, clipboard) = make_service_with_clipboard(AppSettings {
+            max_deliver_chars: 5,
+            ..Default::default()
+        });
+        let service = service.with_delivery_observer(observer.clone());
+        let (session_id, _) = service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+
+        service.deliver_text("こんにちは世界").unwrap();
+
+        // 切り詰め後の実際に配信したテキストが通知される
+        assert_eq!(clipboard.text(), "こんにちは");
+        assert_eq!(
+            *observer.0.lock().unwrap(),
+            vec![(
+                Some(session_id),
+                "こんにちは".to_string(),
+                DeliverTarget::Clipboard
+            )]
+        );
     }
 
-    // ==================== Metrics ====================
+    #[test]
+    fn segment_limit_stops_session_when_reached() {
+        let service = make_service_with_settings(AppSettings {
+            max_segments_per_session: 2,
+            ..Default::default()
+        });
+        let (session_id, _) = service.start_session(Mode::Raw, None).unwrap();
+
+        service
+            .on_pipeline_transcript("seg-1", "一つ目", 0.9, &[], None)
+            .unwrap();
+        assert!(service.enforce_segment_limit().unwrap().is_none());
+        service
+            .on_pipeline_transcript("seg-2", "二つ目", 0.9, &[], None)
+            .unwrap();
+
+        let (limit, transitions) = service.enforce_segment_limit().unwrap().unwrap();
+        assert_eq!(limit.session_id, session_id);
+        assert_eq!(limit.segment_count, 2);
+        assert_eq!(limit.action, SegmentLimitAction::Stop);
+        assert_eq!(limit.new_session_id, None);
+        assert_eq!(transitions.len(), 1);
+        assert!(service.current_session_id().is_none());
+    }
 
-    pub fn get_metrics(&self) -> MetricsSummary {
-        self.metrics.summary()
+    #[test]
+    fn segment_limit_rolls_over_to_new_session() {
+        let service = make_service_with_settings(AppSettings {
+            max_segments_per_session: 1,
+            segment_limit_action: SegmentLimitAction::NewSession,
+            ..Default::default()
+        });
+        let (first, _) = service.start_session(Mode::Memo, None).unwrap();
+        service
+            .on_pipeline_transcript("seg-1", "一つ目", 0.9, &[], None)
+            .unwrap();
+
+        let (limit, transitions) = service.enforce_segment_limit().unwrap().unwrap();
+        let second = limit.new_session_id.unwrap();
+        assert_ne!(second, first);
+        assert_eq!(service.current_session_id(), Some(second.clone()));
+        assert_eq!(service.current_mode(), Some(Mode::Memo));
+        assert_eq!(transitions[0].session_id, first);
+        assert_eq!(transitions[1].session_id, second);
+
+        // 以降のセグメントは新しいセッションに保存され、上限も新しいセッションで数える
+        service
+            .on_pipeline_transcript("seg-2", "二つ目", 0.9, &[], None)
+            .unwrap();
+        let storage = service.storage.lock().unwrap();
+        assert_eq!(storage.count_session_segments(&first).unwrap(), 1);
+        assert_eq!(storage.count_session_segments(&second).unwrap(), 1);
     }
 
-    pub fn record_error(&self, code: &str) {
-        self.metrics.inc_error(code);
+    #[test]
+    fn segment_limit_zero_is_unlimited() {
+        let service = make_service();
+        service.start_session(Mode::Raw, None).unwrap();
+        for i in 0..3 {
+            service
+                .on_pipeline_transcript(&format!("seg-{i}"), "テキスト", 0.9, &[], None)
+                .unwrap();
+        }
+        assert!(service.enforce_segment_limit().unwrap().is_none());
     }
 
-    // ==================== Data Protection ====================
+    #[test]
+    fn voice_command_switches_mode_without_saving_segment() {
+        let service = make_service_with_settings(AppSettings {
+            voice_commands_enabled: true,
+            ..Default::default()
+        });
+        let (session_id, _) = service.start_session(Mode::Raw, None).unwrap();
+
+        let switched = service.apply_voice_command("モード、議事録。").unwrap();
+        assert_eq!(
+            switched,
+            Some(ModeSwitchedByVoice {
+                session_id: session_id.clone(),
+                mode: Mode::Minutes,
+                phrase: "モード：議事録".to_string(),
+            })
+        );
+        assert_eq!(service.current_mode(), Some(Mode::Minutes));
+
+        // 通常の書き起こしはそのまま保存に回る
+        let text = "議事録のモードについて話します";
+        assert_eq!(service.apply_voice_command(text).unwrap(), None);
+        service
+            .on_pipeline_transcript("seg-1", text, 0.9, &[], None)
+            .unwrap();
+        let detail = service.get_session(&session_id).unwrap().unwrap();
+        assert_eq!(detail.segments.len(), 1);
+        assert_eq!(service.current_mode(), Some(Mode::Minutes));
+    }
+
+    #[test]
+    fn handle_pipeline_transcript_reports_review_caption_and_voice_command() {
+        let service = make_service_with_settings(AppSettings {
+            voice_commands_enabled: true,
+            review_before_deliver: true,
+            captions_enabled: true,
+            ..Default::default()
+        });
+        let (session_id, _) = service.start_session(Mode::Raw, None).unwrap();
+
+        let outcome = service
+            .handle_pipeline_transcript("seg-cmd", "モード、議事録。", 0.9, &[], None)
+            .unwrap();
+        assert!(matches!(
+            outcome,
+            TranscriptOutcome::ModeSwitched(ModeSwitchedByVoice {
+                mode: Mode::Minutes,
+                ..
+            })
+        ));
+
+        let outcome = service
+            .handle_pipeline_transcript("seg-1", "会議を始めます", 0.8, &[], None)
+            .unwrap();
+        let TranscriptOutcome::Saved(saved) = outcome else {
+            panic!("セグメントとして保存されるはず: {outcome:?}");
+        };
+        assert_eq!(saved.segment_id, "seg-1");
+        assert_eq!(saved.text, "会議を始めます");
+        assert!(saved.caption);
+        assert!(saved.for_review);
+        assert!(saved.segment_limit.is_none());
+
+        // 音声コマンドは保存せず、通常の書き起こしだけが切り替え後のモードで残る
+        let detail = service.get_session(&session_id).unwrap().unwrap();
+        assert_eq!(detail.segments.len(), 1);
+        assert_eq!(detail.segments[0].mode, Mode::Minutes);
+    }
+
+    #[test]
+    fn voice_command_ignored_when_disabled() {
+        let service = make_service();
+        service.start_session(Mode::Raw, None).unwrap();
+        assert_eq!(service.apply_voice_command("モード：議事録").unwrap(), None);
+        assert_eq!(service.current_mode(), Some(Mode::Raw));
+    }
+
+    #[test]
+    fn suggest_dictionary_terms_returns_frequent_uncovered_term() {
+        let service = make_service();
+        {
+            let storage = service.storage.lock().unwrap();
+            storage
+                .insert_session("s1", Mode::Memo, "2025-01-15T10:00:00Z")
+                .unwrap();
+            let texts = [
+                "タウリのビルドでエラー",
+                "タウリとSQLの設定",
+                "タウリを更新してビルド",
+                "SQLを確認",
+            ];
+            for (i, text) in texts.iter().enumerate() {
+                let id = format!("seg{i}");
+                storage
+                    .insert_segment(&id, "s1", Mode::Memo, &format!("2025-01-15T10:0{i}:00Z"))
+                    .unwrap();
+                storage.update_segment_text(&id, text, 0.9).unwrap();
+            }
+        }
+        service
+            .upsert_dictionary(DictionaryEntry {
+                id: None,
+                scope: crate::domain::types::DictionaryScope::Global,
+                mode: None,
+                pattern: "びるど".into(),
+                replacement: "ビルド".into(),
+                priority: 0,
+                enabled: true,
+                word_boundary: false,
+            })
+            .unwrap();
+
+        let suggestions = service.suggest_dictionary_terms(5).unwrap();
+        assert_eq!(
+            suggestions.first(),
+            Some(&TermSuggestion {
+                term: "タウリ".into(),
+                occurrences: 3,
+            })
+        );
+        // 辞書でカバー済みの「ビルド」は出ない
+        assert!(suggestions.iter().all(|s| s.term != "ビルド"));
+        assert_eq!(suggestions.len(), 2);
+    }
+
+    /// キャプチャを開始できない音声入力（デバイスが途中で消えた場合など）
+    struct FailingAudioSource;
 
-    pub fn cleanup_old_data(&self, ttl_days: u32) -> Result<(u32, u32), AppError> {
-        if ttl_days == 0 {
-            return Ok((0, 0));
+    impl crate::infra::audio::source::AudioSource for FailingAudioSource {
+        fn check(&self) -> Result<(), crate::infra::audio::capture::AudioCaptureError> {
+            Ok(())
         }
 
-        let cutoff = chrono::Utc::now() - chrono::Duration::days(ttl_days as i64);
-        let cutoff_str = cutoff.to_rfc3339();
-
-        let storage = self.storage.lock().unwrap();
-        let segments_deleted = storage.delete_old_segments(&cutoff_str)?;
-        let sessions_deleted = storage.delete_old_sessions(&cutoff_str)?;
+        fn start(
+            &self,
+            _sample_tx: crate::infra::audio::frame_queue::FrameSender,
+            _stop_flag: Arc<AtomicBool>,
+            _agc: Option<AutomaticGainControl>,
+            _channel_mode: crate::infra::audio::capture::ChannelMode,
+        ) -> Result<
+            crate::infra::audio::capture::CaptureConfig,
+            crate::infra::audio::capture::AudioCaptureError,
+        > {
+            Err(crate::infra::audio::capture::AudioCaptureError::NoDevice)
+        }
+    }
 
-        log::info!(
-            "データクリーンアップ: {segments_deleted} セグメント、{sessions_deleted} セッション削除（TTL: {ttl_days}日）"
-        );
+    /// 録音を開始し、キャプチャ開始失敗のエラーイベントを受け取るまで待つ
+    fn start_failing_recording(auto_recover: bool) -> (AppService, AppError) {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .save_settings(&AppSettings {
+                auto_recover,
+                ..AppSettings::default()
+            })
+            .unwrap();
+        let service = AppService::new(storage, Arc::new(NoopSttEngine), Arc::new(NoopRewriter))
+            .with_clipboard_backend(Arc::new(MemoryClipboard::default()))
+            .with_audio_source(Arc::new(FailingAudioSource));
+        service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        service.toggle_recording().unwrap();
+        let events = service.start_pipeline().unwrap();
+        let error = events
+            .iter()
+            .find_map(|event| match event {
+                PipelineEvent::Error(e) => Some(e),
+                _ => None,
+            })
+            .unwrap();
+        (service, error)
+    }
 
-        Ok((segments_deleted, sessions_deleted))
+    #[test]
+    fn recoverable_pipeline_error_auto_recovers() {
+        let (service, error) = start_failing_recording(true);
+        assert!(error.recoverable);
+
+        let transitions = service.on_pipeline_error(&error).unwrap();
+        let states: Vec<&str> = transitions.iter().map(|t| t.new_state.as_str()).collect();
+        assert_eq!(states, vec!["error", "idle"]);
+        assert_eq!(service.current_state().as_deref(), Some("idle"));
+        // 録音をやり直せる
+        service.toggle_recording().unwrap();
+        assert_eq!(service.current_state().as_deref(), Some("recording"));
     }
 
-    // ==================== State Accessors ====================
+    #[test]
+    fn fatal_or_unrecovered_pipeline_error_stays_in_error() {
+        // 回復不可能なエラーは auto_recover でも戻さない
+        let (service, _) = start_failing_recording(true);
+        let transitions = service
+            .on_pipeline_error(&AppError::internal("runtime"))
+            .unwrap();
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(service.current_state().as_deref(), Some("error"));
+
+        // auto_recover 無効なら回復可能なエラーでも Error のまま
+        let (service, error) = start_failing_recording(false);
+        service.on_pipeline_error(&error).unwrap();
+        assert_eq!(service.current_state().as_deref(), Some("error"));
+    }
 
-    pub fn current_state(&self) -> Option<String> {
-        let mgr = self.session_mgr.lock().unwrap();
-        mgr.active().map(|s| s.state.as_str().to_string())
+    #[test]
+    fn pipeline_error_without_stopped_pipeline_keeps_state() {
+        let service = make_service();
+        service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        service.toggle_recording().unwrap();
+
+        // パイプラインが動き続けている（または停止処理で取り出し済みの）間のエラーは状態を変えない
+        let transitions = service
+            .on_pipeline_error(&AppError::internal("stt"))
+            .unwrap();
+        assert!(transitions.is_empty());
+        assert_eq!(service.current_state().as_deref(), Some("recording"));
     }
 
-    pub fn current_session_id(&self) -> Option<String> {
-        let mgr = self.session_mgr.lock().unwrap();
-        mgr.active().map(|s| s.session_id.clone())
+    #[test]
+    fn copy_session_joins_all_segments() {
+        let (service, clipboard) = make_service_with_clipboard(AppSettings::default());
+        let (session_id, _) = service
+            .start_session(Mode::Memo, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        service
+            .on_pipeline_transcript("seg-1", "一つ目", 0.9, &[], None)
+            .unwrap();
+        service
+            .on_pipeline_transcript("seg-2", "二つ目", 0.9, &[], None)
+            .unwrap();
+        service
+            .on_pipeline_transcript("seg-3", "三つ目", 0.9, &[], None)
+            .unwrap();
+        service
+            .storage
+            .lock()
+            .unwrap()
+            .update_segment_rewritten("seg-2", "2 番目")
+            .unwrap();
+
+        let text = service
+            .get_session_text(&session_id, DeliverText::Auto, JoinStyle::DoubleNewline)
+            .unwrap();
+        assert_eq!(text, "一つ目\n\n2 番目\n\n三つ目");
+        let raw = service
+            .get_session_text(&session_id, DeliverText::Raw, JoinStyle::Space)
+            .unwrap();
+        assert_eq!(raw, "一つ目 二つ目 三つ目");
+
+        let copied = service
+            .copy_session(&session_id, DeliverText::Raw, JoinStyle::Newline)
+            .unwrap();
+        assert_eq!(copied, "一つ目\n二つ目\n三つ目");
+        assert_eq!(clipboard.text(), copied);
+
+        let err = service
+            .get_session_text("missing", DeliverText::Auto, JoinStyle::Newline)
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidState);
     }
 
-    pub fn current_mode(&self) -> Option<Mode> {
-        let mgr = self.session_mgr.lock().unwrap();
-        mgr.active().map(|s| s.mode)
+    #[test]
+    fn start_session_without_policy_uses_mode_default() {
+        let service = make_service_with_settings(AppSettings {
+            deliver_policy_by_mode: std::collections::HashMap::from([(
+                Mode::Minutes,
+                DeliverPolicy::FileAppend,
+            )]),
+            ..AppSettings::default()
+        });
+
+        service.start_session(Mode::Minutes, None).unwrap();
+        assert_eq!(
+            service.resolve_deliver_target(None).unwrap(),
+            DeliverTarget::FileAppend
+        );
+
+        service.start_session(Mode::Raw, None).unwrap();
+        assert_eq!(
+            service.resolve_deliver_target(None).unwrap(),
+            DeliverTarget::Clipboard
+        );
+
+        // 明示したポリシーが優先される
+        service
+            .start_session(Mode::Minutes, DeliverPolicy::Keystroke)
+            .unwrap();
+        assert_eq!(
+            service.resolve_deliver_target(None).unwrap(),
+            DeliverTarget::Keystroke
+        );
     }
 
-    pub fn rewriter_name(&self) -> &str {
-        self.rewriter.name()
+    #[test]
+    fn set_mode_switches_to_mode_policy() {
+        let service = make_service_with_settings(AppSettings {
+            deliver_policy_by_mode: std::collections::HashMap::from([(
+                Mode::Minutes,
+                DeliverPolicy::FileAppend,
+            )]),
+            ..AppSettings::default()
+        });
+        service.start_session(Mode::Raw, None).unwrap();
+
+        service.set_mode(Mode::Minutes).unwrap();
+        assert_eq!(
+            service.resolve_deliver_target(None).unwrap(),
+            DeliverTarget::FileAppend
+        );
+        // 設定の無いモードへ切り替えてもポリシーはそのまま
+        service.set_mode(Mode::Memo).unwrap();
+        assert_eq!(
+            service.resolve_deliver_target(None).unwrap(),
+            DeliverTarget::FileAppend
+        );
     }
 
-    pub fn stt_engine_name(&self) -> &str {
-        self.stt_engine.name()
+    #[test]
+    fn cache_transcripts_setting_toggles_cache() {
+        let service = make_service_with_settings(AppSettings {
+            cache_transcripts: true,
+            ..AppSettings::default()
+        });
+        assert!(service.transcript_cache.is_enabled());
+
+        service.update_settings(AppSettings::default()).unwrap();
+        assert!(!service.transcript_cache.is_enabled());
     }
 
-    // ==================== Setup Status ====================
+    #[test]
+    fn truncate_chars_cuts_on_multibyte_boundary() {
+        // 3 バイト文字の途中で切らず、先頭 3 文字で切る
+        assert_eq!(truncate_chars("議事録テキスト", 3), Some("議事録"));
+        assert_eq!(truncate_chars("aé議x", 3), Some("aé議"));
+        // ちょうど上限以下なら切り詰めない
+        assert_eq!(truncate_chars("議事録", 3), None);
+    }
 
-    pub fn check_setup_status(&self) -> SetupStatus {
-        let settings = self
-            .storage
-            .lock()
-            .unwrap()
-            .get_settings()
-            .unwrap_or_default();
+    #[test]
+    fn truncate_chars_zero_means_unlimited() {
+        let long = "あ".repeat(10_000);
+        assert_eq!(truncate_chars(&long, 0), None);
+    }
 
-        let stt_name = self.stt_engine.name().to_string();
-        let rewriter_name = self.rewriter.name().to_string();
+    #[test]
+    fn deliver_text_normalizes_line_endings() {
+        let settings = AppSettings {
+            deliver_line_endings: crate::domain::settings::LineEnding::CrLf,
+            deliver_trim: true,
+            ..AppSettings::default()
+        };
+        let (service, clipboard) = make_service_with_clipboard(settings);
+        service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
 
-        let mut issues = Vec::new();
+        service.deliver_text("一行目 \n二行目\r\n").unwrap();
+        assert_eq!(clipboard.text(), "一行目\r\n二行目");
+    }
 
-        // 1. STT が Noop → 選択されたエンジンに応じたエラーメッセージ
-        if stt_name == "noop" {
-            let (message, action) = match settings.stt_engine {
-                crate::domain::settings::SttEngineChoice::Soniox
-                | crate::domain::settings::SttEngineChoice::Cloud => (
-                    "Soniox API キーが設定されていないため、音声認識が動作しません".to_string(),
-                    "Settings で Soniox API キーを入力してください（変更後はアプリ再起動が必要です）"
-                        .to_string(),
-                ),
-                crate::domain::settings::SttEngineChoice::Whisper => (
-                    format!(
-                        "Whisper モデル（{:?}）がダウンロードされていないため、音声認識が動作しません",
-                        settings.whisper_model_size
-                    ),
-                    "Settings で Whisper モデルをダウンロードしてください（変更後はアプリ再起動が必要です）"
-                        .to_string(),
-                ),
-                crate::domain::settings::SttEngineChoice::Apple => (
-                    "Apple Speech が利用できないため、音声認識が動作しません".to_string(),
-                    "macOS で Speech.framework が有効か確認してください".to_string(),
-                ),
-            };
-            issues.push(SetupIssue {
-                category: "stt".to_string(),
-                severity: "error".to_string(),
-                message,
-                action,
-                navigate_to: Some("settings".to_string()),
-            });
-        }
+    #[test]
+    fn deliver_text_truncates_and_reports() {
+        let settings = AppSettings {
+            max_deliver_chars: 3,
+            ..AppSettings::default()
+        };
+        let (service, clipboard) = make_service_with_clipboard(settings);
+        let truncation_rx = service.take_truncation_events().unwrap();
+        service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+
+        service.deliver_text("議事録テキスト").unwrap();
+        assert_eq!(clipboard.text(), "議事録");
+        assert_eq!(
+            truncation_rx.try_recv().unwrap(),
+            DeliverTruncated {
+                original_len: 7,
+                delivered_len: 3,
+            }
+        );
+    }
 
-        // 2. Rewriter が Noop かつ rewrite_enabled → 警告
-        if rewriter_name == "noop" && settings.rewrite_enabled {
-            issues.push(SetupIssue {
-                category: "rewriter".to_string(),
-                severity: "warning".to_string(),
-                message: "Claude API キーが未設定のため、リライト機能が動作しません".to_string(),
-                action: "Settings で Claude API キーを入力してください（変更後はアプリ再起動が必要です）"
-                    .to_string(),
-                navigate_to: Some("settings".to_string()),
-            });
-        }
+    /// 指定時間スリープしてから返すリライター
+    struct SlowRewriter(std::time::Duration);
 
-        // 3. 未実装の配信ターゲット → 警告
-        match settings.default_deliver_target.as_str() {
-            "file_append" => {
-                issues.push(SetupIssue {
-                    category: "delivery".to_string(),
-                    severity: "warning".to_string(),
-                    message: "ファイル追記（file_append）は現在未実装です".to_string(),
-                    action: "Clipboard または Paste をご利用ください".to_string(),
-                    navigate_to: Some("settings".to_string()),
-                });
-            }
-            "webhook" => {
-                issues.push(SetupIssue {
-                    category: "delivery".to_string(),
-                    severity: "warning".to_string(),
-                    message: "Webhook 配信は現在未実装です".to_string(),
-                    action: "Clipboard または Paste をご利用ください".to_string(),
-                    navigate_to: Some("settings".to_string()),
-                });
-            }
-            _ => {}
+    #[async_trait::async_trait]
+    impl Rewriter for SlowRewriter {
+        async fn rewrite(&self, text: &str, _ctx: RewriteContext) -> Result<String, RewriteError> {
+            tokio::time::sleep(self.0).await;
+            Ok(text.to_string())
         }
 
-        // 4. マイク権限チェック
-        use crate::infra::os_integration::PermissionState;
-        let permissions = self.check_permissions();
-        if permissions.microphone != PermissionState::Granted {
-            issues.push(SetupIssue {
-                category: "permission".to_string(),
-                severity: "error".to_string(),
-                message: "マイクのアクセス権限がありません".to_string(),
-                action: "システム環境設定でマイクへのアクセスを許可してください".to_string(),
-                navigate_to: Some("permissions".to_string()),
-            });
+        fn name(&self) -> &str {
+            "slow"
         }
+    }
 
-        let ready = !issues.iter().any(|i| i.severity == "error");
-
-        SetupStatus {
-            ready,
-            issues,
-            active_stt_engine: stt_name,
-            active_rewriter: rewriter_name,
-        }
+    fn make_service_with_rewriter(
+        rewriter: Arc<dyn Rewriter>,
+        settings: AppSettings,
+    ) -> AppService {
+        let storage = Storage::open_in_memory().unwrap();
+        storage.save_settings(&settings).unwrap();
+        AppService::new(storage, Arc::new(NoopSttEngine), rewriter)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::infra::rewriter::NoopRewriter;
-    use crate::infra::stt::NoopSttEngine;
+    #[tokio::test(start_paused = true)]
+    async fn rewrite_times_out_after_configured_seconds() {
+        let service = make_service_with_rewriter(
+            Arc::new(SlowRewriter(std::time::Duration::from_secs(60))),
+            AppSettings {
+                rewrite_timeout_secs: 2,
+                ..Default::default()
+            },
+        );
 
-    fn make_service() -> AppService {
-        let storage = Storage::open_in_memory().unwrap();
-        let stt: Arc<dyn SttEngine> = Arc::new(NoopSttEngine);
-        let rewriter: Arc<dyn Rewriter> = Arc::new(NoopRewriter);
-        AppService::new(storage, stt, rewriter)
+        let err = service.rewrite_text("遅い", Mode::Memo).await.unwrap_err();
+        assert_eq!(err.code, crate::domain::error::ErrorCode::Timeout);
     }
 
-    fn make_service_with_settings(settings: AppSettings) -> AppService {
-        let storage = Storage::open_in_memory().unwrap();
-        storage.save_settings(&settings).unwrap();
-        let stt: Arc<dyn SttEngine> = Arc::new(NoopSttEngine);
-        let rewriter: Arc<dyn Rewriter> = Arc::new(NoopRewriter);
-        AppService::new(storage, stt, rewriter)
+    #[tokio::test(start_paused = true)]
+    async fn stop_session_cancels_running_rewrite() {
+        let service = make_service_with_rewriter(
+            Arc::new(SlowRewriter(std::time::Duration::from_secs(10))),
+            AppSettings::default(),
+        );
+        service
+            .start_session(Mode::Memo, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+
+        let (result, _) = tokio::join!(service.rewrite_text("途中", Mode::Memo), async {
+            tokio::task::yield_now().await;
+            service.stop_session().unwrap();
+        });
+        assert_eq!(
+            result.unwrap_err().code,
+            crate::domain::error::ErrorCode::Canceled
+        );
     }
 
     #[test]
-    fn check_setup_status_reports_noop_stt_as_error() {
+    fn current_snapshot_reflects_recording_session() {
         let service = make_service();
-        let status = service.check_setup_status();
-
-        assert_eq!(status.active_stt_engine, "noop");
-        let stt_issues: Vec<_> = status
-            .issues
-            .iter()
-            .filter(|i| i.category == "stt")
-            .collect();
-        assert_eq!(stt_issues.len(), 1);
-        assert_eq!(stt_issues[0].severity, "error");
+        let snapshot = service.current_snapshot();
+        assert!(snapshot.session_id.is_none());
+        assert_eq!(snapshot.pipeline, PipelineStatus::Stopped);
+
+        let (session_id, _) = service
+            .start_session(Mode::Tech, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        service.toggle_recording().unwrap();
+        service.on_pipeline_partial("途中まで");
+
+        let snapshot = service.current_snapshot();
+        assert_eq!(snapshot.session_id.as_deref(), Some(session_id.as_str()));
+        assert_eq!(snapshot.state, Some(SessionState::Recording));
+        assert_eq!(snapshot.mode, Some(Mode::Tech));
+        assert_eq!(snapshot.last_partial_text.as_deref(), Some("途中まで"));
     }
 
     #[test]
-    fn check_setup_status_reports_noop_rewriter_warning_when_rewrite_enabled() {
-        let mut settings = AppSettings::default();
-        settings.rewrite_enabled = true;
-        let service = make_service_with_settings(settings);
-
-        let status = service.check_setup_status();
-
-        let rewriter_issues: Vec<_> = status
-            .issues
-            .iter()
-            .filter(|i| i.category == "rewriter")
-            .collect();
-        assert_eq!(rewriter_issues.len(), 1);
-        assert_eq!(rewriter_issues[0].severity, "warning");
+    fn store_partials_keeps_last_partial_with_final() {
+        for (store_partials, expected) in [(true, Some("会議の予定を")), (false, None)] {
+            let service = make_service_with_settings(AppSettings {
+                store_partials,
+                ..Default::default()
+            });
+            let (session_id, _) = service.start_session(Mode::Raw, None).unwrap();
+            service.on_pipeline_partial("会議の");
+            service.on_pipeline_partial("会議の予定を");
+            service
+                .on_pipeline_transcript("seg-1", "会議の予定を確認します", 0.9, &[], None)
+                .unwrap();
+
+            let detail = service.get_session(&session_id).unwrap().unwrap();
+            assert_eq!(detail.segments[0].raw_text, "会議の予定を確認します");
+            assert_eq!(detail.segments[0].last_partial.as_deref(), expected);
+        }
     }
 
     #[test]
-    fn check_setup_status_no_rewriter_warning_when_rewrite_disabled() {
+    fn pause_capture_keeps_session_recording_until_resumed() {
         let service = make_service();
-        let status = service.check_setup_status();
+        let (session_id, _) = service
+            .start_session(Mode::Memo, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        service.toggle_recording().unwrap();
+
+        let transition = service.pause_capture().unwrap();
+        assert_eq!(transition.new_state, SessionState::Paused);
+        assert_eq!(service.current_state().as_deref(), Some("paused"));
+        {
+            let storage = service.storage.lock().unwrap();
+            let detail = storage.get_session_detail(&session_id).unwrap().unwrap();
+            assert_eq!(detail.state, "paused");
+            // 一時停止ではセグメントを確定させない
+            assert!(detail.segments.is_empty());
+        }
 
-        let rewriter_issues: Vec<_> = status
-            .issues
-            .iter()
-            .filter(|i| i.category == "rewriter")
-            .collect();
-        assert!(rewriter_issues.is_empty());
+        let transition = service.resume_capture().unwrap();
+        assert_eq!(transition.new_state, SessionState::Recording);
+        assert!(service.resume_capture().is_err());
     }
 
     #[test]
-    fn check_setup_status_reports_file_append_as_warning() {
-        let mut settings = AppSettings::default();
-        settings.default_deliver_target = "file_append".to_string();
-        let service = make_service_with_settings(settings);
+    fn review_before_deliver_pauses_until_commit() {
+        let service = make_service_with_settings(AppSettings {
+            review_before_deliver: true,
+            ..Default::default()
+        });
+        let (session_id, _) = service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        service.toggle_recording().unwrap();
+        service.toggle_recording().unwrap();
+
+        let segment_id = {
+            let storage = service.storage.lock().unwrap();
+            let detail = storage.get_session_detail(&session_id).unwrap().unwrap();
+            detail.segments[0].segment_id.clone()
+        };
 
-        let status = service.check_setup_status();
+        let (transition, _) = service
+            .on_transcript_done(&segment_id, "こんにちわ", 0.9)
+            .unwrap();
+        assert_eq!(transition.new_state, SessionState::Reviewing);
 
-        let delivery_issues: Vec<_> = status
-            .issues
-            .iter()
-            .filter(|i| i.category == "delivery")
-            .collect();
-        assert_eq!(delivery_issues.len(), 1);
-        assert_eq!(delivery_issues[0].severity, "warning");
+        let transition = service
+            .commit_segment(&segment_id, "こんにちは")
+            .unwrap()
+            .unwrap();
+        assert_eq!(transition.prev_state, "reviewing");
+        assert_eq!(transition.new_state, SessionState::Delivering);
+
+        let storage = service.storage.lock().unwrap();
+        let detail = storage.get_session_detail(&session_id).unwrap().unwrap();
+        assert_eq!(detail.segments[0].raw_text, "こんにちは");
     }
 
     #[test]
-    fn check_setup_status_reports_webhook_as_warning() {
-        let mut settings = AppSettings::default();
-        settings.default_deliver_target = "webhook".to_string();
-        let service = make_service_with_settings(settings);
-
-        let status = service.check_setup_status();
-
-        let delivery_issues: Vec<_> = status
-            .issues
-            .iter()
-            .filter(|i| i.category == "delivery")
-            .collect();
-        assert_eq!(delivery_issues.len(), 1);
-        assert_eq!(delivery_issues[0].severity, "warning");
+    fn failed_state_write_keeps_session_state() {
+        let service = make_service();
+        let (session_id, _) = service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        service.toggle_recording().unwrap();
+        service.toggle_recording().unwrap();
+        let segment_id = {
+            let storage = service.storage.lock().unwrap();
+            let detail = storage.get_session_detail(&session_id).unwrap().unwrap();
+            detail.segments[0].segment_id.clone()
+        };
+        // セッション状態の書き込みだけを失敗させる
+        service
+            .storage
+            .lock()
+            .unwrap()
+            .execute_batch(
+                "CREATE TRIGGER fail_session_state BEFORE UPDATE OF state ON sessions
+                 BEGIN SELECT RAISE(ABORT, 'state write failed'); END;",
+            )
+            .unwrap();
+
+        assert!(service
+            .on_transcript_done(&segment_id, "こんにちは", 0.9)
+            .is_err());
+
+        // メモリ上の状態も DB も書き込み前のまま
+        assert_eq!(
+            service.current_snapshot().state,
+            Some(SessionState::Transcribing)
+        );
+        let storage = service.storage.lock().unwrap();
+        let detail = storage.get_session_detail(&session_id).unwrap().unwrap();
+        assert_eq!(detail.state, "transcribing");
+        assert!(detail.segments[0].raw_text.is_empty());
     }
 
     #[test]
-    fn check_setup_status_ready_false_when_stt_is_noop() {
+    fn last_segment_text_follows_preference() {
         let service = make_service();
-        let status = service.check_setup_status();
-
-        // noop STT → error exists → ready should be false
-        assert!(!status.ready);
+        let (session_id, _) = service
+            .start_session(Mode::Memo, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+        service.toggle_recording().unwrap();
+        service.toggle_recording().unwrap();
+        let segment_id = {
+            let storage = service.storage.lock().unwrap();
+            let detail = storage.get_session_detail(&session_id).unwrap().unwrap();
+            detail.segments[0].segment_id.clone()
+        };
+        service
+            .on_transcript_done(&segment_id, "えーと明日の会議", 0.9)
+            .unwrap();
+
+        // リライト前: Rewritten 指定は生テキストへフォールバック
+        let (_, text, fell_back) = service.last_segment_text(DeliverText::Rewritten).unwrap();
+        assert_eq!(text, "えーと明日の会議");
+        assert!(fell_back);
+
+        service.on_rewrite_done(&segment_id, "明日の会議").unwrap();
+
+        let cases = [
+            (DeliverText::Auto, "明日の会議"),
+            (DeliverText::Raw, "えーと明日の会議"),
+            (DeliverText::Rewritten, "明日の会議"),
+        ];
+        for (prefer, expected) in cases {
+            let (_, text, fell_back) = service.last_segment_text(prefer).unwrap();
+            assert_eq!(text, expected, "{prefer:?}");
+            assert!(!fell_back);
+        }
     }
 
     #[test]
-    fn check_setup_status_engine_names() {
-        let service = make_service();
-        let status = service.check_setup_status();
+    fn concurrent_pipeline_transcripts_and_history_do_not_deadlock() {
+        const WORKERS: usize = 4;
+        const ITERATIONS: usize = 50;
+
+        let service = Arc::new(make_service());
+        let (session_id, _) = service
+            .start_session(Mode::Raw, DeliverPolicy::Clipboard { wrap: None })
+            .unwrap();
+
+        // デッドロック時にテストがハングしないよう、別スレッドで実行してタイムアウトを設ける
+        let (done_tx, done_rx) = mpsc::channel();
+        let runner = Arc::clone(&service);
+        std::thread::spawn(move || {
+            std::thread::scope(|scope| {
+                for w in 0..WORKERS {
+                    let service = &runner;
+                    scope.spawn(move || {
+                        for i in 0..ITERATIONS {
+                            let segment_id = format!("seg-{w}-{i}");
+                            service
+                                .on_pipeline_transcript(&segment_id, "テスト", 0.9, &[], None)
+                                .unwrap();
+                            // session_mgr と storage を同時に保持する経路
+                            service.commit_segment(&segment_id, "修正").unwrap();
+                        }
+                    });
+                    scope.spawn(move || {
+                        for _ in 0..ITERATIONS {
+                            service.get_history(10, None, None, false).unwrap();
+                            service.current_state();
+                        }
+                    });
+                }
+            });
+            done_tx.send(()).unwrap();
+        });
 
-        assert_eq!(status.active_stt_engine, "noop");
-        assert_eq!(status.active_rewriter, "noop");
+        done_rx
+            .recv_timeout(std::time::Duration::from_secs(30))
+            .expect("concurrent AppService calls deadlocked");
+
+        let detail = service.get_session(&session_id).unwrap().unwrap();
+        assert_eq!(detail.segments.len(), WORKERS * ITERATIONS);
     }
 }
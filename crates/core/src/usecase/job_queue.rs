@@ -1,11 +1,18 @@
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::{oneshot, Mutex};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
 use tokio::task::JoinHandle;
 
 use crate::domain::job::{JobInfo, JobKind, JobStatus};
+use crate::infra::stt::CancelToken;
 
 /// ジョブキュー: Tokioタスクの発行・追跡・キャンセル
+///
+/// ロックは await を跨がないため std の Mutex を使う。
+/// 同期コンテキスト（`AppService::stop_session` など）からもキャンセルできる。
+///
+/// キャンセル時は oneshot の送信と JoinHandle の abort に加えて、ジョブの [`CancelToken`] も立てる。
+/// await を挟まない処理（Whisper の推論など）は select! では止まらないため、トークンで中断する。
 pub struct JobQueue {
     jobs: Arc<Mutex<HashMap<String, JobEntry>>>,
 }
@@ -13,9 +20,24 @@ pub struct JobQueue {
 struct JobEntry {
     info: JobInfo,
     cancel_tx: Option<oneshot::Sender<()>>,
+    cancel_token: CancelToken,
     handle: Option<JoinHandle<()>>,
 }
 
+impl JobEntry {
+    /// キャンセルシグナルを送信し、タスクを abort する
+    fn signal_cancel(&mut self) {
+        self.info.status = JobStatus::Canceled;
+        self.cancel_token.cancel();
+        if let Some(tx) = self.cancel_tx.take() {
+            let _ = tx.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+        }
+    }
+}
+
 impl JobQueue {
     pub fn new() -> Self {
         Self {
@@ -38,10 +60,11 @@ impl JobQueue {
         let entry = JobEntry {
             info,
             cancel_tx: Some(cancel_tx),
+            cancel_token: CancelToken::new(),
             handle: None,
         };
 
-        let mut jobs = self.jobs.lock().await;
+        let mut jobs = self.jobs.lock().unwrap();
         jobs.insert(job_id.clone(), entry);
 
         (job_id, cancel_rx)
@@ -49,7 +72,7 @@ impl JobQueue {
 
     /// ジョブのJoinHandleを設定（spawn後に呼ぶ）
     pub async fn set_handle(&self, job_id: &str, handle: JoinHandle<()>) {
-        let mut jobs = self.jobs.lock().await;
+        let mut jobs = self.jobs.lock().unwrap();
         if let Some(entry) = jobs.get_mut(job_id) {
             entry.handle = Some(handle);
         }
@@ -57,7 +80,7 @@ impl JobQueue {
 
     /// ジョブをRunning状態に変更
     pub async fn mark_running(&self, job_id: &str) {
-        let mut jobs = self.jobs.lock().await;
+        let mut jobs = self.jobs.lock().unwrap();
         if let Some(entry) = jobs.get_mut(job_id) {
             entry.info.status = JobStatus::Running;
         }
@@ -65,7 +88,7 @@ impl JobQueue {
 
     /// ジョブをDone状態に変更
     pub async fn mark_done(&self, job_id: &str) {
-        let mut jobs = self.jobs.lock().await;
+        let mut jobs = self.jobs.lock().unwrap();
         if let Some(entry) = jobs.get_mut(job_id) {
             entry.info.status = JobStatus::Done;
             entry.cancel_tx = None;
@@ -75,7 +98,7 @@ impl JobQueue {
 
     /// ジョブをFailed状態に変更
     pub async fn mark_failed(&self, job_id: &str, error: String) {
-        let mut jobs = self.jobs.lock().await;
+        let mut jobs = self.jobs.lock().unwrap();
         if let Some(entry) = jobs.get_mut(job_id) {
             entry.info.status = JobStatus::Failed;
             entry.info.error = Some(error);
@@ -86,18 +109,10 @@ impl JobQueue {
 
     /// ジョブをキャンセル
     pub async fn cancel(&self, job_id: &str) -> bool {
-        let mut jobs = self.jobs.lock().await;
+        let mut jobs = self.jobs.lock().unwrap();
         if let Some(entry) = jobs.get_mut(job_id) {
             if entry.info.status == JobStatus::Queued || entry.info.status == JobStatus::Running {
-                entry.info.status = JobStatus::Canceled;
-                // キャンセルシグナルを送信
-                if let Some(tx) = entry.cancel_tx.take() {
-                    let _ = tx.send(());
-                }
-                // タスクをabort
-                if let Some(handle) = entry.handle.take() {
-                    handle.abort();
-                }
+                entry.signal_cancel();
                 return true;
             }
         }
@@ -106,7 +121,12 @@ impl JobQueue {
 
     /// セッション内の全ジョブをキャンセル
     pub async fn cancel_session(&self, session_id: &str) -> Vec<String> {
-        let mut jobs = self.jobs.lock().await;
+        self.cancel_session_now(session_id)
+    }
+
+    /// セッション内の全ジョブをキャンセル（同期版）
+    pub fn cancel_session_now(&self, session_id: &str) -> Vec<String> {
+        let mut jobs = self.jobs.lock().unwrap();
         let mut canceled = vec![];
 
         for (job_id, entry) in jobs.iter_mut() {
@@ -114,13 +134,7 @@ impl JobQueue {
                 && (entry.info.status == JobStatus::Queued
                     || entry.info.status == JobStatus::Running)
             {
-                entry.info.status = JobStatus::Canceled;
-                if let Some(tx) = entry.cancel_tx.take() {
-                    let _ = tx.send(());
-                }
-                if let Some(handle) = entry.handle.take() {
-                    handle.abort();
-                }
+                entry.signal_cancel();
                 canceled.push(job_id.clone());
             }
         }
@@ -128,15 +142,43 @@ impl JobQueue {
         canceled
     }
 
+    /// 待機中・実行中のジョブがあるか
+    pub fn has_active_jobs(&self) -> bool {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.values()
+            .any(|e| matches!(e.info.status, JobStatus::Queued | JobStatus::Running))
+    }
+
+    /// 全セッションの待機中・実行中ジョブをキャンセル（終了処理用）
+    pub fn cancel_all_now(&self) -> Vec<String> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let mut canceled = vec![];
+
+        for (job_id, entry) in jobs.iter_mut() {
+            if matches!(entry.info.status, JobStatus::Queued | JobStatus::Running) {
+                entry.signal_cancel();
+                canceled.push(job_id.clone());
+            }
+        }
+
+        canceled
+    }
+
+    /// ジョブの中断トークンを取得（STT など await で止まらない処理に渡す）
+    pub async fn cancel_token(&self, job_id: &str) -> Option<CancelToken> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.get(job_id).map(|e| e.cancel_token.clone())
+    }
+
     /// ジョブ情報を取得
     pub async fn get_job(&self, job_id: &str) -> Option<JobInfo> {
-        let jobs = self.jobs.lock().await;
+        let jobs = self.jobs.lock().unwrap();
         jobs.get(job_id).map(|e| e.info.clone())
     }
 
     /// 完了済みジョブを削除（メモリ解放）
     pub async fn cleanup_completed(&self) {
-        let mut jobs = self.jobs.lock().await;
+        let mut jobs = self.jobs.lock().unwrap();
         jobs.retain(|_, entry| {
             !matches!(
                 entry.info.status,
@@ -201,8 +243,12 @@ mod tests {
             .await;
 
         queue.mark_running(&job_id).await;
+        let token = queue.cancel_token(&job_id).await.unwrap();
+        assert!(!token.is_canceled());
+
         let canceled = queue.cancel(&job_id).await;
         assert!(canceled);
+        assert!(token.is_canceled());
         assert_eq!(
             queue.get_job(&job_id).await.unwrap().status,
             JobStatus::Canceled
@@ -229,6 +275,28 @@ mod tests {
         assert_eq!(queue.get_job(&j3).await.unwrap().status, JobStatus::Queued);
     }
 
+    #[tokio::test]
+    async fn test_cancel_all_now() {
+        let queue = JobQueue::new();
+        assert!(!queue.has_active_jobs());
+        let (j1, _) = queue
+            .enqueue("s1".to_string(), None, JobKind::Transcribe)
+            .await;
+        let (j2, _) = queue
+            .enqueue("s2".to_string(), None, JobKind::Rewrite)
+            .await;
+        queue.mark_done(&j2).await;
+        assert!(queue.has_active_jobs());
+
+        assert_eq!(queue.cancel_all_now(), vec![j1.clone()]);
+        assert!(!queue.has_active_jobs());
+        assert_eq!(
+            queue.get_job(&j1).await.unwrap().status,
+            JobStatus::Canceled
+        );
+        assert_eq!(queue.get_job(&j2).await.unwrap().status, JobStatus::Done);
+    }
+
     #[tokio::test]
     async fn test_mark_failed() {
         let queue = JobQueue::new();
@@ -1,2 +1,3 @@
 pub mod app_service;
 pub mod job_queue;
+pub mod stdio;
@@ -0,0 +1,615 @@
+//! ヘッドレスモード: 標準入出力の改行区切り JSON で AppService を操作する
+//!
+//! 1 行 1 リクエスト `{"id": 1, "method": "start_session", "params": {...}}` を読み、
+//! 応答 `{"id": 1, "result": ...}` または `{"id": 1, "error": {...}}` を 1 行ずつ書き出す。
+//! パイプラインからの通知は `{"event": "transcript_final", "payload": {...}}` として同じ出力に流す。
+//! params は Tauri コマンドの引数と同じ形（camelCase）。
+
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::domain::error::AppError;
+use crate::domain::session::{
+    SavedTranscript, SessionSegmentLimit, SessionState, StateTransition, TranscriptOutcome,
+};
+use crate::domain::settings::AppSettings;
+use crate::domain::types::{
+    DeliverPolicy, DeliverText, GetHistoryArgs, JoinStyle, Mode, WhisperTask,
+};
+use crate::infra::audio::pipeline::PipelineEvent;
+use crate::usecase::app_service::{AppService, SHUTDOWN_TIMEOUT};
+
+/// 受け付けるコマンド（method 名は Tauri コマンド名と同じ）
+#[derive(Debug, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+enum StdioCommand {
+    #[serde(alias = "start", rename_all = "camelCase")]
+    StartSession {
+        mode: Mode,
+        #[serde(default)]
+        deliver_policy: Option<DeliverPolicy>,
+    },
+    #[serde(alias = "stop")]
+    StopSession,
+    ToggleRecording,
+    PauseCapture,
+    ResumeCapture,
+    SetMode {
+        mode: Mode,
+    },
+    SetTask {
+        task: WhisperTask,
+    },
+    SetVadMuted {
+        muted: bool,
+    },
+    GetHistory {
+        args: GetHistoryArgs,
+    },
+    #[serde(rename_all = "camelCase")]
+    GetSession {
+        session_id: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    CopySession {
+        session_id: String,
+        #[serde(default)]
+        prefer: Option<DeliverText>,
+        #[serde(default)]
+        join: Option<JoinStyle>,
+    },
+    ListDictionary {
+        #[serde(default)]
+        scope: Option<String>,
+    },
+    SuggestDictionaryTerms {
+        #[serde(default)]
+        limit: Option<usize>,
+    },
+    DeliverLast {
+        #[serde(default)]
+        prefer: Option<DeliverText>,
+    },
+    DeliverTo {
+        text: String,
+        policy: DeliverPolicy,
+    },
+    #[serde(rename_all = "camelCase")]
+    CommitSegment {
+        segment_id: String,
+        edited_text: String,
+    },
+    GetSnapshot,
+    GetSettings,
+    UpdateSettings {
+        settings: AppSettings,
+    },
+}
+
+/// 出力先（応答とイベント転送スレッドで共有する）
+type SharedWriter = Arc<Mutex<Box<dyn Write + Send>>>;
+
+/// 標準入出力でコマンドを処理する（入力が EOF になるまで）
+///
+/// 終了時は録音中のパイプラインを停止し、最終セグメントのイベントを書き出してから戻る。
+pub fn run_stdio<R, W>(service: Arc<AppService>, input: R, output: W) -> io::Result<()>
+where
+    R: BufRead,
+    W: Write + Send + 'static,
+{
+    let writer: SharedWriter = Arc::new(Mutex::new(Box::new(output)));
+    let mut forwarders = Vec::new();
+    if let Some(editor_rx) = service.take_editor_events() {
+        let writer = writer.clone();
+        // AppService が生きている間は終了しないため join しない
+        thread::spawn(move || {
+            for payload in editor_rx {
+                emit_event(&writer, "deliver_to_editor", json!(payload));
+            }
+        });
+    }
+    if let Some(truncation_rx) = service.take_truncation_events() {
+        let writer = writer.clone();
+        thread::spawn(move || {
+            for payload in truncation_rx {
+                emit_event(&writer, "deliver_truncated", json!(payload));
+            }
+        });
+    }
+
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(mut request) => {
+                let id = request
+                    .as_object_mut()
+                    .and_then(|o| o.remove("id"))
+                    .unwrap_or(Value::Null);
+                match serde_json::from_value::<StdioCommand>(request) {
+                    Ok(command) => match dispatch(&service, command, &writer, &mut forwarders) {
+                        Ok(result) => json!({ "id": id, "result": result }),
+                        Err(e) => json!({ "id": id, "error": e }),
+                    },
+                    Err(e) => json!({
+                        "id": id,
+                        "error": AppError::invalid_state(format!("不正なコマンドです: {e}")),
+                    }),
+                }
+            }
+            Err(e) => json!({
+                "id": Value::Null,
+                "error": AppError::invalid_state(format!("JSON を解析できません: {e}")),
+            }),
+        };
+        write_line(&writer, &response)?;
+    }
+
+    // 入力が閉じたら録音中のセグメントを書き起こして保存し、セッションを閉じる
+    if let Err(e) = service.shutdown(SHUTDOWN_TIMEOUT) {
+        log::warn!("終了処理に失敗しました: {e}");
+    }
+    for forwarder in forwarders {
+        let _ = forwarder.join();
+    }
+    Ok(())
+}
+
+fn dispatch(
+    service: &Arc<AppService>,
+    command: StdioCommand,
+    writer: &SharedWriter,
+    forwarders: &mut Vec<thread::JoinHandle<()>>,
+) -> Result<Value, AppError> {
+    match command {
+        StdioCommand::StartSession {
+            mode,
+            deliver_policy,
+        } => {
+            let (session_id, transition) = service.start_session(mode, deliver_policy)?;
+            emit_state_changed(writer, &transition);
+            to_result(session_id)
+        }
+        StdioCommand::StopSession => {
+            if let Some(transition) = service.stop_session()? {
+                emit_state_changed(writer, &transition);
+            }
+            Ok(Value::Null)
+        }
+        StdioCommand::ToggleRecording => {
+            // 直前のトグルから間もない連打は無視する（状態は変えず、イベントも送らない）
+            if !service.admit_toggle() {
+                return Ok(Value::Null);
+            }
+            if matches!(
+                service.current_state().as_deref(),
+                Some("recording" | "paused")
+            ) {
+                let transition = service.pause_recording()?;
+                emit_state_changed(writer, &transition);
+                return Ok(Value::Null);
+            }
+            let transition = service.toggle_recording()?;
+            emit_state_changed(writer, &transition);
+            if transition.new_state == SessionState::Recording {
+                match service.start_pipeline() {
+                    Ok(event_rx) => {
+                        forwarders.push(spawn_event_forwarder(
+                            service.clone(),
+                            writer.clone(),
+                            event_rx,
+                        ));
+                    }
+                    Err(e) => {
+                        // パイプライン開始失敗 → Idle に戻す
+                        if let Ok(revert) = service.pause_recording() {
+                            emit_state_changed(writer, &revert);
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+            Ok(Value::Null)
+        }
+        StdioCommand::PauseCapture => {
+            let transition = service.pause_capture()?;
+            emit_state_changed(writer, &transition);
+            Ok(Value::Null)
+        }
+        StdioCommand::ResumeCapture => {
+            let transition = service.resume_capture()?;
+            emit_state_changed(writer, &transition);
+            Ok(Value::Null)
+        }
+        StdioCommand::SetMode { mode } => {
+            service.set_mode(mode)?;
+            Ok(Value::Null)
+        }
+        StdioCommand::SetTask { task } => {
+            service.set_task(task)?;
+            Ok(Value::Null)
+        }
+        StdioCommand::SetVadMuted { muted } => {
+            service.set_vad_muted(muted);
+            Ok(Value::Null)
+        }
+        StdioCommand::GetHistory { args } => to_result(service.get_history(
+            args.limit,
+            args.cursor.as_deref(),
+            args.query.as_deref(),
+            args.pinned_first,
+        )?),
+        StdioCommand::GetSession { session_id } => to_result(service.get_session(&session_id)?),
+        StdioCommand::CopySession {
+            session_id,
+            prefer,
+            join,
+        } => to_result(service.copy_session(
+            &session_id,
+            prefer.unwrap_or_default(),
+            join.unwrap_or_default(),
+        )?),
+        StdioCommand::ListDictionary { scope } => {
+            to_result(service.list_dictionary(scope.as_deref())?)
+        }
+        StdioCommand::SuggestDictionaryTerms { limit } => {
+            to_result(service.suggest_dictionary_terms(limit.unwrap_or(20))?)
+        }
+        StdioCommand::DeliverLast { prefer } => {
+            let (transition, result) = service.deliver_last(None, prefer.unwrap_or_default())?;
+            emit_state_changed(writer, &transition);
+            to_result(result)
+        }
+        StdioCommand::DeliverTo { text, policy } => to_result(service.deliver_to(&text, policy)?),
+        StdioCommand::CommitSegment {
+            segment_id,
+            edited_text,
+        } => {
+            let delivered =
+                service.commit_and_deliver(&segment_id, &edited_text, |transition| {
+                    emit_state_changed(writer, transition);
+                })?;
+            to_result(delivered.map(|(_, target)| target))
+        }
+        StdioCommand::GetSnapshot => to_result(service.current_snapshot()),
+        StdioCommand::GetSettings => to_result(service.get_settings()?),
+        StdioCommand::UpdateSettings { settings } => {
+            service.update_settings(settings)?;
+            Ok(Value::Null)
+        }
+    }
+}
+
+fn to_result(value: impl Serialize) -> Result<Value, AppError> {
+    serde_json::to_value(value)
+        .map_err(|e| AppError::internal(format!("応答のシリアライズ失敗: {e}")))
+}
+
+fn write_line(writer: &SharedWriter, value: &Value) -> io::Result<()> {
+    let mut writer = writer.lock().unwrap();
+    serde_json::to_writer(&mut *writer, value)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+fn emit_event(writer: &SharedWriter, event: &str, payload: Value) {
+    if let Err(e) = write_line(writer, &json!({ "event": event, "payload": payload })) {
+        log::error!("イベント送信失敗 [{event}]: {e}");
+    }
+}
+
+fn emit_state_changed(writer: &SharedWriter, transition: &StateTransition) {
+    emit_event(
+        writer,
+        "session_state_changed",
+        json!({
+            "session_id": transition.session_id,
+            "prev_state": transition.prev_state,
+            "new_state": transition.new_state.as_str(),
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        }),
+    );
+}
+
+/// パイプラインイベントを保存しつつ出力へ転送するスレッドを起動する
+///
+/// 入力レベル（audio_level）は行数が多すぎるため転送しない。
+fn spawn_event_forwarder(
+    service: Arc<AppService>,
+    writer: SharedWriter,
+    event_rx: mpsc::Receiver<PipelineEvent>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        // 終了前に書き出せるよう、自動リライトのスレッドは最後に待つ
+        let mut rewrites = Vec::new();
+        for event in event_rx {
+            match event {
+                PipelineEvent::AudioLevel(_) => {}
+                PipelineEvent::Clipping { ratio } => {
+                    emit_event(&writer, "clipping", json!({ "ratio": ratio }));
+                }
+                PipelineEvent::TranscriptPartial { text } => {
+                    service.on_pipeline_partial(&text);
+                    emit_event(&writer, "transcript_partial", json!({ "text": text }));
+                }
+                PipelineEvent::CaptionUpdate { text, is_partial } => {
+                    emit_event(
+                        &writer,
+                        "caption_update",
+                        json!({ "text": text, "is_partial": is_partial }),
+                    );
+                }
+                PipelineEvent::TranscriptFinal {
+                    segment_id,
+                    text,
+                    confidence,
+                    timings,
+                    mode,
+                } => match service.handle_pipeline_transcript(
+                    &segment_id,
+                    &text,
+                    confidence,
+                    &timings,
+                    mode,
+                ) {
+                    Ok(TranscriptOutcome::ModeSwitched(switched)) => {
+                        emit_event(&writer, "mode_switched_by_voice", json!(switched));
+                    }
+                    Ok(TranscriptOutcome::Saved(saved)) => {
+                        if let Some(rewrite) = emit_saved_transcript(&service, &writer, saved) {
+                            rewrites.push(rewrite);
+                        }
+                    }
+                    Err(e) => emit_event(&writer, "error", json!(e)),
+                },
+                PipelineEvent::CaptureFormat {
+                    sample_rate,
+                    channels,
+                } => emit_event(
+                    &writer,
+                    "capture_format",
+                    json!({ "sample_rate": sample_rate, "channels": channels }),
+                ),
+                PipelineEvent::AudioSegmentSaved {
+                    segment_id,
+                    audio_path,
+                } => match service.on_pipeline_audio_segment(&segment_id, &audio_path) {
+                    Ok(()) => apply_segment_limit(&service, &writer),
+                    Err(e) => emit_event(&writer, "error", json!(e)),
+                },
+                PipelineEvent::SessionIdleTimeout => match service.pause_recording() {
+                    Ok(transition) => emit_state_changed(&writer, &transition),
+                    Err(e) => emit_event(&writer, "error", json!(e)),
+                },
+                PipelineEvent::SessionDurationLimit => match service.on_session_duration_limit() {
+                    Ok(Some((limit, transition))) => {
+                        emit_event(&writer, "session_duration_limit", json!(limit));
+                        emit_state_changed(&writer, &transition);
+                    }
+                    Ok(None) => {}
+                    Err(e) => emit_event(&writer, "error", json!(e)),
+                },
+                PipelineEvent::Error(e) => {
+                    emit_event(&writer, "error", json!(e));
+                    match service.on_pipeline_error(&e) {
+                        Ok(transitions) => {
+                            for transition in &transitions {
+                                emit_state_changed(&writer, transition);
+                            }
+                        }
+                        Err(e) => emit_event(&writer, "error", json!(e)),
+                    }
+                }
+            }
+        }
+        service.on_pipeline_events_drained();
+        for rewrite in rewrites {
+            let _ = rewrite.join();
+        }
+    })
+}
+
+/// 保存した確定セグメントを書き出し、レビュー待ちでなければ自動リライトのスレッドを起動する
+fn emit_saved_transcript(
+    service: &Arc<AppService>,
+    writer: &SharedWriter,
+    saved: SavedTranscript,
+) -> Option<thread::JoinHandle<()>> {
+    emit_event(
+        writer,
+        "transcript_final",
+        json!({
+            "text": saved.text,
+            "confidence": saved.confidence,
+            "segment_id": saved.segment_id,
+        }),
+    );
+    // 字幕は確定時だけ後処理済みのテキストで更新する
+    if saved.caption {
+        emit_event(
+            writer,
+            "caption_update",
+            json!({ "text": saved.text, "is_partial": false }),
+        );
+    }
+    // レビュー有効時は確認待ちにし、リライト・配信は commit_segment 後に行う
+    let rewrite = if saved.for_review {
+        emit_event(
+            writer,
+            "transcript_for_review",
+            json!({ "segment_id": saved.segment_id, "text": saved.text }),
+        );
+        None
+    } else {
+        Some(spawn_auto_rewrite(
+            service.clone(),
+            writer.clone(),
+            saved.segment_id,
+            saved.text,
+        ))
+    };
+    if let Some((limit, transitions)) = saved.segment_limit {
+        emit_segment_limit(writer, limit, &transitions);
+    }
+    rewrite
+}
+
+/// 自動リライトを別スレッドで実行し、rewrite_done を書き出す（実行するかはセグメントのモードで決まる）
+fn spawn_auto_rewrite(
+    service: Arc<AppService>,
+    writer: SharedWriter,
+    segment_id: String,
+    text: String,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let rt = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(rt) => rt,
+            Err(e) => {
+                log::error!("リライト用ランタイムを作成できません: {e}");
+                return;
+            }
+        };
+        let session_id = service.segment_session_id(&segment_id).ok().flatten();
+        match rt.block_on(service.auto_rewrite_segment(&segment_id, &text)) {
+            Ok(Some((mode, rewritten))) => emit_event(
+                &writer,
+                "rewrite_done",
+                json!({
+                    "session_id": session_id.unwrap_or_default(),
+                    "segment_id": segment_id,
+                    "text": rewritten,
+                    "mode": mode.to_string(),
+                }),
+            ),
+            Ok(None) => {}
+            Err(e) => emit_event(&writer, "error", json!(e)),
+        }
+    })
+}
+
+/// セグメント数が上限に達していれば設定どおりにセッションを止める・切り替え、session_segment_limit を書き出す
+fn apply_segment_limit(service: &AppService, writer: &SharedWriter) {
+    match service.enforce_segment_limit() {
+        Ok(Some((limit, transitions))) => emit_segment_limit(writer, limit, &transitions),
+        Ok(None) => {}
+        Err(e) => emit_event(writer, "error", json!(e)),
+    }
+}
+
+fn emit_segment_limit(
+    writer: &SharedWriter,
+    limit: SessionSegmentLimit,
+    transitions: &[StateTransition],
+) {
+    emit_event(writer, "session_segment_limit", json!(limit));
+    for transition in transitions {
+        emit_state_changed(writer, transition);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::rewriter::NoopRewriter;
+    use crate::infra::storage::Storage;
+    use crate::infra::stt::NoopSttEngine;
+
+    /// 書き込み内容をテストから読めるようにする出力先
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn run(input: &str) -> Vec<Value> {
+        let storage = Storage::open_in_memory().unwrap();
+        let service = Arc::new(AppService::new(
+            storage,
+            Arc::new(NoopSttEngine),
+            Arc::new(NoopRewriter),
+        ));
+        let out = SharedBuf::default();
+        run_stdio(service, input.as_bytes(), out.clone()).unwrap();
+        let bytes = out.0.lock().unwrap().clone();
+        String::from_utf8(bytes)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    /// id を持つ応答だけを取り出す（イベント行を除く）
+    fn responses(lines: &[Value]) -> Vec<&Value> {
+        lines.iter().filter(|l| l.get("event").is_none()).collect()
+    }
+
+    #[test]
+    fn test_commands_are_answered_in_order() {
+        let lines = run(concat!(
+            r#"{"id": 1, "method": "start", "params": {"mode": "memo", "deliverPolicy": {"target": "clipboard"}}}"#,
+            "\n",
+            r#"{"id": 2, "method": "get_history", "params": {"args": {"limit": 10}}}"#,
+            "\n",
+            r#"{"id": 3, "method": "stop"}"#,
+            "\n",
+        ));
+        let responses = responses(&lines);
+        assert_eq!(responses.len(), 3);
+
+        let session_id = responses[0]["result"].as_str().unwrap();
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[1]["id"], 2);
+        let items = responses[1]["result"]["items"].as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["session_id"], session_id);
+        assert_eq!(items[0]["mode"], "memo");
+        assert_eq!(responses[2], &json!({ "id": 3, "result": null }));
+
+        let new_states: Vec<&Value> = lines
+            .iter()
+            .filter(|l| l["event"] == "session_state_changed")
+            .map(|l| &l["payload"]["new_state"])
+            .collect();
+        assert_eq!(new_states, vec!["idle", "idle"]);
+    }
+
+    #[test]
+    fn test_invalid_requests_return_errors_and_continue() {
+        let lines = run(concat!(
+            "not json\n",
+            r#"{"id": "a", "method": "unknown"}"#,
+            "\n",
+            r#"{"id": "b", "method": "set_mode", "params": {"mode": "tech"}}"#,
+            "\n",
+            r#"{"id": "c", "method": "get_settings"}"#,
+            "\n",
+        ));
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0]["id"].is_null());
+        assert_eq!(lines[0]["error"]["code"], "E_INVALID_STATE");
+        assert_eq!(lines[1]["id"], "a");
+        assert!(lines[1]["error"]["message"].is_string());
+        // アクティブセッションが無いので set_mode は失敗する
+        assert_eq!(lines[2]["id"], "b");
+        assert!(lines[2].get("error").is_some());
+        assert_eq!(lines[3]["id"], "c");
+        assert_eq!(lines[3]["result"]["language"], "ja-JP");
+    }
+}
@@ -9,9 +9,14 @@ fn main() {
 fn compile_swift() {
     let out_dir = std::env::var("OUT_DIR").unwrap();
 
-    let swift_src = std::path::Path::new("swift-lib/Sources/VTSwift/SpeechRecognizer.swift");
-    if !swift_src.exists() {
-        panic!("Swift source not found: {}", swift_src.display());
+    let swift_srcs = [
+        "swift-lib/Sources/VTSwift/SpeechRecognizer.swift",
+        "swift-lib/Sources/VTSwift/KeystrokeSynthesizer.swift",
+    ];
+    for src in swift_srcs {
+        if !std::path::Path::new(src).exists() {
+            panic!("Swift source not found: {}", src);
+        }
     }
 
     // SDK パスを取得
@@ -23,11 +28,12 @@ fn compile_swift() {
         .trim()
         .to_string();
 
-    // Swift コードをオブジェクトファイルにコンパイル
-    let obj_path = format!("{}/SpeechRecognizer.o", out_dir);
+    // Swift コードを 1 つのオブジェクトファイルにコンパイル（複数ソースのため -wmo）
+    let obj_path = format!("{}/VTSwift.o", out_dir);
     let status = std::process::Command::new("swiftc")
         .args([
             "-emit-object",
+            "-wmo",
             "-module-name",
             "VTSwift",
             "-sdk",
@@ -35,8 +41,8 @@ fn compile_swift() {
             "-parse-as-library",
             "-o",
             &obj_path,
-            swift_src.to_str().unwrap(),
         ])
+        .args(swift_srcs)
         .status()
         .expect("Failed to run swiftc");
 
@@ -72,7 +78,10 @@ fn compile_swift() {
     println!("cargo:rustc-link-lib=framework=Speech");
     println!("cargo:rustc-link-lib=framework=AVFoundation");
     println!("cargo:rustc-link-lib=framework=Foundation");
+    println!("cargo:rustc-link-lib=framework=CoreGraphics");
 
     // ソースファイル変更時の再ビルド
-    println!("cargo:rerun-if-changed=swift-lib/Sources/VTSwift/SpeechRecognizer.swift");
+    for src in swift_srcs {
+        println!("cargo:rerun-if-changed={}", src);
+    }
 }
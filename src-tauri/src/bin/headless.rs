@@ -0,0 +1,7 @@
+//! UI なしで AppService を操作するヘッドレス実行ファイル
+//!
+//! 使い方: `echo '{"id":1,"method":"get_settings"}' | headless`
+
+fn main() {
+    app_lib::run_headless();
+}
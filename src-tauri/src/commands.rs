@@ -1,23 +1,35 @@
 use std::sync::mpsc;
 
-use serde::Deserialize;
 use tauri::{AppHandle, State};
 
 use vt_core::domain::error::ErrorCode;
-use vt_core::domain::session::{SessionState, StateTransition};
-use vt_core::domain::settings::AppSettings;
+use vt_core::domain::session::{
+    AppSnapshot, SavedTranscript, SessionSegmentLimit, SessionState, StateTransition,
+    TranscriptOutcome,
+};
+use vt_core::domain::settings::{AppSettings, VadConfig};
 use vt_core::domain::types::{
-    DeliverPolicy, DictionaryEntry, HistoryPage, Mode, SessionDetail, SetupStatus,
+    AudioCacheStats, DeleteSessionResult, DeliverLastResult, DeliverPolicy, DeliverTarget,
+    DeliverText, DeliverTruncated, DiagnosticsReport, DictionaryEntry, GetHistoryArgs, HistoryPage,
+    JoinStyle, MicTestResult, Mode, SegmentTiming, SessionDetail, SetupStatus, TermSuggestion,
+    TranscribeFileResult, TranscribeSessionResult, WhisperTask,
 };
 use vt_core::infra::audio::pipeline::PipelineEvent;
-use vt_core::infra::metrics::MetricsSummary;
+use vt_core::infra::metrics::{MetricsExportFormat, MetricsSummary};
 use vt_core::infra::os_integration::{PasteResult, PermissionStatus};
+use vt_core::infra::output::DeliverToEditorPayload;
+use vt_core::infra::stt::model_manager::{ModelInfo, ModelStatus, WhisperModelManager};
+use vt_core::infra::stt::EngineCapabilities;
 use vt_core::usecase::app_service::AppService;
 
 use crate::events::{
-    self, AudioLevelPayload, ErrorPayload, SessionStateChangedPayload, TranscriptFinalPayload,
-    TranscriptPartialPayload, AUDIO_LEVEL, DELIVER_DONE, ERROR, REWRITE_DONE,
-    SESSION_STATE_CHANGED, TRANSCRIPT_FINAL, TRANSCRIPT_PARTIAL,
+    self, AudioLevelPayload, CaptionUpdatePayload, CaptureFormatPayload, ClippingPayload,
+    ErrorPayload, ModelDownloadProgressPayload, SessionStateChangedPayload, TranscriptFinalPayload,
+    TranscriptForReviewPayload, TranscriptPartialPayload, AUDIO_LEVEL, CAPTION_UPDATE,
+    CAPTURE_FORMAT, CLIPPING, DELIVER_DONE, DELIVER_TO_EDITOR, DELIVER_TRUNCATED, ERROR,
+    MODEL_DOWNLOAD_PROGRESS, MODE_SWITCHED_BY_VOICE, REWRITE_DONE, SESSION_DURATION_LIMIT,
+    SESSION_SEGMENT_LIMIT, SESSION_STATE_CHANGED, TRANSCRIBE_PROGRESS, TRANSCRIPT_FINAL,
+    TRANSCRIPT_FOR_REVIEW, TRANSCRIPT_PARTIAL,
 };
 
 /// コマンドエラー型（Tauri の Result で使用）
@@ -59,7 +71,7 @@ pub fn start_session(
     app: AppHandle,
     service: State<'_, AppService>,
     mode: Mode,
-    deliver_policy: DeliverPolicy,
+    deliver_policy: Option<DeliverPolicy>,
 ) -> CmdResult<String> {
     log::info!("start_session called: mode={:?}", mode);
     let (session_id, transition) = service.start_session(mode, deliver_policy)?;
@@ -82,19 +94,39 @@ pub fn stop_session(app: AppHandle, service: State<'_, AppService>) -> CmdResult
 
 #[tauri::command]
 pub fn toggle_recording(app: AppHandle, service: State<'_, AppService>) -> CmdResult<()> {
+    toggle_recording_with(&app, &service, false)
+}
+
+/// 音声のみ録音をトグルする（STT は行わず、セグメントを WAV として保存する）
+#[tauri::command]
+pub fn record_audio_only(app: AppHandle, service: State<'_, AppService>) -> CmdResult<()> {
+    toggle_recording_with(&app, &service, true)
+}
+
+fn toggle_recording_with(app: &AppHandle, service: &AppService, audio_only: bool) -> CmdResult<()> {
+    // 直前のトグルから間もない連打は無視する（状態は変えず、イベントも送らない）
+    if !service.admit_toggle() {
+        log::debug!("Toggle ignored: within toggle_debounce_ms");
+        return Ok(());
+    }
     let current_state = service.current_state();
 
-    if current_state.as_deref() == Some("recording") {
-        // 録音中 → 一時停止（パイプライン停止 + Recording→Idle）
+    if matches!(current_state.as_deref(), Some("recording" | "paused")) {
+        // 録音中・一時停止中 → 録音終了（パイプライン停止 + Recording/Paused→Idle）
         let transition = service.pause_recording()?;
-        emit_state_changed(&app, &transition);
+        emit_state_changed(app, &transition);
     } else {
         // Idle → Recording（パイプライン開始）
         let transition = service.toggle_recording()?;
-        emit_state_changed(&app, &transition);
+        emit_state_changed(app, &transition);
 
         if transition.new_state == SessionState::Recording {
-            match service.start_pipeline() {
+            let started = if audio_only {
+                service.start_audio_only_pipeline()
+            } else {
+                service.start_pipeline()
+            };
+            match started {
                 Ok(event_rx) => {
                     spawn_event_forwarder(app.clone(), event_rx);
                 }
@@ -102,15 +134,15 @@ pub fn toggle_recording(app: AppHandle, service: State<'_, AppService>) -> CmdRe
                     log::error!("Failed to start audio pipeline: {}", e);
                     // パイプライン開始失敗 → Idle に戻す
                     if let Ok(revert) = service.pause_recording() {
-                        emit_state_changed(&app, &revert);
+                        emit_state_changed(app, &revert);
                     }
                     events::emit_event(
-                        &app,
+                        app,
                         ERROR,
                         ErrorPayload {
-                            code: ErrorCode::Device,
-                            message: e.to_string(),
-                            recoverable: true,
+                            code: e.code,
+                            message: e.message.clone(),
+                            recoverable: e.recoverable,
                             session_id: service.current_session_id(),
                         },
                     );
@@ -123,6 +155,28 @@ pub fn toggle_recording(app: AppHandle, service: State<'_, AppService>) -> CmdRe
     Ok(())
 }
 
+/// 現在の状態のスナップショットを返す（UI がマウント時に呼んで再同期する）
+#[tauri::command]
+pub fn get_snapshot(service: State<'_, AppService>) -> AppSnapshot {
+    service.current_snapshot()
+}
+
+/// 録音を一時停止する（セグメントは確定させず、resume_capture で同じセグメントに続けて録音する）
+#[tauri::command]
+pub fn pause_capture(app: AppHandle, service: State<'_, AppService>) -> CmdResult<()> {
+    let transition = service.pause_capture()?;
+    emit_state_changed(&app, &transition);
+    Ok(())
+}
+
+/// 一時停止中の録音を再開する
+#[tauri::command]
+pub fn resume_capture(app: AppHandle, service: State<'_, AppService>) -> CmdResult<()> {
+    let transition = service.resume_capture()?;
+    emit_state_changed(&app, &transition);
+    Ok(())
+}
+
 /// パイプラインイベント → Tauri イベントの転送スレッドを起動
 fn spawn_event_forwarder(app: AppHandle, event_rx: mpsc::Receiver<PipelineEvent>) {
     std::thread::spawn(move || {
@@ -132,123 +186,338 @@ fn spawn_event_forwarder(app: AppHandle, event_rx: mpsc::Receiver<PipelineEvent>
                 PipelineEvent::AudioLevel(rms) => {
                     events::emit_event(&app, AUDIO_LEVEL, AudioLevelPayload { rms });
                 }
+                PipelineEvent::Clipping { ratio } => {
+                    events::emit_event(&app, CLIPPING, ClippingPayload { ratio });
+                }
                 PipelineEvent::TranscriptPartial { text } => {
+                    app.state::<AppService>().on_pipeline_partial(&text);
                     events::emit_event(&app, TRANSCRIPT_PARTIAL, TranscriptPartialPayload { text });
                 }
-                PipelineEvent::TranscriptFinal { text, confidence } => {
+                PipelineEvent::CaptionUpdate { text, is_partial } => {
+                    events::emit_event(
+                        &app,
+                        CAPTION_UPDATE,
+                        CaptionUpdatePayload { text, is_partial },
+                    );
+                }
+                PipelineEvent::TranscriptFinal {
+                    segment_id,
+                    text,
+                    confidence,
+                    timings,
+                    mode,
+                } => {
                     let service = app.state::<AppService>();
-                    match service.on_pipeline_transcript(&text, confidence) {
-                        Ok((processed_text, segment_id)) => {
-                            events::emit_event(
-                                &app,
-                                TRANSCRIPT_FINAL,
-                                TranscriptFinalPayload {
-                                    text: processed_text.clone(),
-                                    confidence,
-                                    segment_id: Some(segment_id.clone()),
-                                },
-                            );
-
-                            // 自動リライト: rewrite_enabled && mode != Raw の場合
-                            let should_rewrite = {
-                                let settings = service.get_settings().ok();
-                                let mode = service.current_mode();
-                                settings.map(|s| s.rewrite_enabled).unwrap_or(false)
-                                    && mode.map(|m| m != Mode::Raw).unwrap_or(false)
-                            };
-
-                            if should_rewrite {
-                                let app_clone = app.clone();
-                                let text_for_rewrite = processed_text;
-                                let seg_id = segment_id;
-                                let mode = service.current_mode().unwrap_or(Mode::Raw);
-                                let session_id = service.current_session_id().unwrap_or_default();
-
-                                // 非同期でリライト実行（パイプラインをブロックしない）
-                                std::thread::spawn(move || {
-                                    let rt = tokio::runtime::Builder::new_current_thread()
-                                        .enable_all()
-                                        .build();
-                                    if let Ok(rt) = rt {
-                                        let svc = app_clone.state::<AppService>();
-                                        match rt.block_on(svc.rewrite_text(&text_for_rewrite, mode))
-                                        {
-                                            Ok(rewritten) => {
-                                                let _ = svc.on_rewrite_done(&seg_id, &rewritten);
-                                                events::emit_event(
-                                                    &app_clone,
-                                                    REWRITE_DONE,
-                                                    events::RewriteDonePayload {
-                                                        session_id,
-                                                        segment_id: seg_id,
-                                                        text: rewritten,
-                                                        mode: mode.to_string(),
-                                                    },
-                                                );
-                                            }
-                                            Err(e) => {
-                                                log::error!("Auto-rewrite failed: {}", e);
-                                                events::emit_event(
-                                                    &app_clone,
-                                                    ERROR,
-                                                    ErrorPayload {
-                                                        code: ErrorCode::Rewrite,
-                                                        message: e.to_string(),
-                                                        recoverable: true,
-                                                        session_id: Some(session_id),
-                                                    },
-                                                );
-                                            }
-                                        }
-                                    }
-                                });
-                            }
+                    match service.handle_pipeline_transcript(
+                        &segment_id,
+                        &text,
+                        confidence,
+                        &timings,
+                        mode,
+                    ) {
+                        Ok(TranscriptOutcome::ModeSwitched(switched)) => {
+                            events::emit_event(&app, MODE_SWITCHED_BY_VOICE, switched);
+                        }
+                        Ok(TranscriptOutcome::Saved(saved)) => {
+                            emit_saved_transcript(&app, saved);
                         }
                         Err(e) => {
-                            log::error!("Pipeline transcript processing error: {}", e);
+                            log::error!(
+                                target: "vt_core::pipeline",
+                                "[segment={segment_id}] Pipeline transcript processing error: {}",
+                                e
+                            );
                         }
                     }
                 }
-                PipelineEvent::Error(msg) => {
+                PipelineEvent::CaptureFormat {
+                    sample_rate,
+                    channels,
+                } => {
+                    events::emit_event(
+                        &app,
+                        CAPTURE_FORMAT,
+                        CaptureFormatPayload {
+                            sample_rate,
+                            channels,
+                        },
+                    );
+                }
+                PipelineEvent::AudioSegmentSaved {
+                    segment_id,
+                    audio_path,
+                } => {
+                    let service = app.state::<AppService>();
+                    match service.on_pipeline_audio_segment(&segment_id, &audio_path) {
+                        Ok(()) => apply_segment_limit(&app, &service),
+                        Err(e) => log::error!(
+                            target: "vt_core::pipeline",
+                            "[segment={segment_id}] Audio-only segment processing error: {}",
+                            e
+                        ),
+                    }
+                }
+                PipelineEvent::SessionIdleTimeout => {
+                    // 長い無音 → 録音を自動停止（Recording→Idle）
+                    let service = app.state::<AppService>();
+                    match service.pause_recording() {
+                        Ok(transition) => emit_state_changed(&app, &transition),
+                        Err(e) => log::error!("Idle auto-stop failed: {}", e),
+                    }
+                }
+                PipelineEvent::SessionDurationLimit => {
+                    // 録音時間の上限 → 録音を自動停止（Recording→Idle）
+                    let service = app.state::<AppService>();
+                    match service.on_session_duration_limit() {
+                        Ok(Some((limit, transition))) => {
+                            events::emit_event(&app, SESSION_DURATION_LIMIT, limit);
+                            emit_state_changed(&app, &transition);
+                        }
+                        Ok(None) => {}
+                        Err(e) => log::error!("Duration limit auto-stop failed: {}", e),
+                    }
+                }
+                PipelineEvent::Error(e) => {
+                    let service = app.state::<AppService>();
                     events::emit_event(
                         &app,
                         ERROR,
                         ErrorPayload {
-                            code: ErrorCode::Pipeline,
-                            message: msg,
-                            recoverable: true,
-                            session_id: None,
+                            code: e.code,
+                            message: e.message.clone(),
+                            recoverable: e.recoverable,
+                            session_id: service.current_session_id(),
                         },
                     );
+                    // パイプラインが止まったエラーなら Error 状態へ（auto_recover 時は Idle まで戻す）
+                    match service.on_pipeline_error(&e) {
+                        Ok(transitions) => {
+                            for transition in &transitions {
+                                emit_state_changed(&app, transition);
+                            }
+                        }
+                        Err(err) => log::error!("Failed to apply pipeline error: {}", err),
+                    }
                 }
             }
         }
+        app.state::<AppService>().on_pipeline_events_drained();
         log::info!("Pipeline event forwarder thread exiting");
     });
 }
 
+/// 保存した確定セグメントをイベントとして送り、レビュー待ちでなければ自動リライトを始める
+fn emit_saved_transcript(app: &AppHandle, saved: SavedTranscript) {
+    events::emit_event(
+        app,
+        TRANSCRIPT_FINAL,
+        TranscriptFinalPayload {
+            text: saved.text.clone(),
+            confidence: saved.confidence,
+            segment_id: Some(saved.segment_id.clone()),
+        },
+    );
+    // 字幕は確定時だけ後処理済みのテキストで更新する
+    if saved.caption {
+        events::emit_event(
+            app,
+            CAPTION_UPDATE,
+            CaptionUpdatePayload {
+                text: saved.text.clone(),
+                is_partial: false,
+            },
+        );
+    }
+
+    // レビュー有効時は確認待ちにし、リライト・配信は commit_segment 後に行う
+    if saved.for_review {
+        events::emit_event(
+            app,
+            TRANSCRIPT_FOR_REVIEW,
+            TranscriptForReviewPayload {
+                segment_id: saved.segment_id,
+                text: saved.text,
+            },
+        );
+    } else {
+        spawn_auto_rewrite(app.clone(), saved.segment_id, saved.text);
+    }
+
+    if let Some((limit, transitions)) = saved.segment_limit {
+        emit_segment_limit(app, limit, &transitions);
+    }
+}
+
+/// 自動リライトを別スレッドで実行する（パイプラインをブロックしない）
+///
+/// 実行するかはセグメントを録音したときのモードで `auto_rewrite_by_mode` を見て決める（Raw は常にスキップ）。
+fn spawn_auto_rewrite(app: AppHandle, segment_id: String, text: String) {
+    std::thread::spawn(move || {
+        use tauri::Manager;
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build();
+        if let Ok(rt) = rt {
+            let service = app.state::<AppService>();
+            let session_id = service.segment_session_id(&segment_id).ok().flatten();
+            match rt.block_on(service.auto_rewrite_segment(&segment_id, &text)) {
+                Ok(Some((mode, rewritten))) => {
+                    events::emit_event(
+                        &app,
+                        REWRITE_DONE,
+                        events::RewriteDonePayload {
+                            session_id: session_id.unwrap_or_default(),
+                            segment_id,
+                            text: rewritten,
+                            mode: mode.to_string(),
+                        },
+                    );
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    log::error!("Auto-rewrite failed: {}", e);
+                    events::emit_event(
+                        &app,
+                        ERROR,
+                        ErrorPayload {
+                            code: ErrorCode::Rewrite,
+                            message: e.to_string(),
+                            recoverable: true,
+                            session_id,
+                        },
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// セグメント数が上限に達していれば設定どおりにセッションを止める・切り替え、session_segment_limit を送る
+fn apply_segment_limit(app: &AppHandle, service: &AppService) {
+    match service.enforce_segment_limit() {
+        Ok(Some((limit, transitions))) => emit_segment_limit(app, limit, &transitions),
+        Ok(None) => {}
+        Err(e) => log::error!("Segment limit handling failed: {}", e),
+    }
+}
+
+fn emit_segment_limit(
+    app: &AppHandle,
+    limit: SessionSegmentLimit,
+    transitions: &[StateTransition],
+) {
+    events::emit_event(app, SESSION_SEGMENT_LIMIT, limit);
+    for transition in transitions {
+        emit_state_changed(app, transition);
+    }
+}
+
+/// app_event 出力先への配信 → deliver_to_editor イベントの転送スレッドを起動
+pub fn spawn_editor_forwarder(app: AppHandle, editor_rx: mpsc::Receiver<DeliverToEditorPayload>) {
+    std::thread::spawn(move || {
+        for payload in editor_rx {
+            events::emit_event(&app, DELIVER_TO_EDITOR, payload);
+        }
+    });
+}
+
+/// 配信テキストの切り詰め通知 → deliver_truncated イベントの転送スレッドを起動
+pub fn spawn_truncation_forwarder(app: AppHandle, truncation_rx: mpsc::Receiver<DeliverTruncated>) {
+    std::thread::spawn(move || {
+        for payload in truncation_rx {
+            events::emit_event(&app, DELIVER_TRUNCATED, payload);
+        }
+    });
+}
+
 #[tauri::command]
 pub fn set_mode(service: State<'_, AppService>, mode: Mode) -> CmdResult<()> {
     service.set_mode(mode)?;
     Ok(())
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct GetHistoryArgs {
-    #[serde(default)]
-    query: Option<String>,
-    limit: u32,
-    #[serde(default)]
-    cursor: Option<String>,
+/// スピーカー再生中など、VAD に発話を検出させたくない区間の開始・終了で呼ぶ
+#[tauri::command]
+pub fn set_vad_muted(service: State<'_, AppService>, muted: bool) -> CmdResult<()> {
+    service.set_vad_muted(muted);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_task(service: State<'_, AppService>, task: WhisperTask) -> CmdResult<()> {
+    service.set_task(task)?;
+    Ok(())
 }
 
 #[tauri::command]
 pub fn get_history(service: State<'_, AppService>, args: GetHistoryArgs) -> CmdResult<HistoryPage> {
-    let page = service.get_history(args.limit, args.cursor.as_deref(), args.query.as_deref())?;
+    let page = service.get_history(
+        args.limit,
+        args.cursor.as_deref(),
+        args.query.as_deref(),
+        args.pinned_first,
+    )?;
     Ok(page)
 }
 
+#[tauri::command]
+pub fn pin_session(
+    service: State<'_, AppService>,
+    session_id: String,
+    pinned: bool,
+) -> CmdResult<()> {
+    service.pin_session(&session_id, pinned)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn tag_session(
+    service: State<'_, AppService>,
+    session_id: String,
+    tag: String,
+) -> CmdResult<Vec<String>> {
+    Ok(service.tag_session(&session_id, &tag)?)
+}
+
+#[tauri::command]
+pub fn untag_session(
+    service: State<'_, AppService>,
+    session_id: String,
+    tag: String,
+) -> CmdResult<Vec<String>> {
+    Ok(service.untag_session(&session_id, &tag)?)
+}
+
+#[tauri::command]
+pub fn list_sessions_by_tag(
+    service: State<'_, AppService>,
+    tag: String,
+    limit: u32,
+    cursor: Option<String>,
+) -> CmdResult<HistoryPage> {
+    Ok(service.list_sessions_by_tag(&tag, limit, cursor.as_deref())?)
+}
+
+#[tauri::command]
+pub fn edit_segment(
+    service: State<'_, AppService>,
+    segment_id: String,
+    raw: String,
+    rewritten: Option<String>,
+) -> CmdResult<()> {
+    service.edit_segment(&segment_id, &raw, rewritten.as_deref())?;
+    Ok(())
+}
+
+/// 現在の辞書・設定でセッションのセグメントの後処理をやり直す（更新したセグメント数を返す）
+#[tauri::command]
+pub fn reapply_post_processing(
+    service: State<'_, AppService>,
+    session_id: String,
+) -> CmdResult<u32> {
+    Ok(service.reapply_post_processing(&session_id)?)
+}
+
 #[tauri::command]
 pub fn get_session(
     service: State<'_, AppService>,
@@ -258,6 +527,31 @@ pub fn get_session(
     Ok(detail)
 }
 
+/// セッション全体のテキストを連結してクリップボードへ出力する（履歴からのコピー用）
+#[tauri::command]
+pub fn copy_session(
+    service: State<'_, AppService>,
+    session_id: String,
+    prefer: Option<DeliverText>,
+    join: Option<JoinStyle>,
+) -> CmdResult<String> {
+    let text = service.copy_session(
+        &session_id,
+        prefer.unwrap_or_default(),
+        join.unwrap_or_default(),
+    )?;
+    Ok(text)
+}
+
+#[tauri::command]
+pub fn get_segment_timings(
+    service: State<'_, AppService>,
+    segment_id: String,
+) -> CmdResult<Vec<SegmentTiming>> {
+    let timings = service.get_segment_timings(&segment_id)?;
+    Ok(timings)
+}
+
 #[tauri::command]
 pub fn upsert_dictionary(
     service: State<'_, AppService>,
@@ -276,6 +570,15 @@ pub fn list_dictionary(
     Ok(entries)
 }
 
+#[tauri::command]
+pub fn suggest_dictionary_terms(
+    service: State<'_, AppService>,
+    limit: Option<usize>,
+) -> CmdResult<Vec<TermSuggestion>> {
+    let suggestions = service.suggest_dictionary_terms(limit.unwrap_or(20))?;
+    Ok(suggestions)
+}
+
 #[tauri::command]
 pub async fn rewrite_last(
     app: AppHandle,
@@ -303,21 +606,73 @@ pub async fn rewrite_last(
     Ok(())
 }
 
+/// セッションのポリシーを変えずに、指定ポリシーで一度だけ配信する
+#[tauri::command]
+pub fn deliver_to(
+    service: State<'_, AppService>,
+    text: String,
+    policy: DeliverPolicy,
+) -> CmdResult<DeliverTarget> {
+    let target = service.deliver_to(&text, policy)?;
+    Ok(target)
+}
+
 #[tauri::command]
-pub fn deliver_last(app: AppHandle, service: State<'_, AppService>) -> CmdResult<()> {
-    let (transition, _text, delivered_target) = service.deliver_last(None)?;
+pub fn deliver_last(
+    app: AppHandle,
+    service: State<'_, AppService>,
+    prefer: Option<DeliverText>,
+) -> CmdResult<DeliverLastResult> {
+    let (transition, result) = service.deliver_last(None, prefer.unwrap_or_default())?;
 
     events::emit_event(
         &app,
         DELIVER_DONE,
         events::DeliverDonePayload {
             session_id: transition.session_id.clone(),
-            target: delivered_target.as_str().to_string(),
+            target: result.target.as_str().to_string(),
         },
     );
 
     emit_state_changed(&app, &transition);
 
+    Ok(result)
+}
+
+/// レビューで修正したテキストを確定し、配信へ進める
+#[tauri::command]
+pub fn commit_segment(
+    app: AppHandle,
+    service: State<'_, AppService>,
+    segment_id: String,
+    edited_text: String,
+) -> CmdResult<()> {
+    let delivered = service.commit_and_deliver(&segment_id, &edited_text, |transition| {
+        emit_state_changed(&app, transition);
+    })?;
+    if let Some((session_id, target)) = delivered {
+        events::emit_event(
+            &app,
+            DELIVER_DONE,
+            events::DeliverDonePayload {
+                session_id,
+                target: target.as_str().to_string(),
+            },
+        );
+    }
+    Ok(())
+}
+
+/// 蓄積済みテキストをクリップボードへ書き込み、バッファを空にする
+#[tauri::command]
+pub fn flush_accumulated(service: State<'_, AppService>) -> CmdResult<String> {
+    let text = service.flush_accumulated()?;
+    Ok(text)
+}
+
+#[tauri::command]
+pub fn clear_accumulated(service: State<'_, AppService>) -> CmdResult<()> {
+    service.clear_accumulated();
     Ok(())
 }
 
@@ -335,6 +690,12 @@ pub fn update_settings(service: State<'_, AppService>, settings: AppSettings) ->
     Ok(())
 }
 
+#[tauri::command]
+pub fn update_vad_config(service: State<'_, AppService>, config: VadConfig) -> CmdResult<()> {
+    service.update_vad_config(config)?;
+    Ok(())
+}
+
 #[tauri::command]
 pub fn check_permissions(service: State<'_, AppService>) -> CmdResult<PermissionStatus> {
     Ok(service.check_permissions())
@@ -345,17 +706,71 @@ pub fn check_setup_status(service: State<'_, AppService>) -> CmdResult<SetupStat
     Ok(service.check_setup_status())
 }
 
+#[tauri::command]
+pub fn diagnose(service: State<'_, AppService>) -> CmdResult<DiagnosticsReport> {
+    Ok(service.diagnose())
+}
+
+/// マイクを指定時間だけ開いて入力レベルを測る
+///
+/// 測定中（最大 10 秒）は待ち続けるため、非同期ランタイムのワーカーではなくブロッキング用のスレッドで実行する。
+#[tauri::command]
+pub async fn test_microphone(app: AppHandle, duration_ms: u64) -> CmdResult<MicTestResult> {
+    use tauri::Manager;
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        app.state::<AppService>().test_microphone(duration_ms)
+    })
+    .await
+    .map_err(|e| {
+        vt_core::domain::error::AppError::internal(format!("マイクテストが中断されました: {e}"))
+    })??;
+    Ok(result)
+}
+
 #[tauri::command]
 pub fn get_metrics(service: State<'_, AppService>) -> CmdResult<MetricsSummary> {
     Ok(service.get_metrics())
 }
 
 #[tauri::command]
-pub fn cleanup_data(service: State<'_, AppService>, ttl_days: u32) -> CmdResult<(u32, u32)> {
+pub fn export_metrics(
+    service: State<'_, AppService>,
+    format: MetricsExportFormat,
+) -> CmdResult<String> {
+    let exported = service.export_metrics(format)?;
+    Ok(exported)
+}
+
+#[tauri::command]
+pub fn cleanup_data(
+    service: State<'_, AppService>,
+    ttl_days: u32,
+) -> CmdResult<DeleteSessionResult> {
     let result = service.cleanup_old_data(ttl_days)?;
     Ok(result)
 }
 
+#[tauri::command]
+pub fn audio_cache_stats(service: State<'_, AppService>) -> CmdResult<AudioCacheStats> {
+    let stats = service.audio_cache_stats()?;
+    Ok(stats)
+}
+
+#[tauri::command]
+pub fn clear_audio_cache(service: State<'_, AppService>) -> CmdResult<u32> {
+    let removed = service.clear_audio_cache()?;
+    Ok(removed)
+}
+
+#[tauri::command]
+pub fn delete_session(
+    service: State<'_, AppService>,
+    session_id: String,
+) -> CmdResult<DeleteSessionResult> {
+    let result = service.delete_session(&session_id)?;
+    Ok(result)
+}
+
 #[tauri::command]
 pub fn paste_to_active_app(service: State<'_, AppService>, text: String) -> CmdResult<PasteResult> {
     let result = service.paste_to_active_app(&text)?;
@@ -398,46 +813,111 @@ fn parse_model_size(s: Option<String>) -> vt_core::domain::settings::WhisperMode
 #[tauri::command]
 pub fn check_whisper_model(model_size: Option<String>) -> bool {
     let size = parse_model_size(model_size);
-    vt_core::infra::stt::whisper::WhisperSttEngine::is_model_available_for(size)
+    WhisperModelManager::default().is_available(size)
 }
 
 #[tauri::command]
-pub async fn download_whisper_model(model_size: Option<String>) -> CmdResult<String> {
-    let size = parse_model_size(model_size);
-    let model_path = vt_core::infra::stt::whisper::WhisperSttEngine::model_path_for(size);
+pub fn list_whisper_models() -> Vec<ModelStatus> {
+    WhisperModelManager::default().list()
+}
 
-    if model_path.exists() {
-        return Ok(model_path.to_string_lossy().to_string());
-    }
+#[tauri::command]
+pub fn list_installed_whisper_models() -> Vec<ModelInfo> {
+    WhisperModelManager::default().list_installed()
+}
+
+#[tauri::command]
+pub fn get_engine_capabilities(service: State<'_, AppService>) -> EngineCapabilities {
+    service.engine_capabilities()
+}
+
+#[tauri::command]
+pub fn set_whisper_model(service: State<'_, AppService>, name: String) -> CmdResult<ModelInfo> {
+    Ok(service.set_whisper_model(&name)?)
+}
 
-    // モデルディレクトリ作成
-    if let Some(parent) = model_path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| {
-            vt_core::domain::error::AppError::internal(format!("モデルディレクトリ作成失敗: {e}"))
+#[tauri::command]
+pub async fn download_whisper_model(
+    app: AppHandle,
+    model_size: Option<String>,
+) -> CmdResult<String> {
+    let size = parse_model_size(model_size);
+    let model_path = WhisperModelManager::default()
+        .download(size, |progress| {
+            events::emit_event(
+                &app,
+                MODEL_DOWNLOAD_PROGRESS,
+                ModelDownloadProgressPayload {
+                    model_size: size,
+                    downloaded_bytes: progress.downloaded_bytes,
+                    total_bytes: progress.total_bytes,
+                },
+            );
+        })
+        .await
+        .map_err(|e| {
+            vt_core::domain::error::AppError::internal(format!("モデルダウンロード失敗: {e}"))
         })?;
-    }
 
-    let url = size.download_url();
+    Ok(model_path.to_string_lossy().to_string())
+}
 
-    let response = reqwest::get(&url).await.map_err(|e| {
-        vt_core::domain::error::AppError::internal(format!("モデルダウンロード失敗: {e}"))
-    })?;
+/// 保存済み音声から、指定サイズの Whisper モデルでセグメントを書き起こし直す
+#[tauri::command]
+pub async fn retranscribe_segment(
+    service: State<'_, AppService>,
+    session_id: String,
+    segment_id: String,
+    model_size: Option<String>,
+) -> CmdResult<String> {
+    use vt_core::infra::stt::whisper::WhisperSttEngine;
 
-    if !response.status().is_success() {
-        return Err(vt_core::domain::error::AppError::internal(format!(
-            "モデルダウンロード失敗: HTTP {}",
-            response.status()
+    let size = parse_model_size(model_size);
+    let manager = WhisperModelManager::default();
+    if !manager.is_available(size) {
+        return Err(vt_core::domain::error::AppError::stt_unavailable(format!(
+            "Whisper モデルがありません: {}",
+            size.filename()
         ))
         .into());
     }
+    let model_path = manager.model_path(size);
+    let engine = WhisperSttEngine::new(&model_path.to_string_lossy())
+        .map_err(vt_core::domain::error::AppError::from)?;
+
+    let text = service
+        .retranscribe_segment(&session_id, &segment_id, &engine)
+        .await?;
+    Ok(text)
+}
 
-    let bytes = response.bytes().await.map_err(|e| {
-        vt_core::domain::error::AppError::internal(format!("モデルデータ受信失敗: {e}"))
-    })?;
-
-    std::fs::write(&model_path, &bytes).map_err(|e| {
-        vt_core::domain::error::AppError::internal(format!("モデルファイル保存失敗: {e}"))
-    })?;
+/// 音声のみ録音したセッションを一括で書き起こす（進捗は transcribe_progress イベント）
+#[tauri::command]
+pub async fn transcribe_session(
+    app: AppHandle,
+    service: State<'_, AppService>,
+    session_id: String,
+) -> CmdResult<TranscribeSessionResult> {
+    let result = service
+        .transcribe_session(&session_id, |progress| {
+            events::emit_event(&app, TRANSCRIBE_PROGRESS, progress);
+        })
+        .await?;
+    Ok(result)
+}
 
-    Ok(model_path.to_string_lossy().to_string())
+/// 録音済みの WAV ファイルを書き起こし、新しいセッションとして保存する（進捗は transcribe_progress イベント）
+#[tauri::command]
+pub async fn transcribe_file(
+    app: AppHandle,
+    service: State<'_, AppService>,
+    path: String,
+    mode: Mode,
+) -> CmdResult<TranscribeFileResult> {
+    let result = service
+        .transcribe_file(std::path::Path::new(&path), mode, |progress| {
+            events::emit_event(&app, TRANSCRIBE_PROGRESS, progress);
+        })
+        .await?;
+    Ok(result)
 }
@@ -1,15 +1,28 @@
 use serde::Serialize;
 use tauri::{AppHandle, Emitter};
 use vt_core::domain::error::ErrorCode;
+use vt_core::domain::settings::WhisperModelSize;
 
 /// イベント名定数
 pub const SESSION_STATE_CHANGED: &str = "session_state_changed";
+pub const SESSION_SEGMENT_LIMIT: &str = "session_segment_limit";
+pub const SESSION_DURATION_LIMIT: &str = "session_duration_limit";
 pub const AUDIO_LEVEL: &str = "audio_level";
+pub const CLIPPING: &str = "clipping";
+pub const CAPTURE_FORMAT: &str = "capture_format";
 pub const TRANSCRIPT_PARTIAL: &str = "transcript_partial";
+pub const CAPTION_UPDATE: &str = "caption_update";
 pub const TRANSCRIPT_FINAL: &str = "transcript_final";
+pub const TRANSCRIPT_FOR_REVIEW: &str = "transcript_for_review";
 pub const REWRITE_DONE: &str = "rewrite_done";
 pub const DELIVER_DONE: &str = "deliver_done";
+pub const DELIVER_TO_EDITOR: &str = "deliver_to_editor";
+pub const DELIVER_TRUNCATED: &str = "deliver_truncated";
+pub const MODE_SWITCHED_BY_VOICE: &str = "mode_switched_by_voice";
 pub const ERROR: &str = "error";
+pub const MODEL_DOWNLOAD_PROGRESS: &str = "model_download_progress";
+pub const TRANSCRIBE_PROGRESS: &str = "transcribe_progress";
+pub const APP_SNAPSHOT: &str = "app_snapshot";
 
 /// 統一イベント送信関数
 pub fn emit_event<S: Serialize + Clone>(app: &AppHandle, event_name: &str, payload: S) {
@@ -52,12 +65,32 @@ pub struct AudioLevelPayload {
     pub rms: f32,
 }
 
+/// clipping ペイロード
+#[derive(Debug, Clone, Serialize)]
+pub struct ClippingPayload {
+    pub ratio: f32,
+}
+
+/// capture_format ペイロード（デバイスのネイティブ形式）
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureFormatPayload {
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
 /// transcript_partial ペイロード
 #[derive(Debug, Clone, Serialize)]
 pub struct TranscriptPartialPayload {
     pub text: String,
 }
 
+/// caption_update ペイロード（部分書き起こしは後処理前、確定は後処理済みのテキスト）
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptionUpdatePayload {
+    pub text: String,
+    pub is_partial: bool,
+}
+
 /// transcript_final ペイロード
 #[derive(Debug, Clone, Serialize)]
 pub struct TranscriptFinalPayload {
@@ -67,9 +100,25 @@ pub struct TranscriptFinalPayload {
     pub segment_id: Option<String>,
 }
 
+/// transcript_for_review ペイロード
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptForReviewPayload {
+    pub segment_id: String,
+    pub text: String,
+}
+
 /// deliver_done ペイロード
 #[derive(Debug, Clone, Serialize)]
 pub struct DeliverDonePayload {
     pub session_id: String,
     pub target: String,
 }
+
+/// model_download_progress ペイロード
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelDownloadProgressPayload {
+    pub model_size: WhisperModelSize,
+    pub downloaded_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_bytes: Option<u64>,
+}
@@ -3,18 +3,23 @@ mod events;
 
 use std::sync::Arc;
 
-use vt_core::domain::settings::SttEngineChoice;
+use tauri::Manager;
+use vt_core::domain::settings::{AppSettings, SttEngineChoice};
 use vt_core::infra::rewriter::Rewriter;
 use vt_core::infra::storage::Storage;
-use vt_core::infra::stt::SttEngine;
-use vt_core::usecase::app_service::AppService;
+use vt_core::infra::stt::{fallback_order, FallbackSttEngine, SttEngine};
+use vt_core::usecase::app_service::{AppService, SHUTDOWN_TIMEOUT};
 
 /// リライターを構築する（API Key あり → Claude, なし → Noop）
+///
+/// API キーはシークレットストア（macOS Keychain / 開発環境ではファイル）から読む。
 fn create_rewriter(storage: &Storage) -> Arc<dyn Rewriter> {
     let api_key = storage
-        .get_settings()
-        .ok()
-        .and_then(|s| s.claude_api_key)
+        .get_secret("claude_api_key")
+        .unwrap_or_else(|e| {
+            log::error!("Claude API キーの読み取りに失敗: {}", e);
+            None
+        })
         .unwrap_or_default();
 
     if !api_key.is_empty() {
@@ -28,76 +33,92 @@ fn create_rewriter(storage: &Storage) -> Arc<dyn Rewriter> {
     }
 }
 
-/// STT エンジンを構築する（設定に応じて選択）
-fn create_stt_engine(storage: &Storage) -> Arc<dyn SttEngine> {
-    let settings = storage.get_settings().unwrap_or_default();
-
-    match settings.stt_engine {
-        SttEngineChoice::Apple => {
-            #[cfg(target_os = "macos")]
-            {
-                use vt_core::infra::stt::apple_speech::AppleSttEngine;
-                if AppleSttEngine::is_available() {
-                    log::info!("Apple Speech STT engine selected");
-                    return Arc::new(AppleSttEngine);
-                }
-                log::warn!("Apple Speech not available, falling back to Noop STT");
-            }
-            #[cfg(not(target_os = "macos"))]
-            {
-                log::warn!("Apple Speech is only available on macOS, falling back to Noop STT");
-            }
+/// Apple Speech エンジンを構築する（利用不可なら None）
+fn build_apple_engine() -> Option<Arc<dyn SttEngine>> {
+    #[cfg(target_os = "macos")]
+    {
+        use vt_core::infra::stt::apple_speech::AppleSttEngine;
+        if AppleSttEngine::is_available() {
+            return Some(Arc::new(AppleSttEngine));
         }
-        SttEngineChoice::Whisper => {
-            use vt_core::infra::stt::whisper::{WhisperConfig, WhisperSttEngine};
-            let model_path = WhisperSttEngine::model_path_for(settings.whisper_model_size);
-            if model_path.exists() {
-                match WhisperSttEngine::with_config(
-                    &model_path.to_string_lossy(),
-                    WhisperConfig::default(),
-                ) {
-                    Ok(engine) => {
-                        log::info!(
-                            "Whisper STT engine selected (model: {:?})",
-                            settings.whisper_model_size
-                        );
-                        return Arc::new(engine);
-                    }
-                    Err(e) => {
-                        log::error!("Whisper engine init failed: {}, falling back to Noop", e);
-                    }
-                }
-            } else {
-                log::warn!(
-                    "Whisper model not found at {:?}, falling back to Noop STT",
-                    model_path
-                );
-            }
+        log::warn!("Apple Speech not available");
+    }
+    #[cfg(not(target_os = "macos"))]
+    log::debug!("Apple Speech is only available on macOS");
+    None
+}
+
+/// Whisper エンジンを構築する（モデルが無い・初期化失敗なら None）
+fn build_whisper_engine(settings: &AppSettings) -> Option<Arc<dyn SttEngine>> {
+    use vt_core::infra::stt::whisper::{WhisperConfig, WhisperSttEngine};
+    let model_path = WhisperSttEngine::model_path_for(settings.whisper_model_size);
+    if !model_path.exists() {
+        log::warn!("Whisper model not found at {:?}", model_path);
+        return None;
+    }
+    let model_path = match WhisperSttEngine::preflight(&model_path, settings.auto_downgrade_model) {
+        Ok(path) => path,
+        Err(e) => {
+            log::error!("Whisper engine init skipped: {}", e);
+            return None;
         }
-        SttEngineChoice::Cloud | SttEngineChoice::Soniox => {
-            let api_key = settings.soniox_api_key.unwrap_or_default();
-            if !api_key.is_empty() {
-                if matches!(settings.stt_engine, SttEngineChoice::Cloud) {
-                    log::info!("Cloud STT selected; using Soniox backend");
-                } else {
-                    log::info!("Soniox STT engine selected");
-                }
-                return Arc::new(vt_core::infra::stt::soniox::SonioxSttEngine::new(api_key));
-            }
-            if matches!(settings.stt_engine, SttEngineChoice::Cloud) {
-                log::warn!("Cloud STT selected but soniox_api_key is not configured, falling back to Noop STT");
-            } else {
-                log::warn!("Soniox API key not configured, falling back to Noop STT");
-            }
+    };
+    match WhisperSttEngine::with_config(
+        &model_path.to_string_lossy(),
+        WhisperConfig::from_settings(settings),
+    ) {
+        Ok(engine) => Some(Arc::new(engine)),
+        Err(e) => {
+            log::error!("Whisper engine init failed: {}", e);
+            None
         }
     }
+}
 
-    log::info!("Using Noop STT engine");
-    Arc::new(vt_core::infra::stt::NoopSttEngine)
+/// Soniox（Cloud）エンジンを構築する（API キー未設定なら None）
+fn build_cloud_engine(settings: &AppSettings) -> Option<Arc<dyn SttEngine>> {
+    let api_key = settings.soniox_api_key.clone().unwrap_or_default();
+    if api_key.is_empty() {
+        log::warn!("Soniox API key not configured");
+        return None;
+    }
+    Some(Arc::new(vt_core::infra::stt::soniox::SonioxSttEngine::new(
+        api_key,
+    )))
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
+/// STT エンジンを構築する
+///
+/// 選択中のエンジンを先頭に、Apple → Whisper の順で利用可能なものを FallbackSttEngine に並べる
+/// （並び順は [`fallback_order`]、クラウドは選択中か `allow_cloud_fallback` 有効時のみ）。
+/// Noop は `allow_noop_stt` が有効な場合のみ末尾に加える。
+fn create_stt_engine(storage: &Storage) -> Arc<dyn SttEngine> {
+    let settings = storage.get_settings().unwrap_or_default();
+
+    let mut engines: Vec<Arc<dyn SttEngine>> = fallback_order(&settings)
+        .into_iter()
+        .filter_map(|choice| match choice {
+            SttEngineChoice::Apple => build_apple_engine(),
+            SttEngineChoice::Whisper => build_whisper_engine(&settings),
+            SttEngineChoice::Cloud | SttEngineChoice::Soniox => build_cloud_engine(&settings),
+        })
+        .collect();
+
+    if settings.allow_noop_stt {
+        engines.push(Arc::new(vt_core::infra::stt::NoopSttEngine));
+    }
+
+    let chain = FallbackSttEngine::new(engines);
+    log::info!(
+        "STT engine chain (selected {:?}): {:?}",
+        settings.stt_engine,
+        chain.engine_names()
+    );
+    Arc::new(chain)
+}
+
+/// DB を開き、設定に従ってエンジンを組み立てた AppService を返す（GUI・ヘッドレス共通）
+fn create_app_service() -> AppService {
     // DB パスはアプリデータディレクトリに配置
     // 開発時は一時ファイルを使用
     let db_path = std::env::var("VT_DB_PATH").unwrap_or_else(|_| {
@@ -109,35 +130,119 @@ pub fn run() {
     });
 
     let storage = Storage::open(&db_path).expect("SQLite の初期化に失敗しました");
+    // 前回クラッシュ時に idle 以外で残ったセッションを復旧（起動時点で進行中のセッションは無い）
+    match storage.reset_stale_sessions(&chrono::Utc::now().to_rfc3339()) {
+        Ok(0) => {}
+        Ok(n) => log::info!("未終了のセッション {n} 件を idle に戻しました"),
+        Err(e) => log::error!("セッション状態の復旧に失敗: {e}"),
+    }
     let stt_engine = create_stt_engine(&storage);
     let rewriter = create_rewriter(&storage);
-    let app_service = AppService::new(storage, stt_engine, rewriter);
+    AppService::new(storage, stt_engine, rewriter)
+}
+
+/// ヘッドレスモード: 標準入出力の改行区切り JSON でコマンドを受け付ける（UI なし）
+pub fn run_headless() {
+    let service = Arc::new(create_app_service());
+    let stdin = std::io::stdin();
+    if let Err(e) = vt_core::usecase::stdio::run_stdio(service, stdin.lock(), std::io::stdout()) {
+        eprintln!("ヘッドレスモードの入出力エラー: {e}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    let app_service = create_app_service();
+    let editor_events = app_service.take_editor_events();
+    let truncation_events = app_service.take_truncation_events();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_log::Builder::default().build())
         .manage(app_service)
+        .setup(move |app| {
+            if let Some(editor_rx) = editor_events {
+                commands::spawn_editor_forwarder(app.handle().clone(), editor_rx);
+            }
+            if let Some(truncation_rx) = truncation_events {
+                commands::spawn_truncation_forwarder(app.handle().clone(), truncation_rx);
+            }
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            // 再表示時に取りこぼしたイベントを補えるよう、フォーカスのたびに状態を送る
+            if let tauri::WindowEvent::Focused(true) = event {
+                let service = window.state::<AppService>();
+                events::emit_event(
+                    window.app_handle(),
+                    events::APP_SNAPSHOT,
+                    service.current_snapshot(),
+                );
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             commands::start_session,
             commands::stop_session,
             commands::toggle_recording,
+            commands::record_audio_only,
+            commands::pause_capture,
+            commands::resume_capture,
+            commands::transcribe_session,
+            commands::transcribe_file,
             commands::set_mode,
+            commands::set_task,
+            commands::set_vad_muted,
             commands::get_history,
             commands::get_session,
+            commands::get_snapshot,
+            commands::copy_session,
+            commands::get_segment_timings,
+            commands::pin_session,
+            commands::tag_session,
+            commands::untag_session,
+            commands::list_sessions_by_tag,
+            commands::edit_segment,
+            commands::reapply_post_processing,
+            commands::delete_session,
             commands::upsert_dictionary,
             commands::list_dictionary,
+            commands::suggest_dictionary_terms,
             commands::rewrite_last,
             commands::deliver_last,
+            commands::deliver_to,
+            commands::commit_segment,
+            commands::flush_accumulated,
+            commands::clear_accumulated,
             commands::get_settings,
             commands::update_settings,
+            commands::update_vad_config,
             commands::check_permissions,
             commands::check_setup_status,
+            commands::diagnose,
+            commands::test_microphone,
             commands::get_metrics,
+            commands::export_metrics,
             commands::cleanup_data,
+            commands::audio_cache_stats,
+            commands::clear_audio_cache,
             commands::paste_to_active_app,
             commands::open_system_settings,
             commands::check_whisper_model,
             commands::download_whisper_model,
+            commands::list_whisper_models,
+            commands::list_installed_whisper_models,
+            commands::set_whisper_model,
+            commands::get_engine_capabilities,
+            commands::retranscribe_segment,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // 終了直前に録音中のセグメントと実行中のジョブを片付けてからプロセスを終える
+            if let tauri::RunEvent::Exit = event {
+                if let Err(e) = app.state::<AppService>().shutdown(SHUTDOWN_TIMEOUT) {
+                    log::error!("Shutdown failed: {}", e);
+                }
+            }
+        });
 }
@@ -0,0 +1,32 @@
+import CoreGraphics
+import Foundation
+
+/// 文字列をキーボードイベントとしてアクティブアプリに送る
+///
+/// keyboardSetUnicodeString で任意の Unicode 文字（日本語を含む）を 1 イベントで送る。
+/// 長い文字列の分割と送信間隔の制御は Rust 側で行う。
+/// アクセシビリティ権限が無い場合はイベントが破棄されるため、呼び出し側で事前に確認すること。
+@_cdecl("swift_keystroke_type")
+func swiftKeystrokeType(text: UnsafePointer<CChar>) -> Bool {
+    let utf16 = Array(String(cString: text).utf16)
+    if utf16.isEmpty {
+        return true
+    }
+
+    guard let source = CGEventSource(stateID: .combinedSessionState),
+        let keyDown = CGEvent(keyboardEventSource: source, virtualKey: 0, keyDown: true),
+        let keyUp = CGEvent(keyboardEventSource: source, virtualKey: 0, keyDown: false)
+    else {
+        return false
+    }
+
+    utf16.withUnsafeBufferPointer { buffer in
+        keyDown.keyboardSetUnicodeString(
+            stringLength: buffer.count, unicodeString: buffer.baseAddress)
+        keyUp.keyboardSetUnicodeString(
+            stringLength: buffer.count, unicodeString: buffer.baseAddress)
+    }
+    keyDown.post(tap: .cghidEventTap)
+    keyUp.post(tap: .cghidEventTap)
+    return true
+}
@@ -3,13 +3,16 @@
 // アプリ起動時に一度だけ呼ぶ
 // ============================================================
 
-import { subscribe } from "./coreClient";
+import { invokeCommand, subscribe } from "./coreClient";
 import type {
+  AppSnapshot,
   SessionState,
   AudioLevelPayload,
   TranscriptPartialPayload,
   TranscriptFinalPayload,
   RewriteDonePayload,
+  DeliverTruncatedPayload,
+  ModeSwitchedByVoicePayload,
   ErrorPayload,
 } from "./types";
 
@@ -52,10 +55,35 @@ export async function initEventListeners(): Promise<() => void> {
       useToastStore.getState().addToast("success", "Rewrite complete");
     }),
 
+    subscribe<DeliverTruncatedPayload>(
+      "deliver_truncated",
+      ({ original_len, delivered_len }) => {
+        useToastStore
+          .getState()
+          .addToast("warning", `Output truncated: ${original_len} → ${delivered_len} chars`);
+      },
+    ),
+
+    subscribe<ModeSwitchedByVoicePayload>("mode_switched_by_voice", ({ mode }) => {
+      useSessionStore.getState()._setCurrentMode(mode);
+      useToastStore.getState().addToast("info", `Mode switched to ${mode}`);
+    }),
+
     subscribe<ErrorPayload>("error", ({ code, message }) => {
       useToastStore.getState().addToast("error", `[${code}] ${message}`);
     }),
+
+    // ウィンドウのフォーカス時に送られる（非表示中に取りこぼしたイベントの補完）
+    subscribe<AppSnapshot>("app_snapshot", (snapshot) => {
+      useSessionStore.getState()._applySnapshot(snapshot);
+    }),
   ]);
 
+  // 再読み込み時は購読前のイベントを受け取れないため、現在の状態で同期する
+  const snapshot = await invokeCommand<AppSnapshot | undefined>("get_snapshot");
+  if (snapshot) {
+    useSessionStore.getState()._applySnapshot(snapshot);
+  }
+
   return () => unlisteners.forEach((fn) => fn());
 }
@@ -22,11 +22,40 @@ export const MODE_DESCRIPTIONS: Record<Mode, string> = {
   minutes: "決定/ToDo/論点",
 };
 
+// Rust WhisperTask 互換（translate は Whisper でのみ英訳される）
+export type WhisperTask = "transcribe" | "translate";
+
 // === Deliver ===
-export type DeliverTarget = "clipboard" | "paste" | "file_append" | "webhook";
+export type DeliverTarget =
+  | "clipboard"
+  | "paste"
+  | "file_append"
+  | "webhook"
+  | "keystroke"
+  | "app_event";
+
+// 組み込み側が登録した独自出力先（Rust DeliverTarget::Custom）
+export type CustomDeliverTarget = { custom: string };
+
+// Rust WrapStyle 互換（clipboard 配信時のみ有効）
+export type WrapStyle = "none" | "code_fence" | "quote" | "timestamp_prefix";
+
+// Rust DeliverText 互換（deliver_last で出力するテキストの選択）
+export type DeliverText = "auto" | "raw" | "rewritten";
+
+// Rust JoinStyle 互換（copy_session のセグメント区切り）
+export type JoinStyle = "newline" | "double_newline" | "space";
+
+export interface DeliverLastResult {
+  text: string;
+  target: DeliverTarget | CustomDeliverTarget;
+  fell_back_to_raw: boolean;
+}
 
 export interface DeliverPolicy {
-  target: DeliverTarget;
+  target: DeliverTarget | "custom";
+  name?: string; // target が "custom" のときの登録名
+  wrap?: WrapStyle;
   config?: Record<string, unknown>;
 }
 
@@ -38,11 +67,25 @@ export type SessionStateSimple =
   | "idle"
   | "armed"
   | "recording"
+  | "paused"
   | "transcribing"
+  | "reviewing"
   | "rewriting"
   | "delivering";
 export type SessionState = SessionStateSimple | SessionStateError;
 
+// Rust PipelineStatus 互換
+export type PipelineStatus = "stopped" | "running" | "paused";
+
+// Rust AppSnapshot 互換（get_snapshot / app_snapshot イベント）
+export interface AppSnapshot {
+  session_id: string | null;
+  state: SessionState | null;
+  mode: Mode | null;
+  pipeline: PipelineStatus;
+  last_partial_text: string | null;
+}
+
 export function isErrorState(state: SessionState): state is SessionStateError {
   return typeof state === "object" && "error" in state;
 }
@@ -53,6 +96,7 @@ export function getStateLabel(state: SessionState): string {
     idle: "Idle",
     armed: "Armed",
     recording: "Recording",
+    paused: "Paused",
     transcribing: "Transcribing...",
     rewriting: "Rewriting...",
     delivering: "Delivering...",
@@ -83,16 +127,39 @@ export interface AudioLevelPayload {
   rms: number;
 }
 
+// clipping イベント（クリップしたサンプルの割合がしきい値を超えた）
+export interface ClippingPayload {
+  ratio: number;
+}
+
+// capture_format イベント（デバイスのネイティブ形式、リサンプル前）
+export interface CaptureFormatPayload {
+  sample_rate: number;
+  channels: number;
+}
+
 export interface TranscriptPartialPayload {
   text: string;
 }
 
+// caption_update イベント（部分書き起こしは後処理前、確定は後処理済みのテキスト）
+export interface CaptionUpdatePayload {
+  text: string;
+  is_partial: boolean;
+}
+
 export interface TranscriptFinalPayload {
   text: string;
   confidence: number;
   segment_id?: string;
 }
 
+// Rust TranscriptForReviewPayload 互換（transcript_for_review イベント）
+export interface TranscriptForReviewPayload {
+  segment_id: string;
+  text: string;
+}
+
 export interface RewriteDonePayload {
   session_id: string;
   segment_id: string;
@@ -104,12 +171,77 @@ export interface DeliverDonePayload {
   target: string;
 }
 
+// Rust DeliverToEditorPayload 互換（deliver_to_editor イベント、app_event 配信時）
+export interface DeliverToEditorPayload {
+  text: string;
+}
+
+// Rust DeliverTruncated 互換（deliver_truncated イベント、文字数単位）
+export interface DeliverTruncatedPayload {
+  original_len: number;
+  delivered_len: number;
+}
+
+// Rust SessionSegmentLimit 互換（session_segment_limit イベント）
+export interface SessionSegmentLimitPayload {
+  session_id: string;
+  segment_count: number;
+  limit: number;
+  action: SegmentLimitAction;
+  new_session_id: string | null;
+}
+
+// Rust SessionDurationLimit 互換（session_duration_limit イベント）
+export interface SessionDurationLimitPayload {
+  session_id: string;
+  recorded_secs: number;
+  limit_secs: number;
+}
+
+// Rust ModeSwitchedByVoice 互換（mode_switched_by_voice イベント）
+export interface ModeSwitchedByVoicePayload {
+  session_id: string;
+  mode: Mode;
+  phrase: string;
+}
+
 export interface ErrorPayload {
   code: string;
   message: string;
   recoverable: boolean;
 }
 
+// Rust TranscribeProgress 互換（transcribe_progress イベント）
+export interface TranscribeProgressPayload {
+  session_id: string;
+  segment_id: string;
+  completed: number;
+  total: number;
+  text: string | null;
+  error: string | null;
+}
+
+export interface TranscribeSessionResult {
+  transcribed: number;
+  failed: number;
+  skipped: number;
+}
+
+// Rust TranscribeFileResult 互換（transcribe_file の戻り値）
+export interface TranscribeFileResult {
+  session_id: string;
+  transcribed: number;
+  failed: number;
+}
+
+// Rust SegmentTiming 互換（get_segment_timings、時刻はセグメント先頭からのミリ秒）
+export interface SegmentTiming {
+  text: string;
+  start_ms: number;
+  end_ms: number;
+  probability: number;
+}
+
 // === History ===
 // Rust SessionSummary 互換
 export interface HistoryItem {
@@ -120,6 +252,8 @@ export interface HistoryItem {
   updated_at: string;
   segment_count: number;
   preview_text?: string;
+  pinned?: boolean;
+  tags?: string[];
 }
 
 // Rust HistoryPage 互換
@@ -139,6 +273,13 @@ export interface DictionaryEntry {
   scope: DictionaryScope;
   priority: number;
   enabled: boolean;
+  word_boundary?: boolean;
+}
+
+// Rust TermSuggestion 互換（suggest_dictionary_terms の候補語）
+export interface TermSuggestion {
+  term: string;
+  occurrences: number;
 }
 
 // === Prompts ===
@@ -154,21 +295,136 @@ export type SttEngine = "apple" | "whisper" | "cloud" | "soniox";
 export type AudioRetention = "none" | "ttl" | "permanent";
 export type WhisperModelSize = "base" | "small" | "medium" | "large";
 
+// Rust ModelInfo 互換（list_installed_whisper_models / set_whisper_model）
+export interface ModelInfo {
+  name: string;
+  path: string;
+  size_bytes: number;
+  params: string | null;
+}
+
+// Rust EngineCapabilities 互換（get_engine_capabilities）
+export interface EngineCapabilities {
+  partial: boolean;
+  timings: boolean;
+  confidence: boolean;
+  translate: boolean;
+  // 設定 language と同じ BCP 47 形式。空なら制限なし
+  languages: string[];
+}
+
+// Rust AudioCacheStats 互換（audio_cache_stats）
+export interface AudioCacheStats {
+  file_count: number;
+  total_bytes: number;
+  // 最も古いファイルの更新日時（RFC 3339）
+  oldest?: string | null;
+}
+
+export type CaptureMode = "vad" | "continuous";
+// Rust ChannelMode 互換（{ channel: n } は 0 始まりのチャンネル番号）
+export type ChannelMode = "mix" | "left" | "right" | { channel: number };
+export type AccumulateSeparator = "newline" | "space" | "bullet";
+// Rust SegmentLimitAction 互換（セグメント数上限に達したときの動作）
+export type SegmentLimitAction = "stop" | "new_session";
+// Rust LineEnding 互換（配信時の改行コード）
+export type LineEnding = "lf" | "crlf" | "native";
+
+export interface VadConfig {
+  energy_threshold: number;
+  silence_timeout_ms: number;
+  max_segment_ms: number;
+  speech_start_ms: number;
+  adaptive_threshold: boolean;
+  calibration_duration_ms: number;
+  threshold_multiplier: number;
+  use_zcr: boolean;
+  zcr_threshold: number;
+  normalize_gain: boolean;
+  target_rms: number;
+  trim_silence?: boolean;
+  min_segment_ms?: number;
+  output_dir?: string;
+  wav_bits?: 16 | 32;
+  level_smoothing_alpha?: number;
+  level_event_hz?: number;
+  clip_ratio_threshold?: number;
+  agc?: boolean;
+  agc_target_rms?: number;
+  agc_max_gain?: number;
+}
+
+export interface RedactionRule {
+  name: string;
+  pattern: string;
+  replacement: string;
+}
+
 export interface AppSettings {
   stt_engine: SttEngine;
   default_mode: string;
   default_deliver_target: string;
   rewrite_enabled: boolean;
+  auto_rewrite_by_mode?: Partial<Record<Mode, boolean>>;
+  deliver_policy_by_mode?: Partial<Record<Mode, DeliverPolicy>>;
+  whisper_prompt_prefix?: Partial<Record<Mode, string>>;
+  max_prompt_chars?: number;
+  smart_hints?: boolean;
+  smart_hints_max_terms?: number;
+  rewrite_timeout_secs?: number;
   paste_allowlist: string[];
   paste_confirm: boolean;
   audio_retention: AudioRetention;
   segment_ttl_days: number;
+  max_history_sessions?: number;
   hotkey_toggle: string;
+  toggle_debounce_ms?: number;
   claude_api_key?: string;
   soniox_api_key?: string;
   language: string;
   vad_max_segment_ms?: number;
+  vad?: VadConfig | null;
   whisper_model_size: WhisperModelSize;
+  auto_downgrade_model?: boolean;
+  whisper_entropy_thold?: number;
+  whisper_logprob_thold?: number;
+  capture_mode?: CaptureMode;
+  input_channel?: ChannelMode;
+  min_native_sample_rate?: number;
+  chunk_target_ms?: number;
+  boundary_search_ms?: number;
+  allow_noop_stt?: boolean;
+  allow_cloud_fallback?: boolean;
+  cache_transcripts?: boolean;
+  store_partials?: boolean;
+  accumulate_deliveries?: boolean;
+  accumulate_separator?: AccumulateSeparator;
+  verify_clipboard?: boolean;
+  max_deliver_chars?: number;
+  deliver_line_endings?: LineEnding;
+  deliver_trim?: boolean;
+  review_before_deliver?: boolean;
+  redaction_enabled?: boolean;
+  redaction_rules?: RedactionRule[];
+  transcript_filter_enabled?: boolean;
+  repetition_threshold?: number;
+  hallucination_blocklist?: string[];
+  number_normalization_enabled?: boolean;
+  voice_commands_enabled?: boolean;
+  voice_mode_commands?: Record<string, Mode>;
+  prebuffer_enabled?: boolean;
+  prebuffer_secs?: number;
+  session_idle_timeout_secs?: number;
+  capture_stall_timeout_secs?: number;
+  device_init_timeout_secs?: number;
+  auto_recover?: boolean;
+  max_segments_per_session?: number;
+  segment_limit_action?: SegmentLimitAction;
+  max_session_duration_secs?: number;
+  commit_on_sentence_end?: boolean;
+  captions_enabled?: boolean;
+  audio_dir?: string | null;
+  secure_delete?: boolean;
 }
 
 // === Permissions (Phase 3) ===
@@ -199,9 +455,20 @@ export interface SetupStatus {
   active_rewriter: string;
 }
 
+// Rust MicTestResult 互換（test_microphone の結果）
+export interface MicTestResult {
+  peak: number;
+  mean: number;
+  clipped_frames: number;
+  device_name: string;
+}
+
 // === Metrics ===
 // MetricsSummary は MetricsPage 内でローカル定義（Rust MetricsSummary 互換）
 
+// Rust MetricsExportFormat 互換（export_metrics の形式）
+export type MetricsExportFormat = "csv" | "json";
+
 // === UI Navigation ===
 export type Page =
   | "recorder"
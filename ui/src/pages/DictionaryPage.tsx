@@ -22,6 +22,7 @@ const EMPTY_ENTRY: DictionaryEntry = {
   scope: "global",
   priority: 0,
   enabled: true,
+  word_boundary: false,
 };
 
 export function DictionaryPage() {
@@ -148,6 +149,13 @@ export function DictionaryPage() {
                 setEditingEntry({ ...editingEntry, enabled: checked })
               }
             />
+            <Toggle
+              label="Whole word only"
+              checked={editingEntry.word_boundary ?? false}
+              onChange={(checked) =>
+                setEditingEntry({ ...editingEntry, word_boundary: checked })
+              }
+            />
             <div className="flex gap-2 pt-2">
               <Button variant="primary" size="sm" onClick={handleSave}>
                 Save
@@ -9,6 +9,7 @@ interface BackendMetrics {
   segments_transcribed: number;
   segments_rewritten: number;
   segments_delivered: number;
+  frames_dropped?: number;
   error_counts: {
     permission: number;
     device: number;
@@ -24,6 +24,7 @@ const WHISPER_MODEL_OPTIONS = [
 
 const DELIVER_OPTIONS = [
   { value: "clipboard", label: "Clipboard Only" },
+  { value: "keystroke", label: "Type at Cursor (macOS)" },
   { value: "paste", label: "Paste to App (Coming soon)", disabled: true },
   { value: "file_append", label: "File Append (Coming soon)", disabled: true },
   { value: "webhook", label: "Webhook (Coming soon)", disabled: true },
@@ -191,6 +192,45 @@ export function SettingsPage() {
                 </Button>
               )}
             </div>
+            <div className="mt-3 grid grid-cols-2 gap-3">
+              <div>
+                <label className="mb-1 block text-xs text-gray-400">
+                  Entropy Threshold
+                </label>
+                <input
+                  type="number"
+                  step={0.1}
+                  min={0.1}
+                  value={settings.whisper_entropy_thold ?? 2.4}
+                  onChange={(e) =>
+                    updateSettings({
+                      whisper_entropy_thold: Number(e.target.value),
+                    })
+                  }
+                  className="w-full rounded-lg border border-gray-700 bg-gray-800 px-3 py-2 text-sm text-gray-200 focus:border-purple-500 focus:outline-none"
+                />
+              </div>
+              <div>
+                <label className="mb-1 block text-xs text-gray-400">
+                  Log-prob Threshold
+                </label>
+                <input
+                  type="number"
+                  step={0.1}
+                  max={0}
+                  value={settings.whisper_logprob_thold ?? -1.0}
+                  onChange={(e) =>
+                    updateSettings({
+                      whisper_logprob_thold: Number(e.target.value),
+                    })
+                  }
+                  className="w-full rounded-lg border border-gray-700 bg-gray-800 px-3 py-2 text-sm text-gray-200 focus:border-purple-500 focus:outline-none"
+                />
+              </div>
+            </div>
+            <p className="mt-1 text-xs text-gray-500">
+              Lower entropy / higher log-prob thresholds re-decode more noisy results
+            </p>
           </>
         )}
       </Card>
@@ -1,5 +1,5 @@
 import { create } from "zustand";
-import type { HistoryItem, HistoryPage, Mode } from "../lib/types";
+import type { DeliverText, HistoryItem, HistoryPage, JoinStyle, Mode } from "../lib/types";
 import { invokeCommand } from "../lib/coreClient";
 
 interface HistoryStore {
@@ -14,6 +14,7 @@ interface HistoryStore {
   setQuery: (query: string) => void;
   setFilterMode: (mode: Mode | "all") => void;
   rewriteItem: (sessionId: string, mode: Mode) => Promise<void>;
+  copySession: (sessionId: string, prefer?: DeliverText, join?: JoinStyle) => Promise<void>;
 }
 
 export const useHistoryStore = create<HistoryStore>((set, get) => ({
@@ -73,4 +74,8 @@ export const useHistoryStore = create<HistoryStore>((set, get) => ({
       mode,
     });
   },
+
+  copySession: async (sessionId, prefer, join) => {
+    await invokeCommand("copy_session", { sessionId, prefer, join });
+  },
 }));
@@ -1,13 +1,13 @@
 import { create } from "zustand";
 import type {
+  AppSnapshot,
   Mode,
   SessionState,
   DeliverPolicy,
-  DeliverTarget,
+  DeliverText,
   FinalTranscript,
 } from "../lib/types";
 import { invokeCommand } from "../lib/coreClient";
-import { useSettingsStore } from "./settingsStore";
 
 interface SessionStore {
   // State
@@ -24,17 +24,20 @@ interface SessionStore {
   toggleRecording: () => Promise<void>;
   setMode: (mode: Mode) => Promise<void>;
   rewriteLast: (mode: Mode) => Promise<void>;
-  deliverLast: (target: string) => Promise<void>;
+  deliverLast: (target: string, prefer?: DeliverText) => Promise<void>;
+  deliverTo: (text: string, policy: DeliverPolicy) => Promise<void>;
   clearTranscripts: () => void;
 
   // Event-driven setters (called by eventSetup)
   _setSessionState: (state: SessionState) => void;
   _setAudioLevel: (level: number) => void;
   _setPartialTranscript: (text: string) => void;
+  _setCurrentMode: (mode: Mode) => void;
   _addFinalTranscript: (text: string, confidence: number, segmentId?: string) => void;
   _updateRewrite: (segmentId: string, rewrittenText: string) => void;
   _setRewriting: (segmentId: string, isRewriting: boolean) => void;
   _updateLastTranscript: (text: string) => void;
+  _applySnapshot: (snapshot: AppSnapshot) => void;
 }
 
 export const useSessionStore = create<SessionStore>((set, get) => ({
@@ -47,12 +50,10 @@ export const useSessionStore = create<SessionStore>((set, get) => ({
 
   startSession: async (mode, deliverPolicy) => {
     const m = mode ?? get().currentMode;
-    const dp: DeliverPolicy = deliverPolicy ?? {
-      target: useSettingsStore.getState().settings.default_deliver_target as DeliverTarget,
-    };
+    // 省略時はコア側で deliver_policy_by_mode → default_deliver_target の順に解決する
     const sessionId = await invokeCommand<string>("start_session", {
       mode: m,
-      deliverPolicy: dp,
+      deliverPolicy,
     });
     set({ sessionId, currentMode: m });
   },
@@ -74,8 +75,12 @@ export const useSessionStore = create<SessionStore>((set, get) => ({
     await invokeCommand("rewrite_last", { mode });
   },
 
-  deliverLast: async (target) => {
-    await invokeCommand("deliver_last", { target });
+  deliverLast: async (target, prefer) => {
+    await invokeCommand("deliver_last", { target, prefer });
+  },
+
+  deliverTo: async (text, policy) => {
+    await invokeCommand("deliver_to", { text, policy });
   },
 
   clearTranscripts: () => {
@@ -86,6 +91,14 @@ export const useSessionStore = create<SessionStore>((set, get) => ({
   _setSessionState: (state) => set({ sessionState: state }),
   _setAudioLevel: (level) => set({ audioLevel: level }),
   _setPartialTranscript: (text) => set({ partialTranscript: text }),
+  _setCurrentMode: (mode) => set({ currentMode: mode }),
+  _applySnapshot: (snapshot) =>
+    set((s) => ({
+      sessionId: snapshot.session_id,
+      sessionState: snapshot.state ?? "idle",
+      currentMode: snapshot.mode ?? s.currentMode,
+      partialTranscript: snapshot.last_partial_text ?? "",
+    })),
   _addFinalTranscript: (text, confidence, segmentId) =>
     set((s) => ({
       finalTranscripts: [